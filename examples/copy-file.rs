@@ -9,7 +9,7 @@ fn main() {
         let mut output: File = File::create("test.txt").await.unwrap();
         let mut buf = vec![0; 1024];
         let len = input.read(&mut buf).await.unwrap();
-        output.write(&mut buf[0..len]).await.unwrap();
+        output.write_all(&buf[0..len]).await.unwrap();
         output.flush().await.unwrap();
     });
 }
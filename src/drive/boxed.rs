@@ -0,0 +1,98 @@
+//! A driver whose type has been erased
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use iou::SQEs;
+
+use super::{Drive, Completion};
+
+/// A handle to any driver
+///
+/// `Drive` is not object safe, so IO objects are generic over the driver they run on. A
+/// `BoxDriver` erases the driver's type, so that libraries can expose types like
+/// `TcpStream<BoxDriver>` in their APIs without committing to a driver, and applications mixing
+/// several drivers don't need to instantiate every IO object for each of them.
+pub struct BoxDriver {
+    inner: Pin<Box<dyn DynDrive>>,
+}
+
+impl BoxDriver {
+    /// Erase the type of a driver.
+    pub fn new<D: Drive + Clone + Send + 'static>(driver: D) -> BoxDriver {
+        BoxDriver { inner: Box::pin(driver) }
+    }
+}
+
+impl Clone for BoxDriver {
+    fn clone(&self) -> BoxDriver {
+        BoxDriver { inner: self.inner.clone_dyn() }
+    }
+}
+
+impl Drive for BoxDriver {
+    fn poll_prepare<'cx>(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        let mut prepare = Some(prepare);
+        self.inner.as_mut().poll_prepare_dyn(ctx, count, &mut |sqs, ctx| {
+            let prepare = prepare.take().expect("driver called prepare more than once");
+            prepare(sqs, ctx)
+        })
+    }
+
+    fn poll_submit(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        self.inner.as_mut().poll_submit_dyn(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        self.inner.ring_fd_dyn()
+    }
+}
+
+/// An object safe version of `Drive`, so that a `BoxDriver` can be any driver.
+trait DynDrive: Send {
+    fn poll_prepare_dyn<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: &mut dyn FnMut(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>>;
+
+    fn poll_submit_dyn(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u32>>;
+
+    fn ring_fd_dyn(&self) -> Option<RawFd>;
+
+    fn clone_dyn(&self) -> Pin<Box<dyn DynDrive>>;
+}
+
+impl<D: Drive + Clone + Send + 'static> DynDrive for D {
+    fn poll_prepare_dyn<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: &mut dyn FnMut(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        self.poll_prepare(ctx, count, |sqs, ctx| prepare(sqs, ctx))
+    }
+
+    fn poll_submit_dyn(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u32>> {
+        self.poll_submit(ctx)
+    }
+
+    fn ring_fd_dyn(&self) -> Option<RawFd> {
+        self.ring_fd()
+    }
+
+    fn clone_dyn(&self) -> Pin<Box<dyn DynDrive>> {
+        Box::pin(self.clone())
+    }
+}
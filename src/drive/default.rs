@@ -0,0 +1,123 @@
+//! The driver used by IO objects which were not given one explicitly
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use iou::SQEs;
+use once_cell::sync::OnceCell;
+
+use super::{Drive, Completion, BoxDriver};
+
+type Factory = Box<dyn Fn() -> BoxDriver + Send + Sync>;
+
+static FACTORY: OnceCell<Factory> = OnceCell::new();
+
+/// The default driver handle
+///
+/// This is the driver used by constructors which do not take a driver argument, like
+/// `File::open` or `TcpListener::bind`. Unless another driver has been installed with
+/// [`set_default_driver`], it runs on the driver selected by the crate's features:
+///
+/// - `default-driver-fallback`: the [local driver](super::local) if the default driver is
+///   constructed on a thread running [`local::block_on`](super::local::block_on), and the
+///   [demo driver](super::demo) otherwise
+/// - `default-driver-local`: the [local driver](super::local)
+/// - `default-driver-demo` (enabled by default): the [demo driver](super::demo)
+///
+/// If several features are enabled, the first of them in this list is used. If none is, a driver
+/// must be installed before a default driver is constructed.
+pub struct DefaultDriver {
+    inner: BoxDriver,
+}
+
+impl Default for DefaultDriver {
+    fn default() -> DefaultDriver {
+        let factory = FACTORY.get_or_init(builtin);
+        DefaultDriver { inner: factory() }
+    }
+}
+
+impl Clone for DefaultDriver {
+    fn clone(&self) -> DefaultDriver {
+        DefaultDriver { inner: self.inner.clone() }
+    }
+}
+
+impl Drive for DefaultDriver {
+    fn poll_prepare<'cx>(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        Pin::new(&mut self.inner).poll_prepare(ctx, count, prepare)
+    }
+
+    fn poll_submit(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        Pin::new(&mut self.inner).poll_submit(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        self.inner.ring_fd()
+    }
+}
+
+impl From<DefaultDriver> for BoxDriver {
+    fn from(driver: DefaultDriver) -> BoxDriver {
+        driver.inner
+    }
+}
+
+/// Install the factory used to construct every [`DefaultDriver`].
+///
+/// The default driver can only be set once, and only before any default driver has been
+/// constructed; after that, this returns the factory back as an error.
+pub fn set_default_driver<D, F>(factory: F) -> Result<(), F> where
+    D: Drive + Clone + Send + 'static,
+    F: Fn() -> D + Send + Sync + 'static,
+{
+    let mut factory = Some(factory);
+    FACTORY.get_or_init(|| {
+        let factory = factory.take().unwrap();
+        Box::new(move || BoxDriver::new(factory()))
+    });
+    match factory {
+        Some(factory)   => Err(factory),
+        None            => Ok(()),
+    }
+}
+
+#[cfg(feature = "default-driver-fallback")]
+fn builtin() -> Factory {
+    Box::new(|| match super::local::is_blocking() {
+        true    => BoxDriver::new(super::local::driver()),
+        false   => BoxDriver::new(super::demo::driver()),
+    })
+}
+
+#[cfg(all(feature = "default-driver-local", not(feature = "default-driver-fallback")))]
+fn builtin() -> Factory {
+    Box::new(|| BoxDriver::new(super::local::driver()))
+}
+
+#[cfg(all(
+    feature = "default-driver-demo",
+    not(any(feature = "default-driver-local", feature = "default-driver-fallback")),
+))]
+fn builtin() -> Factory {
+    Box::new(|| BoxDriver::new(super::demo::driver()))
+}
+
+#[cfg(not(any(
+    feature = "default-driver-demo",
+    feature = "default-driver-local",
+    feature = "default-driver-fallback",
+)))]
+fn builtin() -> Factory {
+    panic!("no default driver was enabled or set with ringbahn::set_default_driver")
+}
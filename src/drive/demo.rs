@@ -2,6 +2,7 @@
 
 use std::future::Future;
 use std::io;
+use std::os::unix::io::RawFd;
 use std::pin::Pin;
 use std::sync::Once;
 use std::task::{Poll, Context};
@@ -15,6 +16,8 @@ use parking_lot::Mutex;
 const ENTRIES: u32   = 32;
 
 use super::{Drive, Completion};
+use crate::ring::completion::complete_raw;
+use crate::sys;
 
 use iou::*;
 
@@ -23,8 +26,17 @@ type Queues = (
     Mutex<CompletionQueue<'static>>,
     Registrar<'static>,
     Event,
+    RawFd,
+    RawRing,
 );
 
+/// The ring the queues belong to, from which the completion thread reads CQEs with all of their
+/// flags.
+struct RawRing(*mut uring_sys::io_uring);
+
+unsafe impl Send for RawRing { }
+unsafe impl Sync for RawRing { }
+
 static QUEUES: Lazy<Queues> = Lazy::new(init);
 
 /// The driver handle
@@ -45,7 +57,7 @@ impl DemoDriver {
         match sq.submit() {
             Ok(n)       => Poll::Ready(Ok(n)),
             Err(err)    => {
-                if err.raw_os_error().map_or(false, |code| code == libc::EBUSY) {
+                if err.raw_os_error() == Some(libc::EBUSY) {
                     self.listener = Some(QUEUES.3.listen());
                     Poll::Pending
                 } else {
@@ -80,7 +92,7 @@ impl Drive for DemoDriver {
             match sq.prepare_sqes(count) {
                 Some(sqs)   => return Poll::Ready(prepare(sqs, ctx)),
                 None        => {
-                    let _ = ready!(self.poll_submit_inner(ctx, &mut *sq));
+                    let _ = ready!(self.poll_submit_inner(ctx, &mut sq));
                 }
             }
         }
@@ -90,7 +102,11 @@ impl Drive for DemoDriver {
         mut self: Pin<&mut Self>,
         ctx: &mut Context<'_>,
     ) -> Poll<io::Result<u32>> {
-        self.poll_submit_inner(ctx, &mut *QUEUES.0.lock())
+        self.poll_submit_inner(ctx, &mut QUEUES.0.lock())
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        Some(QUEUES.4)
     }
 }
 
@@ -119,29 +135,32 @@ fn init() -> Queues {
     let features = SetupFeatures::NODROP;
     let ring = Box::new(IoUring::new_with_flags(ENTRIES, flags, features).unwrap());
     let ring = Box::leak(ring);
+    let fd = ring.raw_fd();
+    let raw = RawRing(unsafe { ring.raw_mut() });
     let (sq, cq, reg) = ring.queues();
-    (Mutex::new(sq), Mutex::new(cq), reg, Event::new())
+    (Mutex::new(sq), Mutex::new(cq), reg, Event::new(), fd, raw)
 }
 
 static STARTED_COMPLETION_THREAD: Once = Once::new();
 
 fn start_completion_thread() {
     STARTED_COMPLETION_THREAD.call_once(|| { thread::spawn(move || {
-        let mut cq = QUEUES.1.lock();
-        while let Ok(cqe) = cq.wait_for_cqe() {
+        let cq = QUEUES.1.lock();
+        let ring = QUEUES.5.0;
+        while let Ok(cqe) = unsafe { sys::wait_cqe(ring) } {
             let mut ready = cq.ready() as usize + 1;
             QUEUES.3.notify_additional(ready);
 
-            super::complete(cqe);
+            complete_raw(cqe);
             ready -= 1;
 
-            while let Some(cqe) = cq.peek_for_cqe() {
+            while let Some(cqe) = unsafe { sys::peek_cqe(ring) } {
                 if ready == 0 {
                     ready = cq.ready() as usize + 1;
                     QUEUES.3.notify_additional(ready);
                 }
 
-                super::complete(cqe);
+                complete_raw(cqe);
                 ready -= 1;
             }
 
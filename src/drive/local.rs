@@ -0,0 +1,240 @@
+//! A driver which runs io-uring on the current thread
+//!
+//! Unlike the demo driver, the local driver does not start a thread to process completions.
+//! Instead, each thread has its own io-uring instance, and completions are processed by the
+//! [`block_on`] function while the future it is running is waiting for IO. Because of this, IO
+//! objects on the local driver should only be used inside of `block_on`, on the thread which
+//! constructed them.
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use iou::*;
+
+use super::{Drive, Completion};
+use crate::ring::completion::{complete_raw, is_final};
+use crate::sys;
+
+const ENTRIES: u32   = 32;
+
+// The user data of the read of a ring's wake eventfd; completions are allocated with a greater
+// alignment, so none has this address.
+const WAKE: u64      = 1;
+
+thread_local! {
+    static RING: RefCell<LocalRing> = RefCell::new(LocalRing::new());
+    // Whether the thread is running `block_on`, which processes the local ring's completions
+    static BLOCKING: Cell<bool> = const { Cell::new(false) };
+}
+
+struct LocalRing {
+    ring: IoUring,
+    // The number of events which have been prepared and not yet completed
+    in_flight: u32,
+    // Written when the future `block_on` is running is woken by another thread, so that waiting
+    // for completions stops; it is read by the ring while it waits
+    wake: Arc<WakeFd>,
+    wake_buf: Box<[u8; 8]>,
+    wake_armed: bool,
+}
+
+impl LocalRing {
+    fn new() -> LocalRing {
+        let flags = SetupFlags::empty();
+        let features = SetupFeatures::NODROP;
+        let ring = IoUring::new_with_flags(ENTRIES, flags, features).unwrap();
+        let wake = Arc::new(WakeFd::new().unwrap());
+        LocalRing { ring, in_flight: 0, wake, wake_buf: Box::new([0; 8]), wake_armed: false }
+    }
+
+    fn submit(&mut self, wait: bool) -> io::Result<u32> {
+        let n = match wait && self.in_flight > 0 {
+            true    => {
+                self.arm_wake()?;
+                self.ring.submit_sqes_and_wait(1)?
+            }
+            false   => self.ring.submit_sqes()?,
+        };
+        while let Some(cqe) = unsafe { sys::peek_cqe(self.ring.raw_mut()) } {
+            if cqe.user_data == WAKE {
+                self.wake_armed = false;
+                continue;
+            }
+            if is_final(&cqe) {
+                self.in_flight -= 1;
+            }
+            complete_raw(cqe);
+        }
+        Ok(n)
+    }
+
+    /// Prepare a read of the wake eventfd, unless one is still running.
+    fn arm_wake(&mut self) -> io::Result<()> {
+        if self.wake_armed {
+            return Ok(());
+        }
+        if self.ring.sq_space_left() == 0 {
+            self.ring.submit_sqes()?;
+        }
+        if let Some(mut sqe) = self.ring.prepare_sqe() {
+            unsafe {
+                sqe.prep_read(self.wake.0, &mut self.wake_buf[..], 0);
+                sqe.set_user_data(WAKE);
+            }
+            self.wake_armed = true;
+        }
+        Ok(())
+    }
+}
+
+/// An eventfd which wakes a thread waiting for completions of its local ring.
+struct WakeFd(RawFd);
+
+impl WakeFd {
+    fn new() -> io::Result<WakeFd> {
+        // The eventfd is blocking, so that io-uring waits for it to be written.
+        match unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) } {
+            -1  => Err(io::Error::last_os_error()),
+            fd  => Ok(WakeFd(fd)),
+        }
+    }
+
+    fn wake(&self) {
+        let n: u64 = 1;
+        unsafe { libc::write(self.0, &n as *const u64 as *const libc::c_void, 8); }
+    }
+}
+
+impl Drop for WakeFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.0); }
+    }
+}
+
+/// The driver handle
+#[derive(Default, Clone)]
+pub struct LocalDriver {
+    _private: (),
+}
+
+impl Drive for LocalDriver {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        RING.with(|ring| {
+            let mut ring = ring.borrow_mut();
+            if ring.ring.sq_space_left() < count {
+                if let Err(err) = ring.submit(false) {
+                    panic!("submitting to the local driver failed: {}", err);
+                }
+            }
+            let ring = &mut *ring;
+            match ring.ring.prepare_sqes(count) {
+                Some(sqs)   => {
+                    ring.in_flight += 1;
+                    Poll::Ready(prepare(sqs, ctx))
+                }
+                None        => {
+                    // The submission queue is full of events the kernel has not yet consumed;
+                    // try again once some of them have completed.
+                    ctx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        })
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        Poll::Ready(RING.with(|ring| ring.borrow_mut().submit(false)))
+    }
+}
+
+/// Construct a local driver handle
+pub fn driver() -> LocalDriver {
+    LocalDriver::default()
+}
+
+/// Run a future to completion on the current thread, processing completions for the local
+/// driver while it waits.
+///
+/// If the future is waiting for something other than IO on the local driver, this will park
+/// the thread until the future is awoken.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let _blocking = Blocking(BLOCKING.with(|blocking| blocking.replace(true)));
+    let mut future = Box::pin(future);
+    let notify = Arc::new(Notify {
+        thread: thread::current(),
+        woken: AtomicBool::new(false),
+        wake: RING.with(|ring| ring.borrow().wake.clone()),
+    });
+    let waker = Waker::from(notify.clone());
+    let mut ctx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut ctx) {
+            return output;
+        }
+
+        while !notify.woken.swap(false, Ordering::AcqRel) {
+            let in_flight = RING.with(|ring| {
+                let mut ring = ring.borrow_mut();
+                if let Err(err) = ring.submit(true) {
+                    panic!("submitting to the local driver failed: {}", err);
+                }
+                ring.in_flight
+            });
+            if in_flight == 0 && !notify.woken.load(Ordering::Acquire) {
+                thread::park();
+            }
+        }
+    }
+}
+
+/// Whether the current thread is running [`block_on`], so that IO on the local driver will make
+/// progress.
+pub fn is_blocking() -> bool {
+    BLOCKING.with(Cell::get)
+}
+
+/// Restores whether the thread was already running `block_on` when a call to it returns.
+struct Blocking(bool);
+
+impl Drop for Blocking {
+    fn drop(&mut self) {
+        BLOCKING.with(|blocking| blocking.set(self.0));
+    }
+}
+
+struct Notify {
+    thread: Thread,
+    woken: AtomicBool,
+    wake: Arc<WakeFd>,
+}
+
+impl Wake for Notify {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let woken = self.woken.swap(true, Ordering::AcqRel);
+        self.thread.unpark();
+        // The thread may be waiting for completions of its ring, rather than parked; it cannot
+        // be if this is the thread itself.
+        if !woken && thread::current().id() != self.thread.id() {
+            self.wake.wake();
+        }
+    }
+}
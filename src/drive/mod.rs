@@ -1,9 +1,13 @@
 //! Drive IO on io-uring
 
 pub mod demo;
+pub mod local;
+mod boxed;
+mod default;
 
 use std::io;
 use std::marker::PhantomData;
+use std::os::unix::io::RawFd;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
@@ -12,6 +16,8 @@ use crate::{Submission, Event};
 use iou::{SQE, SQEs};
 
 pub use crate::ring::completion::complete;
+pub use boxed::BoxDriver;
+pub use default::{DefaultDriver, set_default_driver};
 
 /// A completion which will be used to wake the task waiting on this event.
 ///
@@ -31,6 +37,16 @@ impl<'cx> Completion<'cx> {
 
         Completion { real, marker: PhantomData }
     }
+
+    /// Report the CQE of the timeout linked to the event, if there is one, to its completion.
+    pub(crate) fn linked(self, timeout: Option<SQE<'_>>) -> Completion<'cx> {
+        if let Some(mut timeout) = timeout {
+            unsafe {
+                timeout.set_user_data(self.real.link_timeout());
+            }
+        }
+        self
+    }
 }
 
 /// Implemented by drivers for io-uring.
@@ -78,7 +94,90 @@ pub trait Drive {
         ctx: &mut Context<'_>,
     ) -> Poll<io::Result<u32>>;
 
+    /// The file descriptor of the io-uring instance this driver submits events to.
+    ///
+    /// Drivers which return an fd here allow other threads to post messages to their instance
+    /// with a [`RingHandle`](crate::ring::RingHandle). The instance's completions must be
+    /// processed by ringbahn's [`complete`] function, without any other input from the task which
+    /// receives the message; since this method is safe to implement, code which relies on that
+    /// goes through the unsafe [`RingHandle::of`](crate::ring::RingHandle::of). By default,
+    /// drivers do not expose their instance.
+    fn ring_fd(&self) -> Option<RawFd> {
+        None
+    }
+
     fn submit<E: Event>(self, event: E) -> Submission<E, Self> where Self: Sized {
         Submission::new(event, self)
     }
 }
+
+// A driver borrowed mutably or owned by a box can be driven through it. A driver shared through a
+// reference or an `Arc` cannot, since preparing events requires unique access to the driver; it
+// can be shared with a [`SharedRing`](crate::ring::SharedRing) instead, whose shared references
+// implement `Drive`.
+
+impl<D: Drive + Unpin> Drive for &mut D {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        Pin::new(&mut **self.get_mut()).poll_prepare(ctx, count, prepare)
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        Pin::new(&mut **self.get_mut()).poll_submit(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        (**self).ring_fd()
+    }
+}
+
+impl<D: Drive + Unpin> Drive for Box<D> {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        Pin::new(&mut **self.get_mut()).poll_prepare(ctx, count, prepare)
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        Pin::new(&mut **self.get_mut()).poll_submit(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        (**self).ring_fd()
+    }
+}
+
+impl<D: Drive> Drive for Pin<Box<D>> {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        self.get_mut().as_mut().poll_prepare(ctx, count, prepare)
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        self.get_mut().as_mut().poll_submit(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        (**self).ring_fd()
+    }
+}
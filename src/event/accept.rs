@@ -1,9 +1,12 @@
 use std::mem::ManuallyDrop;
 use std::os::unix::io::RawFd;
 
-use iou::sqe::{SockFlag, SockAddrStorage};
+use iou::sqe::SockFlag;
 use iou::registrar::UringFd;
 
+use crate::net::SockAddrStorage;
+use crate::sys;
+
 use super::{Event, SQE, SQEs, Cancellation};
 
 pub struct Accept<FD = RawFd> {
@@ -16,8 +19,39 @@ impl<FD: UringFd + Copy> Event for Accept<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_accept(self.fd, self.addr.as_deref_mut(), self.flags);
+        let mut sqe = sqs.next().unwrap();
+        let addr = self.addr.as_deref_mut().map(SockAddrStorage::as_iou_mut);
+        sqe.prep_accept(self.fd, addr, self.flags);
+        sqe
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        Cancellation::from(ManuallyDrop::into_inner(this).addr)
+    }
+}
+
+/// Accept a connection directly into the fixed-file table, rather than installing it in the
+/// process's file descriptor table.
+///
+/// If `file_index` is `None`, the kernel allocates a free slot, and the event completes with the
+/// index of that slot. Otherwise, the connection is installed in the given slot, replacing any file
+/// already there, and the event completes with 0. Either way, a fixed-file table must already have
+/// been registered with the io-uring instance.
+pub struct AcceptDirect<FD = RawFd> {
+    pub addr: Option<Box<SockAddrStorage>>,
+    pub fd: FD,
+    pub flags: SockFlag,
+    pub file_index: Option<u32>,
+}
+
+impl<FD: UringFd + Copy> Event for AcceptDirect<FD> {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let addr = self.addr.as_deref_mut().map(SockAddrStorage::as_iou_mut);
+        sqe.prep_accept(self.fd, addr, self.flags);
+        sys::set_file_index(&mut sqe, self.file_index);
         sqe
     }
 
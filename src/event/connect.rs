@@ -1,9 +1,11 @@
 use std::mem::ManuallyDrop;
 use std::os::unix::io::RawFd;
 
-use iou::sqe::SockAddr;
 use iou::registrar::UringFd;
 
+use crate::net::SockAddr;
+use crate::sys;
+
 use super::{Event, SQE, SQEs, Cancellation};
 
 pub struct Connect<FD = RawFd> {
@@ -15,8 +17,12 @@ impl<FD: UringFd + Copy> Event for Connect<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_connect(self.fd, &mut *self.addr);
+        let mut sqe = sqs.next().unwrap();
+        let (addr, len) = self.addr.as_raw();
+        let addr = addr as *const libc::sockaddr as u64;
+        let opcode = uring_sys::IoRingOp::IORING_OP_CONNECT as u8;
+        sys::prep_raw(&mut sqe, opcode, self.fd.as_raw_fd(), addr, 0, len as u64);
+        self.fd.update_sqe(&mut sqe);
         sqe
     }
 
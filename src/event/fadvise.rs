@@ -3,12 +3,14 @@ use std::os::unix::io::RawFd;
 use iou::sqe::PosixFadviseAdvice;
 use iou::registrar::UringFd;
 
+use crate::sys;
+
 use super::{Event, SQE, SQEs};
 
 pub struct Fadvise<FD = RawFd> {
     pub fd: FD,
     pub offset: u64,
-    pub size: u64,
+    pub size: u32,
     pub flags: PosixFadviseAdvice,
 }
 
@@ -16,8 +18,9 @@ impl<FD: UringFd + Copy> Event for Fadvise<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_fadvise(self.fd, self.offset, self.size, self.flags);
+        let mut sqe = sqs.next().unwrap();
+        sys::prep_fadvise(&mut sqe, self.fd.as_raw_fd(), self.offset, self.size, self.flags as i32);
+        self.fd.update_sqe(&mut sqe);
         sqe
     }
 }
@@ -16,7 +16,7 @@ impl<FD: UringFd + Copy> Event for Fallocate<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_fallocate(self.fd, self.offset, self.size, self.flags);
         sqe
     }
@@ -12,7 +12,7 @@ impl Event for FilesUpdate {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_files_update(&self.files[..], self.offset);
         sqe
     }
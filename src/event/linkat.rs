@@ -0,0 +1,66 @@
+use std::ffi::CString;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::os::unix::io::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
+
+/// Create a hard link at `new_path` to the file at `old_path`, like `linkat(2)`.
+///
+/// `flags` can contain `AT_SYMLINK_FOLLOW`, to link to the target of a symbolic link at
+/// `old_path` rather than to the link itself.
+pub struct LinkAt {
+    pub old_dir_fd: RawFd,
+    pub old_path: CString,
+    pub new_dir_fd: RawFd,
+    pub new_path: CString,
+    pub flags: i32,
+}
+
+impl LinkAt {
+    pub fn without_dir(old_path: impl AsRef<Path>, new_path: impl AsRef<Path>, flags: i32)
+        -> LinkAt
+    {
+        let old_path = CString::new(old_path.as_ref().as_os_str().as_bytes()).unwrap();
+        let new_path = CString::new(new_path.as_ref().as_os_str().as_bytes()).unwrap();
+        LinkAt {
+            old_dir_fd: libc::AT_FDCWD,
+            new_dir_fd: libc::AT_FDCWD,
+            old_path, new_path, flags,
+        }
+    }
+}
+
+impl Event for LinkAt {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let (old, new, flags) = (self.old_path.as_ptr(), self.new_path.as_ptr(), self.flags);
+        sys::prep_linkat(&mut sqe, self.old_dir_fd, old, self.new_dir_fd, new, flags as u32);
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let (old_dir_fd, new_dir_fd, flags) = (self.old_dir_fd, self.new_dir_fd, self.flags);
+        let old_path = self.old_path.as_ptr() as usize;
+        let new_path = self.new_path.as_ptr() as usize;
+        Some(Emulation::new(sys::IORING_OP_LINKAT, move || {
+            let old_path = old_path as *const libc::c_char;
+            let new_path = new_path as *const libc::c_char;
+            match libc::linkat(old_dir_fd, old_path, new_dir_fd, new_path, flags) {
+                -1  => Err(io::Error::last_os_error()),
+                _   => Ok(0),
+            }
+        }))
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        let this = ManuallyDrop::into_inner(this);
+        Cancellation::from((this.old_path, this.new_path))
+    }
+}
@@ -0,0 +1,50 @@
+use std::ffi::CString;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::os::unix::io::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use iou::sqe::Mode;
+
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
+
+/// Create a directory, like `mkdirat(2)`.
+pub struct MkdirAt {
+    pub dir_fd: RawFd,
+    pub path: CString,
+    pub mode: Mode,
+}
+
+impl MkdirAt {
+    pub fn without_dir(path: impl AsRef<Path>, mode: Mode) -> MkdirAt {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+        MkdirAt { dir_fd: libc::AT_FDCWD, path, mode }
+    }
+}
+
+impl Event for MkdirAt {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sys::prep_mkdirat(&mut sqe, self.dir_fd, self.path.as_ptr(), self.mode.bits());
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let (dir_fd, path, mode) = (self.dir_fd, self.path.as_ptr() as usize, self.mode.bits());
+        Some(Emulation::new(sys::IORING_OP_MKDIRAT, move || {
+            match libc::mkdirat(dir_fd, path as *const libc::c_char, mode) {
+                -1  => Err(io::Error::last_os_error()),
+                _   => Ok(0),
+            }
+        }))
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        Cancellation::from(ManuallyDrop::into_inner(this).path)
+    }
+}
@@ -8,25 +8,35 @@ mod fadvise;
 mod fallocate;
 mod files_update;
 mod fsync;
+mod linkat;
+mod mkdirat;
+mod msg;
 mod openat;
+mod poll_add;
 mod provide_buffers;
 mod read;
 mod readv;
 mod recv;
+mod renameat;
+mod rw_flags;
 mod send;
+mod socket;
 mod splice;
 mod statx;
+mod symlinkat;
 mod timeout;
+mod unlinkat;
 mod write;
 mod writev;
 
+use std::io;
 use std::mem::ManuallyDrop;
 
-use iou::{SQE, SQEs};
+pub use iou::{SQE, SQEs};
 
 use crate::ring::Cancellation;
 
-pub use accept::Accept;
+pub use accept::{Accept, AcceptDirect};
 pub use close::Close;
 pub use connect::Connect;
 pub use epoll_ctl::EpollCtl;
@@ -34,18 +44,57 @@ pub use fadvise::Fadvise;
 pub use fallocate::Fallocate;
 pub use files_update::FilesUpdate;
 pub use fsync::Fsync;
-pub use openat::OpenAt;
+pub use linkat::LinkAt;
+pub use mkdirat::MkdirAt;
+pub use msg::{SendMsg, RecvMsg};
+pub use openat::{OpenAt, OpenAt2, OpenAtDirect, OpenHow, ResolveFlags};
+pub use poll_add::PollAdd;
 pub use provide_buffers::{ProvideBuffers, RemoveBuffers};
-pub use read::{Read, ReadFixed};
+pub use read::{Read, ReadFixed, ReadSelect};
 pub use readv::ReadVectored;
-pub use recv::Recv;
+pub use recv::{Recv, RecvSelect};
+pub use renameat::RenameAt;
+pub use rw_flags::{RwFlags, WithRwFlags};
 pub use send::Send;
+pub use socket::{Socket, SocketDirect};
 pub use splice::Splice;
 pub use statx::Statx;
+pub use symlinkat::SymlinkAt;
 pub use timeout::{Timeout, StaticTimeout};
+pub use unlinkat::UnlinkAt;
 pub use write::{Write, WriteFixed};
 pub use writev::WriteVectored;
 
+pub(crate) use timeout::timespec;
+
+/// The id of the buffer the kernel chose for a buffer-select event, from the flags of its CQE.
+pub(crate) fn selected_buffer(flags: u32) -> Option<u16> {
+    const IORING_CQE_F_BUFFER: u32 = 1;
+    const IORING_CQE_BUFFER_SHIFT: u32 = 16;
+    match flags & IORING_CQE_F_BUFFER {
+        0   => None,
+        _   => Some((flags >> IORING_CQE_BUFFER_SHIFT) as u16),
+    }
+}
+
+/// A blocking implementation of an event, run on a pool of threads if the kernel does not support
+/// the opcode the event prepares.
+pub struct Emulation {
+    pub(crate) opcode: u8,
+    pub(crate) run: Box<dyn FnOnce() -> io::Result<u32> + std::marker::Send>,
+}
+
+impl Emulation {
+    /// Construct an emulation of an event which prepares `opcode`.
+    ///
+    /// `run` performs the event with syscalls, returning what the event's CQE would have.
+    pub fn new(opcode: u8, run: impl FnOnce() -> io::Result<u32> + std::marker::Send + 'static)
+        -> Emulation
+    {
+        Emulation { opcode, run: Box::new(run) }
+    }
+}
+
 /// An IO event that can be scheduled on an io-uring driver.
 ///
 /// ## Safety
@@ -78,6 +127,24 @@ pub trait Event {
     /// completed.
     unsafe fn prepare<'a>(&mut self, sqs: &mut SQEs<'a>) -> SQE<'a>;
 
+    /// Record the flags of the CQE with which this event completed.
+    ///
+    /// This is called before the event is returned to the user, so events which report
+    /// information in the flags of their completion, like the buffer chosen for a buffer-select
+    /// read, can store it. By default, the flags are ignored.
+    fn set_completion_flags(&mut self, _flags: u32) { }
+
+    /// Return a blocking emulation of this event, which a `Submission` runs on a pool of threads
+    /// instead of preparing the event if the kernel does not support its opcode. By default,
+    /// events are not emulated.
+    ///
+    /// ## Safety
+    ///
+    /// When this method is called, the caller maintains the same guarantees as for `prepare`: the
+    /// data contained by this event will not be accessed again until the emulation has completed,
+    /// or interest in the event has been cancelled.
+    unsafe fn emulate(&mut self) -> Option<Emulation> { None }
+
     /// Return the cancellation callback for this event.
     ///
     /// If this event is cancelled, this callback will be stored with the completion to be dropped
@@ -87,3 +154,14 @@ pub trait Event {
         Cancellation::from(())
     }
 }
+
+/// An event which can be moved while the kernel is still using it, so that a cancelled
+/// [`Submission`](crate::Submission) can hand it back with
+/// [`reclaim_into`](crate::Submission::reclaim_into) once it completes.
+///
+/// ## Safety
+///
+/// Every address the event passes to the kernel in `prepare` must point to memory which the
+/// event owns on the heap, like its buffers, and none into the event itself: a reclaimed event is
+/// moved out of its submission while the kernel may still be reading or writing that memory.
+pub unsafe trait Reclaimable: Event { }
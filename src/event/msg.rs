@@ -0,0 +1,238 @@
+use std::io;
+use std::mem::{self, ManuallyDrop};
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+use iou::sqe::MsgFlags;
+use iou::registrar::UringFd;
+
+use crate::net::SockAddr;
+use crate::sys;
+
+use super::{Event, SQE, SQEs, Cancellation};
+
+/// The header of a message, and the storage for its peer's address, which the kernel reads and
+/// writes until the event completes.
+struct MsgHdr {
+    hdr: libc::msghdr,
+    name: libc::sockaddr_storage,
+}
+
+unsafe impl Send for MsgHdr { }
+unsafe impl Sync for MsgHdr { }
+
+impl MsgHdr {
+    fn new() -> Box<MsgHdr> {
+        unsafe { Box::new(mem::zeroed()) }
+    }
+
+    /// Point the header at `bufs` and `control`, returning its address.
+    fn prepare(&mut self, bufs: &mut [Box<[u8]>], control: &mut [u8], namelen: libc::socklen_t)
+        -> *mut libc::msghdr
+    {
+        self.hdr = unsafe { mem::zeroed() };
+        if namelen > 0 {
+            self.hdr.msg_name = &mut self.name as *mut libc::sockaddr_storage as *mut libc::c_void;
+            self.hdr.msg_namelen = namelen;
+        }
+        // Box<[u8]> has the same layout as libc::iovec, as in ReadVectored
+        self.hdr.msg_iov = bufs.as_mut_ptr() as *mut libc::iovec;
+        self.hdr.msg_iovlen = bufs.len() as _;
+        if !control.is_empty() {
+            self.hdr.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            self.hdr.msg_controllen = control.len() as _;
+        }
+        &mut self.hdr
+    }
+}
+
+/// The length of the header of a control message, rounded up to the alignment of the data
+/// which follows it.
+fn cmsg_header_len() -> usize {
+    unsafe { libc::CMSG_LEN(0) as usize }
+}
+
+/// A `sendmsg` event, which sends the data in `bufs` as one message, with the ancillary data in
+/// `control`.
+///
+/// The data is sent to `addr` if it is set, which is how datagrams are sent on an unconnected
+/// socket. File descriptors can be passed over a unix socket with [`SendMsg::set_fds`].
+///
+/// The event owns its buffers, the header pointing at them, and the storage for the address;
+/// if it is cancelled, all of them are kept alive until the kernel is done with them.
+pub struct SendMsg<FD = RawFd> {
+    pub fd: FD,
+    pub bufs: Box<[Box<[u8]>]>,
+    pub addr: Option<SockAddr>,
+    pub control: Box<[u8]>,
+    pub flags: MsgFlags,
+    hdr: Box<MsgHdr>,
+}
+
+impl<FD> SendMsg<FD> {
+    /// Send `bufs` on the socket `fd`, to its connected peer and with no ancillary data.
+    pub fn new(fd: FD, bufs: Box<[Box<[u8]>]>) -> SendMsg<FD> {
+        SendMsg {
+            fd, bufs,
+            addr: None,
+            control: Box::new([]),
+            flags: MsgFlags::empty(),
+            hdr: MsgHdr::new(),
+        }
+    }
+
+    /// Set the ancillary data to an `SCM_RIGHTS` control message carrying `fds`.
+    ///
+    /// The descriptors stay open in this process; the peer receives duplicates of them.
+    pub fn set_fds(&mut self, fds: &[RawFd]) {
+        let len = mem::size_of_val(fds);
+        let mut control = vec![0; unsafe { libc::CMSG_SPACE(len as u32) as usize }];
+        let cmsg = libc::cmsghdr {
+            cmsg_len: unsafe { libc::CMSG_LEN(len as u32) as _ },
+            cmsg_level: libc::SOL_SOCKET,
+            cmsg_type: libc::SCM_RIGHTS,
+        };
+        unsafe { ptr::write_unaligned(control.as_mut_ptr() as *mut libc::cmsghdr, cmsg); }
+        for (i, fd) in fds.iter().enumerate() {
+            let offset = cmsg_header_len() + i * mem::size_of::<RawFd>();
+            control[offset..offset + mem::size_of::<RawFd>()].copy_from_slice(&fd.to_ne_bytes());
+        }
+        self.control = control.into_boxed_slice();
+    }
+}
+
+impl<FD: UringFd + Copy> Event for SendMsg<FD> {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let namelen = match &self.addr {
+            Some(addr)  => addr.write_raw(&mut self.hdr.name),
+            None        => 0,
+        };
+        let hdr = self.hdr.prepare(&mut self.bufs, &mut self.control, namelen);
+        let opcode = uring_sys::IoRingOp::IORING_OP_SENDMSG as u8;
+        sys::prep_raw(&mut sqe, opcode, self.fd.as_raw_fd(), hdr as u64, 1, 0);
+        sys::set_rw_flags(&mut sqe, self.flags.bits());
+        self.fd.update_sqe(&mut sqe);
+        sqe
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        let this = ManuallyDrop::into_inner(this);
+        Cancellation::from(Box::new((this.bufs, this.control, this.hdr)))
+    }
+}
+
+/// A `recvmsg` event, which receives one message into `bufs`, with its ancillary data in
+/// `control`.
+///
+/// Once the event completes, the address of the peer which sent the message is returned by
+/// [`RecvMsg::addr`], and the ancillary data it carried by [`RecvMsg::control_data`]. To receive
+/// file descriptors, make room for them with [`RecvMsg::reserve_fds`], and read them with
+/// [`RecvMsg::received_fds`]; setting `MSG_CMSG_CLOEXEC` in `flags` receives them with
+/// `O_CLOEXEC` set.
+///
+/// The event owns its buffers, the header pointing at them, and the storage for the address;
+/// if it is cancelled, all of them are kept alive until the kernel is done with them.
+pub struct RecvMsg<FD = RawFd> {
+    pub fd: FD,
+    pub bufs: Box<[Box<[u8]>]>,
+    pub control: Box<[u8]>,
+    pub flags: MsgFlags,
+    hdr: Box<MsgHdr>,
+}
+
+impl<FD> RecvMsg<FD> {
+    /// Receive a message from the socket `fd` into `bufs`, with no room for ancillary data.
+    pub fn new(fd: FD, bufs: Box<[Box<[u8]>]>) -> RecvMsg<FD> {
+        RecvMsg {
+            fd, bufs,
+            control: Box::new([]),
+            flags: MsgFlags::empty(),
+            hdr: MsgHdr::new(),
+        }
+    }
+
+    /// Make `control` large enough for an `SCM_RIGHTS` control message carrying `n` file
+    /// descriptors.
+    pub fn reserve_fds(&mut self, n: usize) {
+        let len = n * mem::size_of::<RawFd>();
+        let space = unsafe { libc::CMSG_SPACE(len as u32) as usize };
+        self.control = vec![0; space].into_boxed_slice();
+    }
+
+    /// The address of the peer which sent the message, if the socket reported one.
+    ///
+    /// This is only meaningful once the event has completed successfully.
+    pub fn addr(&self) -> io::Result<Option<SockAddr>> {
+        match self.hdr.hdr.msg_namelen {
+            0   => Ok(None),
+            len => SockAddr::read_raw(&self.hdr.name, len).map(Some),
+        }
+    }
+
+    /// The ancillary data the kernel wrote into `control`.
+    ///
+    /// This is only meaningful once the event has completed successfully.
+    pub fn control_data(&self) -> &[u8] {
+        let len = self.hdr.hdr.msg_controllen;
+        &self.control[..std::cmp::min(len, self.control.len())]
+    }
+
+    /// The flags the kernel set on the message it received, like `MSG_TRUNC` if the message was
+    /// larger than `bufs`, or `MSG_CTRUNC` if its ancillary data did not fit in `control`.
+    pub fn msg_flags(&self) -> MsgFlags {
+        MsgFlags::from_bits_truncate(self.hdr.hdr.msg_flags)
+    }
+
+    /// The file descriptors received in `SCM_RIGHTS` control messages. They belong to the
+    /// caller, and must be closed by it.
+    pub fn received_fds(&self) -> Vec<RawFd> {
+        let control = self.control_data();
+        let header_len = mem::size_of::<libc::cmsghdr>();
+        let mut fds = Vec::new();
+        let mut offset = 0;
+        while offset + header_len <= control.len() {
+            let cmsg = unsafe {
+                ptr::read_unaligned(control[offset..].as_ptr() as *const libc::cmsghdr)
+            };
+            let len = cmsg.cmsg_len as usize;
+            if len < cmsg_header_len() || offset + len > control.len() {
+                break;
+            }
+            if cmsg.cmsg_level == libc::SOL_SOCKET && cmsg.cmsg_type == libc::SCM_RIGHTS {
+                let data = &control[offset + cmsg_header_len()..offset + len];
+                for fd in data.chunks_exact(mem::size_of::<RawFd>()) {
+                    let mut bytes = [0; mem::size_of::<RawFd>()];
+                    bytes.copy_from_slice(fd);
+                    fds.push(RawFd::from_ne_bytes(bytes));
+                }
+            }
+            // Control messages are padded to the alignment of their headers
+            let align = mem::size_of::<usize>();
+            offset += (len + align - 1) & !(align - 1);
+        }
+        fds
+    }
+}
+
+impl<FD: UringFd + Copy> Event for RecvMsg<FD> {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        let hdr = self.hdr.prepare(&mut self.bufs, &mut self.control, namelen);
+        let opcode = uring_sys::IoRingOp::IORING_OP_RECVMSG as u8;
+        sys::prep_raw(&mut sqe, opcode, self.fd.as_raw_fd(), hdr as u64, 1, 0);
+        sys::set_rw_flags(&mut sqe, self.flags.bits());
+        self.fd.update_sqe(&mut sqe);
+        sqe
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        let this = ManuallyDrop::into_inner(this);
+        Cancellation::from(Box::new((this.bufs, this.control, this.hdr)))
+    }
+}
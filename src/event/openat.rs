@@ -1,12 +1,16 @@
 use std::ffi::CString;
-use std::mem::ManuallyDrop;
+use std::io;
+use std::mem::{self, ManuallyDrop};
+use std::ops::BitOr;
 use std::os::unix::io::RawFd;
 use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 
 use iou::sqe::{Mode, OFlag};
 
-use super::{Event, SQE, SQEs, Cancellation};
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
 
 pub struct OpenAt {
     pub path: CString,
@@ -26,12 +30,172 @@ impl Event for OpenAt {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_openat(self.dir_fd, &*self.path, self.flags, self.mode);
+        let mut sqe = sqs.next().unwrap();
+        sqe.prep_openat(self.dir_fd, &self.path, self.flags, self.mode);
         sqe
     }
 
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let opcode = uring_sys::IoRingOp::IORING_OP_OPENAT as u8;
+        let path = self.path.as_ptr() as usize;
+        let (dir_fd, flags, mode) = (self.dir_fd, self.flags.bits(), self.mode.bits());
+        Some(Emulation::new(opcode, move || {
+            match libc::openat(dir_fd, path as *const libc::c_char, flags, mode) {
+                -1  => Err(io::Error::last_os_error()),
+                fd  => Ok(fd as u32),
+            }
+        }))
+    }
+
     fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
         Cancellation::from(ManuallyDrop::into_inner(this).path)
     }
 }
+
+/// Open a file directly into the fixed-file table, rather than installing it in the process's
+/// file descriptor table.
+///
+/// If `file_index` is `None`, the kernel allocates a free slot, and the event completes with the
+/// index of that slot. Otherwise, the file is installed in the given slot, replacing any file
+/// already there, and the event completes with 0. Either way, a fixed-file table must already have
+/// been registered with the io-uring instance. `O_CLOEXEC` is not supported for direct opens.
+pub struct OpenAtDirect {
+    pub path: CString,
+    pub dir_fd: RawFd,
+    pub flags: OFlag,
+    pub mode: Mode,
+    pub file_index: Option<u32>,
+}
+
+impl OpenAtDirect {
+    pub fn without_dir(path: impl AsRef<Path>, flags: OFlag, mode: Mode, file_index: Option<u32>)
+        -> OpenAtDirect
+    {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+        OpenAtDirect { path, dir_fd: libc::AT_FDCWD, flags, mode, file_index }
+    }
+}
+
+impl Event for OpenAtDirect {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sqe.prep_openat(self.dir_fd, &self.path, self.flags, self.mode);
+        sys::set_file_index(&mut sqe, self.file_index);
+        sqe
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        Cancellation::from(ManuallyDrop::into_inner(this).path)
+    }
+}
+
+/// Open a file, like `openat2(2)`, restricting how its path is resolved
+///
+/// With [`ResolveFlags::BENEATH`] or [`ResolveFlags::IN_ROOT`], the path cannot escape `dir_fd`,
+/// so a server can open paths supplied by its users relative to the directory it serves.
+pub struct OpenAt2 {
+    pub path: CString,
+    pub dir_fd: RawFd,
+    pub how: Box<OpenHow>,
+}
+
+/// The arguments of `openat2(2)`, as in `struct open_how`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct OpenHow {
+    pub flags: u64,
+    /// The permissions of a created file. This must be 0 unless `flags` contains `O_CREAT` or
+    /// `O_TMPFILE`.
+    pub mode: u64,
+    pub resolve: u64,
+}
+
+impl OpenAt2 {
+    pub fn without_dir(path: impl AsRef<Path>, flags: OFlag, mode: Mode, resolve: ResolveFlags)
+        -> OpenAt2
+    {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+        let how = OpenHow {
+            flags: flags.bits() as u64,
+            mode: mode.bits() as u64,
+            resolve: resolve.bits(),
+        };
+        OpenAt2 { path, dir_fd: libc::AT_FDCWD, how: Box::new(how) }
+    }
+}
+
+impl Event for OpenAt2 {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let how = &*self.how as *const OpenHow as *const libc::c_void;
+        let size = mem::size_of::<OpenHow>() as u32;
+        sys::prep_openat2(&mut sqe, self.dir_fd, self.path.as_ptr(), how, size);
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let (dir_fd, path) = (self.dir_fd, self.path.as_ptr() as usize);
+        let how = &*self.how as *const OpenHow as usize;
+        Some(Emulation::new(sys::IORING_OP_OPENAT2, move || {
+            let size = mem::size_of::<OpenHow>();
+            match libc::syscall(sys::SYS_OPENAT2, dir_fd, path, how, size) {
+                -1  => Err(io::Error::last_os_error()),
+                fd  => Ok(fd as u32),
+            }
+        }))
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        let this = ManuallyDrop::into_inner(this);
+        Cancellation::from((this.path, this.how))
+    }
+}
+
+/// Restrictions on how the path passed to `openat2(2)` is resolved
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ResolveFlags(u64);
+
+impl ResolveFlags {
+    /// Fail with `EXDEV` if the path crosses a mount point.
+    pub const NO_XDEV: ResolveFlags = ResolveFlags(0x01);
+    /// Fail with `ELOOP` if the path contains a "magic link", like those in `/proc/self/fd`.
+    pub const NO_MAGICLINKS: ResolveFlags = ResolveFlags(0x02);
+    /// Fail with `ELOOP` if the path contains a symbolic link.
+    pub const NO_SYMLINKS: ResolveFlags = ResolveFlags(0x04);
+    /// Fail with `EXDEV` if the path, or a symbolic link in it, resolves outside of the
+    /// directory.
+    pub const BENEATH: ResolveFlags = ResolveFlags(0x08);
+    /// Resolve the path as if the directory were the root of the filesystem, so `..` and
+    /// absolute symbolic links cannot leave it.
+    pub const IN_ROOT: ResolveFlags = ResolveFlags(0x10);
+    /// Fail with `EAGAIN` unless the path can be resolved from the kernel's caches.
+    pub const CACHED: ResolveFlags = ResolveFlags(0x20);
+
+    pub const fn empty() -> ResolveFlags {
+        ResolveFlags(0)
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: ResolveFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ResolveFlags {
+    type Output = ResolveFlags;
+
+    fn bitor(self, other: ResolveFlags) -> ResolveFlags {
+        ResolveFlags(self.0 | other.0)
+    }
+}
@@ -0,0 +1,31 @@
+use std::os::unix::io::RawFd;
+
+use iou::sqe::PollFlags;
+use iou::registrar::UringFd;
+
+use super::{Event, SQE, SQEs};
+
+/// An event which completes when a file descriptor is ready, like a single `poll(2)` on it.
+///
+/// The result of the event is the mask of events which are ready, as `revents` would be, and
+/// can be read with `PollFlags::from_bits_truncate`. The request is one-shot: it completes the
+/// first time one of `flags` is ready, and must be submitted again to wait again. This lets
+/// code written against readiness, like adapters for libraries which expect to register file
+/// descriptors with epoll, be driven by the same ring as other IO.
+///
+/// A submission of this event which is dropped before it completes is cancelled like any other
+/// event; the kernel removes the poll request, as `IORING_OP_POLL_REMOVE` would.
+pub struct PollAdd<FD = RawFd> {
+    pub fd: FD,
+    pub flags: PollFlags,
+}
+
+impl<FD: UringFd + Copy> Event for PollAdd<FD> {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sqe.prep_poll_add(self.fd, self.flags);
+        sqe
+    }
+}
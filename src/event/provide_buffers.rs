@@ -14,7 +14,7 @@ impl Event for ProvideBuffers {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_provide_buffers(&mut self.bufs[..], self.count, self.group, self.index);
         sqe
     }
@@ -33,7 +33,7 @@ impl Event for RemoveBuffers {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_remove_buffers(self.count, self.group);
         sqe
     }
@@ -2,8 +2,11 @@ use std::mem::ManuallyDrop;
 use std::os::unix::io::RawFd;
 
 use iou::registrar::{UringFd, RegisteredBuf};
+use iou::sqe::BufferGroupId;
 
-use super::{Event, SQE, SQEs, Cancellation};
+use crate::sys;
+
+use super::{Event, Reclaimable, SQE, SQEs, Cancellation};
 
 /// A basic read event.
 pub struct Read<FD = RawFd> {
@@ -16,7 +19,7 @@ impl<FD: UringFd + Copy> Event for Read<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_read(self.fd, &mut self.buf[..], self.offset);
         sqe
     }
@@ -26,6 +29,8 @@ impl<FD: UringFd + Copy> Event for Read<FD> {
     }
 }
 
+unsafe impl<FD: UringFd + Copy> Reclaimable for Read<FD> { }
+
 pub struct ReadFixed<FD = RawFd> {
     pub fd: FD,
     pub buf: RegisteredBuf,
@@ -36,7 +41,7 @@ impl<FD: UringFd + Copy> Event for ReadFixed<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_read(self.fd, self.buf.as_mut(), self.offset);
         sqe
     }
@@ -45,3 +50,34 @@ impl<FD: UringFd + Copy> Event for ReadFixed<FD> {
         Cancellation::from(ManuallyDrop::into_inner(this).buf)
     }
 }
+
+/// A read into a buffer chosen by the kernel from a group of provided buffers.
+///
+/// No buffer is tied up while the read waits for data: the kernel picks one from the group,
+/// which must have been provided with [`ProvideBuffers`](super::ProvideBuffers), once the read
+/// is ready to complete. The id of the chosen buffer is stored in `buffer`, and the result of the
+/// event is the number of bytes read into it.
+pub struct ReadSelect<FD = RawFd> {
+    pub fd: FD,
+    pub group: BufferGroupId,
+    pub len: u32,
+    pub offset: u64,
+    pub buffer: Option<u16>,
+}
+
+impl<FD: UringFd + Copy> Event for ReadSelect<FD> {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let opcode = uring_sys::IoRingOp::IORING_OP_READ as u8;
+        sys::prep_raw(&mut sqe, opcode, self.fd.as_raw_fd(), 0, self.len, self.offset);
+        sys::set_buffer_group(&mut sqe, self.group);
+        self.fd.update_sqe(&mut sqe);
+        sqe
+    }
+
+    fn set_completion_flags(&mut self, flags: u32) {
+        self.buffer = super::selected_buffer(flags);
+    }
+}
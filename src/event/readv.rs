@@ -4,7 +4,7 @@ use std::os::unix::io::RawFd;
 
 use iou::registrar::UringFd;
 
-use super::{Event, SQE, SQEs, Cancellation};
+use super::{Event, Reclaimable, SQE, SQEs, Cancellation};
 
 /// A `readv` event.
 pub struct ReadVectored<FD = RawFd> {
@@ -14,7 +14,24 @@ pub struct ReadVectored<FD = RawFd> {
 }
 
 impl<FD> ReadVectored<FD> {
-    fn as_iovecs(buffers: &mut [Box<[u8]>]) -> &mut [IoSliceMut] {
+    /// The parts of the buffers which were filled by a read of `len` bytes, the result of the
+    /// event.
+    ///
+    /// Segments are filled in order, so every segment but the last returned is filled
+    /// completely. Segments past the end of the data are not returned.
+    pub fn filled(&self, len: usize) -> impl Iterator<Item = &[u8]> + '_ {
+        let mut remaining = len;
+        self.bufs.iter().map_while(move |buf| {
+            if remaining == 0 {
+                return None;
+            }
+            let n = std::cmp::min(buf.len(), remaining);
+            remaining -= n;
+            Some(&buf[..n])
+        })
+    }
+
+    fn as_iovecs(buffers: &mut [Box<[u8]>]) -> &mut [IoSliceMut<'_>] {
         // Unsafe contract:
         // This pointer cast is defined behaviour because Box<[u8]> (wide pointer)
         // is currently ABI compatible with libc::iovec.
@@ -37,7 +54,7 @@ impl<FD: UringFd + Copy> Event for ReadVectored<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_read_vectored(self.fd, Self::as_iovecs(&mut self.bufs[..]), self.offset);
         sqe
     }
@@ -46,3 +63,5 @@ impl<FD: UringFd + Copy> Event for ReadVectored<FD> {
         Cancellation::from(ManuallyDrop::into_inner(this).bufs)
     }
 }
+
+unsafe impl<FD: UringFd + Copy> Reclaimable for ReadVectored<FD> { }
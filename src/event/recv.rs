@@ -1,10 +1,12 @@
 use std::mem::ManuallyDrop;
 use std::os::unix::io::RawFd;
 
-use iou::sqe::MsgFlags;
+use iou::sqe::{BufferGroupId, MsgFlags};
 use iou::registrar::UringFd;
 
-use super::{Event, SQE, SQEs, Cancellation};
+use crate::sys;
+
+use super::{Event, Reclaimable, SQE, SQEs, Cancellation};
 
 pub struct Recv<FD = RawFd> {
     pub fd: FD,
@@ -16,8 +18,8 @@ impl<FD: UringFd + Copy> Event for Recv<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_recv(self.fd, &mut self.buf[..], self.flags);
+        let mut sqe = sqs.next().unwrap();
+        sys::prep_recv(&mut sqe, self.fd, &mut self.buf[..], self.flags);
         sqe
     }
 
@@ -25,3 +27,35 @@ impl<FD: UringFd + Copy> Event for Recv<FD> {
         Cancellation::from(ManuallyDrop::into_inner(this).buf)
     }
 }
+
+unsafe impl<FD: UringFd + Copy> Reclaimable for Recv<FD> { }
+
+/// A receive into a buffer chosen by the kernel from a group of provided buffers.
+///
+/// Like [`ReadSelect`](super::ReadSelect), this lets many idle sockets wait for data without a
+/// buffer each. The id of the chosen buffer is stored in `buffer`.
+pub struct RecvSelect<FD = RawFd> {
+    pub fd: FD,
+    pub group: BufferGroupId,
+    pub len: u32,
+    pub flags: MsgFlags,
+    pub buffer: Option<u16>,
+}
+
+impl<FD: UringFd + Copy> Event for RecvSelect<FD> {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let opcode = uring_sys::IoRingOp::IORING_OP_RECV as u8;
+        sys::prep_raw(&mut sqe, opcode, self.fd.as_raw_fd(), 0, self.len, 0);
+        sqe.raw_mut().cmd_flags.msg_flags = self.flags.bits() as u32;
+        sys::set_buffer_group(&mut sqe, self.group);
+        self.fd.update_sqe(&mut sqe);
+        sqe
+    }
+
+    fn set_completion_flags(&mut self, flags: u32) {
+        self.buffer = super::selected_buffer(flags);
+    }
+}
@@ -0,0 +1,69 @@
+use std::ffi::CString;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::os::unix::io::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
+
+/// Rename a file, like `renameat2(2)`.
+///
+/// `flags` can contain `RENAME_NOREPLACE` or `RENAME_EXCHANGE`.
+pub struct RenameAt {
+    pub old_dir_fd: RawFd,
+    pub old_path: CString,
+    pub new_dir_fd: RawFd,
+    pub new_path: CString,
+    pub flags: u32,
+}
+
+impl RenameAt {
+    pub fn without_dir(old_path: impl AsRef<Path>, new_path: impl AsRef<Path>, flags: u32)
+        -> RenameAt
+    {
+        let old_path = CString::new(old_path.as_ref().as_os_str().as_bytes()).unwrap();
+        let new_path = CString::new(new_path.as_ref().as_os_str().as_bytes()).unwrap();
+        RenameAt {
+            old_dir_fd: libc::AT_FDCWD,
+            new_dir_fd: libc::AT_FDCWD,
+            old_path, new_path, flags,
+        }
+    }
+}
+
+impl Event for RenameAt {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let (old, new) = (self.old_path.as_ptr(), self.new_path.as_ptr());
+        sys::prep_renameat(&mut sqe, self.old_dir_fd, old, self.new_dir_fd, new, self.flags);
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let (old_dir_fd, new_dir_fd, flags) = (self.old_dir_fd, self.new_dir_fd, self.flags);
+        let old_path = self.old_path.as_ptr() as usize;
+        let new_path = self.new_path.as_ptr() as usize;
+        Some(Emulation::new(sys::IORING_OP_RENAMEAT, move || {
+            let result = libc::syscall(
+                libc::SYS_renameat2,
+                old_dir_fd, old_path as *const libc::c_char,
+                new_dir_fd, new_path as *const libc::c_char,
+                flags,
+            );
+            match result {
+                -1  => Err(io::Error::last_os_error()),
+                _   => Ok(0),
+            }
+        }))
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        let this = ManuallyDrop::into_inner(this);
+        Cancellation::from((this.old_path, this.new_path))
+    }
+}
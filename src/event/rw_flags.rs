@@ -0,0 +1,80 @@
+use std::mem::ManuallyDrop;
+use std::ops::BitOr;
+
+use crate::sys;
+
+use super::{Event, SQE, SQEs, Cancellation};
+
+/// A read or write event submitted with per-operation flags, like `preadv2(2)` and
+/// `pwritev2(2)`
+///
+/// This wraps any of the read and write events, like [`Read`](super::Read) or
+/// [`WriteVectored`](super::WriteVectored), and completes with the same result.
+pub struct WithRwFlags<E> {
+    pub event: E,
+    pub flags: RwFlags,
+}
+
+impl<E: Event> Event for WithRwFlags<E> {
+    fn sqes_needed(&self) -> u32 {
+        self.event.sqes_needed()
+    }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = self.event.prepare(sqs);
+        sys::set_rw_flags(&mut sqe, self.flags.bits());
+        sqe
+    }
+
+    fn set_completion_flags(&mut self, flags: u32) {
+        self.event.set_completion_flags(flags)
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        E::cancel(ManuallyDrop::new(ManuallyDrop::into_inner(this).event))
+    }
+}
+
+/// Flags which change how a single read or write is performed
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RwFlags(i32);
+
+impl RwFlags {
+    /// Poll for the completion of the IO rather than waiting for an interrupt. This only has an
+    /// effect for direct IO on a ring set up with `IORING_SETUP_IOPOLL`.
+    pub const HIPRI: RwFlags = RwFlags(0x01);
+    /// Make a write durable before it completes, as if followed by `fdatasync(2)`.
+    pub const DSYNC: RwFlags = RwFlags(0x02);
+    /// Make a write and the file's metadata durable before it completes, as if followed by
+    /// `fsync(2)`.
+    pub const SYNC: RwFlags = RwFlags(0x04);
+    /// Fail with `EAGAIN` rather than waiting: a read only succeeds if its data is already in the
+    /// page cache.
+    pub const NOWAIT: RwFlags = RwFlags(sys::RWF_NOWAIT);
+    /// Append a write to the end of the file, whatever its offset.
+    pub const APPEND: RwFlags = RwFlags(0x10);
+
+    pub const fn empty() -> RwFlags {
+        RwFlags(0)
+    }
+
+    pub const fn bits(&self) -> i32 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: RwFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for RwFlags {
+    type Output = RwFlags;
+
+    fn bitor(self, other: RwFlags) -> RwFlags {
+        RwFlags(self.0 | other.0)
+    }
+}
@@ -4,7 +4,7 @@ use std::os::unix::io::RawFd;
 use iou::sqe::MsgFlags;
 use iou::registrar::UringFd;
 
-use super::{Event, SQE, SQEs, Cancellation};
+use super::{Event, Reclaimable, SQE, SQEs, Cancellation};
 
 pub struct Send<FD = RawFd> {
     pub fd: FD,
@@ -16,7 +16,7 @@ impl<FD: UringFd + Copy> Event for Send<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_send(self.fd, &self.buf[..], self.flags);
         sqe
     }
@@ -25,3 +25,5 @@ impl<FD: UringFd + Copy> Event for Send<FD> {
         Cancellation::from(ManuallyDrop::into_inner(this).buf)
     }
 }
+
+unsafe impl<FD: UringFd + Copy> Reclaimable for Send<FD> { }
@@ -0,0 +1,65 @@
+use std::io;
+use std::mem::ManuallyDrop;
+
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
+
+/// Create a socket, like `socket(2)`, completing with its file descriptor.
+///
+/// `ty` can include `SOCK_CLOEXEC` and `SOCK_NONBLOCK`. On kernels without `IORING_OP_SOCKET`
+/// (before 5.19), the socket is created with `socket(2)`.
+pub struct Socket {
+    pub domain: i32,
+    pub ty: i32,
+    pub protocol: i32,
+}
+
+impl Event for Socket {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sys::prep_socket(&mut sqe, self.domain, self.ty, self.protocol);
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let Socket { domain, ty, protocol } = *self;
+        Some(Emulation::new(sys::IORING_OP_SOCKET, move || {
+            match libc::socket(domain, ty, protocol) {
+                -1  => Err(io::Error::last_os_error()),
+                fd  => Ok(fd as u32),
+            }
+        }))
+    }
+
+    /// The socket is closed if it is created after the event has been cancelled.
+    fn cancel(_: ManuallyDrop<Self>) -> Cancellation {
+        Cancellation::from(()).close_results()
+    }
+}
+
+/// Create a socket directly into the fixed-file table, rather than installing it in the process's
+/// file descriptor table.
+///
+/// As for [`OpenAtDirect`](super::OpenAtDirect), if `file_index` is `None`, the kernel allocates a
+/// free slot and the event completes with its index; otherwise the socket is installed in the
+/// given slot and the event completes with 0. `SOCK_CLOEXEC` is not supported for direct sockets.
+pub struct SocketDirect {
+    pub domain: i32,
+    pub ty: i32,
+    pub protocol: i32,
+    pub file_index: Option<u32>,
+}
+
+impl Event for SocketDirect {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sys::prep_socket(&mut sqe, self.domain, self.ty, self.protocol);
+        sys::set_file_index(&mut sqe, self.file_index);
+        sqe
+    }
+}
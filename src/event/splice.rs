@@ -1,8 +1,12 @@
+use std::io;
 use std::os::unix::io::RawFd;
+use std::ptr;
 
 use iou::sqe::SpliceFlags;
 
-use super::{Event, SQE, SQEs};
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs};
 
 pub struct Splice {
     pub fd_in: RawFd,
@@ -17,8 +21,24 @@ impl Event for Splice {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_splice(self.fd_in, self.off_in, self.fd_out, self.off_out, self.bytes, self.flags);
+        let mut sqe = sqs.next().unwrap();
+        let (fd_in, off_in, fd_out, off_out) = (self.fd_in, self.off_in, self.fd_out, self.off_out);
+        sys::prep_splice(&mut sqe, fd_in, off_in, fd_out, off_out, self.bytes, self.flags.bits());
         sqe
     }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let opcode = uring_sys::IoRingOp::IORING_OP_SPLICE as u8;
+        let Splice { fd_in, mut off_in, fd_out, mut off_out, bytes, flags } = *self;
+        Some(Emulation::new(opcode, move || {
+            // An offset of -1 means the file's own offset is used, as for the io-uring event.
+            let off_in = match off_in { -1 => ptr::null_mut(), _ => &mut off_in as *mut i64 };
+            let off_out = match off_out { -1 => ptr::null_mut(), _ => &mut off_out as *mut i64 };
+            let flags = flags.bits() as libc::c_uint;
+            match libc::splice(fd_in, off_in, fd_out, off_out, bytes as usize, flags) {
+                -1  => Err(io::Error::last_os_error()),
+                n   => Ok(n as u32),
+            }
+        }))
+    }
 }
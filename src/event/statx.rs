@@ -41,8 +41,8 @@ impl<FD: UringFd + Copy> Event for Statx<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_statx(self.dir_fd, self.path.as_c_str(), self.flags, self.mask, &mut *self.statx);
+        let mut sqe = sqs.next().unwrap();
+        sqe.prep_statx(self.dir_fd, self.path.as_c_str(), self.flags, self.mask, &mut self.statx);
         sqe
     }
 
@@ -0,0 +1,54 @@
+use std::ffi::CString;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::os::unix::io::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
+
+/// Create a symbolic link at `link_path` which points to `target`, like `symlinkat(2)`.
+pub struct SymlinkAt {
+    pub target: CString,
+    pub new_dir_fd: RawFd,
+    pub link_path: CString,
+}
+
+impl SymlinkAt {
+    pub fn without_dir(target: impl AsRef<Path>, link_path: impl AsRef<Path>) -> SymlinkAt {
+        let target = CString::new(target.as_ref().as_os_str().as_bytes()).unwrap();
+        let link_path = CString::new(link_path.as_ref().as_os_str().as_bytes()).unwrap();
+        SymlinkAt { target, new_dir_fd: libc::AT_FDCWD, link_path }
+    }
+}
+
+impl Event for SymlinkAt {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        let (target, link_path) = (self.target.as_ptr(), self.link_path.as_ptr());
+        sys::prep_symlinkat(&mut sqe, target, self.new_dir_fd, link_path);
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let new_dir_fd = self.new_dir_fd;
+        let target = self.target.as_ptr() as usize;
+        let link_path = self.link_path.as_ptr() as usize;
+        Some(Emulation::new(sys::IORING_OP_SYMLINKAT, move || {
+            let target = target as *const libc::c_char;
+            match libc::symlinkat(target, new_dir_fd, link_path as *const libc::c_char) {
+                -1  => Err(io::Error::last_os_error()),
+                _   => Ok(0),
+            }
+        }))
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        let this = ManuallyDrop::into_inner(this);
+        Cancellation::from((this.target, this.link_path))
+    }
+}
@@ -24,7 +24,7 @@ impl Event for &'static StaticTimeout {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_timeout(&self.ts, self.events, self.flags);
         sqe
     }
@@ -49,8 +49,8 @@ impl Event for Timeout {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
-        sqe.prep_timeout(&*self.ts, self.events, self.flags);
+        let mut sqe = sqs.next().unwrap();
+        sqe.prep_timeout(&self.ts, self.events, self.flags);
         sqe
     }
 
@@ -59,7 +59,7 @@ impl Event for Timeout {
     }
 }
 
-const fn timespec(duration: Duration) -> uring_sys::__kernel_timespec {
+pub(crate) const fn timespec(duration: Duration) -> uring_sys::__kernel_timespec {
     uring_sys::__kernel_timespec {
         tv_sec: duration.as_secs() as i64,
         tv_nsec: duration.subsec_nanos() as _,
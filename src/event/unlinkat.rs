@@ -0,0 +1,48 @@
+use std::ffi::CString;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::os::unix::io::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::sys;
+
+use super::{Event, Emulation, SQE, SQEs, Cancellation};
+
+/// Remove a file, or a directory if `flags` contains `AT_REMOVEDIR`, like `unlinkat(2)`.
+pub struct UnlinkAt {
+    pub dir_fd: RawFd,
+    pub path: CString,
+    pub flags: i32,
+}
+
+impl UnlinkAt {
+    pub fn without_dir(path: impl AsRef<Path>, flags: i32) -> UnlinkAt {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+        UnlinkAt { dir_fd: libc::AT_FDCWD, path, flags }
+    }
+}
+
+impl Event for UnlinkAt {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sys::prep_unlinkat(&mut sqe, self.dir_fd, self.path.as_ptr(), self.flags as u32);
+        sqe
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let (dir_fd, path, flags) = (self.dir_fd, self.path.as_ptr() as usize, self.flags);
+        Some(Emulation::new(sys::IORING_OP_UNLINKAT, move || {
+            match libc::unlinkat(dir_fd, path as *const libc::c_char, flags) {
+                -1  => Err(io::Error::last_os_error()),
+                _   => Ok(0),
+            }
+        }))
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        Cancellation::from(ManuallyDrop::into_inner(this).path)
+    }
+}
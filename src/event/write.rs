@@ -3,7 +3,7 @@ use std::os::unix::io::RawFd;
 
 use iou::registrar::{UringFd, RegisteredBuf};
 
-use super::{Event, SQE, SQEs, Cancellation};
+use super::{Event, Reclaimable, SQE, SQEs, Cancellation};
 
 /// A basic write event.
 pub struct Write<FD = RawFd> {
@@ -16,7 +16,7 @@ impl<FD: UringFd + Copy> Event for Write<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_write(self.fd, &self.buf[..], self.offset);
         sqe
     }
@@ -26,6 +26,8 @@ impl<FD: UringFd + Copy> Event for Write<FD> {
     }
 }
 
+unsafe impl<FD: UringFd + Copy> Reclaimable for Write<FD> { }
+
 pub struct WriteFixed<FD = RawFd> {
     pub fd: FD,
     pub buf: RegisteredBuf,
@@ -36,7 +38,7 @@ impl<FD: UringFd + Copy> Event for WriteFixed<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_write(self.fd, self.buf.as_ref(), self.offset);
         sqe
     }
@@ -4,7 +4,7 @@ use std::os::unix::io::RawFd;
 
 use iou::registrar::UringFd;
 
-use super::{Event, SQE, SQEs, Cancellation};
+use super::{Event, Reclaimable, SQE, SQEs, Cancellation};
 
 /// A `writev` event.
 pub struct WriteVectored<FD = RawFd> {
@@ -14,7 +14,7 @@ pub struct WriteVectored<FD = RawFd> {
 }
 
 impl<FD> WriteVectored<FD> {
-    fn iovecs(&self) -> &[IoSlice] {
+    fn iovecs(&self) -> &[IoSlice<'_>] {
         unsafe { & *(&self.bufs[..] as *const [Box<[u8]>] as *const [IoSlice]) }
     }
 }
@@ -23,7 +23,7 @@ impl<FD: UringFd + Copy> Event for WriteVectored<FD> {
     fn sqes_needed(&self) -> u32 { 1 }
 
     unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
-        let mut sqe = sqs.single().unwrap();
+        let mut sqe = sqs.next().unwrap();
         sqe.prep_write_vectored(self.fd, self.iovecs(), self.offset);
         sqe
     }
@@ -32,3 +32,5 @@ impl<FD: UringFd + Copy> Event for WriteVectored<FD> {
         Cancellation::from(ManuallyDrop::into_inner(this).bufs)
     }
 }
+
+unsafe impl<FD: UringFd + Copy> Reclaimable for WriteVectored<FD> { }
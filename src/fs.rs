@@ -1,10 +1,25 @@
 //! Interact with the file system using io-uring
 
+mod block;
+mod copy;
+mod direct;
+mod direct_reader;
+mod dir;
+mod lock;
+mod metadata;
+mod open_options;
+mod path_ops;
+mod times;
+mod tmpfile;
+mod whole;
+mod xattr;
+
 use std::fs;
 use std::future::Future;
 use std::io;
+use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::path::Path;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -12,19 +27,41 @@ use std::task::{Context, Poll};
 use either::Either;
 use futures_core::ready;
 use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite, AsyncSeek};
-use iou::sqe::{OFlag, Mode};
+use iou::sqe::{FsyncFlags, OFlag, Mode};
 
 use crate::buf::Buffer;
 use crate::drive::Drive;
-use crate::drive::demo::DemoDriver;
-use crate::ring::{Ring, Cancellation};
-use crate::event::OpenAt;
+use crate::drive::DefaultDriver;
+use crate::ring::{self, Ring, Cancellation};
+use crate::event::{self, Event, OpenAt, OpenAt2};
+use crate::sys;
 use crate::Submission;
 
+pub use block::{BlockDevice, OpenBlockDevice, Discard};
+pub use copy::{copy, copy_on_driver, CopyFile};
+pub use direct::AlignedBuf;
+pub use direct_reader::{DirectReader, Chunk};
+pub use lock::Lock;
+pub use open_options::OpenOptions;
+pub use dir::{Dir, DirEntry, ReadDir, OpenDir, OpenReadDir, read_dir, read_dir_on_driver};
+pub use iou::sqe::FallocateFlags;
+pub use crate::io::{BufReader, BufWriter};
+pub use crate::event::{ResolveFlags, RwFlags};
+pub use path_ops::{PathOp, ReadLink};
+pub use times::{set_times, SetTimes, SetPathTimes};
+pub use tmpfile::{tempfile_in, tempfile_in_on_driver};
+pub use path_ops::{create_dir, create_dir_on_driver, remove_dir, remove_dir_on_driver};
+pub use path_ops::{remove_file, remove_file_on_driver, rename, rename_on_driver};
+pub use path_ops::{hard_link, hard_link_on_driver, symlink, symlink_on_driver, read_link};
+pub use metadata::{Metadata, FileType, Stat};
+pub use xattr::{GetXattr, SetXattr, ListXattr};
+pub use whole::{read, read_on_driver, write, write_on_driver, ReadFile, WriteFile};
+pub use metadata::{metadata, metadata_on_driver, symlink_metadata, symlink_metadata_on_driver};
+
 type FileBuf = Either<Buffer, Box<libc::statx>>;
 
 /// A file handle that runs on io-uring
-pub struct File<D: Drive = DemoDriver> {
+pub struct File<D: Drive = DefaultDriver> {
     ring: Ring<D>,
     fd: RawFd,
     active: Op,
@@ -39,18 +76,27 @@ enum Op {
     Close,
     Nothing,
     Statx,
+    Sync,
+    Allocate,
+    SyncRange,
+    Readahead,
+    Direct,
+    Xattr,
+    SetLen,
+    Lock,
+    SetTimes,
     Closed,
 }
 
 impl File {
     /// Open a file using the default driver
     pub fn open(path: impl AsRef<Path>) -> Open {
-        File::open_on_driver(path, DemoDriver::default())
+        File::open_on_driver(path, DefaultDriver::default())
     }
 
     /// Create a new file using the default driver
     pub fn create(path: impl AsRef<Path>) -> Create {
-        File::create_on_driver(path, DemoDriver::default())
+        File::create_on_driver(path, DefaultDriver::default())
     }
 }
 
@@ -58,7 +104,7 @@ impl<D: Drive + Clone> File<D> {
     /// Open a file
     pub fn open_on_driver(path: impl AsRef<Path>, driver: D) -> Open<D> {
         let flags = OFlag::O_CLOEXEC | OFlag::O_RDONLY;
-        Open(driver.submit(OpenAt::without_dir(path, flags, Mode::from_bits(0o666).unwrap())))
+        Open::submit(OpenAt::without_dir(path, flags, Mode::from_bits(0o666).unwrap()), driver)
     }
 
     /// Create a file
@@ -66,13 +112,70 @@ impl<D: Drive + Clone> File<D> {
         let flags = OFlag::O_CLOEXEC | OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_TRUNC;
         Create(driver.submit(OpenAt::without_dir(path, flags, Mode::from_bits(0o666).unwrap())))
     }
+
+    /// Read into `buf` from `offset` in the file, without using or changing the file's position.
+    ///
+    /// The read is submitted on its own, bypassing the file's buffer, and only needs a shared
+    /// reference to the file, so many positional reads and writes can run at once on the same
+    /// file. The future completes with the buffer, and the number of bytes read into it.
+    pub fn read_at(&self, buf: Box<[u8]>, offset: u64) -> ReadAt<'_, D> {
+        let read = event::Read { fd: self.fd, buf, offset };
+        ReadAt { read: Submission::new(read, self.ring.driver().clone()), _file: PhantomData }
+    }
+
+    /// Write `buf` at `offset` in the file, without using or changing the file's position.
+    ///
+    /// Like [`File::read_at`], this only needs a shared reference to the file. The future
+    /// completes with the buffer, and the number of bytes written from it.
+    pub fn write_at(&self, buf: Box<[u8]>, offset: u64) -> WriteAt<'_, D> {
+        let write = event::Write { fd: self.fd, buf, offset };
+        WriteAt { write: Submission::new(write, self.ring.driver().clone()), _file: PhantomData }
+    }
 }
 
 impl<D: Drive> File<D> {
     /// Take an existing file and run its IO on an io-uring driver
     pub fn run_on_driver(file: fs::File, driver: D) -> File<D> {
-        let file = ManuallyDrop::new(file);
-        File::from_fd(file.as_raw_fd(), driver)
+        File::from_std(file, driver)
+    }
+
+    /// Take an existing file and run its IO on an io-uring driver
+    ///
+    /// IO continues from the file's current offset, if it has one.
+    pub fn from_std(file: fs::File, driver: D) -> File<D> {
+        let fd = file.into_raw_fd();
+        let mut file = File::from_fd(fd, driver);
+        file.pos = match unsafe { libc::lseek(fd, 0, libc::SEEK_CUR) } {
+            -1  => 0,
+            pos => pos as u64,
+        };
+        file
+    }
+
+    /// Take ownership of a file descriptor and run its IO on an io-uring driver
+    ///
+    /// # Safety
+    ///
+    /// The fd must be open, and must not be owned by anything else; it will be closed when the
+    /// file is dropped.
+    pub unsafe fn from_raw_fd_on_driver(fd: RawFd, driver: D) -> File<D> {
+        File::from_std(fs::File::from_raw_fd(fd), driver)
+    }
+
+    /// Stop running this file's IO on io-uring, and return it as a standard library file
+    ///
+    /// Any ongoing IO is cancelled. The file's offset is set to the end of the data which has
+    /// been consumed from this file, so that IO through the standard library file continues from
+    /// the same point; data which has been read into the buffer but not consumed will be read
+    /// again.
+    pub fn into_std(mut self) -> fs::File {
+        let pos = self.pos - self.read_buffered().len() as u64;
+        self.cancel();
+        let file = ManuallyDrop::new(self);
+        unsafe {
+            libc::lseek(file.fd, pos as libc::off_t, libc::SEEK_SET);
+            fs::File::from_raw_fd(file.fd)
+        }
     }
 
     fn from_fd(fd: RawFd, driver: D) -> File<D> {
@@ -96,6 +199,118 @@ impl<D: Drive> File<D> {
         } else { &[] }
     }
 
+    /// Read from the file without waiting for its data to be read from storage.
+    ///
+    /// Data already in this file's buffer is returned first. Otherwise, the read is submitted with
+    /// `RWF_NOWAIT`, and fails with `WouldBlock` unless the data is already in the page cache.
+    /// This is useful as a fast path before falling back to an awaited read.
+    pub fn poll_try_read(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.poll_read_with_flags(ctx, buf, RwFlags::NOWAIT)
+    }
+
+    /// Write to the file without blocking.
+    ///
+    /// The write is submitted with `RWF_NOWAIT`, and fails with `WouldBlock` if it cannot be
+    /// performed immediately.
+    pub fn poll_try_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.poll_write_with_flags(ctx, slice, RwFlags::NOWAIT)
+    }
+
+    pub fn try_read<'a>(&'a mut self, buf: &'a mut [u8]) -> TryRead<'a, D> where D: Unpin {
+        TryRead { file: Pin::new(self), buf }
+    }
+
+    pub fn try_write<'a>(&'a mut self, buf: &'a [u8]) -> TryWrite<'a, D> where D: Unpin {
+        TryWrite { file: Pin::new(self), buf }
+    }
+
+    /// Read from the file, submitting the read with `flags`, like `preadv2(2)`.
+    ///
+    /// Data already in this file's buffer is returned first, without submitting anything.
+    pub fn poll_read_with_flags(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+        flags: RwFlags,
+    ) -> Poll<io::Result<usize>> {
+        let mut inner = ready!(self.as_mut().poll_fill_buf_with(ctx, flags.bits()))?;
+        let len = io::Read::read(&mut inner, buf)?;
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+
+    /// Write to the file, submitting the write with `flags`, like `pwritev2(2)`.
+    ///
+    /// With `RwFlags::DSYNC`, the data is durable once the write completes, without a separate
+    /// `sync_data`.
+    pub fn poll_write_with_flags(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        slice: &[u8],
+        flags: RwFlags,
+    ) -> Poll<io::Result<usize>> {
+        self.poll_write_with(ctx, slice, flags.bits())
+    }
+
+    pub fn read_with_flags<'a>(&'a mut self, buf: &'a mut [u8], flags: RwFlags)
+        -> ReadWithFlags<'a, D> where D: Unpin
+    {
+        ReadWithFlags { file: Pin::new(self), buf, flags }
+    }
+
+    pub fn write_with_flags<'a>(&'a mut self, buf: &'a [u8], flags: RwFlags)
+        -> WriteWithFlags<'a, D> where D: Unpin
+    {
+        WriteWithFlags { file: Pin::new(self), buf, flags }
+    }
+
+    fn poll_fill_buf_with(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, rw_flags: i32)
+        -> Poll<io::Result<&[u8]>>
+    {
+        self.as_mut().guard_op(Op::Read);
+        let fd = self.fd;
+        let (ring, buf, pos, ..) = self.split_with_buf();
+        buf.fill_buf(|buf| {
+            let n = ready!(ring.poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_read(fd, buf, *pos);
+                    sys::set_rw_flags(&mut sqe, rw_flags);
+                }
+                sqe
+            }))?;
+            *pos += n as u64;
+            Poll::Ready(Ok(n))
+        })
+    }
+
+    fn poll_write_with(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8], rw_flags: i32)
+        -> Poll<io::Result<usize>>
+    {
+        self.as_mut().guard_op(Op::Write);
+        let fd = self.fd;
+        let (ring, buf, pos, ..) = self.split_with_buf();
+        let data = ready!(buf.fill_buf(|mut buf| {
+            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
+        }))?;
+        let result = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_write(fd, data, *pos);
+                sys::set_rw_flags(&mut sqe, rw_flags);
+            }
+            sqe
+        }));
+        buf.clear();
+        let n = result?;
+        *pos += n as u64;
+        Poll::Ready(Ok(n as usize))
+    }
+
     fn guard_op(self: Pin<&mut Self>, op: Op) {
         let (ring, buf, .., active) = self.split();
         if *active == Op::Closed {
@@ -113,7 +328,253 @@ impl<D: Drive> File<D> {
         self.ring.cancel(Cancellation::from(mem::replace(&mut self.buf, new_buf)));
     }
 
-    fn poll_file_size(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+    /// Read the file's metadata.
+    pub fn metadata(&mut self) -> FileMetadata<'_, D> where D: Unpin {
+        Pin::new(self).metadata_pinned()
+    }
+
+    pub fn metadata_pinned(self: Pin<&mut Self>) -> FileMetadata<'_, D> {
+        FileMetadata { file: self }
+    }
+
+    pub fn poll_metadata(self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<Metadata>>
+    {
+        let statx = ready!(self.poll_statx(ctx, metadata::statx_mask()))?;
+        Poll::Ready(Ok(Metadata::from_statx(*statx)))
+    }
+
+    /// Flush the file's data and metadata to the storage device, like `fsync(2)`.
+    pub fn sync_all(&mut self) -> Fsync<'_, D> where D: Unpin {
+        Pin::new(self).sync_all_pinned()
+    }
+
+    pub fn sync_all_pinned(self: Pin<&mut Self>) -> Fsync<'_, D> {
+        Fsync { file: self, flags: FsyncFlags::empty() }
+    }
+
+    /// Flush the file's data to the storage device, and only the metadata needed to read it
+    /// back, like `fdatasync(2)`.
+    pub fn sync_data(&mut self) -> Fsync<'_, D> where D: Unpin {
+        Pin::new(self).sync_data_pinned()
+    }
+
+    pub fn sync_data_pinned(self: Pin<&mut Self>) -> Fsync<'_, D> {
+        Fsync { file: self, flags: FsyncFlags::FSYNC_DATASYNC }
+    }
+
+    pub fn poll_sync_all(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_fsync(ctx, FsyncFlags::empty())
+    }
+
+    pub fn poll_sync_data(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_fsync(ctx, FsyncFlags::FSYNC_DATASYNC)
+    }
+
+    fn poll_fsync(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, flags: FsyncFlags)
+        -> Poll<io::Result<()>>
+    {
+        self.as_mut().guard_op(Op::Sync);
+        let fd = self.fd;
+        ready!(self.ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_fsync(fd, flags);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Allocate space for the range of `len` bytes at `offset` in the file, like
+    /// `fallocate(2)`.
+    ///
+    /// With no flags, the file is extended if the range ends past its end. `mode` can instead
+    /// keep the size with `FALLOC_FL_KEEP_SIZE`, or punch a hole in the file with
+    /// `FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE`.
+    pub fn allocate(&mut self, offset: u64, len: u64, mode: FallocateFlags) -> Allocate<'_, D>
+        where D: Unpin
+    {
+        Pin::new(self).allocate_pinned(offset, len, mode)
+    }
+
+    pub fn allocate_pinned(self: Pin<&mut Self>, offset: u64, len: u64, mode: FallocateFlags)
+        -> Allocate<'_, D>
+    {
+        Allocate { file: self, offset, len, mode }
+    }
+
+    pub fn poll_allocate(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        offset: u64,
+        len: u64,
+        mode: FallocateFlags,
+    ) -> Poll<io::Result<()>> {
+        self.as_mut().guard_op(Op::Allocate);
+        let fd = self.fd;
+        ready!(self.ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_fallocate(fd, offset, len, mode);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Start or wait for writeback of the `len` bytes at `offset` in the file, like
+    /// `sync_file_range(2)`. A `len` of 0 covers everything from `offset` to the end of the file.
+    ///
+    /// `flags` is a combination of `libc::SYNC_FILE_RANGE_WAIT_BEFORE`,
+    /// `SYNC_FILE_RANGE_WRITE` and `SYNC_FILE_RANGE_WAIT_AFTER`. Unlike `sync_data`, this does
+    /// not flush the file's metadata or the device's write cache, so it does not make the data
+    /// durable; it bounds how much dirty data builds up before a full sync.
+    pub fn sync_range(&mut self, offset: u64, len: u32, flags: u32) -> SyncRange<'_, D>
+        where D: Unpin
+    {
+        Pin::new(self).sync_range_pinned(offset, len, flags)
+    }
+
+    pub fn sync_range_pinned(self: Pin<&mut Self>, offset: u64, len: u32, flags: u32)
+        -> SyncRange<'_, D>
+    {
+        SyncRange { file: self, offset, len, flags }
+    }
+
+    pub fn poll_sync_range(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        offset: u64,
+        len: u32,
+        flags: u32,
+    ) -> Poll<io::Result<()>> {
+        self.as_mut().guard_op(Op::SyncRange);
+        let fd = self.fd;
+        ready!(self.ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_sync_file_range(&mut sqe, fd, offset, len, flags);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Ask the kernel to start reading the `len` bytes at `offset` in the file into the page
+    /// cache, so that reads of that range later do not wait for the disk. A `len` of 0 covers
+    /// everything from `offset` to the end of the file.
+    ///
+    /// This is `posix_fadvise(2)` with `POSIX_FADV_WILLNEED`; the future completes once the
+    /// readahead has been started, not once it has finished. A reader streaming through a file
+    /// can call this for the chunk after the one it is reading to keep the kernel a chunk ahead.
+    /// It has no effect on files opened with `O_DIRECT`, which bypass the page cache.
+    pub fn readahead(&mut self, offset: u64, len: u32) -> Readahead<'_, D> where D: Unpin {
+        Pin::new(self).readahead_pinned(offset, len)
+    }
+
+    pub fn readahead_pinned(self: Pin<&mut Self>, offset: u64, len: u32) -> Readahead<'_, D> {
+        Readahead { file: self, offset, len }
+    }
+
+    pub fn poll_readahead(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        offset: u64,
+        len: u32,
+    ) -> Poll<io::Result<()>> {
+        self.as_mut().guard_op(Op::Readahead);
+        let fd = self.fd;
+        ready!(self.ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_fadvise(&mut sqe, fd, offset, len, libc::POSIX_FADV_WILLNEED);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Truncate or extend the file to `len` bytes, like `ftruncate(2)`. An extended file reads
+    /// as zeroes past its old end.
+    ///
+    /// This uses `IORING_OP_FTRUNCATE` where the kernel supports it (from Linux 6.9), and
+    /// otherwise calls `ftruncate` on ringbahn's pool of threads for blocking work. The file's
+    /// position is not changed.
+    pub fn set_len(&mut self, len: u64) -> SetLen<'_, D> where D: Unpin {
+        Pin::new(self).set_len_pinned(len)
+    }
+
+    pub fn set_len_pinned(self: Pin<&mut Self>, len: u64) -> SetLen<'_, D> {
+        SetLen { file: self, len }
+    }
+
+    pub fn poll_set_len(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, len: u64)
+        -> Poll<io::Result<()>>
+    {
+        self.as_mut().guard_op(Op::SetLen);
+        let fd = self.fd;
+        if !ring::is_supported(sys::IORING_OP_FTRUNCATE) && !self.ring.is_running() {
+            self.ring().start_emulated(ctx, Box::new(move || {
+                match unsafe { libc::ftruncate(fd, len as libc::off_t) } {
+                    -1  => Err(io::Error::last_os_error()),
+                    _   => Ok(0),
+                }
+            }));
+            return Poll::Pending;
+        }
+        ready!(self.ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_ftruncate(&mut sqe, fd, len);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Read into `buf` from `offset` in the file, without going through the file's buffer.
+    ///
+    /// This is meant for files opened with `O_DIRECT`: the length of `buf` and `offset` must be
+    /// multiples of the buffer's alignment, or the read fails with `InvalidInput` without being
+    /// submitted. The file's position is not used or changed. The future completes with the
+    /// buffer, and the number of bytes read into it.
+    pub fn read_direct(&mut self, buf: AlignedBuf, offset: u64) -> ReadDirect<'_, D>
+        where D: Unpin
+    {
+        Pin::new(self).read_direct_pinned(buf, offset)
+    }
+
+    pub fn read_direct_pinned(self: Pin<&mut Self>, buf: AlignedBuf, offset: u64)
+        -> ReadDirect<'_, D>
+    {
+        ReadDirect { file: self, buf: Some(buf), offset }
+    }
+
+    /// Write `buf` at `offset` in the file, without going through the file's buffer.
+    ///
+    /// This has the same requirements as [`File::read_direct`]. The future completes with the
+    /// buffer, and the number of bytes written from it.
+    pub fn write_direct(&mut self, buf: AlignedBuf, offset: u64) -> WriteDirect<'_, D>
+        where D: Unpin
+    {
+        Pin::new(self).write_direct_pinned(buf, offset)
+    }
+
+    pub fn write_direct_pinned(self: Pin<&mut Self>, buf: AlignedBuf, offset: u64)
+        -> WriteDirect<'_, D>
+    {
+        WriteDirect { file: self, buf: Some(buf), offset }
+    }
+
+    fn poll_file_size(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let statx = ready!(self.poll_statx(ctx, iou::sqe::StatxMode::STATX_SIZE))?;
+        Poll::Ready(Ok(statx.stx_size))
+    }
+
+    fn poll_statx(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, mask: iou::sqe::StatxMode)
+        -> Poll<io::Result<&mut libc::statx>>
+    {
         static EMPTY: libc::c_char = 0;
         use std::ffi::CStr;
 
@@ -121,15 +582,14 @@ impl<D: Drive> File<D> {
         let fd = self.fd;
         let (ring, statx, ..) = self.split_with_statx();
         let flags = iou::sqe::StatxFlags::AT_EMPTY_PATH;
-        let mask = iou::sqe::StatxMode::STATX_SIZE;
         ready!(ring.poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
+            let mut sqe = sqs.next().unwrap();
             unsafe {
                 sqe.prep_statx(fd, CStr::from_ptr(&EMPTY), flags, mask, statx);
             }
             sqe
         }))?;
-        Poll::Ready(Ok((*statx).stx_size))
+        Poll::Ready(Ok(statx))
     }
 
     #[inline(always)]
@@ -193,21 +653,8 @@ impl<D: Drive> AsyncRead for File<D> {
 }
 
 impl<D: Drive> AsyncBufRead for File<D> {
-    fn poll_fill_buf(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
-        self.as_mut().guard_op(Op::Read);
-        let fd = self.fd;
-        let (ring, buf, pos, ..) = self.split_with_buf();
-        buf.fill_buf(|buf| {
-            let n = ready!(ring.poll(ctx, 1, |sqs| {
-                let mut sqe = sqs.single().unwrap();
-                unsafe {
-                    sqe.prep_read(fd, buf, *pos);
-                }
-                sqe
-            }))?;
-            *pos += n as u64;
-            Poll::Ready(Ok(n as u32))
-        })
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.poll_fill_buf_with(ctx, 0)
     }
 
     fn consume(self: Pin<&mut Self>, amt: usize) {
@@ -216,23 +663,8 @@ impl<D: Drive> AsyncBufRead for File<D> {
 }
 
 impl<D: Drive> AsyncWrite for File<D> {
-    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8]) -> Poll<io::Result<usize>> {
-        self.as_mut().guard_op(Op::Write);
-        let fd = self.fd;
-        let (ring, buf, pos, ..) = self.split_with_buf();
-        let data = ready!(buf.fill_buf(|mut buf| {
-            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
-        }))?;
-        let n = ready!(ring.poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
-            unsafe {
-                sqe.prep_write(fd, data, *pos);
-            }
-            sqe
-        }))?;
-        *pos += n as u64;
-        buf.clear();
-        Poll::Ready(Ok(n as usize))
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8]) -> Poll<io::Result<usize>> {
+        self.poll_write_with(ctx, slice, 0)
     }
 
     fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -244,7 +676,7 @@ impl<D: Drive> AsyncWrite for File<D> {
         self.as_mut().guard_op(Op::Close);
         let fd = self.fd;
         ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
+            let mut sqe = sqs.next().unwrap();
             unsafe {
                 sqe.prep_close(fd);
             }
@@ -270,7 +702,7 @@ impl<D: Drive> AsyncSeek for File<D> {
             }
         };
         let valid_seek = if offset.is_negative() {
-            match whence.checked_sub(offset.abs() as u64) {
+            match whence.checked_sub(offset.unsigned_abs()) {
                 Some(valid_seek) => valid_seek,
                 None => {
                     let invalid = io::Error::from(io::ErrorKind::InvalidInput);
@@ -293,17 +725,31 @@ impl<D: Drive> AsyncSeek for File<D> {
 
 impl From<fs::File> for File {
     fn from(file: fs::File) -> File {
-        File::run_on_driver(file, DemoDriver::default())
+        File::run_on_driver(file, DefaultDriver::default())
     }
 }
 
 impl<D: Drive> From<File<D>> for fs::File {
-    fn from(mut file: File<D>) -> fs::File {
-        file.cancel();
-        let file = ManuallyDrop::new(file);
-        unsafe {
-            fs::File::from_raw_fd(file.fd)
-        }
+    fn from(file: File<D>) -> fs::File {
+        file.into_std()
+    }
+}
+
+impl<D: Drive> AsRawFd for File<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl<D: Drive + Default> FromRawFd for File<D> {
+    unsafe fn from_raw_fd(fd: RawFd) -> File<D> {
+        File::from_raw_fd_on_driver(fd, D::default())
+    }
+}
+
+impl<D: Drive> IntoRawFd for File<D> {
+    fn into_raw_fd(self) -> RawFd {
+        self.into_std().into_raw_fd()
     }
 }
 
@@ -317,12 +763,302 @@ impl<D: Drive> Drop for File<D> {
     }
 }
 
+/// A future representing a read from a file which does not wait for storage.
+pub struct TryRead<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    buf: &'a mut [u8],
+}
+
+impl<'a, D: Drive> Future for TryRead<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.file.as_mut().poll_try_read(ctx, this.buf)
+    }
+}
+
+/// A future representing a write to a file which does not block.
+pub struct TryWrite<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    buf: &'a [u8],
+}
+
+impl<'a, D: Drive> Future for TryWrite<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let buf = self.buf;
+        self.file.as_mut().poll_try_write(ctx, buf)
+    }
+}
+
+/// A future representing a read from a file with per-operation flags.
+pub struct ReadWithFlags<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    buf: &'a mut [u8],
+    flags: RwFlags,
+}
+
+impl<'a, D: Drive> Future for ReadWithFlags<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.file.as_mut().poll_read_with_flags(ctx, this.buf, this.flags)
+    }
+}
+
+/// A future representing a write to a file with per-operation flags.
+pub struct WriteWithFlags<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    buf: &'a [u8],
+    flags: RwFlags,
+}
+
+impl<'a, D: Drive> Future for WriteWithFlags<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (buf, flags) = (self.buf, self.flags);
+        self.file.as_mut().poll_write_with_flags(ctx, buf, flags)
+    }
+}
+
+/// A future which flushes a [`File`] to the storage device.
+pub struct Fsync<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    flags: FsyncFlags,
+}
+
+impl<'a, D: Drive> Future for Fsync<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let flags = self.flags;
+        self.file.as_mut().poll_fsync(ctx, flags)
+    }
+}
+
+/// A future which allocates space in a [`File`].
+pub struct Allocate<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    offset: u64,
+    len: u64,
+    mode: FallocateFlags,
+}
+
+impl<'a, D: Drive> Future for Allocate<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (offset, len, mode) = (self.offset, self.len, self.mode);
+        self.file.as_mut().poll_allocate(ctx, offset, len, mode)
+    }
+}
+
+/// A future which starts or waits for writeback of a range of a [`File`].
+pub struct SyncRange<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    offset: u64,
+    len: u32,
+    flags: u32,
+}
+
+impl<'a, D: Drive> Future for SyncRange<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (offset, len, flags) = (self.offset, self.len, self.flags);
+        self.file.as_mut().poll_sync_range(ctx, offset, len, flags)
+    }
+}
+
+/// A future which starts readahead of a range of a [`File`].
+pub struct Readahead<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    offset: u64,
+    len: u32,
+}
+
+impl<'a, D: Drive> Future for Readahead<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (offset, len) = (self.offset, self.len);
+        self.file.as_mut().poll_readahead(ctx, offset, len)
+    }
+}
+
+/// A future which sets the length of a [`File`].
+pub struct SetLen<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    len: u64,
+}
+
+impl<'a, D: Drive> Future for SetLen<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let len = self.len;
+        self.file.as_mut().poll_set_len(ctx, len)
+    }
+}
+
+/// A future which reads into an [`AlignedBuf`] from a [`File`].
+pub struct ReadDirect<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    buf: Option<AlignedBuf>,
+    offset: u64,
+}
+
+impl<'a, D: Drive> Future for ReadDirect<'a, D> {
+    type Output = (AlignedBuf, io::Result<usize>);
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let buf = this.buf.as_mut().expect("polled ReadDirect future after completion");
+        let offset = this.offset;
+        if let Err(err) = buf.check(offset) {
+            return Poll::Ready((this.buf.take().unwrap(), Err(err)));
+        }
+        this.file.as_mut().guard_op(Op::Direct);
+        let fd = this.file.fd;
+        let result = ready!(this.file.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_read(fd, &mut buf[..], offset);
+            }
+            sqe
+        }));
+        Poll::Ready((this.buf.take().unwrap(), result.map(|n| n as usize)))
+    }
+}
+
+impl<'a, D: Drive> Drop for ReadDirect<'a, D> {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            self.file.as_mut().ring().cancel_pinned(Cancellation::from(Box::new(buf)));
+        }
+    }
+}
+
+/// A future which writes an [`AlignedBuf`] to a [`File`].
+pub struct WriteDirect<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    buf: Option<AlignedBuf>,
+    offset: u64,
+}
+
+impl<'a, D: Drive> Future for WriteDirect<'a, D> {
+    type Output = (AlignedBuf, io::Result<usize>);
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let buf = this.buf.as_ref().expect("polled WriteDirect future after completion");
+        let offset = this.offset;
+        if let Err(err) = buf.check(offset) {
+            return Poll::Ready((this.buf.take().unwrap(), Err(err)));
+        }
+        this.file.as_mut().guard_op(Op::Direct);
+        let fd = this.file.fd;
+        let result = ready!(this.file.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_write(fd, &buf[..], offset);
+            }
+            sqe
+        }));
+        Poll::Ready((this.buf.take().unwrap(), result.map(|n| n as usize)))
+    }
+}
+
+impl<'a, D: Drive> Drop for WriteDirect<'a, D> {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            self.file.as_mut().ring().cancel_pinned(Cancellation::from(Box::new(buf)));
+        }
+    }
+}
+
+/// A future which reads from a position in a [`File`].
+pub struct ReadAt<'a, D: Drive> {
+    read: Submission<event::Read, D>,
+    _file: PhantomData<&'a File<D>>,
+}
+
+impl<'a, D: Drive> Future for ReadAt<'a, D> {
+    type Output = (Box<[u8]>, io::Result<usize>);
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let read = unsafe { Pin::map_unchecked_mut(self, |this| &mut this.read) };
+        let (read, result) = ready!(read.poll(ctx));
+        Poll::Ready((read.buf, result.map(|n| n as usize)))
+    }
+}
+
+/// A future which writes at a position in a [`File`].
+pub struct WriteAt<'a, D: Drive> {
+    write: Submission<event::Write, D>,
+    _file: PhantomData<&'a File<D>>,
+}
+
+impl<'a, D: Drive> Future for WriteAt<'a, D> {
+    type Output = (Box<[u8]>, io::Result<usize>);
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let write = unsafe { Pin::map_unchecked_mut(self, |this| &mut this.write) };
+        let (write, result) = ready!(write.poll(ctx));
+        Poll::Ready((write.buf, result.map(|n| n as usize)))
+    }
+}
+
+/// A future which reads the metadata of a [`File`].
+pub struct FileMetadata<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+}
+
+impl<'a, D: Drive> Future for FileMetadata<'a, D> {
+    type Output = io::Result<Metadata>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<Metadata>> {
+        self.file.as_mut().poll_metadata(ctx)
+    }
+}
+
 /// A future representing an opening file.
-pub struct Open<D: Drive = DemoDriver>(Submission<OpenAt, D>);
+pub struct Open<D: Drive = DefaultDriver> {
+    open: Option<Either<Submission<OpenAt, D>, Submission<OpenAt2, D>>>,
+    // Why the file could not be opened, if that was known before anything was submitted
+    error: Option<io::Error>,
+}
 
 impl<D: Drive> Open<D> {
-    fn inner(self: Pin<&mut Self>) -> Pin<&mut Submission<OpenAt, D>> {
-        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.0) }
+    fn submit(open: OpenAt, driver: D) -> Open<D> {
+        Open { open: Some(Either::Left(driver.submit(open))), error: None }
+    }
+
+    fn submit_openat2(open: OpenAt2, driver: D) -> Open<D> {
+        Open { open: Some(Either::Right(driver.submit(open))), error: None }
+    }
+
+    fn failed(error: io::Error) -> Open<D> {
+        Open { open: None, error: Some(error) }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn split(self: Pin<&mut Self>) -> (
+        Option<Either<Pin<&mut Submission<OpenAt, D>>, Pin<&mut Submission<OpenAt2, D>>>>,
+        &mut Option<io::Error>,
+    ) {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let open = this.open.as_mut().map(|open| {
+                open.as_mut().map_left(|open| Pin::new_unchecked(open))
+                    .map_right(|open| Pin::new_unchecked(open))
+            });
+            (open, &mut this.error)
+        }
     }
 }
 
@@ -330,16 +1066,28 @@ impl<D: Drive + Clone> Future for Open<D> {
     type Output = io::Result<File<D>>;
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<File<D>>> {
-        let mut inner = self.inner();
-        let (_, result) = ready!(inner.as_mut().poll(ctx));
-        let fd = result? as i32;
-        let driver = inner.driver().clone();
-        Poll::Ready(Ok(File::from_fd(fd, driver)))
+        match self.split() {
+            (Some(Either::Left(open)), _)   => poll_opened(open, ctx),
+            (Some(Either::Right(open)), _)  => poll_opened(open, ctx),
+            (None, error)                   => {
+                Poll::Ready(Err(error.take().expect("polled Open future after completion")))
+            }
+        }
     }
 }
 
+fn poll_opened<E: Event, D: Drive + Clone>(
+    mut open: Pin<&mut Submission<E, D>>,
+    ctx: &mut Context<'_>,
+) -> Poll<io::Result<File<D>>> {
+    let (_, result) = ready!(open.as_mut().poll(ctx));
+    let fd = result? as i32;
+    let driver = open.driver().clone();
+    Poll::Ready(Ok(File::from_fd(fd, driver)))
+}
+
 /// A future representing a file being created.
-pub struct Create<D: Drive = DemoDriver>(Submission<OpenAt, D>);
+pub struct Create<D: Drive = DefaultDriver>(Submission<OpenAt, D>);
 
 impl<D: Drive> Create<D> {
     fn inner(self: Pin<&mut Self>) -> Pin<&mut Submission<OpenAt, D>> {
@@ -0,0 +1,181 @@
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use iou::sqe::{OFlag, Mode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::OpenAt;
+use crate::ring::{Ring, Cancellation};
+use crate::sys;
+use crate::Submission;
+
+use super::File;
+
+/// A block device, like a disk or a partition
+///
+/// This is a [`File`] which provides the operations needed to manage a raw device, like querying
+/// its geometry and discarding ranges of it. The device can be read and written through
+/// [`BlockDevice::file`].
+pub struct BlockDevice<D: Drive = DefaultDriver> {
+    file: File<D>,
+    ring: Ring<D>,
+}
+
+impl BlockDevice {
+    /// Open a block device for reading and writing using the default driver
+    pub fn open(path: impl AsRef<Path>) -> OpenBlockDevice {
+        BlockDevice::open_on_driver(path, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive + Clone> BlockDevice<D> {
+    /// Open a block device for reading and writing
+    pub fn open_on_driver(path: impl AsRef<Path>, driver: D) -> OpenBlockDevice<D> {
+        let flags = OFlag::O_CLOEXEC | OFlag::O_RDWR;
+        let open = OpenAt::without_dir(path, flags, Mode::empty());
+        OpenBlockDevice(driver.submit(open))
+    }
+
+    /// Use an open file as a block device
+    ///
+    /// This fails with `ENOTBLK` if the file is not a block device.
+    pub fn from_file(file: File<D>) -> io::Result<BlockDevice<D>> {
+        let mut stat: libc::stat = unsafe { mem::zeroed() };
+        if unsafe { libc::fstat(file.fd, &mut stat) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if stat.st_mode & libc::S_IFMT != libc::S_IFBLK {
+            return Err(io::Error::from_raw_os_error(libc::ENOTBLK));
+        }
+        let ring = file.ring.with_driver(file.ring.driver().clone());
+        Ok(BlockDevice { file, ring })
+    }
+}
+
+impl<D: Drive> BlockDevice<D> {
+    /// The file through which the device is read and written
+    pub fn file(&mut self) -> &mut File<D> {
+        &mut self.file
+    }
+
+    /// Stop treating this file as a block device
+    pub fn into_file(self) -> File<D> {
+        self.file
+    }
+
+    /// The smallest unit the device can address, in bytes
+    ///
+    /// Buffers and offsets used for direct IO on the device must be aligned to this size.
+    pub fn logical_block_size(&self) -> io::Result<u32> {
+        let mut size: libc::c_int = 0;
+        self.ioctl(libc::BLKSSZGET as _, &mut size as *mut libc::c_int as _)?;
+        Ok(size as u32)
+    }
+
+    /// The smallest unit the device can write without a read-modify-write cycle, in bytes
+    pub fn physical_block_size(&self) -> io::Result<u32> {
+        let mut size: libc::c_uint = 0;
+        self.ioctl(libc::BLKPBSZGET as _, &mut size as *mut libc::c_uint as _)?;
+        Ok(size)
+    }
+
+    /// The size of the device, in bytes
+    pub fn size(&self) -> io::Result<u64> {
+        let mut size: u64 = 0;
+        self.ioctl(sys::BLKGETSIZE64, &mut size as *mut u64 as _)?;
+        Ok(size)
+    }
+
+    /// Discard a range of the device, telling it that the data stored there is no longer needed
+    ///
+    /// The range is given in bytes, and must be aligned to the logical block size. The discard
+    /// is submitted to io-uring if the kernel supports it; otherwise, it falls back to a blocking
+    /// `BLKDISCARD` ioctl.
+    pub fn discard(&mut self, range: Range<u64>) -> Discard<'_, D> where D: Unpin {
+        Pin::new(self).discard_pinned(range)
+    }
+
+    pub fn discard_pinned(self: Pin<&mut Self>, range: Range<u64>) -> Discard<'_, D> {
+        Discard { device: self, range }
+    }
+
+    fn ioctl(&self, request: libc::c_ulong, arg: *mut libc::c_void) -> io::Result<()> {
+        match unsafe { libc::ioctl(self.file.fd, request as _, arg) } {
+            -1  => Err(io::Error::last_os_error()),
+            _   => Ok(()),
+        }
+    }
+
+    fn ring(self: Pin<&mut Self>) -> Pin<&mut Ring<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.ring) }
+    }
+}
+
+/// A future representing a block device being opened.
+pub struct OpenBlockDevice<D: Drive = DefaultDriver>(Submission<OpenAt, D>);
+
+impl<D: Drive> OpenBlockDevice<D> {
+    fn inner(self: Pin<&mut Self>) -> Pin<&mut Submission<OpenAt, D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.0) }
+    }
+}
+
+impl<D: Drive + Clone> Future for OpenBlockDevice<D> {
+    type Output = io::Result<BlockDevice<D>>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut inner = self.inner();
+        let (_, result) = ready!(inner.as_mut().poll(ctx));
+        let fd = result? as i32;
+        let driver = inner.driver().clone();
+        Poll::Ready(BlockDevice::from_file(File::from_fd(fd, driver)))
+    }
+}
+
+/// A future representing a range of a block device being discarded.
+pub struct Discard<'a, D: Drive> {
+    device: Pin<&'a mut BlockDevice<D>>,
+    range: Range<u64>,
+}
+
+impl<'a, D: Drive> Future for Discard<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let fd = self.device.file.fd;
+        let start = self.range.start;
+        let len = self.range.end.saturating_sub(start);
+        let result = ready!(self.device.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_uring_cmd(&mut sqe, fd, sys::BLOCK_URING_CMD_DISCARD, start, len);
+            }
+            sqe
+        }));
+        match result {
+            Ok(_)                       => Poll::Ready(Ok(())),
+            Err(err) if unsupported(&err) => {
+                let mut range = [start, len];
+                Poll::Ready(self.device.ioctl(sys::BLKDISCARD, range.as_mut_ptr() as _))
+            }
+            Err(err)                    => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl<'a, D: Drive> Drop for Discard<'a, D> {
+    fn drop(&mut self) {
+        self.device.as_mut().ring().cancel_pinned(Cancellation::from(()));
+    }
+}
+
+/// Kernels without block device commands reject them with one of these errors.
+fn unsupported(err: &io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EOPNOTSUPP | libc::ENOTTY | libc::EINVAL))
+}
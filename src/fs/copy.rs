@@ -0,0 +1,188 @@
+use std::future::Future;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use iou::sqe::{OFlag, Mode, StatxFlags, StatxMode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::{OpenAt, Statx};
+use crate::ring::{Cancellation, Ring};
+use crate::Submission;
+
+/// The most data read from the source before it is written to the destination.
+const COPY_CHUNK: usize = 1 << 16;
+
+/// Copy the contents of the file at `from` to `to` using the default driver.
+pub fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> CopyFile {
+    copy_on_driver(from, to, DefaultDriver::default())
+}
+
+/// Copy the contents of the file at `from` to `to`, completing with the number of bytes copied
+///
+/// Like `std::fs::copy`, `to` is created if it does not exist and truncated if it does, and is
+/// given the permissions of `from`. Both files are opened on io-uring, and the data is copied
+/// with reads and writes on io-uring, a chunk at a time.
+pub fn copy_on_driver<D: Drive + Clone>(from: impl AsRef<Path>, to: impl AsRef<Path>, driver: D)
+    -> CopyFile<D>
+{
+    let flags = OFlag::O_CLOEXEC | OFlag::O_RDONLY;
+    let open = OpenAt::without_dir(from, flags, Mode::empty());
+    CopyFile {
+        stage: Stage::OpenFrom(Box::pin(Submission::new(open, driver.clone()))),
+        ring: Ring::new(driver),
+        to: to.as_ref().to_owned(),
+        from_fd: None,
+        to_fd: None,
+        buf: None,
+        filled: 0,
+        written: 0,
+        copied: 0,
+    }
+}
+
+/// A future which copies a file, returned by [`copy`].
+pub struct CopyFile<D: Drive = DefaultDriver> {
+    stage: Stage<D>,
+    ring: Ring<D>,
+    to: PathBuf,
+    from_fd: Option<RawFd>,
+    to_fd: Option<RawFd>,
+    buf: Option<Box<[u8]>>,
+    // The bytes of the current chunk which have been read, and which have been written
+    filled: usize,
+    written: usize,
+    copied: u64,
+}
+
+enum Stage<D: Drive> {
+    OpenFrom(Pin<Box<Submission<OpenAt, D>>>),
+    Stat(Pin<Box<Submission<Statx, D>>>),
+    OpenTo(Pin<Box<Submission<OpenAt, D>>>, u32),
+    Read,
+    Write,
+    Done,
+}
+
+impl<D: Drive + Clone> Future for CopyFile<D> {
+    type Output = io::Result<u64>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        loop {
+            match &mut this.stage {
+                Stage::OpenFrom(open)   => {
+                    let (_, result) = ready!(open.as_mut().poll(ctx));
+                    let fd = this.fail_on_err(result)? as RawFd;
+                    this.from_fd = Some(fd);
+                    let mask = StatxMode::STATX_TYPE | StatxMode::STATX_MODE;
+                    let stat = Statx::without_path(fd, StatxFlags::empty(), mask);
+                    let driver = this.ring.driver().clone();
+                    this.stage = Stage::Stat(Box::pin(Submission::new(stat, driver)));
+                }
+                Stage::Stat(stat)       => {
+                    let (stat, result) = ready!(stat.as_mut().poll(ctx));
+                    this.fail_on_err(result)?;
+                    if stat.statx.stx_mode as u32 & libc::S_IFMT != libc::S_IFREG {
+                        this.finish();
+                        let err = "the source path is not a regular file";
+                        return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidInput, err)));
+                    }
+                    let mode = stat.statx.stx_mode as u32 & 0o7777;
+                    let flags = OFlag::O_CLOEXEC | OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_TRUNC;
+                    let open = OpenAt::without_dir(&this.to, flags, Mode::from_bits_truncate(mode));
+                    let driver = this.ring.driver().clone();
+                    this.stage = Stage::OpenTo(Box::pin(Submission::new(open, driver)), mode);
+                }
+                Stage::OpenTo(open, mode)   => {
+                    let mode = *mode;
+                    let (_, result) = ready!(open.as_mut().poll(ctx));
+                    let fd = this.fail_on_err(result)? as RawFd;
+                    this.to_fd = Some(fd);
+                    // The mode passed to open only applies if the file is created, and is masked
+                    // by the umask; the permissions are set here as std::fs::copy sets them.
+                    if unsafe { libc::fchmod(fd, mode) } < 0 {
+                        let err = io::Error::last_os_error();
+                        return Poll::Ready(this.fail_on_err(Err(err)).map(|_| 0));
+                    }
+                    this.buf = Some(vec![0; COPY_CHUNK].into_boxed_slice());
+                    this.stage = Stage::Read;
+                }
+                Stage::Read             => {
+                    let (fd, offset) = (this.from_fd.unwrap(), this.copied);
+                    let buf = this.buf.as_mut().unwrap();
+                    let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+                    let result = ready!(ring.poll(ctx, 1, |sqs| {
+                        let mut sqe = sqs.next().unwrap();
+                        unsafe {
+                            sqe.prep_read(fd, &mut buf[..], offset);
+                        }
+                        sqe
+                    }));
+                    let n = this.fail_on_err(result)? as usize;
+                    if n == 0 {
+                        this.finish();
+                        return Poll::Ready(Ok(this.copied));
+                    }
+                    this.filled = n;
+                    this.written = 0;
+                    this.stage = Stage::Write;
+                }
+                Stage::Write            => {
+                    let fd = this.to_fd.unwrap();
+                    let offset = this.copied + this.written as u64;
+                    let data = &this.buf.as_ref().unwrap()[this.written..this.filled];
+                    let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+                    let result = ready!(ring.poll(ctx, 1, |sqs| {
+                        let mut sqe = sqs.next().unwrap();
+                        unsafe {
+                            sqe.prep_write(fd, data, offset);
+                        }
+                        sqe
+                    }));
+                    let n = this.fail_on_err(result)? as usize;
+                    if n == 0 {
+                        let err = io::Error::from(io::ErrorKind::WriteZero);
+                        return Poll::Ready(this.fail_on_err(Err(err)).map(|_| 0));
+                    }
+                    this.written += n;
+                    if this.written == this.filled {
+                        this.copied += this.filled as u64;
+                        this.stage = Stage::Read;
+                    }
+                }
+                Stage::Done             => panic!("polled CopyFile future after completion"),
+            }
+        }
+    }
+}
+
+impl<D: Drive> CopyFile<D> {
+    /// Stop copying if `result` is an error.
+    fn fail_on_err(&mut self, result: io::Result<u32>) -> io::Result<u32> {
+        if result.is_err() {
+            self.finish();
+        }
+        result
+    }
+
+    fn finish(&mut self) {
+        self.stage = Stage::Done;
+        self.buf = None;
+        for fd in self.from_fd.take().into_iter().chain(self.to_fd.take()) {
+            unsafe { libc::close(fd); }
+        }
+    }
+}
+
+impl<D: Drive> Drop for CopyFile<D> {
+    fn drop(&mut self) {
+        // A read or write still running on io-uring keeps its buffer until it completes. The
+        // kernel holds its own reference to the files, so they can be closed now.
+        self.ring.cancel(Cancellation::from(self.buf.take()));
+        self.finish();
+    }
+}
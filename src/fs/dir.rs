@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+use std::ffi::{CStr, OsStr, OsString};
+use std::future::Future;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::{ready, Stream};
+use iou::sqe::{OFlag, Mode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::OpenAt;
+use crate::ring::{self, Blocking};
+use crate::Submission;
+
+use super::FileType;
+
+/// The size of the buffer each `getdents64(2)` call fills.
+const BATCH_SIZE: usize = 32 * 1024;
+
+/// An open directory
+///
+/// The directory is opened on io-uring. io-uring has no operation for reading directories, so
+/// [`Dir::read_dir`] reads its entries with `getdents64(2)` on ringbahn's pool of threads for
+/// blocking work, a batch at a time.
+pub struct Dir {
+    fd: Arc<DirFd>,
+    path: PathBuf,
+}
+
+/// The fd of a directory, which is closed once neither the `Dir` nor a read of its entries is
+/// using it.
+struct DirFd(RawFd);
+
+impl Drop for DirFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.0); }
+    }
+}
+
+impl Dir {
+    /// Open a directory using the default driver
+    pub fn open(path: impl AsRef<Path>) -> OpenDir {
+        Dir::open_on_driver(path, DefaultDriver::default())
+    }
+
+    /// Open a directory
+    pub fn open_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> OpenDir<D> {
+        let path = path.as_ref();
+        let flags = OFlag::O_CLOEXEC | OFlag::O_RDONLY | OFlag::O_DIRECTORY;
+        let open = driver.submit(OpenAt::without_dir(path, flags, Mode::empty()));
+        OpenDir { open, path: Some(path.to_owned()) }
+    }
+
+    /// The path the directory was opened at.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the entries of the directory, not including `.` and `..`.
+    ///
+    /// The stream shares the directory's position with any other stream of its entries, so each
+    /// entry is only returned once.
+    pub fn read_dir(&self) -> ReadDir {
+        ReadDir {
+            fd: self.fd.clone(),
+            path: self.path.clone(),
+            reading: None,
+            entries: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.0
+    }
+}
+
+/// Open the directory at `path` to read its entries, using the default driver.
+pub fn read_dir(path: impl AsRef<Path>) -> OpenReadDir {
+    read_dir_on_driver(path, DefaultDriver::default())
+}
+
+/// Open the directory at `path` to read its entries.
+pub fn read_dir_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> OpenReadDir<D> {
+    OpenReadDir { open: Dir::open_on_driver(path, driver) }
+}
+
+/// An entry in a directory
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    dir: PathBuf,
+    name: OsString,
+    ino: u64,
+    d_type: u8,
+}
+
+impl DirEntry {
+    /// The path of the entry, joined to the path the directory was opened at.
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(&self.name)
+    }
+
+    pub fn file_name(&self) -> &OsStr {
+        &self.name
+    }
+
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    /// The type of the entry, if the filesystem records it in the directory. Where it does not,
+    /// this is `None`, and the type can be read with [`metadata`](super::metadata).
+    pub fn file_type(&self) -> Option<FileType> {
+        let mode = match self.d_type {
+            libc::DT_REG    => libc::S_IFREG,
+            libc::DT_DIR    => libc::S_IFDIR,
+            libc::DT_LNK    => libc::S_IFLNK,
+            libc::DT_BLK    => libc::S_IFBLK,
+            libc::DT_CHR    => libc::S_IFCHR,
+            libc::DT_FIFO   => libc::S_IFIFO,
+            libc::DT_SOCK   => libc::S_IFSOCK,
+            _               => return None,
+        };
+        Some(FileType::from_mode(mode))
+    }
+}
+
+/// A stream of the entries of a [`Dir`]
+pub struct ReadDir {
+    fd: Arc<DirFd>,
+    path: PathBuf,
+    reading: Option<Blocking<io::Result<Vec<DirEntry>>>>,
+    entries: VecDeque<DirEntry>,
+    done: bool,
+}
+
+impl Stream for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(entry) = this.entries.pop_front() {
+                return Poll::Ready(Some(Ok(entry)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            let (fd, path) = (&this.fd, &this.path);
+            let reading = this.reading.get_or_insert_with(|| read_batch(fd, path));
+            let result = ready!(Pin::new(reading).poll(ctx));
+            this.reading = None;
+            match result {
+                Ok(entries) if entries.is_empty()   => this.done = true,
+                Ok(entries)                         => this.entries.extend(entries),
+                Err(err)                            => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+        }
+    }
+}
+
+/// Read the next batch of entries of the directory on the pool. An empty batch is the end of
+/// the directory.
+fn read_batch(fd: &Arc<DirFd>, path: &Path) -> Blocking<io::Result<Vec<DirEntry>>> {
+    let (fd, path) = (fd.clone(), path.to_owned());
+    ring::spawn_blocking(move || {
+        let mut buf = vec![0u64; BATCH_SIZE / 8];
+        let n = unsafe {
+            libc::syscall(libc::SYS_getdents64, fd.0, buf.as_mut_ptr(), BATCH_SIZE)
+        };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let buf = unsafe { std::slice::from_raw_parts(buf.as_ptr() as *const u8, n as usize) };
+        Ok(parse_dirents(buf, &path))
+    })
+}
+
+/// Parse the `linux_dirent64` records `getdents64` wrote into `buf`, skipping `.` and `..`.
+fn parse_dirents(mut buf: &[u8], dir: &Path) -> Vec<DirEntry> {
+    // The offsets of the fields of struct linux_dirent64
+    const INO: usize = 0;
+    const RECLEN: usize = 16;
+    const TYPE: usize = 18;
+    const NAME: usize = 19;
+
+    let mut entries = Vec::new();
+    while buf.len() >= NAME {
+        let mut ino = [0; 8];
+        ino.copy_from_slice(&buf[INO..INO + 8]);
+        let reclen = u16::from_ne_bytes([buf[RECLEN], buf[RECLEN + 1]]) as usize;
+        let name = unsafe { CStr::from_ptr(buf[NAME..].as_ptr() as *const libc::c_char) };
+        let name = name.to_bytes();
+        if name != b"." && name != b".." {
+            entries.push(DirEntry {
+                dir: dir.to_owned(),
+                name: OsStr::from_bytes(name).to_owned(),
+                ino: u64::from_ne_bytes(ino),
+                d_type: buf[TYPE],
+            });
+        }
+        buf = &buf[reclen..];
+    }
+    entries
+}
+
+/// A future which opens a [`Dir`].
+pub struct OpenDir<D: Drive = DefaultDriver> {
+    open: Submission<OpenAt, D>,
+    path: Option<PathBuf>,
+}
+
+impl<D: Drive> Future for OpenDir<D> {
+    type Output = io::Result<Dir>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<Dir>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let open = unsafe { Pin::new_unchecked(&mut this.open) };
+        let (_, result) = ready!(open.poll(ctx));
+        let fd = Arc::new(DirFd(result? as RawFd));
+        let path = this.path.take().expect("polled OpenDir future after completion");
+        Poll::Ready(Ok(Dir { fd, path }))
+    }
+}
+
+/// A future which opens a directory to read its entries, returned by [`read_dir`].
+pub struct OpenReadDir<D: Drive = DefaultDriver> {
+    open: OpenDir<D>,
+}
+
+impl<D: Drive> Future for OpenReadDir<D> {
+    type Output = io::Result<ReadDir>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<ReadDir>> {
+        let open = unsafe { Pin::map_unchecked_mut(self, |this| &mut this.open) };
+        let dir = ready!(open.poll(ctx))?;
+        Poll::Ready(Ok(dir.read_dir()))
+    }
+}
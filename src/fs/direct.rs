@@ -0,0 +1,108 @@
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+use std::slice;
+
+/// A buffer for direct IO, whose memory is aligned
+///
+/// Files opened with `O_DIRECT` (see [`OpenOptions::direct`](super::OpenOptions::direct)) are
+/// read and written without the page cache, and the kernel rejects IO on them with `EINVAL`
+/// unless the buffer's address, its length and the offset in the file are all aligned to the
+/// logical block size of the storage. [`File::read_direct`](super::File::read_direct) and
+/// [`File::write_direct`](super::File::write_direct) take an `AlignedBuf`, and check the length
+/// and offset against its alignment before submitting anything.
+///
+/// The buffer is owned by the IO while it runs, and handed back when it completes, so that the
+/// kernel never writes into memory which has been freed.
+pub struct AlignedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    align: usize,
+}
+
+unsafe impl Send for AlignedBuf { }
+unsafe impl Sync for AlignedBuf { }
+
+impl AlignedBuf {
+    /// The alignment of buffers constructed with [`AlignedBuf::new`]: the size of a page, which
+    /// is a multiple of the logical block size of any device.
+    pub const DEFAULT_ALIGNMENT: usize = 4096;
+
+    /// Allocate a zeroed buffer of `len` bytes, aligned to `DEFAULT_ALIGNMENT`.
+    pub fn new(len: usize) -> AlignedBuf {
+        AlignedBuf::with_alignment(len, AlignedBuf::DEFAULT_ALIGNMENT)
+            .expect("the default alignment is valid")
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes, aligned to `align`, which must be a power of
+    /// two.
+    ///
+    /// The alignment needed by a block device is its
+    /// [`logical_block_size`](super::BlockDevice::logical_block_size).
+    pub fn with_alignment(len: usize, align: usize) -> io::Result<AlignedBuf> {
+        let layout = Layout::from_size_align(len, align)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid alignment"))?;
+        let ptr = match len {
+            0   => unsafe { NonNull::new_unchecked(align as *mut u8) },
+            _   => {
+                let ptr = unsafe { alloc::alloc_zeroed(layout) };
+                NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+            }
+        };
+        Ok(AlignedBuf { ptr, len, align })
+    }
+
+    /// The alignment of the buffer's address, and of the lengths and offsets of IO using it.
+    pub fn alignment(&self) -> usize {
+        self.align
+    }
+
+    /// Check that IO with this buffer at `offset` will not be rejected for its alignment.
+    pub(super) fn check(&self, offset: u64) -> io::Result<()> {
+        if !self.len.is_multiple_of(self.align) {
+            let msg = "the length of a direct IO buffer must be a multiple of its alignment";
+            Err(io::Error::new(io::ErrorKind::InvalidInput, msg))
+        } else if !offset.is_multiple_of(self.align as u64) {
+            let msg = "the offset of direct IO must be a multiple of the buffer's alignment";
+            Err(io::Error::new(io::ErrorKind::InvalidInput, msg))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl fmt::Debug for AlignedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuf")
+            .field("len", &self.len)
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe {
+                let layout = Layout::from_size_align_unchecked(self.len, self.align);
+                alloc::dealloc(self.ptr.as_ptr(), layout);
+            }
+        }
+    }
+}
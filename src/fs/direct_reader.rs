@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::{ready, Stream};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::{Event, SQE, SQEs};
+use crate::ring::Cancellation;
+use crate::Submission;
+
+use super::{AlignedBuf, File};
+
+/// A stream which reads a file opened with `O_DIRECT` from start to end, keeping several reads
+/// in flight ahead of the consumer
+///
+/// Each read fills one [`AlignedBuf`] of the length the reader was constructed with. While the
+/// consumer processes one chunk, the reads of the following chunks are already running, so a
+/// sequential scan is not slowed down by waiting for each read in turn. Chunks are yielded in
+/// order; the last is shorter than the others if the file's length is not a multiple of the
+/// buffer length.
+///
+/// Buffers of chunks which have been processed can be handed back with
+/// [`DirectReader::recycle`], so that the scan does not allocate a buffer for every read.
+pub struct DirectReader<D: Drive + Clone = DefaultDriver> {
+    reads: VecDeque<Pin<Box<Submission<ReadAligned, D>>>>,
+    free: Vec<AlignedBuf>,
+    file: File<D>,
+    buf_len: usize,
+    depth: usize,
+    next_offset: u64,
+    done: bool,
+}
+
+impl<D: Drive + Clone> DirectReader<D> {
+    /// Read `file` in chunks of `buf_len` bytes, with up to `depth` reads in flight.
+    ///
+    /// The buffers are aligned to [`AlignedBuf::DEFAULT_ALIGNMENT`], and `buf_len` must be a
+    /// multiple of it, or this fails with `InvalidInput`.
+    pub fn new(file: File<D>, buf_len: usize, depth: usize) -> io::Result<DirectReader<D>> {
+        AlignedBuf::new(buf_len).check(0)?;
+        Ok(DirectReader {
+            reads: VecDeque::with_capacity(depth),
+            free: Vec::new(),
+            file,
+            buf_len,
+            depth: depth.max(1),
+            next_offset: 0,
+            done: false,
+        })
+    }
+
+    /// Hand back the buffer of a chunk, to be reused by a later read.
+    pub fn recycle(&mut self, buf: AlignedBuf) {
+        if buf.len() == self.buf_len && buf.alignment() == AlignedBuf::DEFAULT_ALIGNMENT {
+            self.free.push(buf);
+        }
+    }
+
+    /// Stop reading, and return the file. Reads still in flight are cancelled.
+    pub fn into_file(self) -> File<D> {
+        let DirectReader { reads, file, .. } = self;
+        drop(reads);
+        file
+    }
+
+    /// Start reads until `depth` of them are in flight.
+    fn fill(&mut self) {
+        while !self.done && self.reads.len() < self.depth {
+            let buf = self.free.pop().unwrap_or_else(|| AlignedBuf::new(self.buf_len));
+            let read = ReadAligned { fd: self.file.fd, buf, offset: self.next_offset };
+            let driver = self.file.ring.driver().clone();
+            self.reads.push_back(Box::pin(Submission::new(read, driver)));
+            self.next_offset += self.buf_len as u64;
+        }
+    }
+}
+
+impl<D: Drive + Clone> Stream for DirectReader<D> {
+    type Item = io::Result<Chunk>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        this.fill();
+        let read = match this.reads.front_mut() {
+            Some(read)  => read,
+            None        => return Poll::Ready(None),
+        };
+        let (read, result) = ready!(read.as_mut().poll(ctx));
+        this.reads.pop_front();
+        match result {
+            Ok(0)   => {
+                this.done = true;
+                this.reads.clear();
+                Poll::Ready(None)
+            }
+            Ok(n)   => {
+                // A short read is the end of the file; the reads after it will find nothing.
+                if n as usize != this.buf_len {
+                    this.done = true;
+                    this.reads.clear();
+                }
+                Poll::Ready(Some(Ok(Chunk { offset: read.offset, buf: read.buf, len: n as usize })))
+            }
+            Err(err) => {
+                this.done = true;
+                this.reads.clear();
+                Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+}
+
+/// A chunk of a file read by a [`DirectReader`]
+///
+/// The chunk dereferences to the data which was read.
+#[derive(Debug)]
+pub struct Chunk {
+    offset: u64,
+    buf: AlignedBuf,
+    len: usize,
+}
+
+impl Chunk {
+    /// The offset in the file the chunk was read from.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The buffer the chunk was read into, which can be handed back to the reader with
+    /// [`DirectReader::recycle`].
+    pub fn into_buf(self) -> AlignedBuf {
+        self.buf
+    }
+}
+
+impl Deref for Chunk {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// A read into an aligned buffer.
+struct ReadAligned {
+    fd: RawFd,
+    buf: AlignedBuf,
+    offset: u64,
+}
+
+impl Event for ReadAligned {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, sqs: &mut SQEs<'sq>) -> SQE<'sq> {
+        let mut sqe = sqs.next().unwrap();
+        sqe.prep_read(self.fd, &mut self.buf[..], self.offset);
+        sqe
+    }
+
+    fn cancel(this: ManuallyDrop<Self>) -> Cancellation {
+        Cancellation::from(Box::new(ManuallyDrop::into_inner(this).buf))
+    }
+}
@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+
+use crate::drive::Drive;
+
+use super::{File, Op};
+
+impl<D: Drive> File<D> {
+    /// Take an exclusive advisory lock on the file, like `flock(2)` with `LOCK_EX`, waiting
+    /// until no other process holds a lock on it.
+    ///
+    /// io-uring has no operation for locking files, so the lock is taken on ringbahn's pool of
+    /// threads for blocking work. If the future is dropped before it completes, the lock may
+    /// still be taken once the file is available; call [`File::unlock`] to be sure it is not
+    /// held.
+    pub fn lock_exclusive(&mut self) -> Lock<'_, D> where D: Unpin {
+        Pin::new(self).lock_exclusive_pinned()
+    }
+
+    pub fn lock_exclusive_pinned(self: Pin<&mut Self>) -> Lock<'_, D> {
+        Lock { file: self, operation: libc::LOCK_EX }
+    }
+
+    /// Take a shared advisory lock on the file, like `flock(2)` with `LOCK_SH`, waiting until
+    /// no other process holds an exclusive lock on it.
+    ///
+    /// This runs on the pool of threads for blocking work, as [`File::lock_exclusive`] does.
+    pub fn lock_shared(&mut self) -> Lock<'_, D> where D: Unpin {
+        Pin::new(self).lock_shared_pinned()
+    }
+
+    pub fn lock_shared_pinned(self: Pin<&mut Self>) -> Lock<'_, D> {
+        Lock { file: self, operation: libc::LOCK_SH }
+    }
+
+    /// Release the lock held on the file, like `flock(2)` with `LOCK_UN`.
+    ///
+    /// Locks are also released when every descriptor of the open file is closed.
+    pub fn unlock(&mut self) -> Lock<'_, D> where D: Unpin {
+        Pin::new(self).unlock_pinned()
+    }
+
+    pub fn unlock_pinned(self: Pin<&mut Self>) -> Lock<'_, D> {
+        Lock { file: self, operation: libc::LOCK_UN }
+    }
+
+    fn poll_flock(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, operation: i32)
+        -> Poll<io::Result<()>>
+    {
+        self.as_mut().guard_op(Op::Lock);
+        let fd = self.fd;
+        if !self.ring.is_running() {
+            self.ring().start_emulated(ctx, Box::new(move || {
+                match unsafe { libc::flock(fd, operation) } {
+                    -1  => Err(io::Error::last_os_error()),
+                    _   => Ok(0),
+                }
+            }));
+            return Poll::Pending;
+        }
+        ready!(self.ring().poll(ctx, 1, |_| unreachable!("flock is never prepared on io-uring")))?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A future which takes or releases an advisory lock on a [`File`].
+pub struct Lock<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    operation: i32,
+}
+
+impl<'a, D: Drive> Future for Lock<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let operation = self.operation;
+        self.file.as_mut().poll_flock(ctx, operation)
+    }
+}
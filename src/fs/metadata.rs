@@ -0,0 +1,233 @@
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_core::ready;
+use iou::sqe::{StatxFlags, StatxMode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::Statx;
+use crate::Submission;
+
+/// The fields requested from statx: everything `stat(2)` returns, and the creation time.
+pub(super) fn statx_mask() -> StatxMode {
+    StatxMode::from_bits_truncate((libc::STATX_BASIC_STATS | libc::STATX_BTIME) as i32)
+}
+
+/// Metadata about a file, as returned by `statx(2)`
+#[derive(Clone)]
+pub struct Metadata {
+    statx: libc::statx,
+}
+
+impl Metadata {
+    pub(super) fn from_statx(statx: libc::statx) -> Metadata {
+        Metadata { statx }
+    }
+
+    /// The size of the file in bytes.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.statx.stx_size
+    }
+
+    pub fn file_type(&self) -> FileType {
+        FileType { mode: self.mode() }
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.file_type().is_file()
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type().is_dir()
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.file_type().is_symlink()
+    }
+
+    /// The file's type and permission bits, as in `st_mode`.
+    pub fn mode(&self) -> u32 {
+        self.statx.stx_mode as u32
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.statx.stx_uid
+    }
+
+    pub fn gid(&self) -> u32 {
+        self.statx.stx_gid
+    }
+
+    pub fn nlink(&self) -> u64 {
+        self.statx.stx_nlink as u64
+    }
+
+    pub fn ino(&self) -> u64 {
+        self.statx.stx_ino
+    }
+
+    /// The device the file is on.
+    pub fn dev(&self) -> u64 {
+        // Encoded as glibc's makedev(3) does, so that it compares equal to `st_dev`
+        let (major, minor) = (self.statx.stx_dev_major as u64, self.statx.stx_dev_minor as u64);
+        ((major & 0xfffff000) << 32) | ((major & 0xfff) << 8)
+            | ((minor & 0xffffff00) << 12) | (minor & 0xff)
+    }
+
+    /// The preferred size of blocks for IO on the file.
+    pub fn blksize(&self) -> u64 {
+        self.statx.stx_blksize as u64
+    }
+
+    /// The number of 512-byte blocks allocated to the file.
+    pub fn blocks(&self) -> u64 {
+        self.statx.stx_blocks
+    }
+
+    /// The time of the last modification of the file's contents.
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_MTIME, &self.statx.stx_mtime)
+    }
+
+    /// The time of the last access to the file's contents.
+    pub fn accessed(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_ATIME, &self.statx.stx_atime)
+    }
+
+    /// The time of the last change to the file's metadata.
+    pub fn changed(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_CTIME, &self.statx.stx_ctime)
+    }
+
+    /// The time the file was created. Not every filesystem records this; where it is missing,
+    /// this fails with `Other`.
+    pub fn created(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_BTIME, &self.statx.stx_btime)
+    }
+
+    fn time(&self, field: u32, ts: &libc::statx_timestamp) -> io::Result<SystemTime> {
+        if self.statx.stx_mask & field == 0 {
+            return Err(io::Error::other("timestamp not available"));
+        }
+        let secs = match ts.tv_sec >= 0 {
+            true    => UNIX_EPOCH.checked_add(Duration::from_secs(ts.tv_sec as u64)),
+            false   => UNIX_EPOCH.checked_sub(Duration::from_secs(ts.tv_sec.unsigned_abs())),
+        };
+        secs.and_then(|time| time.checked_add(Duration::from_nanos(ts.tv_nsec as u64)))
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::EOVERFLOW))
+    }
+}
+
+impl fmt::Debug for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metadata")
+            .field("file_type", &self.file_type())
+            .field("mode", &format_args!("{:#o}", self.mode() & 0o7777))
+            .field("len", &self.len())
+            .field("modified", &self.modified().ok())
+            .finish()
+    }
+}
+
+/// The type of a file, like a regular file or a directory
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct FileType {
+    mode: u32,
+}
+
+impl FileType {
+    pub(super) fn from_mode(mode: u32) -> FileType {
+        FileType { mode }
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.is(libc::S_IFREG)
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.is(libc::S_IFDIR)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.is(libc::S_IFLNK)
+    }
+
+    pub fn is_block_device(&self) -> bool {
+        self.is(libc::S_IFBLK)
+    }
+
+    pub fn is_char_device(&self) -> bool {
+        self.is(libc::S_IFCHR)
+    }
+
+    pub fn is_fifo(&self) -> bool {
+        self.is(libc::S_IFIFO)
+    }
+
+    pub fn is_socket(&self) -> bool {
+        self.is(libc::S_IFSOCK)
+    }
+
+    fn is(&self, ty: libc::mode_t) -> bool {
+        self.mode & libc::S_IFMT == ty
+    }
+}
+
+impl fmt::Debug for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.mode & libc::S_IFMT {
+            libc::S_IFREG   => "file",
+            libc::S_IFDIR   => "dir",
+            libc::S_IFLNK   => "symlink",
+            libc::S_IFBLK   => "block device",
+            libc::S_IFCHR   => "char device",
+            libc::S_IFIFO   => "fifo",
+            libc::S_IFSOCK  => "socket",
+            _               => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Read the metadata of the file at `path` using the default driver, following symlinks.
+pub fn metadata(path: impl AsRef<Path>) -> Stat {
+    metadata_on_driver(path, DefaultDriver::default())
+}
+
+/// Read the metadata of the file at `path`, following symlinks.
+pub fn metadata_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> Stat<D> {
+    Stat(driver.submit(Statx::without_dir(path, StatxFlags::empty(), statx_mask())))
+}
+
+/// Read the metadata of the file at `path` using the default driver, without following a
+/// symlink at `path`.
+pub fn symlink_metadata(path: impl AsRef<Path>) -> Stat {
+    symlink_metadata_on_driver(path, DefaultDriver::default())
+}
+
+/// Read the metadata of the file at `path`, without following a symlink at `path`.
+pub fn symlink_metadata_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> Stat<D> {
+    // iou gives AT_SYMLINK_NOFOLLOW the value of AT_SYMLINK_FOLLOW, which statx rejects
+    let flags = unsafe { StatxFlags::from_bits_unchecked(libc::AT_SYMLINK_NOFOLLOW) };
+    Stat(driver.submit(Statx::without_dir(path, flags, statx_mask())))
+}
+
+/// A future which reads the metadata of a file by its path.
+pub struct Stat<D: Drive = DefaultDriver>(Submission<Statx, D>);
+
+impl<D: Drive> Future for Stat<D> {
+    type Output = io::Result<Metadata>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<Metadata>> {
+        let inner = unsafe { Pin::map_unchecked_mut(self, |this| &mut this.0) };
+        let (statx, result) = ready!(inner.poll(ctx));
+        result?;
+        Poll::Ready(Ok(Metadata::from_statx(*statx.statx)))
+    }
+}
@@ -0,0 +1,218 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+
+use iou::sqe::{OFlag, Mode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::{OpenAt, OpenAt2, ResolveFlags};
+
+use super::Open;
+
+/// Options for opening a file on io-uring
+///
+/// This is the counterpart of [`std::fs::OpenOptions`], including the `mode` and `custom_flags`
+/// of its Unix extension. The file is opened with an `IORING_OP_OPENAT` event, so opening it
+/// does not block the calling thread.
+///
+/// ```no_run
+/// use ringbahn::fs::OpenOptions;
+///
+/// # fn main() -> std::io::Result<()> { futures::executor::block_on(async {
+/// let file = OpenOptions::new().append(true).create(true).open("log.txt").await?;
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    direct: bool,
+    resolve: ResolveFlags,
+    mode: u32,
+    custom_flags: i32,
+}
+
+impl Default for OpenOptions {
+    fn default() -> OpenOptions {
+        OpenOptions {
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+            direct: false,
+            resolve: ResolveFlags::empty(),
+            mode: 0o666,
+            custom_flags: 0,
+        }
+    }
+}
+
+impl OpenOptions {
+    /// Construct a set of options with every option disabled. At least one of `read`, `write`
+    /// and `append` must be enabled for `open` to succeed.
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+
+    pub fn read(&mut self, read: bool) -> &mut OpenOptions {
+        self.read = read;
+        self
+    }
+
+    pub fn write(&mut self, write: bool) -> &mut OpenOptions {
+        self.write = write;
+        self
+    }
+
+    /// Open the file with `O_APPEND`, so that every write is made at its end. This implies
+    /// `write`.
+    pub fn append(&mut self, append: bool) -> &mut OpenOptions {
+        self.append = append;
+        self
+    }
+
+    /// Truncate the file to 0 bytes when it is opened. This requires `write`.
+    pub fn truncate(&mut self, truncate: bool) -> &mut OpenOptions {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Create the file if it does not exist. This requires `write` or `append`.
+    pub fn create(&mut self, create: bool) -> &mut OpenOptions {
+        self.create = create;
+        self
+    }
+
+    /// Create the file, failing with `AlreadyExists` if it exists. This requires `write` or
+    /// `append`, and takes precedence over `create` and `truncate`.
+    pub fn create_new(&mut self, create_new: bool) -> &mut OpenOptions {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Open the file with `O_DIRECT`, so that its IO bypasses the page cache.
+    ///
+    /// Direct IO must use aligned buffers, lengths and offsets, so the file should be read and
+    /// written with [`File::read_direct`](super::File::read_direct) and
+    /// [`File::write_direct`](super::File::write_direct) rather than through its buffer. Some
+    /// filesystems, like tmpfs, do not support direct IO, and fail to open the file with
+    /// `EINVAL`.
+    pub fn direct(&mut self, direct: bool) -> &mut OpenOptions {
+        self.direct = direct;
+        self
+    }
+
+    /// Restrict how the path is resolved, as `openat2(2)` does. With any flags set, the file is
+    /// opened with `IORING_OP_OPENAT2`, which needs Linux 5.6.
+    ///
+    /// Combined with [`OpenOptions::open_at`], [`ResolveFlags::BENEATH`] or
+    /// [`ResolveFlags::IN_ROOT`] confine paths supplied by users to a directory.
+    pub fn resolve(&mut self, resolve: ResolveFlags) -> &mut OpenOptions {
+        self.resolve = resolve;
+        self
+    }
+
+    /// The permissions a created file is given, before the process's umask is applied. This is
+    /// 0o666 by default.
+    pub fn mode(&mut self, mode: u32) -> &mut OpenOptions {
+        self.mode = mode;
+        self
+    }
+
+    /// Further flags to pass to `openat(2)`, like `O_NOFOLLOW`. The access mode bits are
+    /// ignored; `O_CLOEXEC` is always set.
+    pub fn custom_flags(&mut self, flags: i32) -> &mut OpenOptions {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Open the file at `path` using the default driver.
+    pub fn open(&self, path: impl AsRef<Path>) -> Open {
+        self.open_on_driver(path, DefaultDriver::default())
+    }
+
+    /// Open the file at `path` using the provided driver.
+    ///
+    /// If the options are inconsistent, like `truncate` without `write`, the future fails with
+    /// `InvalidInput` without submitting anything.
+    pub fn open_on_driver<D: Drive + Clone>(&self, path: impl AsRef<Path>, driver: D) -> Open<D> {
+        self.open_in(libc::AT_FDCWD, path, driver)
+    }
+
+    /// Open the file at `path`, relative to the directory `dir`, using the default driver.
+    pub fn open_at(&self, dir: &impl AsRawFd, path: impl AsRef<Path>) -> Open {
+        self.open_at_on_driver(dir, path, DefaultDriver::default())
+    }
+
+    /// Open the file at `path`, relative to the directory `dir`, like `openat(2)`.
+    ///
+    /// `dir` is usually a [`Dir`](super::Dir). It must stay open until the future completes.
+    pub fn open_at_on_driver<D: Drive + Clone>(
+        &self,
+        dir: &impl AsRawFd,
+        path: impl AsRef<Path>,
+        driver: D,
+    ) -> Open<D> {
+        self.open_in(dir.as_raw_fd(), path, driver)
+    }
+
+    fn open_in<D: Drive + Clone>(&self, dir_fd: RawFd, path: impl AsRef<Path>, driver: D)
+        -> Open<D>
+    {
+        let flags = match self.flags() {
+            Ok(flags)   => flags,
+            Err(err)    => return Open::failed(err),
+        };
+        let mode = Mode::from_bits_truncate(self.mode);
+        if self.resolve.is_empty() {
+            let mut open = OpenAt::without_dir(path, flags, mode);
+            open.dir_fd = dir_fd;
+            Open::submit(open, driver)
+        } else {
+            // openat2 rejects a mode unless the file may be created
+            let mode = if flags.contains(OFlag::O_CREAT) { mode } else { Mode::empty() };
+            let mut open = OpenAt2::without_dir(path, flags, mode, self.resolve);
+            open.dir_fd = dir_fd;
+            Open::submit_openat2(open, driver)
+        }
+    }
+
+    fn flags(&self) -> io::Result<OFlag> {
+        let access = match (self.read, self.write, self.append) {
+            (true, false, false)    => libc::O_RDONLY,
+            (false, true, false)    => libc::O_WRONLY,
+            (true, true, false)     => libc::O_RDWR,
+            (false, _, true)        => libc::O_WRONLY | libc::O_APPEND,
+            (true, _, true)         => libc::O_RDWR | libc::O_APPEND,
+            (false, false, false)   => return Err(invalid("no access mode was set")),
+        };
+        let writable = self.write || self.append;
+        let creation = match (writable, self.truncate, self.create, self.create_new) {
+            (_, false, false, false)    => 0,
+            (false, ..)                 => return Err(invalid("creating or truncating requires write")),
+            (true, _, _, true)          => libc::O_CREAT | libc::O_EXCL,
+            (true, false, true, false)  => libc::O_CREAT,
+            (true, true, false, false)  => libc::O_TRUNC,
+            (true, true, true, false)   => libc::O_CREAT | libc::O_TRUNC,
+        };
+        if self.truncate && self.append && !self.create_new {
+            return Err(invalid("a file cannot be both truncated and appended to"));
+        }
+        let direct = if self.direct { libc::O_DIRECT } else { 0 };
+        let custom = self.custom_flags & !libc::O_ACCMODE;
+        let flags = access | creation | direct | libc::O_CLOEXEC | custom;
+        Ok(OFlag::from_bits_truncate(flags))
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
@@ -0,0 +1,130 @@
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use iou::sqe::Mode;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::{Event, LinkAt, MkdirAt, RenameAt, SymlinkAt, UnlinkAt};
+use crate::ring::{self, Blocking};
+use crate::Submission;
+
+/// Remove the file at `path` using the default driver.
+pub fn remove_file(path: impl AsRef<Path>) -> PathOp<UnlinkAt> {
+    remove_file_on_driver(path, DefaultDriver::default())
+}
+
+/// Remove the file at `path`, with `IORING_OP_UNLINKAT`.
+pub fn remove_file_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> PathOp<UnlinkAt, D> {
+    PathOp(driver.submit(UnlinkAt::without_dir(path, 0)))
+}
+
+/// Remove the empty directory at `path` using the default driver.
+pub fn remove_dir(path: impl AsRef<Path>) -> PathOp<UnlinkAt> {
+    remove_dir_on_driver(path, DefaultDriver::default())
+}
+
+/// Remove the empty directory at `path`, with `IORING_OP_UNLINKAT`.
+pub fn remove_dir_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> PathOp<UnlinkAt, D> {
+    PathOp(driver.submit(UnlinkAt::without_dir(path, libc::AT_REMOVEDIR)))
+}
+
+/// Rename `from` to `to` using the default driver.
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> PathOp<RenameAt> {
+    rename_on_driver(from, to, DefaultDriver::default())
+}
+
+/// Rename `from` to `to`, replacing `to` if it exists, with `IORING_OP_RENAMEAT`.
+pub fn rename_on_driver<D: Drive>(from: impl AsRef<Path>, to: impl AsRef<Path>, driver: D)
+    -> PathOp<RenameAt, D>
+{
+    PathOp(driver.submit(RenameAt::without_dir(from, to, 0)))
+}
+
+/// Create a directory at `path` using the default driver.
+pub fn create_dir(path: impl AsRef<Path>) -> PathOp<MkdirAt> {
+    create_dir_on_driver(path, DefaultDriver::default())
+}
+
+/// Create a directory at `path`, with `IORING_OP_MKDIRAT`. Its parent must already exist.
+pub fn create_dir_on_driver<D: Drive>(path: impl AsRef<Path>, driver: D) -> PathOp<MkdirAt, D> {
+    PathOp(driver.submit(MkdirAt::without_dir(path, Mode::from_bits_truncate(0o777))))
+}
+
+/// Create a symbolic link at `link` which points to `target` using the default driver.
+pub fn symlink(target: impl AsRef<Path>, link: impl AsRef<Path>) -> PathOp<SymlinkAt> {
+    symlink_on_driver(target, link, DefaultDriver::default())
+}
+
+/// Create a symbolic link at `link` which points to `target`, with `IORING_OP_SYMLINKAT`.
+///
+/// `target` is not resolved, so it need not exist; a relative `target` is resolved relative to
+/// the directory containing `link` when the link is followed.
+pub fn symlink_on_driver<D: Drive>(target: impl AsRef<Path>, link: impl AsRef<Path>, driver: D)
+    -> PathOp<SymlinkAt, D>
+{
+    PathOp(driver.submit(SymlinkAt::without_dir(target, link)))
+}
+
+/// Create a hard link at `link` to the file at `original` using the default driver.
+pub fn hard_link(original: impl AsRef<Path>, link: impl AsRef<Path>) -> PathOp<LinkAt> {
+    hard_link_on_driver(original, link, DefaultDriver::default())
+}
+
+/// Create a hard link at `link` to the file at `original`, with `IORING_OP_LINKAT`.
+///
+/// If `original` is a symbolic link, the new link is to the symbolic link itself, as with
+/// `std::fs::hard_link` on Linux.
+pub fn hard_link_on_driver<D: Drive>(original: impl AsRef<Path>, link: impl AsRef<Path>, driver: D)
+    -> PathOp<LinkAt, D>
+{
+    PathOp(driver.submit(LinkAt::without_dir(original, link, 0)))
+}
+
+/// Read the target of the symbolic link at `path`
+///
+/// io-uring has no operation for reading symbolic links, so this calls `readlink(2)` on
+/// ringbahn's pool of threads for blocking work. Dropping the future does not stop the call,
+/// whose result is discarded when it completes.
+pub fn read_link(path: impl AsRef<Path>) -> ReadLink {
+    let path = path.as_ref().to_owned();
+    ReadLink(ring::spawn_blocking(move || std::fs::read_link(path)))
+}
+
+/// A future which changes the filesystem at a path, like removing or renaming a file
+///
+/// On kernels which do not support the operation on io-uring (before 5.11 for unlinking and
+/// renaming, and 5.15 for creating directories and links), it runs on ringbahn's pool of
+/// threads instead.
+pub struct PathOp<E: Event, D: Drive = DefaultDriver>(Submission<E, D>);
+
+impl<E: Event, D: Drive> PathOp<E, D> {
+    pub(super) fn submit(event: E, driver: D) -> PathOp<E, D> {
+        PathOp(driver.submit(event))
+    }
+}
+
+impl<E: Event, D: Drive> Future for PathOp<E, D> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let inner = unsafe { Pin::map_unchecked_mut(self, |this| &mut this.0) };
+        let (_, result) = ready!(inner.poll(ctx));
+        result?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A future which reads the target of a symbolic link, returned by [`read_link`].
+pub struct ReadLink(Blocking<io::Result<PathBuf>>);
+
+impl Future for ReadLink {
+    type Output = io::Result<PathBuf>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<PathBuf>> {
+        Pin::new(&mut self.0).poll(ctx)
+    }
+}
@@ -0,0 +1,129 @@
+use std::ffi::CString;
+use std::future::Future;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_core::ready;
+
+use crate::drive::Drive;
+use crate::ring::{self, Blocking};
+
+use super::{File, Op};
+
+impl<D: Drive> File<D> {
+    /// Set the last access and modification times of the file, like `futimens(3)`. A time which
+    /// is `None` is left as it is.
+    ///
+    /// io-uring has no operation for setting timestamps, so this runs on ringbahn's pool of
+    /// threads for blocking work.
+    pub fn set_times(&mut self, accessed: Option<SystemTime>, modified: Option<SystemTime>)
+        -> SetTimes<'_, D> where D: Unpin
+    {
+        Pin::new(self).set_times_pinned(accessed, modified)
+    }
+
+    pub fn set_times_pinned(
+        self: Pin<&mut Self>,
+        accessed: Option<SystemTime>,
+        modified: Option<SystemTime>,
+    ) -> SetTimes<'_, D> {
+        SetTimes { file: self, times: [timespec(accessed), timespec(modified)] }
+    }
+
+    fn poll_set_times(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        times: [libc::timespec; 2],
+    ) -> Poll<io::Result<()>> {
+        self.as_mut().guard_op(Op::SetTimes);
+        let fd = self.fd;
+        if !self.ring.is_running() {
+            self.ring().start_emulated(ctx, Box::new(move || {
+                match unsafe { libc::futimens(fd, times.as_ptr()) } {
+                    -1  => Err(io::Error::last_os_error()),
+                    _   => Ok(0),
+                }
+            }));
+            return Poll::Pending;
+        }
+        ready!(self.ring().poll(ctx, 1, |_| unreachable!("futimens is never prepared")))?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Set the last access and modification times of the file at `path`, like `utimensat(2)`. A
+/// time which is `None` is left as it is. If `path` is a symbolic link, the times of its target
+/// are set.
+///
+/// This runs on ringbahn's pool of threads for blocking work.
+pub fn set_times(
+    path: impl AsRef<Path>,
+    accessed: Option<SystemTime>,
+    modified: Option<SystemTime>,
+) -> SetPathTimes {
+    let path = CString::new(path.as_ref().as_os_str().as_bytes());
+    let times = [timespec(accessed), timespec(modified)];
+    SetPathTimes(ring::spawn_blocking(move || {
+        let path = path.map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "path contains nul")
+        })?;
+        match unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) } {
+            -1  => Err(io::Error::last_os_error()),
+            _   => Ok(()),
+        }
+    }))
+}
+
+/// The timespec for `time` to be passed to `utimensat`, which omits the time if it is `None`.
+fn timespec(time: Option<SystemTime>) -> libc::timespec {
+    let time = match time {
+        Some(time)  => time,
+        None        => return libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_OMIT },
+    };
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since)   => libc::timespec {
+            tv_sec: since.as_secs() as libc::time_t,
+            tv_nsec: since.subsec_nanos() as _,
+        },
+        // Times before the epoch have a negative number of seconds and a positive number of
+        // nanoseconds
+        Err(err)    => {
+            let before = err.duration();
+            let (secs, nanos) = match before.subsec_nanos() {
+                0       => (-(before.as_secs() as i64), 0),
+                nanos   => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            };
+            libc::timespec { tv_sec: secs as libc::time_t, tv_nsec: nanos as _ }
+        }
+    }
+}
+
+/// A future which sets the timestamps of a [`File`].
+pub struct SetTimes<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    times: [libc::timespec; 2],
+}
+
+impl<'a, D: Drive> Future for SetTimes<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let times = self.times;
+        self.file.as_mut().poll_set_times(ctx, times)
+    }
+}
+
+/// A future which sets the timestamps of the file at a path, returned by [`set_times`].
+pub struct SetPathTimes(Blocking<io::Result<()>>);
+
+impl Future for SetPathTimes {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll(ctx)
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use iou::sqe::{OFlag, Mode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::{LinkAt, OpenAt};
+
+use super::{File, Open, PathOp};
+
+/// Open an unnamed temporary file in the directory `dir` using the default driver.
+pub fn tempfile_in(dir: impl AsRef<Path>) -> Open {
+    tempfile_in_on_driver(dir, DefaultDriver::default())
+}
+
+/// Open an unnamed temporary file in the directory `dir`, with `O_TMPFILE`
+///
+/// The file is opened for reading and writing, and has no name: it is removed when it is closed,
+/// unless it is given a name first with [`File::link_into`]. Writing a file this way and then
+/// linking it into place means that a crash never leaves a partly written file behind.
+///
+/// Not every filesystem supports `O_TMPFILE`; those which do not fail with `EOPNOTSUPP`.
+pub fn tempfile_in_on_driver<D: Drive + Clone>(dir: impl AsRef<Path>, driver: D) -> Open<D> {
+    let flags = OFlag::O_CLOEXEC | OFlag::O_RDWR | OFlag::O_TMPFILE;
+    Open::submit(OpenAt::without_dir(dir, flags, Mode::from_bits_truncate(0o600)), driver)
+}
+
+impl<D: Drive + Clone> File<D> {
+    /// Give the file a name at `path`, with `IORING_OP_LINKAT`.
+    ///
+    /// This is how a file opened with [`tempfile_in`] is persisted; `path` must be on the same
+    /// filesystem as it. The link fails with `AlreadyExists` if `path` exists, so to replace a
+    /// file, link the temporary file to another name in the same directory and
+    /// [`rename`](super::rename) it over the old one, which replaces it atomically.
+    pub fn link_into(&self, path: impl AsRef<Path>) -> PathOp<LinkAt, D> {
+        // Linking the file through its descriptor, with AT_EMPTY_PATH, needs CAP_DAC_READ_SEARCH;
+        // following its magic link in /proc does not.
+        let proc_path = format!("/proc/self/fd/{}", self.fd);
+        let link = LinkAt::without_dir(proc_path, path, libc::AT_SYMLINK_FOLLOW);
+        PathOp::submit(link, self.ring.driver().clone())
+    }
+}
@@ -0,0 +1,254 @@
+use std::cmp;
+use std::future::Future;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use iou::sqe::{OFlag, Mode, StatxFlags, StatxMode};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event::{Close, OpenAt, Statx};
+use crate::ring::{Cancellation, Ring};
+use crate::Submission;
+
+/// The smallest buffer a file of unknown size is read into.
+const MIN_READ: usize = 8 * 1024;
+
+/// Read the whole file at `path` using the default driver.
+pub fn read(path: impl AsRef<Path>) -> ReadFile {
+    read_on_driver(path, DefaultDriver::default())
+}
+
+/// Read the whole file at `path`, like `std::fs::read`
+///
+/// The file is opened, read and closed on io-uring. Its size is read first, so that the contents
+/// of a regular file are usually read into a buffer of the right size in a single read.
+pub fn read_on_driver<D: Drive + Clone>(path: impl AsRef<Path>, driver: D) -> ReadFile<D> {
+    let open = OpenAt::without_dir(path, OFlag::O_CLOEXEC | OFlag::O_RDONLY, Mode::empty());
+    ReadFile {
+        stage: ReadStage::Open(Submission::new(open, driver.clone())),
+        ring: Ring::new(driver),
+        fd: None,
+        buf: Vec::new(),
+        filled: 0,
+    }
+}
+
+/// Write `contents` to the file at `path` using the default driver.
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> WriteFile {
+    write_on_driver(path, contents, DefaultDriver::default())
+}
+
+/// Write `contents` to the file at `path`, like `std::fs::write`
+///
+/// The file is created if it does not exist and truncated if it does. It is opened, written and
+/// closed on io-uring; short writes are continued until all of `contents` has been written.
+pub fn write_on_driver<D: Drive + Clone>(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+    driver: D,
+) -> WriteFile<D> {
+    let flags = OFlag::O_CLOEXEC | OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_TRUNC;
+    let open = OpenAt::without_dir(path, flags, Mode::from_bits_truncate(0o666));
+    WriteFile {
+        stage: WriteStage::Open(Submission::new(open, driver.clone())),
+        ring: Ring::new(driver),
+        fd: None,
+        contents: Some(contents.as_ref().into()),
+        written: 0,
+    }
+}
+
+/// A future which reads a whole file, returned by [`read`].
+pub struct ReadFile<D: Drive = DefaultDriver> {
+    stage: ReadStage<D>,
+    ring: Ring<D>,
+    fd: Option<RawFd>,
+    buf: Vec<u8>,
+    // The bytes at the start of `buf` which have been read from the file
+    filled: usize,
+}
+
+enum ReadStage<D: Drive> {
+    Open(Submission<OpenAt, D>),
+    Stat(Submission<Statx, D>),
+    Read,
+    Close(Submission<Close, D>),
+    Done,
+}
+
+impl<D: Drive + Clone> Future for ReadFile<D> {
+    type Output = io::Result<Vec<u8>>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<Vec<u8>>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        loop {
+            match &mut this.stage {
+                ReadStage::Open(open)   => {
+                    let (_, result) = ready!(unsafe { Pin::new_unchecked(open) }.poll(ctx));
+                    let fd = this.fail_on_err(result)? as RawFd;
+                    this.fd = Some(fd);
+                    let stat = Statx::without_path(fd, StatxFlags::empty(), StatxMode::STATX_SIZE);
+                    let driver = this.ring.driver().clone();
+                    this.stage = ReadStage::Stat(Submission::new(stat, driver));
+                }
+                ReadStage::Stat(stat)   => {
+                    let (stat, result) = ready!(unsafe { Pin::new_unchecked(stat) }.poll(ctx));
+                    this.fail_on_err(result)?;
+                    // One byte more than the size, so that the end of the file is found without
+                    // growing the buffer
+                    this.buf = vec![0; stat.statx.stx_size as usize + 1];
+                    this.stage = ReadStage::Read;
+                }
+                ReadStage::Read         => {
+                    if this.filled == this.buf.len() {
+                        let len = cmp::max(this.buf.len() * 2, MIN_READ);
+                        this.buf.resize(len, 0);
+                    }
+                    let (fd, offset) = (this.fd.unwrap(), this.filled as u64);
+                    let buf = &mut this.buf[this.filled..];
+                    let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+                    let result = ready!(ring.poll(ctx, 1, |sqs| {
+                        let mut sqe = sqs.next().unwrap();
+                        unsafe {
+                            sqe.prep_read(fd, buf, offset);
+                        }
+                        sqe
+                    }));
+                    match this.fail_on_err(result)? {
+                        0   => {
+                            this.buf.truncate(this.filled);
+                            let close = Close { fd: this.fd.take().unwrap() };
+                            let driver = this.ring.driver().clone();
+                            this.stage = ReadStage::Close(Submission::new(close, driver));
+                        }
+                        n   => this.filled += n as usize,
+                    }
+                }
+                ReadStage::Close(close) => {
+                    let (_, result) = ready!(unsafe { Pin::new_unchecked(close) }.poll(ctx));
+                    this.stage = ReadStage::Done;
+                    result?;
+                    return Poll::Ready(Ok(std::mem::take(&mut this.buf)));
+                }
+                ReadStage::Done         => panic!("polled ReadFile future after completion"),
+            }
+        }
+    }
+}
+
+impl<D: Drive> ReadFile<D> {
+    /// Stop reading if `result` is an error.
+    fn fail_on_err(&mut self, result: io::Result<u32>) -> io::Result<u32> {
+        if result.is_err() {
+            self.stage = ReadStage::Done;
+            if let Some(fd) = self.fd.take() {
+                unsafe { libc::close(fd); }
+            }
+        }
+        result
+    }
+}
+
+impl<D: Drive> Drop for ReadFile<D> {
+    fn drop(&mut self) {
+        // A read still running on io-uring keeps the buffer until it completes.
+        let buf = std::mem::take(&mut self.buf);
+        self.ring.cancel(Cancellation::from(Box::new(buf)));
+        if let Some(fd) = self.fd.take() {
+            unsafe { libc::close(fd); }
+        }
+    }
+}
+
+/// A future which writes a whole file, returned by [`write`].
+pub struct WriteFile<D: Drive = DefaultDriver> {
+    stage: WriteStage<D>,
+    ring: Ring<D>,
+    fd: Option<RawFd>,
+    contents: Option<Box<[u8]>>,
+    written: usize,
+}
+
+enum WriteStage<D: Drive> {
+    Open(Submission<OpenAt, D>),
+    Write,
+    Close(Submission<Close, D>),
+    Done,
+}
+
+impl<D: Drive + Clone> Future for WriteFile<D> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        loop {
+            match &mut this.stage {
+                WriteStage::Open(open)      => {
+                    let (_, result) = ready!(unsafe { Pin::new_unchecked(open) }.poll(ctx));
+                    this.fd = Some(this.fail_on_err(result)? as RawFd);
+                    this.stage = WriteStage::Write;
+                }
+                WriteStage::Write           => {
+                    let contents = this.contents.as_ref().unwrap();
+                    if this.written == contents.len() {
+                        let close = Close { fd: this.fd.take().unwrap() };
+                        let driver = this.ring.driver().clone();
+                        this.stage = WriteStage::Close(Submission::new(close, driver));
+                        continue;
+                    }
+                    let (fd, offset) = (this.fd.unwrap(), this.written as u64);
+                    let data = &contents[this.written..];
+                    let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+                    let result = ready!(ring.poll(ctx, 1, |sqs| {
+                        let mut sqe = sqs.next().unwrap();
+                        unsafe {
+                            sqe.prep_write(fd, data, offset);
+                        }
+                        sqe
+                    }));
+                    match this.fail_on_err(result)? {
+                        0   => {
+                            let err = io::Error::from(io::ErrorKind::WriteZero);
+                            return Poll::Ready(this.fail_on_err(Err(err)).map(drop));
+                        }
+                        n   => this.written += n as usize,
+                    }
+                }
+                WriteStage::Close(close)    => {
+                    let (_, result) = ready!(unsafe { Pin::new_unchecked(close) }.poll(ctx));
+                    this.stage = WriteStage::Done;
+                    result?;
+                    return Poll::Ready(Ok(()));
+                }
+                WriteStage::Done            => panic!("polled WriteFile future after completion"),
+            }
+        }
+    }
+}
+
+impl<D: Drive> WriteFile<D> {
+    /// Stop writing if `result` is an error.
+    fn fail_on_err(&mut self, result: io::Result<u32>) -> io::Result<u32> {
+        if result.is_err() {
+            self.stage = WriteStage::Done;
+            if let Some(fd) = self.fd.take() {
+                unsafe { libc::close(fd); }
+            }
+        }
+        result
+    }
+}
+
+impl<D: Drive> Drop for WriteFile<D> {
+    fn drop(&mut self) {
+        // A write still running on io-uring keeps the contents until it completes.
+        self.ring.cancel(Cancellation::from(self.contents.take()));
+        if let Some(fd) = self.fd.take() {
+            unsafe { libc::close(fd); }
+        }
+    }
+}
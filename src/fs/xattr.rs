@@ -0,0 +1,185 @@
+use std::ffi::{CString, OsStr, OsString};
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+
+use crate::drive::Drive;
+use crate::ring::{self, Blocking, Cancellation};
+use crate::sys;
+
+use super::{File, Op};
+
+/// The largest value an extended attribute can have on Linux.
+const XATTR_SIZE_MAX: usize = 1 << 16;
+
+impl<D: Drive> File<D> {
+    /// Read the extended attribute `name` of the file, like `fgetxattr(2)`.
+    ///
+    /// This uses `IORING_OP_FGETXATTR`, which needs Linux 5.19; on older kernels, the future
+    /// fails with `Unsupported`. A missing attribute fails with `ENODATA`.
+    pub fn get_xattr(&mut self, name: impl AsRef<OsStr>) -> GetXattr<'_, D> where D: Unpin {
+        Pin::new(self).get_xattr_pinned(name)
+    }
+
+    pub fn get_xattr_pinned(self: Pin<&mut Self>, name: impl AsRef<OsStr>) -> GetXattr<'_, D> {
+        GetXattr { file: self, name: xattr_name(name), value: None }
+    }
+
+    /// Set the extended attribute `name` of the file to `value`, like `fsetxattr(2)`, creating
+    /// it or replacing it.
+    ///
+    /// This uses `IORING_OP_FSETXATTR`, which needs Linux 5.19; on older kernels, the future
+    /// fails with `Unsupported`.
+    pub fn set_xattr(&mut self, name: impl AsRef<OsStr>, value: &[u8]) -> SetXattr<'_, D>
+        where D: Unpin
+    {
+        Pin::new(self).set_xattr_pinned(name, value)
+    }
+
+    pub fn set_xattr_pinned(self: Pin<&mut Self>, name: impl AsRef<OsStr>, value: &[u8])
+        -> SetXattr<'_, D>
+    {
+        SetXattr { file: self, name: xattr_name(name), value: Some(value.into()) }
+    }
+
+    /// List the names of the extended attributes of the file, like `flistxattr(2)`.
+    ///
+    /// io-uring has no operation for listing extended attributes, so this runs on ringbahn's pool
+    /// of threads for blocking work, with a duplicate of the file's descriptor.
+    pub fn list_xattr(&self) -> ListXattr {
+        let dup = match unsafe { libc::fcntl(self.fd, libc::F_DUPFD_CLOEXEC, 0) } {
+            -1  => Err(io::Error::last_os_error()),
+            fd  => Ok(unsafe { fs::File::from_raw_fd(fd) }),
+        };
+        ListXattr(ring::spawn_blocking(move || list_xattr(&dup?)))
+    }
+}
+
+/// The name of an attribute as a C string, or `None` if it contains a nul byte.
+fn xattr_name(name: impl AsRef<OsStr>) -> Option<CString> {
+    CString::new(name.as_ref().as_bytes()).ok()
+}
+
+/// Check that the name of an attribute is valid, and that the kernel can get and set attributes
+/// on io-uring. Both operations were added in the same release.
+fn check(name: &Option<CString>) -> io::Result<*const libc::c_char> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "attribute name contains nul");
+    let name = name.as_ref().ok_or_else(invalid)?;
+    if !ring::is_supported(sys::IORING_OP_FGETXATTR) {
+        let msg = "extended attributes on io-uring need Linux 5.19";
+        return Err(io::Error::new(io::ErrorKind::Unsupported, msg));
+    }
+    Ok(name.as_ptr())
+}
+
+fn list_xattr(file: &fs::File) -> io::Result<Vec<OsString>> {
+    let fd = file.as_raw_fd();
+    loop {
+        let len = match unsafe { libc::flistxattr(fd, ptr::null_mut(), 0) } {
+            -1  => return Err(io::Error::last_os_error()),
+            len => len as usize,
+        };
+        let mut names = vec![0u8; len];
+        match unsafe { libc::flistxattr(fd, names.as_mut_ptr() as *mut libc::c_char, len) } {
+            // The list grew between the two calls
+            -1 if io::Error::last_os_error().raw_os_error() == Some(libc::ERANGE) => continue,
+            -1  => return Err(io::Error::last_os_error()),
+            n   => names.truncate(n as usize),
+        }
+        let names = names.split(|&byte| byte == 0).filter(|name| !name.is_empty());
+        return Ok(names.map(|name| OsString::from_vec(name.to_vec())).collect());
+    }
+}
+
+/// A future which reads an extended attribute of a [`File`].
+pub struct GetXattr<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    name: Option<CString>,
+    value: Option<Box<[u8]>>,
+}
+
+impl<'a, D: Drive> Future for GetXattr<'a, D> {
+    type Output = io::Result<Vec<u8>>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<Vec<u8>>> {
+        let this = &mut *self;
+        let name = check(&this.name)?;
+        let value = this.value.get_or_insert_with(|| vec![0; XATTR_SIZE_MAX].into_boxed_slice());
+        this.file.as_mut().guard_op(Op::Xattr);
+        let fd = this.file.fd;
+        let result = ready!(this.file.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_fgetxattr(&mut sqe, fd, name, value.as_mut_ptr(), value.len() as u32);
+            }
+            sqe
+        }));
+        let value = this.value.take().unwrap();
+        Poll::Ready(result.map(|n| value[..n as usize].to_vec()))
+    }
+}
+
+impl<'a, D: Drive> Drop for GetXattr<'a, D> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            let cancellation = Cancellation::from(Box::new((self.name.take(), value)));
+            self.file.as_mut().ring().cancel_pinned(cancellation);
+        }
+    }
+}
+
+/// A future which sets an extended attribute of a [`File`].
+pub struct SetXattr<'a, D: Drive> {
+    file: Pin<&'a mut File<D>>,
+    name: Option<CString>,
+    value: Option<Box<[u8]>>,
+}
+
+impl<'a, D: Drive> Future for SetXattr<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        let name = check(&this.name)?;
+        let value = this.value.as_ref().expect("polled SetXattr future after completion");
+        this.file.as_mut().guard_op(Op::Xattr);
+        let fd = this.file.fd;
+        let result = ready!(this.file.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_fsetxattr(&mut sqe, fd, name, value.as_ptr(), value.len() as u32, 0);
+            }
+            sqe
+        }));
+        this.value = None;
+        result?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<'a, D: Drive> Drop for SetXattr<'a, D> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            let cancellation = Cancellation::from(Box::new((self.name.take(), value)));
+            self.file.as_mut().ring().cancel_pinned(cancellation);
+        }
+    }
+}
+
+/// A future which lists the extended attributes of a [`File`].
+pub struct ListXattr(Blocking<io::Result<Vec<OsString>>>);
+
+impl Future for ListXattr {
+    type Output = io::Result<Vec<OsString>>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(ctx)
+    }
+}
@@ -1,3 +1,7 @@
+mod buf_reader;
+mod buf_writer;
+mod serial;
+
 use std::borrow::Cow;
 use std::future::Future;
 use std::io;
@@ -6,11 +10,15 @@ use std::pin::Pin;
 use std::task::{Poll, Context};
 
 use futures_core::ready;
-use futures_io::AsyncWrite;
+use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
 
 use crate::buf::Buffer;
 use crate::{Drive, ring::Ring};
-use crate::drive::demo::DemoDriver;
+use crate::drive::DefaultDriver;
+
+pub use buf_reader::BufReader;
+pub use buf_writer::{BufWriter, FlushPolicy, FlushWhenDue};
+pub use serial::SerialPort;
 
 #[macro_export]
 macro_rules! print {
@@ -93,7 +101,7 @@ impl<D: Drive> Future for Print<D> {
         if !bytes.is_empty() {
             loop {
                 let written = ready!(ring.as_mut().poll(ctx, 1, |sqs| unsafe {
-                    let mut sqe = sqs.single().unwrap();
+                    let mut sqe = sqs.next().unwrap();
                     sqe.prep_write(fd, bytes, 0);
                     sqe
                 }))? as usize;
@@ -105,18 +113,48 @@ impl<D: Drive> Future for Print<D> {
                 }
             }
         } else {
-            return Poll::Ready(Ok(()));
+            Poll::Ready(Ok(()))
         }
     }
 }
 
-/// A handle to the standard output of the current process.
-pub struct Stdout<D: Drive> {
+/// A handle to the standard input of the current process.
+///
+/// If standard input is a terminal, each read waits for input with a poll event before it is
+/// submitted, rather than occupying a kernel worker thread until a line is entered.
+pub struct Stdin<D: Drive> {
     ring: Ring<D>,
     buf: Buffer,
+    tty: bool,
+    readable: bool,
 }
 
-/// Constructs a new `stdout` handle run on the demo driver.
+/// A handle to the standard output of the current process.
+pub struct Stdout<D: Drive> {
+    output: Output<D>,
+}
+
+/// A handle to the standard error of the current process.
+pub struct Stderr<D: Drive> {
+    output: Output<D>,
+}
+
+/// Constructs a new `stdin` handle run on the default driver.
+pub fn stdin() -> Stdin<DefaultDriver> {
+    stdin_on_driver(DefaultDriver::default())
+}
+
+/// Constructs a new `stdin` handle run on the provided driver.
+pub fn stdin_on_driver<D: Drive>(driver: D) -> Stdin<D> {
+    Stdin {
+        ring: Ring::new(driver),
+        buf: Buffer::default(),
+        tty: unsafe { libc::isatty(libc::STDIN_FILENO) == 1 },
+        readable: false,
+    }
+}
+
+/// Constructs a new `stdout` handle run on the default driver.
 /// ```no_run
 /// use ringbahn::io;
 ///
@@ -128,41 +166,128 @@ pub struct Stdout<D: Drive> {
 /// # }
 /// ```
 // TODO synchronization note?
-pub fn stdout() -> Stdout<DemoDriver> {
-    stdout_on_driver(DemoDriver::default())
+pub fn stdout() -> Stdout<DefaultDriver> {
+    stdout_on_driver(DefaultDriver::default())
 }
 
 /// Constructs a new `stdout` handle run on the provided driver.
 pub fn stdout_on_driver<D: Drive>(driver: D) -> Stdout<D> {
-    Stdout {
-        ring: Ring::new(driver),
-        buf: Buffer::default(),
-    }
+    Stdout { output: Output::new(libc::STDOUT_FILENO, driver) }
+}
+
+/// Constructs a new `stderr` handle run on the default driver.
+pub fn stderr() -> Stderr<DefaultDriver> {
+    stderr_on_driver(DefaultDriver::default())
+}
+
+/// Constructs a new `stderr` handle run on the provided driver.
+pub fn stderr_on_driver<D: Drive>(driver: D) -> Stderr<D> {
+    Stderr { output: Output::new(libc::STDERR_FILENO, driver) }
 }
 
-impl<D: Drive> Stdout<D> {
+/// An offset of -1 reads or writes at the file's current position, so that standard streams
+/// which are redirected to files behave as they would with blocking IO.
+const CURRENT_POSITION: u64 = -1i64 as u64;
+
+impl<D: Drive> Stdin<D> {
     #[inline(always)]
-    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Buffer) {
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Buffer, bool, &mut bool) {
         unsafe {
             let this = Pin::get_unchecked_mut(self);
-            (Pin::new_unchecked(&mut this.ring), &mut this.buf)
+            (Pin::new_unchecked(&mut this.ring), &mut this.buf, this.tty, &mut this.readable)
         }
     }
 }
 
-impl<D: Drive> AsyncWrite for Stdout<D> {
+impl<D: Drive> AsyncRead for Stdin<D> {
+    fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        let mut inner = ready!(self.as_mut().poll_fill_buf(ctx))?;
+        let len = io::Read::read(&mut inner, buf)?;
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<D: Drive> AsyncBufRead for Stdin<D> {
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let fd = libc::STDIN_FILENO;
+        let (mut ring, buf, tty, readable) = self.split();
+        buf.fill_buf(|buf| {
+            if tty && !*readable {
+                ready!(ring.as_mut().poll(ctx, 1, |sqs| {
+                    let mut sqe = sqs.next().unwrap();
+                    unsafe {
+                        sqe.prep_poll_add(fd, iou::sqe::PollFlags::POLLIN);
+                    }
+                    sqe
+                }))?;
+                *readable = true;
+            }
+            let n = ready!(ring.as_mut().poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_read(fd, buf, CURRENT_POSITION);
+                }
+                sqe
+            }))?;
+            *readable = false;
+            Poll::Ready(Ok(n))
+        })
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.split().1.consume(amt);
+    }
+}
+
+impl<D: Drive> Drop for Stdin<D> {
+    fn drop(&mut self) {
+        self.ring.cancel(self.buf.cancellation());
+    }
+}
+
+impl<D: Drive> AsRawFd for Stdin<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        libc::STDIN_FILENO
+    }
+}
+
+struct Output<D: Drive> {
+    ring: Ring<D>,
+    buf: Buffer,
+    fd: RawFd,
+}
+
+impl<D: Drive> Output<D> {
+    fn new(fd: RawFd, driver: D) -> Output<D> {
+        Output {
+            ring: Ring::new(driver),
+            buf: Buffer::default(),
+            fd,
+        }
+    }
+
+    #[inline(always)]
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Buffer, RawFd) {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            (Pin::new_unchecked(&mut this.ring), &mut this.buf, this.fd)
+        }
+    }
+
     fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
         -> Poll<io::Result<usize>>
     {
-        let fd = self.as_raw_fd();
-        let (ring, buf, ..) = self.split();
+        let (ring, buf, fd) = self.split();
         let data = ready!(buf.fill_buf(|mut buf| {
             Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
         }))?;
         let n = ready!(ring.poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
+            let mut sqe = sqs.next().unwrap();
             unsafe {
-                sqe.prep_write(fd, data, 0);
+                sqe.prep_write(fd, data, CURRENT_POSITION);
             }
             sqe
         }))?;
@@ -174,14 +299,46 @@ impl<D: Drive> AsyncWrite for Stdout<D> {
         ready!(self.poll_write(ctx, &[]))?;
         Poll::Ready(Ok(()))
     }
+}
 
-    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        self.poll_flush(ctx)
+impl<D: Drive> Drop for Output<D> {
+    fn drop(&mut self) {
+        self.ring.cancel(self.buf.cancellation());
     }
 }
 
-impl<D: Drive> AsRawFd for Stdout<D> {
-    fn as_raw_fd(&self) -> RawFd {
-        libc::STDOUT_FILENO
+macro_rules! output {
+    ($name:ident = $fd:expr) => {
+        impl<D: Drive> $name<D> {
+            #[inline(always)]
+            fn output(self: Pin<&mut Self>) -> Pin<&mut Output<D>> {
+                unsafe { Pin::map_unchecked_mut(self, |this| &mut this.output) }
+            }
+        }
+
+        impl<D: Drive> AsyncWrite for $name<D> {
+            fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+                -> Poll<io::Result<usize>>
+            {
+                self.output().poll_write(ctx, slice)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.output().poll_flush(ctx)
+            }
+
+            fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                self.poll_flush(ctx)
+            }
+        }
+
+        impl<D: Drive> AsRawFd for $name<D> {
+            fn as_raw_fd(&self) -> RawFd {
+                $fd
+            }
+        }
     }
 }
+
+output!(Stdout = libc::STDOUT_FILENO);
+output!(Stderr = libc::STDERR_FILENO);
@@ -0,0 +1,113 @@
+use std::cmp;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+/// The capacity of a [`BufReader`] constructed with [`BufReader::new`].
+const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// A reader which reads from its inner reader in large chunks
+///
+/// This is the reading counterpart of [`BufWriter`](super::BufWriter). It keeps a buffer of its
+/// own, and refills it with as much as the inner reader returns, so that line-oriented protocols
+/// can look ahead further than one read without asking the inner reader for data a few bytes at
+/// a time. Reads at least as large as the buffer bypass it when it is empty.
+///
+/// Writes are passed through to the inner reader, so a `BufReader` around a
+/// [`TcpStream`](crate::net::TcpStream) can still be used to respond.
+pub struct BufReader<R> {
+    inner: R,
+    buf: Box<[u8]>,
+    pos: usize,
+    cap: usize,
+}
+
+impl<R: AsyncRead> BufReader<R> {
+    /// Buffer reads from `inner` with a buffer of 64 KiB.
+    pub fn new(inner: R) -> BufReader<R> {
+        BufReader::with_capacity(DEFAULT_CAPACITY, inner)
+    }
+
+    /// Buffer reads from `inner` with a buffer of `capacity` bytes.
+    pub fn with_capacity(capacity: usize, inner: R) -> BufReader<R> {
+        BufReader { inner, buf: vec![0; capacity].into_boxed_slice(), pos: 0, cap: 0 }
+    }
+}
+
+impl<R> BufReader<R> {
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Return the inner reader. Any data which has been buffered but not consumed is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// The data which has been buffered but not yet consumed.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.cap]
+    }
+
+    #[inline(always)]
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut R>, &mut [u8], &mut usize, &mut usize) {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            (Pin::new_unchecked(&mut this.inner), &mut this.buf, &mut this.pos, &mut this.cap)
+        }
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for BufReader<R> {
+    fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        if self.pos == self.cap && buf.len() >= self.buf.len() {
+            return self.split().0.poll_read(ctx, buf);
+        }
+        let available = ready!(self.as_mut().poll_fill_buf(ctx))?;
+        let len = cmp::min(available.len(), buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<R: AsyncRead> AsyncBufRead for BufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let (inner, buf, pos, cap) = self.split();
+        if *pos >= *cap {
+            *cap = ready!(inner.poll_read(ctx, buf))?;
+            *pos = 0;
+        }
+        Poll::Ready(Ok(&buf[*pos..*cap]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let (_, _, pos, cap) = self.split();
+        *pos = cmp::min(*pos + amt, *cap);
+    }
+}
+
+impl<R: AsyncWrite> AsyncWrite for BufReader<R> {
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.split().0.poll_write(ctx, slice)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.split().0.poll_flush(ctx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.split().0.poll_close(ctx)
+    }
+}
@@ -0,0 +1,240 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use futures_core::ready;
+use futures_io::AsyncWrite;
+use iou::sqe::TimeoutFlags;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event;
+use crate::ring::{Ring, Cancellation};
+
+/// When a [`BufWriter`] flushes the data buffered in it.
+#[derive(Clone, Debug)]
+pub struct FlushPolicy {
+    threshold: usize,
+    interval: Option<Duration>,
+    on_drop: bool,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> FlushPolicy {
+        FlushPolicy { threshold: 8192, interval: None, on_drop: false }
+    }
+}
+
+impl FlushPolicy {
+    /// Construct the default policy, which buffers up to 8 KiB and only flushes when the buffer
+    /// is full or when the writer is flushed explicitly.
+    pub fn new() -> FlushPolicy {
+        FlushPolicy::default()
+    }
+
+    /// The number of bytes to buffer before flushing. Writes at least this large bypass the
+    /// buffer.
+    pub fn threshold(mut self, threshold: usize) -> FlushPolicy {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The longest data should stay in the buffer.
+    ///
+    /// Data which has been buffered for longer than this is flushed by the next write, and
+    /// [`BufWriter::flush_when_due`] waits for this to elapse with a timeout on io-uring.
+    pub fn interval(mut self, interval: Duration) -> FlushPolicy {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Whether data still buffered when the writer is dropped is written.
+    ///
+    /// The write is submitted through the inner writer without waiting for it to complete, and
+    /// is only as large as the inner writer accepts in one call. For ringbahn's IO objects, it
+    /// keeps running on io-uring after the writer has been dropped.
+    pub fn flush_on_drop(mut self, on_drop: bool) -> FlushPolicy {
+        self.on_drop = on_drop;
+        self
+    }
+}
+
+/// A writer which coalesces small writes into larger ones
+///
+/// Writing to a ringbahn IO object submits one event per write. `BufWriter` buffers small writes
+/// and passes them to the inner writer together, according to its [`FlushPolicy`].
+pub struct BufWriter<W: AsyncWrite, D: Drive = DefaultDriver> {
+    inner: W,
+    buf: Vec<u8>,
+    written: usize,
+    policy: FlushPolicy,
+    deadline: Option<Instant>,
+    timer: Ring<D>,
+    ts: Option<Box<uring_sys::__kernel_timespec>>,
+}
+
+impl<W: AsyncWrite> BufWriter<W> {
+    /// Buffer writes to `inner` with the default policy.
+    pub fn new(inner: W) -> BufWriter<W> {
+        BufWriter::with_policy(inner, FlushPolicy::default())
+    }
+
+    /// Buffer writes to `inner`, using the default driver for timed flushes.
+    pub fn with_policy(inner: W, policy: FlushPolicy) -> BufWriter<W> {
+        BufWriter::with_policy_on_driver(inner, policy, DefaultDriver::default())
+    }
+}
+
+impl<W: AsyncWrite, D: Drive> BufWriter<W, D> {
+    /// Buffer writes to `inner`, using the provided driver for timed flushes.
+    pub fn with_policy_on_driver(inner: W, policy: FlushPolicy, driver: D) -> BufWriter<W, D> {
+        BufWriter {
+            buf: Vec::with_capacity(policy.threshold),
+            written: 0,
+            deadline: None,
+            timer: Ring::new(driver),
+            ts: None,
+            inner, policy,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The data which has been buffered but not yet written to the inner writer.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.written..]
+    }
+
+    /// Wait until the buffered data is due to be flushed according to the policy's interval,
+    /// then flush it.
+    ///
+    /// If nothing is buffered, or the policy has no interval, this does not complete, and does
+    /// not register for a wakeup. It is intended to be raced against the task's other work, like
+    /// in a `select!` loop which also writes to this writer.
+    pub fn flush_when_due(&mut self) -> FlushWhenDue<'_, W, D> where W: Unpin, D: Unpin {
+        Pin::new(self).flush_when_due_pinned()
+    }
+
+    pub fn flush_when_due_pinned(self: Pin<&mut Self>) -> FlushWhenDue<'_, W, D> {
+        FlushWhenDue { writer: self }
+    }
+
+    pub fn poll_flush_when_due(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<()>>
+    {
+        let this = unsafe { Pin::get_unchecked_mut(self.as_mut()) };
+        loop {
+            let deadline = match this.deadline {
+                Some(deadline)  => deadline,
+                None            => return Poll::Pending,
+            };
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            // A timeout started for an earlier deadline completes first; the loop then starts
+            // another for the current one.
+            let ts = this.ts.get_or_insert_with(|| Box::new(event::timespec(deadline - now)));
+            let timer = unsafe { Pin::new_unchecked(&mut this.timer) };
+            let result = ready!(timer.poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_timeout(ts, 0, TimeoutFlags::empty());
+                }
+                sqe
+            }));
+            this.ts = None;
+            match result {
+                Err(err) if err.raw_os_error() != Some(libc::ETIME) => return Poll::Ready(Err(err)),
+                _                                                     => { }
+            }
+        }
+        self.poll_flush_buf(ctx)
+    }
+
+    fn poll_flush_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (mut inner, buf, written, deadline) = self.split();
+        while *written < buf.len() {
+            let n = ready!(inner.as_mut().poll_write(ctx, &buf[*written..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            *written += n;
+        }
+        buf.clear();
+        *written = 0;
+        *deadline = None;
+        Poll::Ready(Ok(()))
+    }
+
+    fn is_due(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    #[inline(always)]
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut W>, &mut Vec<u8>, &mut usize, &mut Option<Instant>) {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            (Pin::new_unchecked(&mut this.inner), &mut this.buf, &mut this.written, &mut this.deadline)
+        }
+    }
+}
+
+impl<W: AsyncWrite, D: Drive> AsyncWrite for BufWriter<W, D> {
+    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        if self.buf.len() + slice.len() > self.policy.threshold || self.is_due() {
+            ready!(self.as_mut().poll_flush_buf(ctx))?;
+        }
+        if slice.len() >= self.policy.threshold {
+            return self.split().0.poll_write(ctx, slice);
+        }
+        let interval = self.policy.interval;
+        let (_, buf, _, deadline) = self.split();
+        if buf.is_empty() {
+            *deadline = interval.map(|interval| Instant::now() + interval);
+        }
+        buf.extend_from_slice(slice);
+        Poll::Ready(Ok(slice.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush_buf(ctx))?;
+        self.split().0.poll_flush(ctx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush_buf(ctx))?;
+        self.split().0.poll_close(ctx)
+    }
+}
+
+impl<W: AsyncWrite, D: Drive> Drop for BufWriter<W, D> {
+    fn drop(&mut self) {
+        if self.policy.on_drop && self.written < self.buf.len() {
+            // The write is polled once, and dropping the inner writer afterwards leaves it
+            // running on io-uring without anything waiting for it.
+            let mut ctx = Context::from_waker(Waker::noop());
+            let inner = unsafe { Pin::new_unchecked(&mut self.inner) };
+            let _ = inner.poll_write(&mut ctx, &self.buf[self.written..]);
+        }
+        self.timer.cancel(Cancellation::from(self.ts.take()));
+    }
+}
+
+/// A future which flushes a [`BufWriter`] once its buffered data is due.
+pub struct FlushWhenDue<'a, W: AsyncWrite, D: Drive> {
+    writer: Pin<&'a mut BufWriter<W, D>>,
+}
+
+impl<'a, W: AsyncWrite, D: Drive> Future for FlushWhenDue<'a, W, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.writer.as_mut().poll_flush_when_due(ctx)
+    }
+}
@@ -0,0 +1,308 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
+use iou::sqe::PollFlags;
+
+use crate::buf::Buffer;
+use crate::drive::{Drive, DefaultDriver};
+use crate::ring::Ring;
+
+/// A serial port or other terminal device
+///
+/// The port is configured with termios: [`SerialPort::set_raw`] and
+/// [`SerialPort::set_baud_rate`] cover the common cases, and [`SerialPort::termios`] and
+/// [`SerialPort::set_termios`] give access to every setting.
+///
+/// Like [`Stdin`](super::Stdin) on a terminal, each read waits for input with a poll event
+/// before it is submitted, rather than occupying a kernel worker thread until data arrives.
+pub struct SerialPort<D: Drive = DefaultDriver> {
+    ring: Ring<D>,
+    buf: Buffer,
+    active: Op,
+    readable: bool,
+    fd: RawFd,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Op {
+    Read,
+    Write,
+    Close,
+    Nothing,
+    Closed,
+}
+
+impl SerialPort {
+    /// Open a character device for reading and writing using the default driver
+    pub fn open(path: impl AsRef<Path>) -> io::Result<SerialPort> {
+        SerialPort::open_on_driver(path, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive> SerialPort<D> {
+    /// Open a character device for reading and writing
+    ///
+    /// The device does not become the controlling terminal of the process.
+    pub fn open_on_driver(path: impl AsRef<Path>, driver: D) -> io::Result<SerialPort<D>> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let flags = libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC;
+        let fd = match unsafe { libc::open(path.as_ptr(), flags) } {
+            -1  => return Err(io::Error::last_os_error()),
+            fd  => fd,
+        };
+        if unsafe { libc::isatty(fd) } != 1 {
+            let err = io::Error::last_os_error();
+            unsafe { libc::close(fd); }
+            return Err(err);
+        }
+        Ok(SerialPort {
+            ring: Ring::new(driver),
+            buf: Buffer::default(),
+            active: Op::Nothing,
+            readable: false,
+            fd,
+        })
+    }
+
+    /// The current termios settings of the port.
+    pub fn termios(&self) -> io::Result<libc::termios> {
+        let mut termios: libc::termios = unsafe { mem::zeroed() };
+        match unsafe { libc::tcgetattr(self.fd, &mut termios) } {
+            0   => Ok(termios),
+            _   => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Change the termios settings of the port immediately.
+    pub fn set_termios(&self, termios: &libc::termios) -> io::Result<()> {
+        match unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, termios) } {
+            0   => Ok(()),
+            _   => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Put the port in raw mode: input is available byte by byte, and no characters are
+    /// processed by the terminal driver, as `cfmakeraw(3)` does.
+    pub fn set_raw(&self) -> io::Result<()> {
+        let mut termios = self.termios()?;
+        unsafe { libc::cfmakeraw(&mut termios); }
+        self.set_termios(&termios)
+    }
+
+    /// Set the input and output speed of the port, in bits per second.
+    ///
+    /// This fails with `InvalidInput` if the rate is not one of the standard rates.
+    pub fn set_baud_rate(&self, rate: u32) -> io::Result<()> {
+        let speed = baud_rate_to_speed(rate)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "unsupported baud rate"))?;
+        let mut termios = self.termios()?;
+        if unsafe { libc::cfsetspeed(&mut termios, speed) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.set_termios(&termios)
+    }
+
+    /// The input speed of the port, in bits per second.
+    pub fn baud_rate(&self) -> io::Result<u32> {
+        let termios = self.termios()?;
+        let speed = unsafe { libc::cfgetispeed(&termios) };
+        speed_to_baud_rate(speed).ok_or_else(|| io::Error::from_raw_os_error(libc::EINVAL))
+    }
+
+    /// Discard any data written to the port but not transmitted, and any received but not read.
+    pub fn flush_buffers(&self) -> io::Result<()> {
+        match unsafe { libc::tcflush(self.fd, libc::TCIOFLUSH) } {
+            0   => Ok(()),
+            _   => Err(io::Error::last_os_error()),
+        }
+    }
+
+    fn guard_op(self: Pin<&mut Self>, op: Op) {
+        let (ring, buf, active, _) = self.split();
+        if *active == Op::Closed {
+            panic!("Attempted to perform IO on a closed SerialPort");
+        } else if *active != Op::Nothing && *active != op {
+            ring.cancel_pinned(buf.cancellation());
+        }
+        *active = op;
+    }
+
+    fn cancel(&mut self) {
+        self.active = Op::Nothing;
+        self.ring.cancel(self.buf.cancellation());
+    }
+
+    #[inline(always)]
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Buffer, &mut Op, &mut bool) {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let ring = Pin::new_unchecked(&mut this.ring);
+            (ring, &mut this.buf, &mut this.active, &mut this.readable)
+        }
+    }
+
+    fn confirm_close(self: Pin<&mut Self>) {
+        *self.split().2 = Op::Closed;
+    }
+}
+
+impl<D: Drive> AsyncRead for SerialPort<D> {
+    fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        let mut inner = ready!(self.as_mut().poll_fill_buf(ctx))?;
+        let len = io::Read::read(&mut inner, buf)?;
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<D: Drive> AsyncBufRead for SerialPort<D> {
+    fn poll_fill_buf(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.as_mut().guard_op(Op::Read);
+        let fd = self.fd;
+        let (mut ring, buf, _, readable) = self.split();
+        buf.fill_buf(|buf| {
+            if !*readable {
+                ready!(ring.as_mut().poll(ctx, 1, |sqs| {
+                    let mut sqe = sqs.next().unwrap();
+                    unsafe {
+                        sqe.prep_poll_add(fd, PollFlags::POLLIN);
+                    }
+                    sqe
+                }))?;
+                *readable = true;
+            }
+            let n = ready!(ring.as_mut().poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_read(fd, buf, 0);
+                }
+                sqe
+            }))?;
+            *readable = false;
+            Poll::Ready(Ok(n))
+        })
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.split().1.consume(amt);
+    }
+}
+
+impl<D: Drive> AsyncWrite for SerialPort<D> {
+    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.as_mut().guard_op(Op::Write);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        let data = ready!(buf.fill_buf(|mut buf| {
+            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
+        }))?;
+        let n = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_write(fd, data, 0);
+            }
+            sqe
+        }))?;
+        buf.clear();
+        Poll::Ready(Ok(n as usize))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_write(ctx, &[]))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.as_mut().guard_op(Op::Close);
+        let fd = self.fd;
+        ready!(self.as_mut().split().0.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_close(fd);
+            }
+            sqe
+        }))?;
+        self.confirm_close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<D: Drive> AsRawFd for SerialPort<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl<D: Drive> Drop for SerialPort<D> {
+    fn drop(&mut self) {
+        match self.active {
+            Op::Closed  => { }
+            Op::Nothing => unsafe { libc::close(self.fd); },
+            _           => self.cancel(),
+        }
+    }
+}
+
+macro_rules! baud_rates {
+    ($($rate:literal => $speed:ident,)*) => {
+        fn baud_rate_to_speed(rate: u32) -> Option<libc::speed_t> {
+            match rate {
+                $($rate => Some(libc::$speed),)*
+                _       => None,
+            }
+        }
+
+        fn speed_to_baud_rate(speed: libc::speed_t) -> Option<u32> {
+            match speed {
+                $(libc::$speed => Some($rate),)*
+                _               => None,
+            }
+        }
+    }
+}
+
+baud_rates! {
+    50 => B50,
+    75 => B75,
+    110 => B110,
+    134 => B134,
+    150 => B150,
+    200 => B200,
+    300 => B300,
+    600 => B600,
+    1200 => B1200,
+    1800 => B1800,
+    2400 => B2400,
+    4800 => B4800,
+    9600 => B9600,
+    19200 => B19200,
+    38400 => B38400,
+    57600 => B57600,
+    115200 => B115200,
+    230400 => B230400,
+    460800 => B460800,
+    500000 => B500000,
+    576000 => B576000,
+    921600 => B921600,
+    1000000 => B1000000,
+    1152000 => B1152000,
+    1500000 => B1500000,
+    2000000 => B2000000,
+    2500000 => B2500000,
+    3000000 => B3000000,
+    3500000 => B3500000,
+    4000000 => B4000000,
+}
@@ -1,7 +1,12 @@
 pub mod fs;
 pub mod net;
+pub mod pipe;
+pub mod process;
 pub mod unix;
 
+#[cfg(feature = "tls")]
+pub mod tls;
+
 pub mod drive;
 pub mod event;
 pub mod ring;
@@ -9,11 +14,14 @@ pub mod ring;
 pub mod io;
 
 mod buf;
+mod scope;
 mod submission;
+mod sys;
 
 pub use submission::Submission;
+pub use scope::{scope, scope_on_driver, Scope, Scoped};
 
 #[doc(inline)]
-pub use drive::Drive;
+pub use drive::{Drive, set_default_driver};
 #[doc(inline)]
 pub use event::Event;
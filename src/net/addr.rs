@@ -0,0 +1,137 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::ptr;
+
+use nix::sys::socket::{InetAddr, UnixAddr};
+
+/// The address of a socket, as passed to events like [`Connect`](crate::event::Connect).
+///
+/// This wraps the address type of the io-uring backend, so that upgrading the backend does not
+/// change ringbahn's public API.
+pub struct SockAddr {
+    inner: iou::sqe::SockAddr,
+}
+
+impl SockAddr {
+    /// The address of a unix socket bound to `path`.
+    pub fn unix(path: impl AsRef<Path>) -> io::Result<SockAddr> {
+        let addr = UnixAddr::new(path.as_ref())
+            .map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EINVAL))?;
+        Ok(SockAddr { inner: iou::sqe::SockAddr::Unix(addr) })
+    }
+
+    /// The address of a unix socket in Linux's abstract namespace, which is not bound to a path
+    /// in the filesystem.
+    ///
+    /// `name` does not include the leading NUL byte which marks an abstract address.
+    pub fn unix_abstract(name: &[u8]) -> io::Result<SockAddr> {
+        let addr = UnixAddr::new_abstract(name)
+            .map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EINVAL))?;
+        Ok(SockAddr { inner: iou::sqe::SockAddr::Unix(addr) })
+    }
+
+    /// This address, if it is an IPv4 or IPv6 address.
+    pub fn as_inet(&self) -> Option<SocketAddr> {
+        match &self.inner {
+            iou::sqe::SockAddr::Inet(addr)  => Some(addr.to_std()),
+            _                               => None,
+        }
+    }
+
+    /// The path of this address, if it is the address of a unix socket bound to a path.
+    pub fn as_unix_path(&self) -> Option<&Path> {
+        match &self.inner {
+            iou::sqe::SockAddr::Unix(addr)  => addr.path(),
+            _                               => None,
+        }
+    }
+
+    /// The name of this address, if it is the address of a unix socket in the abstract namespace.
+    pub fn as_unix_abstract(&self) -> Option<&[u8]> {
+        match &self.inner {
+            iou::sqe::SockAddr::Unix(addr)  => addr.as_abstract(),
+            _                               => None,
+        }
+    }
+
+    pub(crate) fn as_iou(&self) -> &iou::sqe::SockAddr {
+        &self.inner
+    }
+
+    /// A pointer to this address, and its length, to be passed to the kernel.
+    pub(crate) fn as_raw(&self) -> (&libc::sockaddr, libc::socklen_t) {
+        match &self.inner {
+            // nix finds the offset of sun_path by dereferencing a null pointer, which panics in
+            // debug builds, so the length of a unix address is computed here.
+            iou::sqe::SockAddr::Unix(UnixAddr(addr, len))  => {
+                let addr = unsafe { &*(addr as *const libc::sockaddr_un as *const libc::sockaddr) };
+                (addr, (mem::size_of::<libc::sa_family_t>() + len) as libc::socklen_t)
+            }
+            inner                                           => inner.as_ffi_pair(),
+        }
+    }
+
+    /// Copy this address into `storage`, returning its length.
+    pub(crate) fn write_raw(&self, storage: &mut libc::sockaddr_storage) -> libc::socklen_t {
+        unsafe {
+            let (addr, len) = self.as_raw();
+            let dst = storage as *mut libc::sockaddr_storage as *mut u8;
+            ptr::copy_nonoverlapping(addr as *const libc::sockaddr as *const u8, dst, len as usize);
+            len
+        }
+    }
+
+    /// Read an address of `len` bytes which the kernel wrote into `storage`.
+    pub(crate) fn read_raw(storage: &libc::sockaddr_storage, len: libc::socklen_t)
+        -> io::Result<SockAddr>
+    {
+        let inner = nix::sys::socket::sockaddr_storage_to_addr(storage, len as usize)
+            .map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EINVAL))?;
+        Ok(SockAddr { inner })
+    }
+}
+
+impl From<SocketAddr> for SockAddr {
+    fn from(addr: SocketAddr) -> SockAddr {
+        SockAddr { inner: iou::sqe::SockAddr::Inet(InetAddr::from_std(&addr)) }
+    }
+}
+
+impl fmt::Debug for SockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.as_inet(), self.as_unix_path(), self.as_unix_abstract()) {
+            (Some(addr), ..)        => fmt::Debug::fmt(&addr, f),
+            (_, Some(path), _)      => fmt::Debug::fmt(path, f),
+            // Abstract addresses are conventionally shown with a leading @, as by ss(8).
+            (.., Some(name))        => write!(f, "@{}", String::from_utf8_lossy(name)),
+            _                       => f.write_str("SockAddr { .. }"),
+        }
+    }
+}
+
+/// Storage for the address of a peer, filled in by events like [`Accept`](crate::event::Accept).
+pub struct SockAddrStorage {
+    inner: iou::sqe::SockAddrStorage,
+}
+
+impl SockAddrStorage {
+    pub fn uninit() -> SockAddrStorage {
+        SockAddrStorage { inner: iou::sqe::SockAddrStorage::uninit() }
+    }
+
+    /// Read the address stored by the kernel.
+    ///
+    /// # Safety
+    ///
+    /// An event which fills in this storage must have completed successfully.
+    pub unsafe fn as_socket_addr(&self) -> io::Result<SockAddr> {
+        Ok(SockAddr { inner: self.inner.as_socket_addr()? })
+    }
+
+    pub(crate) fn as_iou_mut(&mut self) -> &mut iou::sqe::SockAddrStorage {
+        &mut self.inner
+    }
+}
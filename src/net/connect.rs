@@ -0,0 +1,284 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::ready;
+use iou::sqe::TimeoutFlags;
+use nix::sys::socket as nix_socket;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::event;
+use crate::ring::{self, Ring};
+use crate::sys;
+use crate::Submission;
+
+use super::{domain, socket, Protocol, Resolve, SockAddr, TcpStream};
+
+/// How long an attempt runs before the attempt to the next address is started alongside it, as
+/// recommended by RFC 8305.
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// A future which connects a [`TcpStream`]
+///
+/// When the address resolves to several addresses, they are tried in the manner of Happy
+/// Eyeballs (RFC 8305): alternating between IPv6 and IPv4, starting with the family of the first
+/// address. Each attempt is given 250ms before the next is started alongside it, and a failed
+/// attempt starts the next one immediately. The first attempt to succeed wins, and the others are
+/// cancelled on io-uring.
+pub struct Connect<D: Drive = DefaultDriver> {
+    driver: D,
+    // The resolution of a host name, which is awaited before the first attempt starts
+    resolving: Option<Resolving>,
+    addrs: VecDeque<SocketAddr>,
+    attempts: Vec<Attempt<D>>,
+    delay: Option<Pin<Box<Submission<event::Timeout, D>>>>,
+    timeout: Option<Duration>,
+    local: Option<SocketAddr>,
+    protocol: Protocol,
+    error: Option<io::Error>,
+}
+
+struct Attempt<D: Drive> {
+    addr: SocketAddr,
+    stage: Stage<D>,
+    fd: Option<RawFd>,
+}
+
+enum Stage<D: Drive> {
+    // The socket is being created with IORING_OP_SOCKET
+    Socket(Pin<Box<Submission<event::Socket, D>>>),
+    Connect(Pin<Box<Submission<event::Connect, D>>>),
+}
+
+impl<D: Drive + Clone> Connect<D> {
+    /// Connect to the addresses `addr` resolves to, giving each attempt `timeout` if there is one.
+    pub(super) fn new<A: ToSocketAddrs>(addr: A, timeout: Option<Duration>, driver: D)
+        -> Connect<D>
+    {
+        let (addrs, error) = match addr.to_socket_addrs() {
+            Ok(addrs)   => (interleave(addrs.collect()), None),
+            Err(err)    => (VecDeque::new(), Some(err)),
+        };
+        let error = error.or_else(|| Some(io::Error::new(
+            io::ErrorKind::InvalidInput, "could not resolve to any addresses"
+        )));
+        Connect {
+            driver, addrs,
+            resolving: None,
+            attempts: Vec::new(),
+            delay: None,
+            timeout,
+            local: None,
+            protocol: Protocol::TCP,
+            error,
+        }
+    }
+
+    /// Resolve `host` with `resolver`, then connect to the addresses it resolves to.
+    pub(super) fn resolve<R: Resolve>(
+        host: &str,
+        port: u16,
+        resolver: &R,
+        timeout: Option<Duration>,
+        driver: D,
+    ) -> Connect<D> {
+        let no_addrs: &[SocketAddr] = &[];
+        let mut connect = Connect::new(no_addrs, timeout, driver);
+        connect.resolving = Some(Box::pin(resolver.resolve(host, port)));
+        connect
+    }
+
+    /// Bind each attempt's socket to `local` before it connects. Addresses of the other family
+    /// than `local` are skipped.
+    pub(super) fn bind_local(mut self, local: SocketAddr) -> Connect<D> {
+        self.local = Some(local);
+        self
+    }
+
+    /// Create the attempts' sockets with `protocol` rather than TCP.
+    pub(super) fn with_protocol(mut self, protocol: Protocol) -> Connect<D> {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Start an attempt to connect to the next address, returning whether there was one.
+    ///
+    /// Where the kernel supports it, the attempt's socket is created on io-uring, and the connect
+    /// is submitted once it has been.
+    fn start_next(&mut self) -> bool {
+        if let Some(local) = self.local {
+            self.addrs.retain(|addr| addr.is_ipv6() == local.is_ipv6());
+        }
+        while let Some(addr) = self.addrs.pop_front() {
+            let attempt = match ring::is_supported(sys::IORING_OP_SOCKET) {
+                true    => {
+                    let socket = event::Socket {
+                        domain: domain(&addr),
+                        ty: self.protocol.ty | libc::SOCK_CLOEXEC,
+                        protocol: self.protocol.protocol,
+                    };
+                    let submission = Box::pin(Submission::new(socket, self.driver.clone()));
+                    Attempt { addr, stage: Stage::Socket(submission), fd: None }
+                }
+                false   => {
+                    let fd = match socket(addr, self.protocol) {
+                        Ok((fd, _)) => fd,
+                        Err(err)    => {
+                            self.error = Some(err);
+                            continue;
+                        }
+                    };
+                    match self.connect_stage(fd, addr) {
+                        Ok(stage)   => Attempt { addr, stage, fd: Some(fd) },
+                        Err(err)    => {
+                            unsafe { libc::close(fd); }
+                            self.error = Some(err);
+                            continue;
+                        }
+                    }
+                }
+            };
+            self.attempts.push(attempt);
+            self.delay = match self.addrs.is_empty() {
+                true    => None,
+                false   => {
+                    let flags = TimeoutFlags::empty();
+                    let delay = event::Timeout::new(CONNECTION_ATTEMPT_DELAY, 0, flags);
+                    Some(Box::pin(Submission::new(delay, self.driver.clone())))
+                }
+            };
+            return true;
+        }
+        false
+    }
+
+    /// Bind the socket `fd` if there is a local address, and submit its connect to `addr`.
+    fn connect_stage(&self, fd: RawFd, addr: SocketAddr) -> io::Result<Stage<D>> {
+        if let Some(local) = self.local {
+            nix_socket::bind(fd, SockAddr::from(local).as_iou())
+                .map_err(|err| err.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
+        }
+        let ring = match self.timeout {
+            Some(timeout)   => ring::Builder::new().timeout(timeout).build(self.driver.clone()),
+            None            => Ring::new(self.driver.clone()),
+        };
+        let connect = event::Connect { fd, addr: Box::new(SockAddr::from(addr)) };
+        Ok(Stage::Connect(Box::pin(Submission::on_ring(connect, ring))))
+    }
+}
+
+impl<D: Drive + Clone> Future for Connect<D> {
+    type Output = io::Result<TcpStream<D>>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        if let Some(resolving) = &mut this.resolving {
+            let addrs = ready!(resolving.as_mut().poll(ctx));
+            this.resolving = None;
+            match addrs {
+                Ok(addrs)   => this.addrs = interleave(addrs),
+                Err(err)    => this.error = Some(err),
+            }
+        }
+        if this.attempts.is_empty() && !this.start_next() {
+            let err = this.error.take().expect("polled Connect future after completion");
+            return Poll::Ready(Err(err));
+        }
+        loop {
+            let mut failed = false;
+            let mut i = 0;
+            while i < this.attempts.len() {
+                let submission = match &mut this.attempts[i].stage {
+                    Stage::Connect(submission)  => submission,
+                    Stage::Socket(submission)   => {
+                        match submission.as_mut().poll(ctx) {
+                            Poll::Ready((_, Ok(fd)))    => {
+                                let (fd, addr) = (fd as RawFd, this.attempts[i].addr);
+                                this.attempts[i].fd = Some(fd);
+                                match this.connect_stage(fd, addr) {
+                                    Ok(stage)   => this.attempts[i].stage = stage,
+                                    Err(err)    => {
+                                        this.attempts.swap_remove(i);
+                                        this.error = Some(err);
+                                        failed = true;
+                                    }
+                                }
+                            }
+                            Poll::Ready((_, Err(err)))  => {
+                                this.attempts.swap_remove(i);
+                                this.error = Some(err);
+                                failed = true;
+                            }
+                            Poll::Pending               => i += 1,
+                        }
+                        continue;
+                    }
+                };
+                match submission.as_mut().poll(ctx) {
+                    Poll::Ready((connect, Ok(_)))   => {
+                        this.attempts[i].fd = None;
+                        this.attempts.clear();
+                        this.addrs.clear();
+                        this.delay = None;
+                        this.error = None;
+                        let ring = Ring::new(this.driver.clone());
+                        return Poll::Ready(Ok(TcpStream::from_fd(connect.fd, ring)));
+                    }
+                    Poll::Ready((_, Err(err)))      => {
+                        this.attempts.swap_remove(i);
+                        this.error = Some(err);
+                        failed = true;
+                    }
+                    Poll::Pending                   => i += 1,
+                }
+            }
+            if failed && this.start_next() {
+                continue;
+            }
+            if let Some(delay) = &mut this.delay {
+                if delay.as_mut().poll(ctx).is_ready() {
+                    this.delay = None;
+                    if this.start_next() {
+                        continue;
+                    }
+                }
+            }
+            if this.attempts.is_empty() {
+                let err = this.error.take().expect("polled Connect future after completion");
+                return Poll::Ready(Err(err));
+            }
+            return Poll::Pending;
+        }
+    }
+}
+
+impl<D: Drive> Drop for Attempt<D> {
+    fn drop(&mut self) {
+        // The connect itself is cancelled when the submission is dropped. A socket which is still
+        // being created when the attempt is abandoned is closed by the cancellation of its event.
+        if let Some(fd) = self.fd {
+            unsafe { libc::close(fd); }
+        }
+    }
+}
+
+/// The resolution of a host name by a [`Resolve`](super::Resolve) implementation.
+type Resolving = Pin<Box<dyn Future<Output = io::Result<Vec<SocketAddr>>> + Send>>;
+
+/// Order addresses alternating between IPv6 and IPv4, starting with the family of the first.
+fn interleave(addrs: Vec<SocketAddr>) -> VecDeque<SocketAddr> {
+    let first_v6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (mut first, mut second): (VecDeque<_>, VecDeque<_>) = addrs.into_iter()
+        .partition(|addr| addr.is_ipv6() == first_v6);
+    let mut addrs = VecDeque::with_capacity(first.len() + second.len());
+    while !first.is_empty() || !second.is_empty() {
+        addrs.extend(first.pop_front());
+        addrs.extend(second.pop_front());
+    }
+    addrs
+}
@@ -0,0 +1,116 @@
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::{Bytes, BytesMut};
+use futures_core::{ready, Stream};
+use futures_sink::Sink;
+
+use crate::drive::{Drive, DefaultDriver};
+
+use super::UdpSocket;
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// The capacity the pool is allocated with, which many small datagrams are split off.
+const POOL_CAPACITY: usize = 64 * 1024;
+
+/// The payload of a datagram, and the address of its peer.
+type Datagram = (Bytes, SocketAddr);
+
+/// A [`UdpSocket`] as a stream of the datagrams it receives and a sink of datagrams to send
+///
+/// This is enabled by the `framed` feature, and constructed by [`UdpSocket::framed`]. Each item
+/// is the payload of a datagram along with the address of its peer.
+///
+/// Received datagrams are split off a buffer which the framed socket keeps, so that once the
+/// `Bytes` of earlier datagrams have been dropped, their memory is reused for later ones.
+///
+/// The stream and the sink share the socket, which runs one operation at a time. Receiving while
+/// a datagram is being sent cancels the send, and sending while a datagram is being received
+/// cancels the receive, so a task which does both should not poll them concurrently.
+pub struct UdpFramed<D: Drive = DefaultDriver> {
+    socket: UdpSocket<D>,
+    // The datagram being received, which is copied into `pool` once it completes
+    recv_buf: Box<[u8]>,
+    pool: BytesMut,
+    // The datagram being sent, which is taken once it has been
+    sending: Option<Datagram>,
+}
+
+impl<D: Drive> UdpFramed<D> {
+    pub(super) fn new(socket: UdpSocket<D>) -> UdpFramed<D> {
+        UdpFramed {
+            socket,
+            recv_buf: vec![0; MAX_DATAGRAM].into_boxed_slice(),
+            pool: BytesMut::new(),
+            sending: None,
+        }
+    }
+
+    /// The socket datagrams are received and sent with.
+    pub fn get_ref(&self) -> &UdpSocket<D> {
+        &self.socket
+    }
+
+    /// Take the socket back, dropping any datagram which has not been sent.
+    pub fn into_inner(self) -> UdpSocket<D> {
+        self.socket
+    }
+
+    fn split(self: Pin<&mut Self>)
+        -> (Pin<&mut UdpSocket<D>>, &mut [u8], &mut BytesMut, &mut Option<Datagram>)
+    {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let socket = Pin::new_unchecked(&mut this.socket);
+            (socket, &mut this.recv_buf[..], &mut this.pool, &mut this.sending)
+        }
+    }
+}
+
+impl<D: Drive> Stream for UdpFramed<D> {
+    type Item = io::Result<Datagram>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (socket, recv_buf, pool, _) = self.split();
+        let (n, addr) = ready!(socket.poll_recv_from(ctx, recv_buf))?;
+        if pool.capacity() < n {
+            // Reclaims the pool's memory if every datagram split off it has been dropped
+            pool.reserve(POOL_CAPACITY);
+        }
+        pool.extend_from_slice(&recv_buf[..n]);
+        Poll::Ready(Some(Ok((pool.split().freeze(), addr))))
+    }
+}
+
+impl<D: Drive> Sink<Datagram> for UdpFramed<D> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(ctx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Datagram) -> io::Result<()> {
+        let (.., sending) = self.split();
+        debug_assert!(sending.is_none(), "start_send called without poll_ready");
+        *sending = Some(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (socket, .., sending) = self.split();
+        if let Some((buf, addr)) = sending {
+            let result = ready!(socket.poll_send_to(ctx, buf, *addr));
+            *sending = None;
+            result?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(ctx)
+    }
+}
@@ -0,0 +1,95 @@
+use std::io;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+use super::sockopt::{self, KeepAlive, TcpKeepCount, TcpKeepIdle, TcpKeepInterval};
+
+/// The configuration of TCP keepalive probes
+///
+/// Settings which are not configured keep the system's defaults, from the
+/// `net.ipv4.tcp_keepalive_*` sysctls.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use ringbahn::net::{TcpKeepalive, TcpStream};
+///
+/// # fn main() -> std::io::Result<()> { futures::executor::block_on(async {
+/// let stream = TcpStream::connect(("127.0.0.1", 7878)).await?;
+/// let keepalive = TcpKeepalive::new()
+///     .time(Duration::from_secs(60))
+///     .interval(Duration::from_secs(10))
+///     .retries(5);
+/// stream.set_tcp_keepalive(&keepalive)?;
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TcpKeepalive {
+    time: Option<Duration>,
+    interval: Option<Duration>,
+    retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    pub fn new() -> TcpKeepalive {
+        TcpKeepalive::default()
+    }
+
+    /// How long the connection is idle before the first probe is sent (`TCP_KEEPIDLE`).
+    ///
+    /// The kernel counts in whole seconds, so this is rounded down, to at least one second.
+    pub fn time(mut self, time: Duration) -> TcpKeepalive {
+        self.time = Some(time);
+        self
+    }
+
+    /// How long to wait between unanswered probes (`TCP_KEEPINTVL`).
+    ///
+    /// The kernel counts in whole seconds, so this is rounded down, to at least one second.
+    pub fn interval(mut self, interval: Duration) -> TcpKeepalive {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// How many unanswered probes are sent before the connection is dropped (`TCP_KEEPCNT`).
+    pub fn retries(mut self, retries: u32) -> TcpKeepalive {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Enable keepalive on a socket with this configuration.
+    pub(crate) fn apply(&self, fd: RawFd) -> io::Result<()> {
+        sockopt::set(fd, KeepAlive(true))?;
+        if let Some(time) = self.time {
+            sockopt::set(fd, TcpKeepIdle(secs(time)))?;
+        }
+        if let Some(interval) = self.interval {
+            sockopt::set(fd, TcpKeepInterval(secs(interval)))?;
+        }
+        if let Some(retries) = self.retries {
+            sockopt::set(fd, TcpKeepCount(retries))?;
+        }
+        Ok(())
+    }
+
+    /// Read the keepalive configuration of a socket, or `None` if keepalive is disabled.
+    pub(crate) fn read(fd: RawFd) -> io::Result<Option<TcpKeepalive>> {
+        let KeepAlive(enabled) = sockopt::get(fd)?;
+        if !enabled {
+            return Ok(None);
+        }
+        let TcpKeepIdle(time) = sockopt::get(fd)?;
+        let TcpKeepInterval(interval) = sockopt::get(fd)?;
+        let TcpKeepCount(retries) = sockopt::get(fd)?;
+        Ok(Some(TcpKeepalive {
+            time: Some(Duration::from_secs(time as u64)),
+            interval: Some(Duration::from_secs(interval as u64)),
+            retries: Some(retries),
+        }))
+    }
+}
+
+fn secs(duration: Duration) -> u32 {
+    duration.as_secs().max(1).min(i32::MAX as u64) as u32
+}
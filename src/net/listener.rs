@@ -1,87 +1,422 @@
+use std::cmp;
 use std::io;
 use std::future::Future;
-use std::net::{ToSocketAddrs, SocketAddr};
-use std::os::unix::io::{RawFd};
+use std::mem::ManuallyDrop;
+use std::net::{self, ToSocketAddrs, SocketAddr};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::task::{Context, Poll};
 
 use futures_core::{ready, Stream};
-use iou::sqe::SockAddrStorage;
-use nix::sys::socket::{self as nix_socket, SockProtocol, SockFlag};
+use iou::registrar::RegisteredFd;
+use nix::sys::socket::{self as nix_socket, SockFlag};
 
-use crate::drive::{Drive, demo::DemoDriver};
+use crate::drive::{Drive, DefaultDriver};
 use crate::ring::{Cancellation, Ring};
+use crate::sys;
 
-use super::TcpStream;
+use super::{Protocol, SockAddr, SockAddrStorage, TcpStream};
+use super::sockopt::{self, SetSocketOpt, SocketOpt, Ipv6Only, KeepAlive, ReuseAddr, ReusePort};
+use super::sockopt::{TcpFastOpen, TcpNoDelay, Ttl};
 
-pub struct TcpListener<D: Drive = DemoDriver> {
+pub struct TcpListener<D: Drive = DefaultDriver> {
     ring: Ring<D>,
     fd: RawFd,
     active: Op,
-    addr: Option<Box<iou::sqe::SockAddrStorage>>,
+    addr: Option<Box<SockAddrStorage>>,
+    stream_driver: Option<Box<dyn FnMut() -> D + Send + Sync>>,
+    accepting: Arc<Accepting>,
+    accept_flags: SockFlag,
+}
+
+/// The state a listener shares with its [`ShutdownHandle`]s.
+struct Accepting {
+    // The listener's fd, until it is closed or given up by the listener
+    fd: Mutex<Option<RawFd>>,
+    shut_down: AtomicBool,
+}
+
+impl Accepting {
+    fn new(fd: RawFd) -> Arc<Accepting> {
+        Arc::new(Accepting { fd: Mutex::new(Some(fd)), shut_down: AtomicBool::new(false) })
+    }
+
+    fn shut_down(&self) {
+        if let Some(fd) = *self.fd.lock().unwrap() {
+            self.shut_down.store(true, Ordering::Release);
+            unsafe { libc::shutdown(fd, libc::SHUT_RD); }
+        }
+    }
+
+    fn release(&self) {
+        *self.fd.lock().unwrap() = None;
+    }
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 enum Op {
     Nothing = 0,
     Accept,
+    AcceptDirect,
+    Incoming,
     Close,
     Closed,
 }
 
+/// Whether the kernel supports multishot accepts (Linux 5.19 and later). This is learned from the
+/// first one submitted: older kernels reject the flag with `EINVAL`.
+static MULTISHOT_ACCEPT: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+const UNKNOWN: u8 = 0;
+const SUPPORTED: u8 = 1;
+const UNSUPPORTED: u8 = 2;
+
 impl TcpListener {
+    /// Bind a listener with the `bind(2)` and `listen(2)` syscalls, using the default driver.
     pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<TcpListener> {
-        TcpListener::bind_on_driver(addr, DemoDriver::default())
+        TcpListenerBuilder::new().bind(addr)
+    }
+
+    /// Construct a builder to configure the listener's socket before it is bound.
+    pub fn builder() -> TcpListenerBuilder {
+        TcpListenerBuilder::new()
+    }
+
+    /// Bind a listener using io-uring rather than syscalls, using the default driver
+    pub fn bind_async<A: ToSocketAddrs>(addr: A) -> Bind {
+        TcpListener::bind_async_on_driver(addr, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive + Unpin> TcpListener<D> {
+    /// Bind a listener using io-uring rather than syscalls
+    ///
+    /// On kernels which support them (6.11 and later), the socket is bound and set listening
+    /// with the `IORING_OP_BIND` and `IORING_OP_LISTEN` operations. On older kernels, this falls
+    /// back to the `bind(2)` and `listen(2)` syscalls.
+    pub fn bind_async_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> Bind<D> {
+        TcpListenerBuilder::new().bind_async_on_driver(addr, driver)
     }
 }
 
 impl<D: Drive> TcpListener<D> {
     pub fn bind_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> io::Result<TcpListener<D>> {
-        let (fd, addr) = super::socket(addr, SockProtocol::Tcp)?;
-        let addr = iou::sqe::SockAddr::Inet(nix_socket::InetAddr::from_std(&addr));
-        nix_socket::setsockopt(fd, nix_socket::sockopt::ReuseAddr, &true)
-            .map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
-        nix_socket::bind(fd, &addr).map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
-        nix_socket::listen(fd, 128).map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
-        let ring = Ring::new(driver);
-        Ok(TcpListener {
+        TcpListenerBuilder::new().bind_on_driver(addr, driver)
+    }
+
+    /// Take a listener bound elsewhere, like a socket passed by systemd, and run its accepts on
+    /// an io-uring driver
+    ///
+    /// The listener is put in blocking mode, so that io-uring waits for connections rather than
+    /// failing with `EAGAIN`.
+    pub fn from_std(listener: net::TcpListener, driver: D) -> io::Result<TcpListener<D>> {
+        listener.set_nonblocking(false)?;
+        Ok(unsafe { TcpListener::from_raw_fd_on_driver(listener.into_raw_fd(), driver) })
+    }
+
+    /// Take ownership of the file descriptor of a listening socket and run its accepts on an
+    /// io-uring driver
+    ///
+    /// # Safety
+    ///
+    /// The fd must be a listening socket, and must not be owned by anything else; it will be
+    /// closed when the listener is dropped.
+    pub unsafe fn from_raw_fd_on_driver(fd: RawFd, driver: D) -> TcpListener<D> {
+        TcpListener {
+            ring: Ring::new(driver),
             active: Op::Nothing,
             addr: None,
-            fd, ring,
-        })
+            stream_driver: None,
+            accepting: Accepting::new(fd),
+            accept_flags: SockFlag::SOCK_CLOEXEC,
+            fd,
+        }
     }
 
-    pub fn close(&mut self) -> Close<D> where D: Unpin {
+    /// Stop running this listener on io-uring, and return it as a standard library listener
+    ///
+    /// Any ongoing accept is cancelled. The multishot accept submitted by `incoming` cannot be
+    /// stopped without shutting the socket down, so a listener which has been used with
+    /// `incoming` may still accept a connection into it, which is then closed.
+    pub fn into_std(mut self) -> net::TcpListener {
+        self.cancel();
+        self.accepting.release();
+        let listener = ManuallyDrop::new(self);
+        unsafe { net::TcpListener::from_raw_fd(listener.fd) }
+    }
+
+    pub fn close(&mut self) -> Close<'_, D> where D: Unpin {
         Pin::new(self).close_pinned()
     }
 
-    pub fn close_pinned(self: Pin<&mut Self>) -> Close<D> {
+    pub fn close_pinned(self: Pin<&mut Self>) -> Close<'_, D> {
         Close { socket: self }
     }
 
+    /// Stop accepting connections.
+    ///
+    /// The socket is shut down, so that an accept in progress fails, and streams of incoming
+    /// connections end: once they have yielded the connections accepted before this, they yield
+    /// `None`. The listener still has to be closed or dropped.
+    pub fn shutdown_accepting(&self) {
+        self.accepting.shut_down();
+    }
+
+    /// A handle which can stop this listener accepting connections while it is borrowed, for
+    /// example by a stream of incoming connections.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle { accepting: self.accepting.clone() }
+    }
+
+    /// Whether the listener has stopped accepting connections, because it was shut down with
+    /// `shutdown_accepting` or closed.
+    fn stopped_accepting(&self) -> bool {
+        self.active == Op::Closed || self.accepting.shut_down.load(Ordering::Acquire)
+    }
+
+    /// The address this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        super::local_addr(self.fd)
+    }
+
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        sockopt::set(self.fd, opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        sockopt::get(self.fd)
+    }
+
+    /// Set `TCP_NODELAY`, disabling Nagle's algorithm.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.set_opt(TcpNoDelay(nodelay))
+    }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.opt().map(|TcpNoDelay(nodelay)| nodelay)
+    }
+
+    /// Set the time-to-live of outgoing IPv4 packets.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.set_opt(Ttl(ttl))
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.opt().map(|Ttl(ttl)| ttl)
+    }
+
+    /// Set `SO_KEEPALIVE`, sending keepalive probes while the connection is idle.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.set_opt(KeepAlive(keepalive))
+    }
+
+    pub fn keepalive(&self) -> io::Result<bool> {
+        self.opt().map(|KeepAlive(keepalive)| keepalive)
+    }
+
+    /// Construct the drivers of accepted streams with this factory, rather than by cloning the
+    /// listener's driver.
+    ///
+    /// This can be used to distribute accepted connections across several io-uring instances,
+    /// for example one per core, while the listener stays on one.
+    pub fn set_stream_driver<F>(&mut self, factory: F) where
+        F: FnMut() -> D + Send + Sync + 'static,
+    {
+        self.stream_driver = Some(Box::new(factory));
+    }
+
+    /// Set the flags accepted sockets are created with, as by `accept4(2)`.
+    ///
+    /// By default this is `SOCK_CLOEXEC`, so that accepted sockets are not inherited by child
+    /// processes.
+    pub fn set_accept_flags(&mut self, flags: SockFlag) {
+        self.accept_flags = flags;
+    }
+
+    /// The flags accepted sockets are created with.
+    pub fn accept_flags(&self) -> SockFlag {
+        self.accept_flags
+    }
+
+    /// Accept a connection, running the accepted stream on the provided driver.
+    pub fn accept_on<E: Drive>(&mut self, driver: E) -> AcceptOn<'_, D, E> where D: Unpin {
+        Pin::new(self).accept_on_pinned(driver)
+    }
+
+    pub fn accept_on_pinned<E: Drive>(self: Pin<&mut Self>, driver: E) -> AcceptOn<'_, D, E> {
+        AcceptOn { socket: self, driver: Some(driver) }
+    }
+
+    /// Accept connections, running each accepted stream on a driver constructed by `factory`.
+    ///
+    /// Unlike `incoming`, this does not require the listener's driver to be `Clone`.
+    pub fn incoming_on<E, F>(&mut self, factory: F) -> IncomingOn<'_, D, F> where
+        D: Unpin,
+        E: Drive,
+        F: FnMut() -> E,
+    {
+        Pin::new(self).incoming_on_pinned(factory)
+    }
+
+    pub fn incoming_on_pinned<E, F>(self: Pin<&mut Self>, factory: F) -> IncomingOn<'_, D, F> where
+        E: Drive,
+        F: FnMut() -> E,
+    {
+        IncomingOn { socket: self, factory }
+    }
+
+    /// Accept a connection, returning the file descriptor of the accepted socket rather than a
+    /// `TcpStream`.
+    ///
+    /// The caller takes ownership of the file descriptor and is responsible for closing it.
+    pub fn accept_raw(&mut self) -> AcceptRaw<'_, D> where D: Unpin {
+        Pin::new(self).accept_raw_pinned()
+    }
+
+    pub fn accept_raw_pinned(self: Pin<&mut Self>) -> AcceptRaw<'_, D> {
+        AcceptRaw { socket: self }
+    }
+
+    pub fn poll_accept_raw(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<(RawFd, SocketAddr)>>
+    {
+        self.as_mut().guard_op(Op::Accept);
+        let (fd, flags) = (self.fd, self.accept_flags);
+        let (ring, addr, ..) = self.as_mut().split_with_addr();
+        let fd = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_accept(fd, Some(addr.as_iou_mut()), flags);
+            }
+            sqe
+        }))? as RawFd;
+        let addr = {
+            let result = unsafe { addr.as_socket_addr() };
+            // Reset the storage in place so the next accept can reuse its allocation; it is only
+            // given up to the ring if an accept is cancelled.
+            *addr = SockAddrStorage::uninit();
+            match result.map(|addr| addr.as_inet()) {
+                Ok(Some(addr))  => addr,
+                result          => {
+                    unsafe { libc::close(fd); }
+                    return Poll::Ready(Err(result.err().unwrap_or_else(|| {
+                        io::Error::from(io::ErrorKind::InvalidData)
+                    })));
+                }
+            }
+        };
+        Poll::Ready(Ok((fd, addr)))
+    }
+
+    /// Accept a connection directly into a free slot of the fixed-file table of the driver's
+    /// io-uring instance.
+    ///
+    /// The accepted socket has no file descriptor in the process's table; it can only be used by
+    /// events on the same io-uring instance, through the `RegisteredFd` this returns. A fixed-file
+    /// table with free slots must already have been registered.
+    pub fn accept_direct(&mut self) -> AcceptDirect<'_, D> where D: Unpin {
+        Pin::new(self).accept_direct_pinned()
+    }
+
+    pub fn accept_direct_pinned(self: Pin<&mut Self>) -> AcceptDirect<'_, D> {
+        AcceptDirect { socket: self }
+    }
+
+    pub fn poll_accept_direct(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<RegisteredFd>>
+    {
+        self.as_mut().guard_op(Op::AcceptDirect);
+        let (fd, flags) = (self.fd, self.accept_flags);
+        let index = ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_accept(fd, None, flags);
+                sys::set_file_index(&mut sqe, None);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(RegisteredFd::new(index, iou::registrar::PLACEHOLDER_FD)))
+    }
+
+    fn poll_accept_fd(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<RawFd>> {
+        self.as_mut().guard_op(Op::Accept);
+        let (fd, flags) = (self.fd, self.accept_flags);
+        let fd = ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_accept(fd, None, flags);
+            }
+            sqe
+        }))?;
+        Poll::Ready(Ok(fd as RawFd))
+    }
+
+    /// Accept a connection for `incoming`.
+    ///
+    /// Where the kernel supports it, this submits one multishot accept, which stays armed and
+    /// completes again for each following connection, rather than one accept per connection.
+    fn poll_accept_multishot(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<RawFd>>
+    {
+        if MULTISHOT_ACCEPT.load(Ordering::Relaxed) == UNSUPPORTED {
+            return self.poll_accept_fd(ctx);
+        }
+        self.as_mut().guard_op(Op::Incoming);
+        let (fd, flags) = (self.fd, self.accept_flags);
+        let (result, _) = ready!(self.as_mut().ring().poll_multishot(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_accept(fd, None, flags);
+                sqe.raw_mut().ioprio |= sys::IORING_ACCEPT_MULTISHOT;
+            }
+            sqe
+        }));
+        match result {
+            Ok(fd)                                                      => {
+                MULTISHOT_ACCEPT.store(SUPPORTED, Ordering::Relaxed);
+                Poll::Ready(Ok(fd as RawFd))
+            }
+            // A listener which has been shut down also fails accepts with EINVAL.
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL)
+                && MULTISHOT_ACCEPT.load(Ordering::Relaxed) == UNKNOWN
+                && !self.stopped_accepting()                            => {
+                MULTISHOT_ACCEPT.store(UNSUPPORTED, Ordering::Relaxed);
+                self.poll_accept_fd(ctx)
+            }
+            Err(err)                                                    => Poll::Ready(Err(err)),
+        }
+    }
+
     fn guard_op(self: Pin<&mut Self>, op: Op) {
         let (ring, addr, active) = self.split();
         if *active == Op::Closed {
             panic!("Attempted to perform IO on a closed TcpListener");
         } else if *active != Op::Nothing && *active != op {
-            ring.cancel_pinned(Cancellation::from(addr.take()));
+            ring.cancel_pinned(TcpListener::<D>::cancellation(*active, addr));
         }
         *active = op;
     }
 
     fn cancel(&mut self) {
-        let cancellation = match self.active {
-            Op::Accept  => Cancellation::from(self.addr.take()),
-            Op::Close   => Cancellation::from(()),
-            Op::Closed  => return,
-            Op::Nothing => return,
-        };
+        if let Op::Closed | Op::Nothing = self.active {
+            return;
+        }
+        let cancellation = TcpListener::<D>::cancellation(self.active, &mut self.addr);
         self.active = Op::Nothing;
         self.ring.cancel(cancellation);
     }
 
-    fn drop_addr(self: Pin<&mut Self>) {
-        self.split().1.take();
+    /// The cancellation of the active op. Connections which are accepted after it has been
+    /// cancelled are closed.
+    fn cancellation(active: Op, addr: &mut Option<Box<SockAddrStorage>>) -> Cancellation {
+        match active {
+            Op::Accept | Op::Incoming   => Cancellation::from(addr.take()).close_results(),
+            _                           => Cancellation::from(addr.take()),
+        }
     }
 
     fn ring(self: Pin<&mut Self>) -> Pin<&mut Ring<D>> {
@@ -125,8 +460,15 @@ impl<D: Drive + Clone> TcpListener<D> {
         Pin::new(self).incoming_pinned()
     }
 
+    /// Accept connections as a stream.
+    ///
+    /// The stream ends once the listener has been shut down with `shutdown_accepting` or closed.
+    ///
+    /// On Linux 5.19 and later, a single multishot accept is submitted, which the kernel
+    /// completes for every connection; older kernels fall back to one accept per connection. The
+    /// address of each peer is read with `getpeername(2)`.
     pub fn incoming_pinned(self: Pin<&mut Self>) -> Incoming<'_, D> {
-        Incoming { accept: self.accept_pinned() }
+        Incoming { socket: self }
     }
 
     pub fn accept_no_addr(&mut self) -> AcceptNoAddr<'_, D> where D: Unpin {
@@ -148,54 +490,266 @@ impl<D: Drive + Clone> TcpListener<D> {
     pub fn poll_accept(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
         -> Poll<io::Result<(TcpStream<D>, SocketAddr)>>
     {
-        self.as_mut().guard_op(Op::Accept);
-        let fd = self.fd;
-        let (ring, addr, ..) = self.as_mut().split_with_addr();
-        let fd = ready!(ring.poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
-            unsafe {
-                sqe.prep_accept(fd, Some(addr), SockFlag::empty());
-            }
-            sqe
-        }))? as RawFd;
-        let addr = {
-            let result = unsafe { addr.as_socket_addr() };
-            self.as_mut().drop_addr();
-            match result? {
-                iou::sqe::SockAddr::Inet(addr) => addr.to_std(),
-                addr => panic!("TcpListener addr cannot be {:?}", addr.family()),
-            }
-        };
-
-        Poll::Ready(Ok((TcpStream::from_fd(fd, self.ring().clone()), addr)))
+        let (fd, addr) = ready!(self.as_mut().poll_accept_raw(ctx))?;
+        Poll::Ready(Ok((TcpStream::from_fd(fd, self.stream_ring()), addr)))
     }
 
     pub fn poll_accept_no_addr(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
         -> Poll<io::Result<TcpStream<D>>>
     {
-        self.as_mut().guard_op(Op::Accept);
-        let fd = self.fd;
-        let fd = ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
-            unsafe {
-                sqe.prep_accept(fd, None, SockFlag::empty());
-            }
-            sqe
-        }))? as RawFd;
-        Poll::Ready(Ok(TcpStream::from_fd(fd, self.ring().clone())))
+        let fd = ready!(self.as_mut().poll_accept_fd(ctx))?;
+        Poll::Ready(Ok(TcpStream::from_fd(fd, self.stream_ring())))
+    }
+
+    fn stream_ring(self: Pin<&mut Self>) -> Ring<D> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        match &mut this.stream_driver {
+            Some(factory)   => this.ring.with_driver(factory()),
+            None            => this.ring.clone(),
+        }
+    }
+}
+
+impl<D: Drive> AsRawFd for TcpListener<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl<D: Drive + Default> FromRawFd for TcpListener<D> {
+    unsafe fn from_raw_fd(fd: RawFd) -> TcpListener<D> {
+        TcpListener::from_raw_fd_on_driver(fd, D::default())
+    }
+}
+
+impl<D: Drive> IntoRawFd for TcpListener<D> {
+    fn into_raw_fd(self) -> RawFd {
+        self.into_std().into_raw_fd()
+    }
+}
+
+impl<D: Drive> From<TcpListener<D>> for net::TcpListener {
+    fn from(listener: TcpListener<D>) -> net::TcpListener {
+        listener.into_std()
     }
 }
 
 impl<D: Drive> Drop for TcpListener<D> {
     fn drop(&mut self) {
+        self.accepting.release();
         match self.active {
-            Op::Closed  => { }
-            Op::Nothing => unsafe { libc::close(self.fd); }
-            _           => self.cancel(),
+            Op::Closed      => { }
+            Op::Nothing     => unsafe { libc::close(self.fd); }
+            Op::Close       => self.cancel(),
+            // The socket is closed once the kernel has completed the accept for the last time. A
+            // multishot accept keeps accepting connections until it is stopped; shutting the
+            // socket down fails it, so that it does.
+            op              => {
+                if op == Op::Incoming {
+                    unsafe { libc::shutdown(self.fd, libc::SHUT_RDWR); }
+                }
+                let cancellation = TcpListener::<D>::cancellation(op, &mut self.addr);
+                self.active = Op::Nothing;
+                self.ring.cancel(cancellation.keep(ListenerFd(self.fd)));
+            }
         }
     }
 }
 
+/// The socket of a dropped listener, which is closed once its last accept has completed.
+struct ListenerFd(RawFd);
+
+impl Drop for ListenerFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.0); }
+    }
+}
+
+/// A builder for a [`TcpListener`], which configures its socket before it is bound.
+///
+/// ```no_run
+/// use ringbahn::net::TcpListener;
+///
+/// // Several listeners, for example one per driver, can share the port.
+/// let listener = TcpListener::builder().reuse_port(true).bind(("0.0.0.0", 7878))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct TcpListenerBuilder {
+    reuse_addr: bool,
+    reuse_port: bool,
+    only_v6: Option<bool>,
+    backlog: u32,
+    // Further options, as their level, name and the bytes of their value
+    opts: Vec<(libc::c_int, libc::c_int, Vec<u8>)>,
+    // Whether an option was added whose value could not be represented
+    invalid_opt: bool,
+}
+
+const BACKLOG: u32 = 128;
+
+impl Default for TcpListenerBuilder {
+    fn default() -> TcpListenerBuilder {
+        TcpListenerBuilder {
+            reuse_addr: true,
+            reuse_port: false,
+            only_v6: None,
+            backlog: BACKLOG,
+            opts: Vec::new(),
+            invalid_opt: false,
+        }
+    }
+}
+
+impl TcpListenerBuilder {
+    /// Construct a builder with the configuration used by `TcpListener::bind`.
+    pub fn new() -> TcpListenerBuilder {
+        TcpListenerBuilder::default()
+    }
+
+    /// Set `SO_REUSEADDR` before binding. This is enabled by default.
+    pub fn reuse_addr(mut self, reuse_addr: bool) -> TcpListenerBuilder {
+        self.reuse_addr = reuse_addr;
+        self
+    }
+
+    /// Set `SO_REUSEPORT` before binding, so that several listeners can bind the same address
+    /// and have the kernel distribute connections between them.
+    pub fn reuse_port(mut self, reuse_port: bool) -> TcpListenerBuilder {
+        self.reuse_port = reuse_port;
+        self
+    }
+
+    /// Set `IPV6_V6ONLY` before binding to an IPv6 address. By default, the system's setting is
+    /// used, which on Linux lets a listener on `[::]` accept IPv4 connections too.
+    ///
+    /// This has no effect when binding to an IPv4 address.
+    pub fn only_v6(mut self, only_v6: bool) -> TcpListenerBuilder {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// The number of connections the kernel queues until they are accepted. This is 128 by
+    /// default; the kernel caps it at `net.core.somaxconn`.
+    pub fn backlog(mut self, backlog: u32) -> TcpListenerBuilder {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Enable TCP Fast Open, accepting data in the SYN of up to `queue_len` connections at a time
+    /// whose handshakes have not completed.
+    pub fn fastopen(self, queue_len: u32) -> TcpListenerBuilder {
+        self.socket_opt(TcpFastOpen(queue_len))
+    }
+
+    /// Bind the socket to a network interface with `SO_BINDTODEVICE`, so that it only accepts
+    /// connections which arrive through it. This usually requires `CAP_NET_RAW`.
+    pub fn bind_device(mut self, interface: &str) -> TcpListenerBuilder {
+        let opt = (libc::SOL_SOCKET, libc::SO_BINDTODEVICE, interface.as_bytes().to_vec());
+        self.opts.push(opt);
+        self
+    }
+
+    /// Set any other option on the socket before it is bound. Options are set in the order they
+    /// are added, after those configured with the builder's other methods.
+    ///
+    /// If the option's value cannot be passed to the kernel, binding fails with `EINVAL`.
+    pub fn socket_opt<O: SetSocketOpt>(mut self, opt: O) -> TcpListenerBuilder {
+        match sockopt::to_bytes(&opt) {
+            Ok(value)   => self.opts.push((O::LEVEL, O::NAME, value)),
+            Err(_)      => self.invalid_opt = true,
+        }
+        self
+    }
+
+    /// Bind a listener using the default driver.
+    pub fn bind<A: ToSocketAddrs>(&self, addr: A) -> io::Result<TcpListener> {
+        self.bind_on_driver(addr, DefaultDriver::default())
+    }
+
+    /// Bind a listener using the provided driver.
+    pub fn bind_on_driver<A: ToSocketAddrs, D: Drive>(&self, addr: A, driver: D)
+        -> io::Result<TcpListener<D>>
+    {
+        self.bind_protocol_on_driver(addr, Protocol::TCP, driver)
+    }
+
+    /// Bind a listening socket of another protocol which is accepted like TCP, like one-to-one
+    /// SCTP.
+    pub(super) fn bind_protocol_on_driver<A: ToSocketAddrs, D: Drive>(
+        &self,
+        addr: A,
+        protocol: Protocol,
+        driver: D,
+    ) -> io::Result<TcpListener<D>> {
+        let (fd, addr) = super::socket(addr, protocol)?;
+        if let Err(err) = self.configure(fd, &addr) {
+            unsafe { libc::close(fd); }
+            return Err(err);
+        }
+        Ok(unsafe { TcpListener::from_raw_fd_on_driver(fd, driver) })
+    }
+
+    /// Bind a listener using io-uring rather than syscalls, using the default driver.
+    pub fn bind_async<A: ToSocketAddrs>(&self, addr: A) -> Bind {
+        self.bind_async_on_driver(addr, DefaultDriver::default())
+    }
+
+    /// Bind a listener using io-uring rather than syscalls, using the provided driver.
+    ///
+    /// The socket's options are still set with syscalls; see
+    /// [`TcpListener::bind_async_on_driver`] for how it is bound.
+    pub fn bind_async_on_driver<A: ToSocketAddrs, D: Drive + Unpin>(&self, addr: A, driver: D)
+        -> Bind<D>
+    {
+        let (fd, addr) = match super::socket(addr, Protocol::TCP) {
+            Ok(socket)  => socket,
+            Err(err)    => return Bind::failed(err, driver),
+        };
+        if let Err(err) = self.set_opts(fd, &addr) {
+            unsafe { libc::close(fd); }
+            return Bind::failed(err, driver);
+        }
+        let addr = iou::sqe::SockAddr::Inet(nix_socket::InetAddr::from_std(&addr));
+        Bind {
+            ring: Some(Ring::new(driver)),
+            fd,
+            addr: Some(Box::new(addr)),
+            backlog: cmp::min(self.backlog, libc::c_int::MAX as u32),
+            stage: Stage::Bind,
+            fallback: false,
+        }
+    }
+
+    fn configure(&self, fd: RawFd, addr: &SocketAddr) -> io::Result<()> {
+        self.set_opts(fd, addr)?;
+        nix_socket::bind(fd, SockAddr::from(*addr).as_iou())
+            .map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
+        let backlog = cmp::min(self.backlog, libc::c_int::MAX as u32) as usize;
+        nix_socket::listen(fd, backlog).map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
+        Ok(())
+    }
+
+    fn set_opts(&self, fd: RawFd, addr: &SocketAddr) -> io::Result<()> {
+        if self.invalid_opt {
+            return Err(io::Error::from_raw_os_error(libc::EINVAL));
+        }
+        if self.reuse_addr {
+            sockopt::set(fd, ReuseAddr(true))?;
+        }
+        if self.reuse_port {
+            sockopt::set(fd, ReusePort(true))?;
+        }
+        if let (Some(only_v6), SocketAddr::V6(_)) = (self.only_v6, addr) {
+            sockopt::set(fd, Ipv6Only(only_v6))?;
+        }
+        for (level, name, value) in &self.opts {
+            sockopt::set_bytes(fd, *level, *name, value)?;
+        }
+        Ok(())
+    }
+}
+
 pub struct Accept<'a, D: Drive> {
     socket: Pin<&'a mut TcpListener<D>>,
 }
@@ -208,6 +762,35 @@ impl<'a, D: Drive + Clone> Future for Accept<'a, D> {
     }
 }
 
+pub struct AcceptOn<'a, D: Drive, E: Drive> {
+    socket: Pin<&'a mut TcpListener<D>>,
+    driver: Option<E>,
+}
+
+impl<'a, D: Drive, E: Drive> Future for AcceptOn<'a, D, E> {
+    type Output = io::Result<(TcpStream<E>, SocketAddr)>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let (fd, addr) = ready!(this.socket.as_mut().poll_accept_raw(ctx))?;
+        let driver = this.driver.take().expect("polled AcceptOn after completion");
+        let ring = this.socket.ring.with_driver(driver);
+        Poll::Ready(Ok((TcpStream::from_fd(fd, ring), addr)))
+    }
+}
+
+pub struct AcceptRaw<'a, D: Drive> {
+    socket: Pin<&'a mut TcpListener<D>>,
+}
+
+impl<'a, D: Drive> Future for AcceptRaw<'a, D> {
+    type Output = io::Result<(RawFd, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket.as_mut().poll_accept_raw(ctx)
+    }
+}
+
 pub struct AcceptNoAddr<'a, D: Drive> {
     socket: Pin<&'a mut TcpListener<D>>,
 }
@@ -220,22 +803,65 @@ impl<'a, D: Drive + Clone> Future for AcceptNoAddr<'a, D> {
     }
 }
 
-pub struct Incoming<'a, D: Drive> {
-    accept: Accept<'a, D>,
+pub struct AcceptDirect<'a, D: Drive> {
+    socket: Pin<&'a mut TcpListener<D>>,
 }
 
-impl<'a, D: Drive> Incoming<'a, D> {
-    fn inner(self: Pin<&mut Self>) -> Pin<&mut Accept<'a, D>> {
-        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.accept) }
+impl<'a, D: Drive> Future for AcceptDirect<'a, D> {
+    type Output = io::Result<RegisteredFd>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket.as_mut().poll_accept_direct(ctx)
     }
 }
 
+pub struct Incoming<'a, D: Drive> {
+    socket: Pin<&'a mut TcpListener<D>>,
+}
+
 impl<'a, D: Drive + Clone> Stream for Incoming<'a, D> {
     type Item = io::Result<(TcpStream<D>, SocketAddr)>;
 
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.socket.active == Op::Closed {
+            return Poll::Ready(None);
+        }
+        let fd = match ready!(self.socket.as_mut().poll_accept_multishot(ctx)) {
+            Ok(fd)                                      => fd,
+            Err(_) if self.socket.stopped_accepting()   => return Poll::Ready(None),
+            Err(err)                                    => return Poll::Ready(Some(Err(err))),
+        };
+        let addr = match super::peer_addr(fd) {
+            Ok(addr)    => addr,
+            Err(err)    => {
+                unsafe { libc::close(fd); }
+                return Poll::Ready(Some(Err(err)));
+            }
+        };
+        Poll::Ready(Some(Ok((TcpStream::from_fd(fd, self.socket.as_mut().stream_ring()), addr))))
+    }
+}
+
+pub struct IncomingOn<'a, D: Drive, F> {
+    socket: Pin<&'a mut TcpListener<D>>,
+    factory: F,
+}
+
+impl<'a, D: Drive, E: Drive, F: FnMut() -> E> Stream for IncomingOn<'a, D, F> {
+    type Item = io::Result<(TcpStream<E>, SocketAddr)>;
+
     fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let next = ready!(self.inner().poll(ctx));
-        Poll::Ready(Some(next))
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        if this.socket.active == Op::Closed {
+            return Poll::Ready(None);
+        }
+        let (fd, addr) = match ready!(this.socket.as_mut().poll_accept_raw(ctx)) {
+            Ok(accepted)                                => accepted,
+            Err(_) if this.socket.stopped_accepting()   => return Poll::Ready(None),
+            Err(err)                                    => return Poll::Ready(Some(Err(err))),
+        };
+        let ring = this.socket.ring.with_driver((this.factory)());
+        Poll::Ready(Some(Ok((TcpStream::from_fd(fd, ring), addr))))
     }
 }
 
@@ -252,9 +878,31 @@ impl<'a, D: Drive> IncomingNoAddr<'a, D> {
 impl<'a, D: Drive + Clone> Stream for IncomingNoAddr<'a, D> {
     type Item = io::Result<TcpStream<D>>;
 
-    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let next = ready!(self.inner().poll(ctx));
-        Poll::Ready(Some(next))
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.accept.socket.active == Op::Closed {
+            return Poll::Ready(None);
+        }
+        match ready!(self.as_mut().inner().poll(ctx)) {
+            Err(_) if self.accept.socket.stopped_accepting()    => Poll::Ready(None),
+            next                                                => Poll::Ready(Some(next)),
+        }
+    }
+}
+
+/// A handle which stops a [`TcpListener`] accepting connections
+///
+/// This is returned by [`TcpListener::shutdown_handle`], and can be used from another task, or
+/// from the task which is waiting for connections, while the listener is borrowed. Once the
+/// listener has been closed or dropped, shutting it down through a handle has no effect.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    accepting: Arc<Accepting>,
+}
+
+impl ShutdownHandle {
+    /// Stop the listener accepting connections, like [`TcpListener::shutdown_accepting`].
+    pub fn shutdown_accepting(&self) {
+        self.accepting.shut_down();
     }
 }
 
@@ -267,9 +915,10 @@ impl<'a, D: Drive> Future for Close<'a, D> {
 
     fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
         self.socket.as_mut().guard_op(Op::Close);
+        self.socket.accepting.release();
         let fd = self.socket.fd;
         ready!(self.socket.as_mut().ring().poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
+            let mut sqe = sqs.next().unwrap();
             unsafe {
                 sqe.prep_close(fd);
             }
@@ -279,3 +928,121 @@ impl<'a, D: Drive> Future for Close<'a, D> {
         Poll::Ready(Ok(()))
     }
 }
+
+/// A future representing a listener being bound with io-uring.
+pub struct Bind<D: Drive = DefaultDriver> {
+    // The ring the events are submitted on, until it is given to the listener
+    ring: Option<Ring<D>>,
+    fd: RawFd,
+    addr: Option<Box<iou::sqe::SockAddr>>,
+    backlog: u32,
+    stage: Stage,
+    fallback: bool,
+}
+
+enum Stage {
+    Bind,
+    Listen,
+    Done,
+    Failed(Option<io::Error>),
+}
+
+impl<D: Drive> Bind<D> {
+    fn failed(err: io::Error, driver: D) -> Bind<D> {
+        Bind {
+            ring: Some(Ring::new(driver)),
+            fd: -1,
+            addr: None,
+            backlog: 0,
+            stage: Stage::Failed(Some(err)),
+            fallback: false,
+        }
+    }
+
+    fn fail(&mut self, err: io::Error) -> Poll<io::Result<TcpListener<D>>> {
+        unsafe { libc::close(self.fd); }
+        self.stage = Stage::Done;
+        Poll::Ready(Err(err))
+    }
+}
+
+impl<D: Drive + Unpin> Future for Bind<D> {
+    type Output = io::Result<TcpListener<D>>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let fd = this.fd;
+        loop {
+            let mut ring = Pin::new(this.ring.as_mut().expect("polled Bind after completion"));
+            match &mut this.stage {
+                Stage::Bind     => {
+                    let (addr, len) = this.addr.as_ref().unwrap().as_ffi_pair();
+                    let result = match this.fallback {
+                        false   => ready!(ring.as_mut().poll(ctx, 1, |sqs| unsafe {
+                            let mut sqe = sqs.next().unwrap();
+                            let addr = addr as *const libc::sockaddr as u64;
+                            sys::prep_raw(&mut sqe, sys::IORING_OP_BIND, fd, addr, 0, len as u64);
+                            sqe
+                        })),
+                        true    => Err(io::Error::from_raw_os_error(libc::EINVAL)),
+                    };
+                    let result = match result {
+                        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => {
+                            this.fallback = true;
+                            match unsafe { libc::bind(fd, addr, len) } {
+                                0   => Ok(0),
+                                _   => Err(io::Error::last_os_error()),
+                            }
+                        }
+                        result  => result,
+                    };
+                    if let Err(err) = result {
+                        return this.fail(err);
+                    }
+                    this.stage = Stage::Listen;
+                }
+                Stage::Listen   => {
+                    let backlog = this.backlog;
+                    let result = match this.fallback {
+                        false   => ready!(ring.as_mut().poll(ctx, 1, |sqs| unsafe {
+                            let mut sqe = sqs.next().unwrap();
+                            sys::prep_raw(&mut sqe, sys::IORING_OP_LISTEN, fd, 0, backlog, 0);
+                            sqe
+                        })),
+                        true    => match unsafe { libc::listen(fd, backlog as libc::c_int) } {
+                            0   => Ok(0),
+                            _   => Err(io::Error::last_os_error()),
+                        },
+                    };
+                    if let Err(err) = result {
+                        return this.fail(err);
+                    }
+                    this.stage = Stage::Done;
+                    return Poll::Ready(Ok(TcpListener {
+                        ring: this.ring.take().unwrap(),
+                        active: Op::Nothing,
+                        addr: None,
+                        stream_driver: None,
+                        accepting: Accepting::new(fd),
+                        accept_flags: SockFlag::SOCK_CLOEXEC,
+                        fd,
+                    }));
+                }
+                Stage::Failed(err)  => {
+                    let err = err.take().expect("polled Bind after completion");
+                    return Poll::Ready(Err(err));
+                }
+                Stage::Done         => panic!("polled Bind after completion"),
+            }
+        }
+    }
+}
+
+impl<D: Drive> Drop for Bind<D> {
+    fn drop(&mut self) {
+        if let (Stage::Bind | Stage::Listen, Some(ring)) = (&self.stage, &mut self.ring) {
+            ring.cancel(Cancellation::from(self.addr.take()));
+            unsafe { libc::close(self.fd); }
+        }
+    }
+}
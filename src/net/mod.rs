@@ -1,33 +1,99 @@
+mod addr;
+mod connect;
+#[cfg(feature = "framed")]
+mod framed;
+mod keepalive;
 mod listener;
+mod recv_stream;
+mod resolve;
+mod sctp;
+mod socket;
+mod split;
 mod stream;
+mod udp;
+
+pub mod sockopt;
 
 use std::io;
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::os::unix::io::RawFd;
 
-pub use listener::{TcpListener, Accept, AcceptNoAddr, Close, Incoming, IncomingNoAddr};
-pub use stream::{TcpStream, Connect};
+pub use addr::{SockAddr, SockAddrStorage};
+pub use listener::{TcpListener, Accept, AcceptDirect, AcceptNoAddr, AcceptOn, AcceptRaw, Close};
+pub use listener::{Bind, Incoming, IncomingNoAddr, IncomingOn, ShutdownHandle};
+pub use listener::TcpListenerBuilder;
+pub use connect::Connect;
+#[cfg(feature = "framed")]
+pub use framed::UdpFramed;
+pub use recv_stream::RecvStream;
+pub use resolve::{Resolve, GaiResolver, GaiResolve};
+pub use sctp::{SctpListener, SctpStream, SctpAccept, SctpConnect};
+pub use keepalive::TcpKeepalive;
+pub use stream::{TcpStream, SendFile, SendZc, TcpRecv, TcpSend, TcpShutdown};
+pub use stream::{TryRead, TryWrite};
+pub use split::{OwnedReadHalf, OwnedWriteHalf};
+pub use socket::{Socket, SocketConnect, SocketSend, SocketRecv, SendTo, RecvFrom};
+pub use socket::{PacketInfo, SendToFrom, RecvFromPktInfo};
+pub use udp::{UdpSocket, UdpConnect, UdpSend, UdpRecv};
+pub use sockopt::{SetSocketOpt, SocketOpt};
+pub use iou::sqe::{MsgFlags, SockFlag};
+
+/// The type and protocol of an internet socket, as passed to `socket(2)`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Protocol {
+    ty: libc::c_int,
+    protocol: libc::c_int,
+}
+
+impl Protocol {
+    const TCP: Protocol = Protocol { ty: libc::SOCK_STREAM, protocol: libc::IPPROTO_TCP };
+    const UDP: Protocol = Protocol { ty: libc::SOCK_DGRAM, protocol: libc::IPPROTO_UDP };
+    const UDP_LITE: Protocol = Protocol { ty: libc::SOCK_DGRAM, protocol: libc::IPPROTO_UDPLITE };
+    // One-to-one style SCTP, which is connected and accepted like TCP (RFC 6458)
+    const SCTP: Protocol = Protocol { ty: libc::SOCK_STREAM, protocol: libc::IPPROTO_SCTP };
+}
 
-use nix::sys::socket as nix;
+fn domain(addr: &SocketAddr) -> libc::c_int {
+    match addr.is_ipv6() {
+        true    => libc::AF_INET6,
+        false   => libc::AF_INET,
+    }
+}
 
-fn socket<A: ToSocketAddrs>(addr: A, protocol: nix::SockProtocol) -> io::Result<(RawFd, SocketAddr)> {
+/// Create a socket of `protocol` for the first address `addr` resolves to which one can be
+/// created for.
+fn socket<A: ToSocketAddrs>(addr: A, protocol: Protocol) -> io::Result<(RawFd, SocketAddr)> {
     use io::{Error, ErrorKind};
 
     let mut error = Error::new(ErrorKind::InvalidInput, "could not resolve to any addresses");
 
     for addr in addr.to_socket_addrs()? {
-        let domain = match addr.is_ipv6() {
-            true    => nix::AddressFamily::Inet6,
-            false   => nix::AddressFamily::Inet,
-        };
-
-        let flags = nix::SockFlag::SOCK_CLOEXEC;
-
-        match nix::socket(domain, nix::SockType::Stream, flags, Some(protocol)) {
-            Ok(fd)          => return Ok((fd, addr)),
-            _               => error = io::Error::last_os_error(),
+        let ty = protocol.ty | libc::SOCK_CLOEXEC;
+        match unsafe { libc::socket(domain(&addr), ty, protocol.protocol) } {
+            -1  => error = io::Error::last_os_error(),
+            fd  => return Ok((fd, addr)),
         }
     }
 
     Err(error)
 }
+
+fn local_addr(fd: RawFd) -> io::Result<SocketAddr> {
+    inet_addr(|addr, len| unsafe { libc::getsockname(fd, addr, len) })
+}
+
+fn peer_addr(fd: RawFd) -> io::Result<SocketAddr> {
+    inet_addr(|addr, len| unsafe { libc::getpeername(fd, addr, len) })
+}
+
+/// Read an internet address with `getsockname` or `getpeername`.
+fn inet_addr(get: impl FnOnce(*mut libc::sockaddr, *mut libc::socklen_t) -> libc::c_int)
+    -> io::Result<SocketAddr>
+{
+    let mut storage: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    if get(&mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr, &mut len) == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    SockAddr::read_raw(&storage, len)?.as_inet().ok_or_else(|| io::ErrorKind::InvalidData.into())
+}
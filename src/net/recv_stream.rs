@@ -0,0 +1,188 @@
+use std::convert::TryFrom;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::{ready, Stream};
+use iou::{SQE, SQEs};
+use iou::sqe::{BufferGroupId, MsgFlags};
+
+use crate::drive::Drive;
+use crate::event;
+use crate::ring::{Cancellation, Ring};
+use crate::sys;
+
+use super::TcpStream;
+
+/// Whether the kernel supports multishot receives (Linux 6.0 and later). This is learned from the
+/// first one submitted: older kernels reject the flag with `EINVAL`.
+static MULTISHOT_RECV: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+const UNKNOWN: u8 = 0;
+const SUPPORTED: u8 = 1;
+const UNSUPPORTED: u8 = 2;
+
+/// A stream of the data received by a [`TcpStream`], created by [`TcpStream::recv_stream`]
+///
+/// The stream owns a group of buffers which it provides to the kernel, and receives into them
+/// with a single multishot receive: the kernel completes it each time data arrives, choosing a
+/// buffer from the group, without another submission. Once the kernel has filled every buffer,
+/// the receive ends, and the stream provides the buffers again and submits the next one. On
+/// kernels without multishot receives, one receive is submitted for each item.
+pub struct RecvStream<'a, D: Drive> {
+    ring: Ring<D>,
+    // Used to remove the buffers from their group when the stream is dropped
+    remove: Ring<D>,
+    bufs: Option<Box<[u8]>>,
+    group: u16,
+    count: u16,
+    size: u32,
+    // The number of buffers in the group which the kernel has not yet filled
+    provided: u16,
+    // Whether a multishot receive is submitted and will complete again
+    armed: bool,
+    stream: &'a TcpStream<D>,
+}
+
+impl<'a, D: Drive + Clone> RecvStream<'a, D> {
+    pub(super) fn new(stream: &'a TcpStream<D>, group: BufferGroupId, count: u16, size: u32)
+        -> RecvStream<'a, D>
+    {
+        RecvStream {
+            ring: Ring::new(stream.driver().clone()),
+            remove: Ring::new(stream.driver().clone()),
+            bufs: Some(vec![0; count as usize * size as usize].into_boxed_slice()),
+            group: u16::try_from(group.id).expect("buffer group ids are 16 bits"),
+            provided: 0,
+            armed: false,
+            count, size, stream,
+        }
+    }
+}
+
+impl<'a, D: Drive> RecvStream<'a, D> {
+    fn poll_provide(&mut self, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let bufs = self.bufs.as_deref_mut().unwrap();
+        let (count, group) = (self.count as u32, BufferGroupId { id: u32::from(self.group) });
+        let ring = unsafe { Pin::new_unchecked(&mut self.ring) };
+        ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_provide_buffers(bufs, count, group, 0);
+            }
+            sqe
+        }))?;
+        self.provided = self.count;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_recv(&mut self, ctx: &mut Context<'_>, multishot: bool)
+        -> Poll<(io::Result<u32>, bool)>
+    {
+        let (fd, size, group) = (self.stream.as_raw_fd(), self.size, self.group);
+        let ring = unsafe { Pin::new_unchecked(&mut self.ring) };
+        match multishot {
+            true    => ring.poll_multishot(ctx, 1, |sqs| prep_recv(sqs, fd, size, group, true)),
+            false   => {
+                let result = ready!(ring.poll(ctx, 1, |sqs| {
+                    prep_recv(sqs, fd, size, group, false)
+                }));
+                Poll::Ready((result, false))
+            }
+        }
+    }
+}
+
+fn prep_recv<'sq>(sqs: &mut SQEs<'sq>, fd: RawFd, size: u32, group: u16, multishot: bool)
+    -> SQE<'sq>
+{
+    let mut sqe = sqs.next().unwrap();
+    unsafe {
+        let opcode = uring_sys::IoRingOp::IORING_OP_RECV as u8;
+        sys::prep_raw(&mut sqe, opcode, fd, 0, size, 0);
+        sqe.raw_mut().cmd_flags.msg_flags = MsgFlags::empty().bits() as u32;
+        sys::set_buffer_group(&mut sqe, BufferGroupId { id: u32::from(group) });
+        if multishot {
+            sqe.raw_mut().ioprio |= sys::IORING_RECV_MULTISHOT;
+        }
+    }
+    sqe
+}
+
+impl<'a, D: Drive> Stream for RecvStream<'a, D> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        loop {
+            if this.provided == 0 && !this.armed {
+                ready!(this.poll_provide(ctx))?;
+            }
+            let multishot = MULTISHOT_RECV.load(Ordering::Relaxed) != UNSUPPORTED;
+            let (result, more) = ready!(this.poll_recv(ctx, multishot));
+            this.armed = more;
+            let buffer = event::selected_buffer(this.ring.completion_flags());
+            if buffer.is_some() {
+                this.provided = this.provided.saturating_sub(1);
+            }
+            let n = match result {
+                Ok(n)                                                       => n as usize,
+                // Every buffer has been filled; they are provided again by the next iteration.
+                Err(err) if err.raw_os_error() == Some(libc::ENOBUFS)       => {
+                    this.provided = 0;
+                    continue;
+                }
+                Err(err) if err.raw_os_error() == Some(libc::EINVAL)
+                    && multishot
+                    && MULTISHOT_RECV.load(Ordering::Relaxed) == UNKNOWN    => {
+                    MULTISHOT_RECV.store(UNSUPPORTED, Ordering::Relaxed);
+                    continue;
+                }
+                Err(err)                                                    => {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            };
+            if multishot {
+                MULTISHOT_RECV.store(SUPPORTED, Ordering::Relaxed);
+            }
+            return match buffer {
+                Some(id) if n > 0   => {
+                    let start = id as usize * this.size as usize;
+                    let bufs = this.bufs.as_deref().unwrap();
+                    Poll::Ready(Some(Ok(bufs[start..start + n].to_vec())))
+                }
+                _                   => Poll::Ready(None),
+            };
+        }
+    }
+}
+
+impl<'a, D: Drive> Drop for RecvStream<'a, D> {
+    fn drop(&mut self) {
+        // Nothing must be received into the buffers once they are freed, so they are removed
+        // from their group first, and kept alive until the removal completes. The receive itself
+        // ends once the kernel finds no buffers.
+        self.ring.cancel(Cancellation::from(()));
+        let (count, group) = (self.count as u32, BufferGroupId { id: u32::from(self.group) });
+        let mut prepared = false;
+        let mut ctx = Context::from_waker(Waker::noop());
+        let remove = unsafe { Pin::new_unchecked(&mut self.remove) };
+        let removed = remove.poll(&mut ctx, 1, |sqs| {
+            prepared = true;
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_remove_buffers(count, group);
+            }
+            sqe
+        });
+        match (prepared, removed) {
+            (_, Poll::Ready(_)) => { }
+            (true, _)           => self.remove.cancel(Cancellation::from(self.bufs.take())),
+            // The removal could not be prepared, so the kernel may still use the buffers.
+            (false, _)          => mem::forget(self.bufs.take()),
+        }
+    }
+}
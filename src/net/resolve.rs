@@ -0,0 +1,69 @@
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::ring::{self, Blocking};
+
+/// A way to resolve host names to addresses without blocking the thread which is connecting
+///
+/// Resolving with [`ToSocketAddrs`] calls `getaddrinfo(3)`, which blocks until the name has been
+/// looked up. [`TcpStream::connect_host_on_driver`](super::TcpStream::connect_host_on_driver)
+/// resolves the host with a `Resolve` implementation instead, which can be asynchronous, like a
+/// DNS client running on io-uring.
+pub trait Resolve {
+    type Future: Future<Output = io::Result<Vec<SocketAddr>>> + Send + 'static;
+
+    /// Resolve `host` to the addresses of `port` on it.
+    fn resolve(&self, host: &str, port: u16) -> Self::Future;
+}
+
+/// The default resolver, which calls `getaddrinfo(3)` on ringbahn's pool of threads for blocking
+/// work
+///
+/// Hosts which are IP address literals are not passed to `getaddrinfo`, and resolve immediately.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GaiResolver;
+
+impl Resolve for GaiResolver {
+    type Future = GaiResolve;
+
+    fn resolve(&self, host: &str, port: u16) -> GaiResolve {
+        let lookup = match host.parse::<IpAddr>() {
+            Ok(ip)  => Lookup::Done(Some(vec![SocketAddr::new(ip, port)])),
+            Err(_)  => {
+                let host = host.to_owned();
+                Lookup::Pending(ring::spawn_blocking(move || {
+                    (&host[..], port).to_socket_addrs().map(Iterator::collect)
+                }))
+            }
+        };
+        GaiResolve { lookup }
+    }
+}
+
+/// A future which resolves a host name with [`GaiResolver`]
+///
+/// Dropping the future does not stop the lookup, whose result is discarded when it completes.
+pub struct GaiResolve {
+    lookup: Lookup,
+}
+
+enum Lookup {
+    Done(Option<Vec<SocketAddr>>),
+    Pending(Blocking<io::Result<Vec<SocketAddr>>>),
+}
+
+impl Future for GaiResolve {
+    type Output = io::Result<Vec<SocketAddr>>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.lookup {
+            Lookup::Done(addrs)     => {
+                Poll::Ready(Ok(addrs.take().expect("polled GaiResolve future after completion")))
+            }
+            Lookup::Pending(lookup) => Pin::new(lookup).poll(ctx),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::future::Future;
+use std::io;
+use std::net::{Shutdown, SocketAddr, ToSocketAddrs};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
+
+use crate::drive::{Drive, DefaultDriver};
+
+use super::{Connect, Protocol, TcpListener, TcpListenerBuilder, TcpShutdown, TcpStream};
+use super::sockopt::{SetSocketOpt, SocketOpt};
+
+/// A one-to-one style SCTP socket listening for associations
+///
+/// Each accepted association is an [`SctpStream`]. The socket is created with the
+/// `IPPROTO_SCTP` protocol, which requires SCTP support in the kernel; where it is missing,
+/// binding fails with `EPROTONOSUPPORT`.
+pub struct SctpListener<D: Drive = DefaultDriver> {
+    inner: TcpListener<D>,
+}
+
+impl SctpListener {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<SctpListener> {
+        SctpListener::bind_on_driver(addr, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive> SctpListener<D> {
+    pub fn bind_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> io::Result<SctpListener<D>> {
+        let inner = TcpListenerBuilder::new()
+            .bind_protocol_on_driver(addr, Protocol::SCTP, driver)?;
+        Ok(SctpListener { inner })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Set an option on the socket, like the options at the `IPPROTO_SCTP` level.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        self.inner.set_opt(opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        self.inner.opt()
+    }
+
+    #[inline(always)]
+    fn inner(self: Pin<&mut Self>) -> Pin<&mut TcpListener<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.inner) }
+    }
+}
+
+impl<D: Drive + Clone> SctpListener<D> {
+    pub fn accept(&mut self) -> SctpAccept<'_, D> where D: Unpin {
+        Pin::new(self).accept_pinned()
+    }
+
+    pub fn accept_pinned(self: Pin<&mut Self>) -> SctpAccept<'_, D> {
+        SctpAccept { socket: self }
+    }
+
+    pub fn poll_accept(self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<(SctpStream<D>, SocketAddr)>>
+    {
+        let (inner, addr) = ready!(self.inner().poll_accept(ctx))?;
+        Poll::Ready(Ok((SctpStream { inner }, addr)))
+    }
+}
+
+impl<D: Drive> AsRawFd for SctpListener<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// An association of a one-to-one style SCTP socket
+///
+/// The stream is read and written like a [`TcpStream`]: SCTP's message boundaries are not
+/// preserved by reads, and every message is sent on the association's first stream.
+pub struct SctpStream<D: Drive = DefaultDriver> {
+    inner: TcpStream<D>,
+}
+
+impl SctpStream {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> SctpConnect {
+        SctpStream::connect_on_driver(addr, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive + Clone> SctpStream<D> {
+    /// Connect to the addresses `addr` resolves to, trying them as [`TcpStream::connect`]
+    /// does.
+    pub fn connect_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> SctpConnect<D> {
+        SctpConnect { inner: Connect::new(addr, None, driver).with_protocol(Protocol::SCTP) }
+    }
+}
+
+impl<D: Drive> SctpStream<D> {
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Set an option on the socket, like the options at the `IPPROTO_SCTP` level.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        self.inner.set_opt(opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        self.inner.opt()
+    }
+
+    pub fn shutdown(&mut self, how: Shutdown) -> TcpShutdown<'_, D> where D: Unpin {
+        self.inner.shutdown(how)
+    }
+
+    pub fn shutdown_pinned(self: Pin<&mut Self>, how: Shutdown) -> TcpShutdown<'_, D> {
+        self.inner().shutdown_pinned(how)
+    }
+
+    #[inline(always)]
+    fn inner(self: Pin<&mut Self>) -> Pin<&mut TcpStream<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.inner) }
+    }
+}
+
+impl<D: Drive> AsyncRead for SctpStream<D> {
+    fn poll_read(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.inner().poll_read(ctx, buf)
+    }
+}
+
+impl<D: Drive> AsyncBufRead for SctpStream<D> {
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.inner().poll_fill_buf(ctx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.inner().consume(amt)
+    }
+}
+
+impl<D: Drive> AsyncWrite for SctpStream<D> {
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8]) -> Poll<io::Result<usize>> {
+        self.inner().poll_write(ctx, slice)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.inner().poll_flush(ctx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.inner().poll_close(ctx)
+    }
+}
+
+impl<D: Drive> AsRawFd for SctpStream<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+pub struct SctpAccept<'a, D: Drive> {
+    socket: Pin<&'a mut SctpListener<D>>,
+}
+
+impl<'a, D: Drive + Clone> Future for SctpAccept<'a, D> {
+    type Output = io::Result<(SctpStream<D>, SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket.as_mut().poll_accept(ctx)
+    }
+}
+
+/// A future which connects an [`SctpStream`]
+pub struct SctpConnect<D: Drive = DefaultDriver> {
+    inner: Connect<D>,
+}
+
+impl<D: Drive + Clone> Future for SctpConnect<D> {
+    type Output = io::Result<SctpStream<D>>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = unsafe { Pin::map_unchecked_mut(self, |this| &mut this.inner) };
+        let inner = ready!(inner.poll(ctx))?;
+        Poll::Ready(Ok(SctpStream { inner }))
+    }
+}
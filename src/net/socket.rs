@@ -0,0 +1,626 @@
+use std::cmp;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use iou::sqe::{MsgFlags, SQE};
+use nix::sys::socket as nix_socket;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::ring::{Cancellation, Ring};
+use crate::sys;
+
+use super::SockAddr;
+use super::sockopt::{self, SetSocketOpt, SocketOpt};
+
+/// A socket of any domain, type and protocol, like a raw socket or an ICMP socket
+///
+/// Data is sent with `IORING_OP_SEND` and received with `IORING_OP_RECV` once the socket is
+/// connected, and with `IORING_OP_SENDMSG` and `IORING_OP_RECVMSG` to and from explicit peers.
+/// The data of each operation is copied through a buffer owned by the socket, so that a cancelled
+/// operation never refers to memory the caller has reused.
+///
+/// ```no_run
+/// use ringbahn::net::Socket;
+///
+/// # fn main() -> std::io::Result<()> { futures::executor::block_on(async {
+/// // An unprivileged ICMP socket, as used by ping
+/// let mut socket = Socket::new(libc::AF_INET, libc::SOCK_DGRAM, libc::IPPROTO_ICMP)?;
+/// let echo_request = [8, 0, 0, 0, 0, 0, 0, 1];
+/// socket.send_to(&echo_request, "127.0.0.1:0".parse().unwrap()).await?;
+/// let mut reply = [0; 64];
+/// let (n, from) = socket.recv_from(&mut reply).await?;
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub struct Socket<D: Drive = DefaultDriver> {
+    ring: Ring<D>,
+    msg: Option<Box<Message>>,
+    active: Op,
+    fd: RawFd,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Op {
+    Connect,
+    Send,
+    Recv,
+    SendTo,
+    RecvFrom,
+    SendToFrom,
+    RecvFromPktInfo,
+    Nothing,
+}
+
+/// The destination address and receiving interface of a datagram, as reported in an
+/// `IP_PKTINFO` or `IPV6_PKTINFO` control message.
+///
+/// A socket bound to the unspecified address receives datagrams sent to any of the host's
+/// addresses. Passing the info of a datagram to [`Socket::send_to_from`] sends the reply from the
+/// address the datagram was sent to, which is the address its peer expects to hear from.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PacketInfo {
+    /// The address the datagram was sent to.
+    pub addr: IpAddr,
+    /// The index of the interface the datagram was received on.
+    pub interface: u32,
+}
+
+/// The buffers of an operation on the socket, which the kernel may read or write until it
+/// completes.
+struct Message {
+    hdr: libc::msghdr,
+    iov: libc::iovec,
+    addr: libc::sockaddr_storage,
+    data: Vec<u8>,
+    // u64s, so that the control messages in it are aligned
+    control: Vec<u64>,
+}
+
+unsafe impl Send for Message { }
+unsafe impl Sync for Message { }
+
+impl Message {
+    fn new() -> Box<Message> {
+        Box::new(Message {
+            hdr: unsafe { mem::zeroed() },
+            iov: unsafe { mem::zeroed() },
+            addr: unsafe { mem::zeroed() },
+            data: Vec::new(),
+            control: Vec::new(),
+        })
+    }
+
+    fn header(&mut self, namelen: libc::socklen_t) -> *mut libc::msghdr {
+        self.iov = libc::iovec {
+            iov_base: self.data.as_mut_ptr() as *mut libc::c_void,
+            iov_len: self.data.len(),
+        };
+        self.hdr.msg_name = &mut self.addr as *mut libc::sockaddr_storage as *mut libc::c_void;
+        self.hdr.msg_namelen = namelen;
+        self.hdr.msg_iov = &mut self.iov;
+        self.hdr.msg_iovlen = 1;
+        self.hdr.msg_control = ptr::null_mut();
+        self.hdr.msg_controllen = 0;
+        &mut self.hdr
+    }
+
+    /// Give the header room for a control message of `len` bytes, returning it.
+    unsafe fn control(&mut self, len: usize) -> *mut libc::cmsghdr {
+        let space = libc::CMSG_SPACE(len as u32) as usize;
+        self.control.clear();
+        self.control.resize(space.div_ceil(8), 0);
+        self.hdr.msg_control = self.control.as_mut_ptr() as *mut libc::c_void;
+        self.hdr.msg_controllen = space as _;
+        libc::CMSG_FIRSTHDR(&self.hdr)
+    }
+
+    /// Write a control message carrying `info`, so that a datagram is sent from its address.
+    unsafe fn write_pktinfo(&mut self, info: &PacketInfo) {
+        match info.addr {
+            IpAddr::V4(addr)    => {
+                let cmsg = self.control(mem::size_of::<libc::in_pktinfo>());
+                (*cmsg).cmsg_level = libc::IPPROTO_IP;
+                (*cmsg).cmsg_type = libc::IP_PKTINFO;
+                (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<libc::in_pktinfo>() as u32) as _;
+                let pktinfo = libc::in_pktinfo {
+                    ipi_ifindex: info.interface as libc::c_int,
+                    ipi_spec_dst: libc::in_addr { s_addr: u32::from_ne_bytes(addr.octets()) },
+                    ipi_addr: libc::in_addr { s_addr: 0 },
+                };
+                (libc::CMSG_DATA(cmsg) as *mut libc::in_pktinfo).write_unaligned(pktinfo);
+            }
+            IpAddr::V6(addr)    => {
+                let cmsg = self.control(mem::size_of::<libc::in6_pktinfo>());
+                (*cmsg).cmsg_level = libc::IPPROTO_IPV6;
+                (*cmsg).cmsg_type = libc::IPV6_PKTINFO;
+                (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<libc::in6_pktinfo>() as u32) as _;
+                let pktinfo = libc::in6_pktinfo {
+                    ipi6_addr: libc::in6_addr { s6_addr: addr.octets() },
+                    ipi6_ifindex: info.interface,
+                };
+                (libc::CMSG_DATA(cmsg) as *mut libc::in6_pktinfo).write_unaligned(pktinfo);
+            }
+        }
+    }
+
+    /// Read the info of a received datagram from its control messages, if there is any.
+    unsafe fn read_pktinfo(&self) -> Option<PacketInfo> {
+        let mut cmsg = libc::CMSG_FIRSTHDR(&self.hdr);
+        while !cmsg.is_null() {
+            match ((*cmsg).cmsg_level, (*cmsg).cmsg_type) {
+                (libc::IPPROTO_IP, libc::IP_PKTINFO)        => {
+                    let data = libc::CMSG_DATA(cmsg) as *const libc::in_pktinfo;
+                    let pktinfo = data.read_unaligned();
+                    return Some(PacketInfo {
+                        addr: Ipv4Addr::from(pktinfo.ipi_addr.s_addr.to_ne_bytes()).into(),
+                        interface: pktinfo.ipi_ifindex as u32,
+                    });
+                }
+                (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO)    => {
+                    let data = libc::CMSG_DATA(cmsg) as *const libc::in6_pktinfo;
+                    let pktinfo = data.read_unaligned();
+                    return Some(PacketInfo {
+                        addr: Ipv6Addr::from(pktinfo.ipi6_addr.s6_addr).into(),
+                        interface: pktinfo.ipi6_ifindex,
+                    });
+                }
+                _                                           => { }
+            }
+            cmsg = libc::CMSG_NXTHDR(&self.hdr, cmsg);
+        }
+        None
+    }
+}
+
+impl Socket {
+    /// Create a socket with `socket(2)`, using the default driver.
+    pub fn new(domain: libc::c_int, ty: libc::c_int, protocol: libc::c_int) -> io::Result<Socket> {
+        Socket::new_on_driver(domain, ty, protocol, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive> Socket<D> {
+    /// Create a socket with `socket(2)`, like `Socket::new(libc::AF_INET, libc::SOCK_RAW,
+    /// libc::IPPROTO_ICMP)`.
+    ///
+    /// The socket is created with `SOCK_CLOEXEC`.
+    pub fn new_on_driver(domain: libc::c_int, ty: libc::c_int, protocol: libc::c_int, driver: D)
+        -> io::Result<Socket<D>>
+    {
+        match unsafe { libc::socket(domain, ty | libc::SOCK_CLOEXEC, protocol) } {
+            -1  => Err(io::Error::last_os_error()),
+            fd  => Ok(unsafe { Socket::from_raw_fd_on_driver(fd, driver) }),
+        }
+    }
+
+    /// Take ownership of the file descriptor of a socket and run its IO on an io-uring driver
+    ///
+    /// # Safety
+    ///
+    /// The fd must be an open socket, and must not be owned by anything else; it will be closed
+    /// when the socket is dropped.
+    pub unsafe fn from_raw_fd_on_driver(fd: RawFd, driver: D) -> Socket<D> {
+        Socket {
+            ring: Ring::new(driver),
+            msg: None,
+            active: Op::Nothing,
+            fd,
+        }
+    }
+
+    /// Bind the socket to `addr` with `bind(2)`.
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<()> {
+        nix_socket::bind(self.fd, SockAddr::from(addr).as_iou())
+            .map_err(|err| err.as_errno().unwrap_or(nix::errno::Errno::EIO).into())
+    }
+
+    /// The address this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        super::local_addr(self.fd)
+    }
+
+    /// The address of the peer this socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        super::peer_addr(self.fd)
+    }
+
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        sockopt::set(self.fd, opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        sockopt::get(self.fd)
+    }
+
+    /// Connect the socket to a peer, so that it can `send` and `recv`.
+    pub fn connect<A: ToSocketAddrs>(&mut self, addr: A) -> SocketConnect<'_, D> where D: Unpin {
+        Pin::new(self).connect_pinned(addr)
+    }
+
+    pub fn connect_pinned<A: ToSocketAddrs>(self: Pin<&mut Self>, addr: A) -> SocketConnect<'_, D> {
+        let addr = addr.to_socket_addrs().and_then(|mut addrs| addrs.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "could not resolve to any addresses")
+        }));
+        SocketConnect { socket: self, addr: addr.map_err(Some) }
+    }
+
+    /// Send data to the connected peer.
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> SocketSend<'a, D> where D: Unpin {
+        Pin::new(self).send_pinned(buf)
+    }
+
+    pub fn send_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a [u8]) -> SocketSend<'a, D> {
+        SocketSend { socket: self, buf, flags: MsgFlags::empty() }
+    }
+
+    /// Send data to the connected peer, passing `flags` to the kernel.
+    pub fn send_with_flags<'a>(&'a mut self, buf: &'a [u8], flags: MsgFlags) -> SocketSend<'a, D>
+        where D: Unpin
+    {
+        SocketSend { socket: Pin::new(self), buf, flags }
+    }
+
+    /// Receive data from the connected peer.
+    ///
+    /// If a datagram is larger than `buf`, the rest of it is discarded.
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> SocketRecv<'a, D> where D: Unpin {
+        Pin::new(self).recv_pinned(buf)
+    }
+
+    pub fn recv_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> SocketRecv<'a, D> {
+        SocketRecv { socket: self, buf, flags: MsgFlags::empty() }
+    }
+
+    /// Receive data from the connected peer, passing `flags` to the kernel. With `MSG_PEEK`, the
+    /// data is left to be received again.
+    pub fn recv_with_flags<'a>(&'a mut self, buf: &'a mut [u8], flags: MsgFlags)
+        -> SocketRecv<'a, D> where D: Unpin
+    {
+        SocketRecv { socket: Pin::new(self), buf, flags }
+    }
+
+    /// Send data to `addr`.
+    pub fn send_to<'a>(&'a mut self, buf: &'a [u8], addr: SocketAddr) -> SendTo<'a, D> where
+        D: Unpin
+    {
+        Pin::new(self).send_to_pinned(buf, addr)
+    }
+
+    pub fn send_to_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a [u8], addr: SocketAddr)
+        -> SendTo<'a, D>
+    {
+        SendTo { socket: self, buf, addr }
+    }
+
+    /// Receive data, along with the address of the peer which sent it.
+    ///
+    /// If a datagram is larger than `buf`, the rest of it is discarded. This fails with
+    /// `InvalidData` if the peer's address is not an IPv4 or IPv6 address.
+    pub fn recv_from<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvFrom<'a, D> where D: Unpin {
+        Pin::new(self).recv_from_pinned(buf)
+    }
+
+    pub fn recv_from_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> RecvFrom<'a, D> {
+        RecvFrom { socket: self, buf }
+    }
+
+    /// Send data to `addr`, from the address and through the interface in `from`.
+    ///
+    /// This is how a socket bound to the unspecified address replies to a datagram from the
+    /// address it was sent to; see [`PacketInfo`]. An interface of 0 leaves the kernel to route
+    /// the data as usual.
+    pub fn send_to_from<'a>(&'a mut self, buf: &'a [u8], addr: SocketAddr, from: PacketInfo)
+        -> SendToFrom<'a, D> where D: Unpin
+    {
+        Pin::new(self).send_to_from_pinned(buf, addr, from)
+    }
+
+    pub fn send_to_from_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        buf: &'a [u8],
+        addr: SocketAddr,
+        from: PacketInfo,
+    ) -> SendToFrom<'a, D> {
+        SendToFrom { socket: self, buf, addr, from }
+    }
+
+    /// Receive data, along with the address of the peer which sent it and the info of the
+    /// datagram which carried it.
+    ///
+    /// The info is only reported once it has been enabled with the
+    /// [`PktInfo`](super::sockopt::PktInfo) option for IPv4, or the
+    /// [`RecvPktInfoV6`](super::sockopt::RecvPktInfoV6) option for IPv6.
+    pub fn recv_from_pktinfo<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvFromPktInfo<'a, D>
+        where D: Unpin
+    {
+        Pin::new(self).recv_from_pktinfo_pinned(buf)
+    }
+
+    pub fn recv_from_pktinfo_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8])
+        -> RecvFromPktInfo<'a, D>
+    {
+        RecvFromPktInfo { socket: self, buf }
+    }
+
+    pub fn poll_connect(self: Pin<&mut Self>, ctx: &mut Context<'_>, addr: SocketAddr)
+        -> Poll<io::Result<()>>
+    {
+        ready!(self.poll_op(ctx, Op::Connect, |sqe, fd, msg| unsafe {
+            let len = SockAddr::from(addr).write_raw(&mut msg.addr);
+            let ptr = &msg.addr as *const libc::sockaddr_storage;
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_CONNECT as u8, fd, ptr as u64, 0, len as u64);
+        }))?;
+        Poll::Ready(Ok(()))
+    }
+
+    pub fn poll_send(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(self.poll_op(ctx, Op::Send, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.extend_from_slice(buf);
+            sqe.prep_send(fd, &msg.data[..], flags);
+        }))?;
+        Poll::Ready(Ok(n as usize))
+    }
+
+    pub fn poll_recv(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        let len = buf.len();
+        let n = ready!(self.as_mut().poll_op(ctx, Op::Recv, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.resize(len, 0);
+            sys::prep_recv(sqe, fd, &mut msg.data[..], flags);
+        }))?;
+        Poll::Ready(Ok(self.copy_received(n, buf)))
+    }
+
+    pub fn poll_send_to(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &[u8], addr: SocketAddr)
+        -> Poll<io::Result<usize>>
+    {
+        let n = ready!(self.poll_op(ctx, Op::SendTo, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.extend_from_slice(buf);
+            let len = SockAddr::from(addr).write_raw(&mut msg.addr);
+            let hdr = msg.header(len);
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_SENDMSG as u8, fd, hdr as u64, 1, 0);
+        }))?;
+        Poll::Ready(Ok(n as usize))
+    }
+
+    pub fn poll_recv_from(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<(usize, SocketAddr)>>
+    {
+        let len = buf.len();
+        let n = ready!(self.as_mut().poll_op(ctx, Op::RecvFrom, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.resize(len, 0);
+            let hdr = msg.header(mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t);
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_RECVMSG as u8, fd, hdr as u64, 1, 0);
+        }))?;
+        let addr = {
+            let msg = self.msg.as_ref().unwrap();
+            SockAddr::read_raw(&msg.addr, msg.hdr.msg_namelen)?
+        };
+        let addr = addr.as_inet().ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+        Poll::Ready(Ok((self.copy_received(n, buf), addr)))
+    }
+
+    pub fn poll_send_to_from(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+        addr: SocketAddr,
+        from: &PacketInfo,
+    ) -> Poll<io::Result<usize>> {
+        let n = ready!(self.poll_op(ctx, Op::SendToFrom, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.extend_from_slice(buf);
+            let len = SockAddr::from(addr).write_raw(&mut msg.addr);
+            let hdr = msg.header(len);
+            msg.write_pktinfo(from);
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_SENDMSG as u8, fd, hdr as u64, 1, 0);
+        }))?;
+        Poll::Ready(Ok(n as usize))
+    }
+
+    pub fn poll_recv_from_pktinfo(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<(usize, SocketAddr, Option<PacketInfo>)>>
+    {
+        let len = buf.len();
+        let n = ready!(self.as_mut().poll_op(ctx, Op::RecvFromPktInfo, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.resize(len, 0);
+            let hdr = msg.header(mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t);
+            // Room for either kind of pktinfo, whichever the socket's family reports
+            let pktinfo = cmp::max(
+                mem::size_of::<libc::in_pktinfo>(),
+                mem::size_of::<libc::in6_pktinfo>(),
+            );
+            msg.control(pktinfo);
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_RECVMSG as u8, fd, hdr as u64, 1, 0);
+        }))?;
+        let (addr, info) = {
+            let msg = self.msg.as_ref().unwrap();
+            (SockAddr::read_raw(&msg.addr, msg.hdr.msg_namelen)?, unsafe { msg.read_pktinfo() })
+        };
+        let addr = addr.as_inet().ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+        Poll::Ready(Ok((self.copy_received(n, buf), addr, info)))
+    }
+
+    fn copy_received(&self, n: u32, buf: &mut [u8]) -> usize {
+        let data = &self.msg.as_ref().unwrap().data;
+        let n = cmp::min(cmp::min(n as usize, data.len()), buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        n
+    }
+
+    fn poll_op(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        op: Op,
+        prepare: impl FnOnce(&mut SQE<'_>, RawFd, &mut Message),
+    ) -> Poll<io::Result<u32>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        if this.active != Op::Nothing && this.active != op {
+            this.cancel();
+        }
+        this.active = op;
+        let fd = this.fd;
+        let msg = this.msg.get_or_insert_with(Message::new);
+        let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+        let result = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            prepare(&mut sqe, fd, msg);
+            sqe
+        }));
+        this.active = Op::Nothing;
+        Poll::Ready(result)
+    }
+
+    fn cancel(&mut self) {
+        self.active = Op::Nothing;
+        self.ring.cancel(Cancellation::from(self.msg.take()));
+    }
+}
+
+impl<D: Drive> AsRawFd for Socket<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl<D: Drive> Drop for Socket<D> {
+    fn drop(&mut self) {
+        match self.active {
+            Op::Nothing => unsafe { libc::close(self.fd); },
+            _           => self.cancel(),
+        }
+    }
+}
+
+pub struct SocketConnect<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    addr: Result<SocketAddr, Option<io::Error>>,
+}
+
+impl<'a, D: Drive> Future for SocketConnect<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        match &mut this.addr {
+            Ok(addr)    => this.socket.as_mut().poll_connect(ctx, *addr),
+            Err(err)    => {
+                let err = err.take().expect("polled SocketConnect future after completion");
+                Poll::Ready(Err(err))
+            }
+        }
+    }
+}
+
+pub struct SocketSend<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    buf: &'a [u8],
+    flags: MsgFlags,
+}
+
+impl<'a, D: Drive> Future for SocketSend<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (buf, flags) = (self.buf, self.flags);
+        self.socket.as_mut().poll_send(ctx, buf, flags)
+    }
+}
+
+pub struct SocketRecv<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    buf: &'a mut [u8],
+    flags: MsgFlags,
+}
+
+impl<'a, D: Drive> Future for SocketRecv<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.socket.as_mut().poll_recv(ctx, this.buf, this.flags)
+    }
+}
+
+pub struct SendTo<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    buf: &'a [u8],
+    addr: SocketAddr,
+}
+
+impl<'a, D: Drive> Future for SendTo<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (buf, addr) = (self.buf, self.addr);
+        self.socket.as_mut().poll_send_to(ctx, buf, addr)
+    }
+}
+
+pub struct RecvFrom<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    buf: &'a mut [u8],
+}
+
+impl<'a, D: Drive> Future for RecvFrom<'a, D> {
+    type Output = io::Result<(usize, SocketAddr)>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.socket.as_mut().poll_recv_from(ctx, this.buf)
+    }
+}
+
+pub struct SendToFrom<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    buf: &'a [u8],
+    addr: SocketAddr,
+    from: PacketInfo,
+}
+
+impl<'a, D: Drive> Future for SendToFrom<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (buf, addr, from) = (self.buf, self.addr, self.from);
+        self.socket.as_mut().poll_send_to_from(ctx, buf, addr, &from)
+    }
+}
+
+pub struct RecvFromPktInfo<'a, D: Drive> {
+    socket: Pin<&'a mut Socket<D>>,
+    buf: &'a mut [u8],
+}
+
+impl<'a, D: Drive> Future for RecvFromPktInfo<'a, D> {
+    type Output = io::Result<(usize, SocketAddr, Option<PacketInfo>)>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.socket.as_mut().poll_recv_from_pktinfo(ctx, this.buf)
+    }
+}
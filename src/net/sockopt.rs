@@ -0,0 +1,279 @@
+//! Typed socket options
+//!
+//! Each option is a type implementing [`SocketOpt`], which can be read from a socket with `opt`.
+//! Options which can also be set implement [`SetSocketOpt`], and are set with `set_opt`:
+//!
+//! ```no_run
+//! use ringbahn::net::TcpStream;
+//! use ringbahn::net::sockopt::{TcpNoDelay, SoError};
+//!
+//! # fn main() -> std::io::Result<()> { futures::executor::block_on(async {
+//! let stream = TcpStream::connect(("127.0.0.1", 7878)).await?;
+//! stream.set_opt(TcpNoDelay(true))?;
+//! let SoError(error) = stream.opt()?;
+//! # Ok(())
+//! # })
+//! # }
+//! ```
+
+use std::convert::TryFrom;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::slice;
+use std::time::Duration;
+
+/// A socket option, as read with `getsockopt(2)`.
+pub trait SocketOpt: Sized {
+    /// The representation of the option's value passed to the kernel.
+    type Raw: RawOpt;
+
+    /// The protocol level of the option, like `SOL_SOCKET` or `IPPROTO_TCP`.
+    const LEVEL: libc::c_int;
+    /// The name of the option, like `SO_REUSEADDR`.
+    const NAME: libc::c_int;
+
+    fn from_raw(raw: Self::Raw) -> Self;
+}
+
+/// A socket option which can also be set, with `setsockopt(2)`.
+pub trait SetSocketOpt: SocketOpt {
+    /// The raw value of the option, or an `EINVAL` error if the value cannot be represented.
+    fn to_raw(&self) -> io::Result<Self::Raw>;
+}
+
+/// A plain C type which can be the value of a socket option.
+///
+/// This trait is sealed: it is implemented for `c_int` and `linger`.
+pub trait RawOpt: Copy + private::Sealed {
+    #[doc(hidden)]
+    fn zeroed() -> Self;
+}
+
+mod private {
+    pub trait Sealed { }
+    impl Sealed for libc::c_int { }
+    impl Sealed for libc::linger { }
+}
+
+impl RawOpt for libc::c_int {
+    fn zeroed() -> Self { 0 }
+}
+
+impl RawOpt for libc::linger {
+    fn zeroed() -> Self { libc::linger { l_onoff: 0, l_linger: 0 } }
+}
+
+pub(crate) fn set<O: SetSocketOpt>(fd: RawFd, opt: O) -> io::Result<()> {
+    let raw = opt.to_raw()?;
+    let len = mem::size_of::<O::Raw>() as libc::socklen_t;
+    let ptr = &raw as *const O::Raw as *const libc::c_void;
+    match unsafe { libc::setsockopt(fd, O::LEVEL, O::NAME, ptr, len) } {
+        0   => Ok(()),
+        _   => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Set an option from the bytes of its raw value.
+pub(crate) fn set_bytes(fd: RawFd, level: libc::c_int, name: libc::c_int, value: &[u8])
+    -> io::Result<()>
+{
+    let ptr = value.as_ptr() as *const libc::c_void;
+    let len = value.len() as libc::socklen_t;
+    match unsafe { libc::setsockopt(fd, level, name, ptr, len) } {
+        0   => Ok(()),
+        _   => Err(io::Error::last_os_error()),
+    }
+}
+
+/// The bytes of the raw value of an option, so that it can be set later with `set_bytes`.
+pub(crate) fn to_bytes<O: SetSocketOpt>(opt: &O) -> io::Result<Vec<u8>> {
+    let raw = opt.to_raw()?;
+    let ptr = &raw as *const O::Raw as *const u8;
+    Ok(unsafe { slice::from_raw_parts(ptr, mem::size_of::<O::Raw>()).to_vec() })
+}
+
+pub(crate) fn get<O: SocketOpt>(fd: RawFd) -> io::Result<O> {
+    let mut raw = O::Raw::zeroed();
+    let mut len = mem::size_of::<O::Raw>() as libc::socklen_t;
+    let ptr = &mut raw as *mut O::Raw as *mut libc::c_void;
+    match unsafe { libc::getsockopt(fd, O::LEVEL, O::NAME, ptr, &mut len) } {
+        0   => Ok(O::from_raw(raw)),
+        _   => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Set the DSCP (differentiated services codepoint) of outgoing packets, in the type-of-service
+/// field for an IPv4 socket or the traffic class for an IPv6 one, keeping the ECN bits.
+pub(crate) fn set_dscp(fd: RawFd, dscp: u8) -> io::Result<()> {
+    if dscp >= 64 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "DSCP must be less than 64"));
+    }
+    let dscp = (dscp as u32) << 2;
+    match super::local_addr(fd)?.is_ipv6() {
+        true    => {
+            let TrafficClassV6(class) = get(fd)?;
+            set(fd, TrafficClassV6(dscp | class & 0b11))
+        }
+        false   => {
+            let Tos(tos) = get(fd)?;
+            set(fd, Tos(dscp | tos & 0b11))
+        }
+    }
+}
+
+/// The DSCP of outgoing packets.
+pub(crate) fn dscp(fd: RawFd) -> io::Result<u8> {
+    let field = match super::local_addr(fd)?.is_ipv6() {
+        true    => get(fd).map(|TrafficClassV6(class)| class)?,
+        false   => get(fd).map(|Tos(tos)| tos)?,
+    };
+    Ok((field >> 2) as u8 & 0b11_1111)
+}
+
+macro_rules! bool_opts {
+    ($($(#[$attr:meta])* $name:ident = ($level:expr, $opt:expr);)*) => {$(
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+        pub struct $name(pub bool);
+
+        impl SocketOpt for $name {
+            type Raw = libc::c_int;
+            const LEVEL: libc::c_int = $level;
+            const NAME: libc::c_int = $opt;
+
+            fn from_raw(raw: libc::c_int) -> Self { $name(raw != 0) }
+        }
+
+        impl SetSocketOpt for $name {
+            fn to_raw(&self) -> io::Result<libc::c_int> { Ok(self.0 as libc::c_int) }
+        }
+    )*}
+}
+
+macro_rules! int_opts {
+    ($($(#[$attr:meta])* $name:ident($ty:ty) = ($level:expr, $opt:expr);)*) => {$(
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+        pub struct $name(pub $ty);
+
+        impl SocketOpt for $name {
+            type Raw = libc::c_int;
+            const LEVEL: libc::c_int = $level;
+            const NAME: libc::c_int = $opt;
+
+            fn from_raw(raw: libc::c_int) -> Self { $name(raw as $ty) }
+        }
+
+        impl SetSocketOpt for $name {
+            fn to_raw(&self) -> io::Result<libc::c_int> {
+                libc::c_int::try_from(self.0)
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))
+            }
+        }
+    )*}
+}
+
+bool_opts! {
+    /// `TCP_NODELAY`: disable Nagle's algorithm.
+    TcpNoDelay = (libc::IPPROTO_TCP, libc::TCP_NODELAY);
+    /// `SO_REUSEADDR`: allow binding to an address which is in `TIME_WAIT`.
+    ReuseAddr = (libc::SOL_SOCKET, libc::SO_REUSEADDR);
+    /// `SO_REUSEPORT`: allow several sockets to bind to the same address.
+    ReusePort = (libc::SOL_SOCKET, libc::SO_REUSEPORT);
+    /// `SO_KEEPALIVE`: send keepalive probes on an idle connection.
+    KeepAlive = (libc::SOL_SOCKET, libc::SO_KEEPALIVE);
+    /// `SO_BROADCAST`: allow sending datagrams to a broadcast address.
+    Broadcast = (libc::SOL_SOCKET, libc::SO_BROADCAST);
+    /// `IP_PKTINFO`: report the destination address and receiving interface of each IPv4 datagram
+    /// in the ancillary data of `recvmsg(2)`.
+    PktInfo = (libc::IPPROTO_IP, libc::IP_PKTINFO);
+    /// `IPV6_RECVPKTINFO`: report the destination address and receiving interface of each IPv6
+    /// datagram in the ancillary data of `recvmsg(2)`.
+    RecvPktInfoV6 = (libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO);
+    /// `IPV6_V6ONLY`: restrict an IPv6 socket to IPv6, rather than also accepting IPv4 traffic
+    /// through IPv4-mapped addresses.
+    Ipv6Only = (libc::IPPROTO_IPV6, libc::IPV6_V6ONLY);
+    /// `IP_MULTICAST_LOOP`: deliver IPv4 multicast datagrams sent by the socket back to the host.
+    MulticastLoopV4 = (libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP);
+    /// `IPV6_MULTICAST_LOOP`: deliver IPv6 multicast datagrams sent by the socket back to the
+    /// host.
+    MulticastLoopV6 = (libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_LOOP);
+}
+
+int_opts! {
+    /// `IP_TTL`: the time-to-live of outgoing IPv4 packets.
+    Ttl(u32) = (libc::IPPROTO_IP, libc::IP_TTL);
+    /// `SO_RCVBUF`: the size of the receive buffer.
+    RecvBufferSize(usize) = (libc::SOL_SOCKET, libc::SO_RCVBUF);
+    /// `SO_SNDBUF`: the size of the send buffer.
+    SendBufferSize(usize) = (libc::SOL_SOCKET, libc::SO_SNDBUF);
+    /// `TCP_FASTOPEN`: the length of the queue of TCP Fast Open connections a listener accepts
+    /// data from before the handshake completes, or 0 to disable it.
+    TcpFastOpen(u32) = (libc::IPPROTO_TCP, libc::TCP_FASTOPEN);
+    /// `TCP_KEEPIDLE`: the seconds a connection is idle before keepalive probes are sent.
+    TcpKeepIdle(u32) = (libc::IPPROTO_TCP, libc::TCP_KEEPIDLE);
+    /// `TCP_KEEPINTVL`: the seconds between unanswered keepalive probes.
+    TcpKeepInterval(u32) = (libc::IPPROTO_TCP, libc::TCP_KEEPINTVL);
+    /// `TCP_KEEPCNT`: the number of unanswered keepalive probes before the connection is dropped.
+    TcpKeepCount(u32) = (libc::IPPROTO_TCP, libc::TCP_KEEPCNT);
+    /// `IP_TOS`: the type-of-service field of outgoing IPv4 packets, which holds their DSCP
+    /// and ECN bits.
+    Tos(u32) = (libc::IPPROTO_IP, libc::IP_TOS);
+    /// `IPV6_TCLASS`: the traffic class of outgoing IPv6 packets, which holds their DSCP and ECN
+    /// bits.
+    TrafficClassV6(u32) = (libc::IPPROTO_IPV6, libc::IPV6_TCLASS);
+    /// `IPV6_UNICAST_HOPS`: the hop limit of outgoing IPv6 packets.
+    UnicastHopsV6(u32) = (libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS);
+    /// `IP_MULTICAST_TTL`: the time-to-live of outgoing IPv4 multicast datagrams.
+    MulticastTtlV4(u32) = (libc::IPPROTO_IP, libc::IP_MULTICAST_TTL);
+}
+
+/// `SO_LINGER`: how long closing the socket waits for unsent data to be sent.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Linger(pub Option<Duration>);
+
+impl SocketOpt for Linger {
+    type Raw = libc::linger;
+    const LEVEL: libc::c_int = libc::SOL_SOCKET;
+    const NAME: libc::c_int = libc::SO_LINGER;
+
+    fn from_raw(raw: libc::linger) -> Self {
+        match raw.l_onoff {
+            0   => Linger(None),
+            _   => Linger(Some(Duration::from_secs(raw.l_linger as u64))),
+        }
+    }
+}
+
+impl SetSocketOpt for Linger {
+    fn to_raw(&self) -> io::Result<libc::linger> {
+        match self.0 {
+            Some(duration)  => {
+                let secs = libc::c_int::try_from(duration.as_secs())
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+                Ok(libc::linger { l_onoff: 1, l_linger: secs })
+            }
+            None            => Ok(libc::linger { l_onoff: 0, l_linger: 0 }),
+        }
+    }
+}
+
+/// `SO_ERROR`: the pending error on the socket, which is cleared by reading it.
+///
+/// This option can only be read, so it does not implement [`SetSocketOpt`].
+#[derive(Debug)]
+pub struct SoError(pub Option<io::Error>);
+
+impl SocketOpt for SoError {
+    type Raw = libc::c_int;
+    const LEVEL: libc::c_int = libc::SOL_SOCKET;
+    const NAME: libc::c_int = libc::SO_ERROR;
+
+    fn from_raw(raw: libc::c_int) -> Self {
+        match raw {
+            0       => SoError(None),
+            errno   => SoError(Some(io::Error::from_raw_os_error(errno))),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
+
+use crate::drive::{Drive, DefaultDriver};
+
+use super::TcpStream;
+
+/// The read half of a [`TcpStream`], created by [`TcpStream::into_split`].
+pub struct OwnedReadHalf<D: Drive = DefaultDriver> {
+    stream: TcpStream<D>,
+    fd: Arc<SharedFd>,
+}
+
+/// The write half of a [`TcpStream`], created by [`TcpStream::into_split`].
+///
+/// Closing the write half shuts down the writing side of the connection, rather than closing the
+/// socket, which stays open for the read half.
+pub struct OwnedWriteHalf<D: Drive = DefaultDriver> {
+    stream: TcpStream<D>,
+    fd: Arc<SharedFd>,
+}
+
+/// The socket shared by the two halves, which is closed when both have been dropped.
+struct SharedFd(RawFd);
+
+impl Drop for SharedFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.0); }
+    }
+}
+
+pub(super) fn halves<D: Drive>(read: TcpStream<D>, write: TcpStream<D>)
+    -> (OwnedReadHalf<D>, OwnedWriteHalf<D>)
+{
+    let fd = Arc::new(SharedFd(read.as_raw_fd()));
+    (OwnedReadHalf { stream: read, fd: fd.clone() }, OwnedWriteHalf { stream: write, fd })
+}
+
+impl<D: Drive> OwnedReadHalf<D> {
+    #[inline(always)]
+    fn stream(self: Pin<&mut Self>) -> Pin<&mut TcpStream<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.stream) }
+    }
+}
+
+impl<D: Drive> OwnedWriteHalf<D> {
+    #[inline(always)]
+    fn stream(self: Pin<&mut Self>) -> Pin<&mut TcpStream<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.stream) }
+    }
+}
+
+impl<D: Drive> AsyncRead for OwnedReadHalf<D> {
+    fn poll_read(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.stream().poll_read(ctx, buf)
+    }
+}
+
+impl<D: Drive> AsyncBufRead for OwnedReadHalf<D> {
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.stream().poll_fill_buf(ctx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.stream().consume(amt)
+    }
+}
+
+impl<D: Drive> AsyncWrite for OwnedWriteHalf<D> {
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.stream().poll_write(ctx, slice)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.stream().poll_flush(ctx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.stream().poll_shutdown(ctx, Shutdown::Write)
+    }
+}
+
+impl<D: Drive> AsRawFd for OwnedReadHalf<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.0
+    }
+}
+
+impl<D: Drive> AsRawFd for OwnedWriteHalf<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.0
+    }
+}
+
+impl<D: Drive> Drop for OwnedReadHalf<D> {
+    fn drop(&mut self) {
+        self.stream.release();
+    }
+}
+
+impl<D: Drive> Drop for OwnedWriteHalf<D> {
+    fn drop(&mut self) {
+        self.stream.release();
+    }
+}
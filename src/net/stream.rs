@@ -1,24 +1,28 @@
+use std::cmp;
 use std::io;
 use std::future::Future;
-use std::net::ToSocketAddrs;
-use std::os::unix::io::RawFd;
+use std::mem::ManuallyDrop;
+use std::net::{self, Shutdown, SocketAddr, ToSocketAddrs};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use futures_core::ready;
 use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
-use iou::sqe::SockAddr;
-use nix::sys::socket::SockProtocol;
+use iou::sqe::{BufferGroupId, MsgFlags, PollFlags};
 
 use crate::buf::Buffer;
-use crate::drive::{Drive, demo::DemoDriver};
-use crate::ring::Ring;
-use crate::event;
-use crate::Submission;
+use crate::drive::{Drive, DefaultDriver};
+use crate::ring::{self, Cancellation, Ring};
+use crate::sys;
 
-use super::socket;
+use super::{split, Connect, GaiResolver, OwnedReadHalf, OwnedWriteHalf, Resolve, RecvStream};
+use super::TcpKeepalive;
+use super::sockopt::{self, SetSocketOpt, SocketOpt, KeepAlive, TcpNoDelay};
+use super::sockopt::{Tos, TrafficClassV6, Ttl};
 
-pub struct TcpStream<D: Drive = DemoDriver> {
+pub struct TcpStream<D: Drive = DefaultDriver> {
     ring: Ring<D>,
     buf: Buffer,
     active: Op,
@@ -29,6 +33,9 @@ pub struct TcpStream<D: Drive = DemoDriver> {
 enum Op {
     Read,
     Write,
+    Readable,
+    Writable,
+    Shutdown,
     Close,
     Nothing,
     Closed,
@@ -36,22 +43,124 @@ enum Op {
 
 impl TcpStream {
     pub fn connect<A: ToSocketAddrs>(addr: A) -> Connect {
-        TcpStream::connect_on_driver(addr, DemoDriver::default())
+        TcpStream::connect_on_driver(addr, DefaultDriver::default())
+    }
+
+    /// Connect to `addr`, failing with `TimedOut` if the connection is not established within
+    /// `timeout`.
+    pub fn connect_timeout<A: ToSocketAddrs>(addr: A, timeout: Duration) -> Connect {
+        TcpStream::connect_timeout_on_driver(addr, timeout, DefaultDriver::default())
+    }
+
+    /// Connect to `addr` from the local address `local`.
+    pub fn connect_from<A: ToSocketAddrs>(local: SocketAddr, addr: A) -> Connect {
+        TcpStream::connect_from_on_driver(local, addr, DefaultDriver::default())
+    }
+
+    /// Connect to `port` on `host`, resolving the host name without blocking the calling thread.
+    pub fn connect_host(host: &str, port: u16) -> Connect {
+        TcpStream::connect_host_on_driver(host, port, &GaiResolver, DefaultDriver::default())
     }
 }
 
 impl<D: Drive + Clone> TcpStream<D> {
+    /// Connect to `addr` on the provided driver.
+    ///
+    /// If `addr` resolves to several addresses, connections to them are raced as described on
+    /// [`Connect`].
     pub fn connect_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> Connect<D> {
-        let (fd, addr) = match socket(addr, SockProtocol::Tcp) {
-            Ok(fd)  => fd,
-            Err(e)  => return Connect(Err(Some(e))),
-        };
-        let addr = Box::new(SockAddr::Inet(nix::sys::socket::InetAddr::from_std(&addr)));
-        Connect(Ok(driver.submit(event::Connect { fd, addr })))
+        Connect::new(addr, None, driver)
+    }
+
+    /// Connect to `addr` on the provided driver, failing with `TimedOut` if the connection is not
+    /// established within `timeout`.
+    ///
+    /// The timeout is linked to the connect with `IORING_OP_LINK_TIMEOUT`, so the kernel cancels
+    /// the connect when it expires. If `addr` resolves to several addresses, each attempt is given
+    /// the timeout.
+    pub fn connect_timeout_on_driver<A: ToSocketAddrs>(addr: A, timeout: Duration, driver: D)
+        -> Connect<D>
+    {
+        Connect::new(addr, Some(timeout), driver)
+    }
+
+    /// Connect to `addr` from the local address `local` on the provided driver.
+    ///
+    /// The socket is bound to `local` before it connects, which chooses the interface the
+    /// connection is made from on a host with several, and the local port if it is not 0. Only
+    /// the addresses `addr` resolves to of the same family as `local` are tried.
+    pub fn connect_from_on_driver<A: ToSocketAddrs>(local: SocketAddr, addr: A, driver: D)
+        -> Connect<D>
+    {
+        Connect::new(addr, None, driver).bind_local(local)
+    }
+
+    /// Connect to `port` on `host` on the provided driver, resolving the host name with
+    /// `resolver`.
+    ///
+    /// Resolving with `ToSocketAddrs`, as [`TcpStream::connect_on_driver`] does, blocks the
+    /// calling thread until the name has been looked up. Here the lookup is awaited by the
+    /// returned future, and the addresses it finds are raced as described on [`Connect`].
+    pub fn connect_host_on_driver<R: Resolve>(host: &str, port: u16, resolver: &R, driver: D)
+        -> Connect<D>
+    {
+        Connect::resolve(host, port, resolver, None, driver)
     }
 }
 
 impl<D: Drive> TcpStream<D> {
+    /// Take a stream connected elsewhere and run its IO on an io-uring driver
+    ///
+    /// The stream is put in blocking mode, so that io-uring waits for it to become ready rather
+    /// than failing with `EAGAIN`.
+    pub fn from_std(stream: net::TcpStream, driver: D) -> io::Result<TcpStream<D>> {
+        stream.set_nonblocking(false)?;
+        Ok(TcpStream::from_fd(stream.into_raw_fd(), Ring::new(driver)))
+    }
+
+    /// Take ownership of the file descriptor of a connected stream socket and run its IO on an
+    /// io-uring driver
+    ///
+    /// # Safety
+    ///
+    /// The fd must be an open socket, and must not be owned by anything else; it will be closed
+    /// when the stream is dropped.
+    pub unsafe fn from_raw_fd_on_driver(fd: RawFd, driver: D) -> TcpStream<D> {
+        TcpStream::from_fd(fd, Ring::new(driver))
+    }
+
+    /// Stop running this stream's IO on io-uring, and return it as a standard library stream
+    ///
+    /// Any ongoing IO is cancelled, and data which has been read into this stream's buffer but
+    /// not consumed is discarded.
+    pub fn into_std(mut self) -> net::TcpStream {
+        self.cancel();
+        let stream = ManuallyDrop::new(self);
+        unsafe { net::TcpStream::from_raw_fd(stream.fd) }
+    }
+
+    /// Split this stream into a read half and a write half, which can be used from separate
+    /// tasks.
+    ///
+    /// Each half runs its IO on its own ring, so a read and a write can be in flight at the same
+    /// time. Data which has been read into this stream's buffer stays buffered in the read half.
+    /// The stream is closed once both halves have been dropped.
+    pub fn into_split(mut self) -> (OwnedReadHalf<D>, OwnedWriteHalf<D>) where D: Clone {
+        if self.active != Op::Read && self.active != Op::Nothing {
+            self.cancel();
+        }
+        let write = TcpStream::from_fd(self.fd, Ring::new(self.driver().clone()));
+        split::halves(self, write)
+    }
+
+    /// Cancel any ongoing IO, and leave the fd open when the stream is dropped.
+    pub(crate) fn release(&mut self) {
+        if self.active != Op::Nothing && self.active != Op::Closed {
+            self.cancel();
+        }
+        self.active = Op::Closed;
+    }
+
     pub(crate) fn from_fd(fd: RawFd, ring: Ring<D>) -> TcpStream<D> {
         TcpStream {
             buf: Buffer::default(),
@@ -60,6 +169,387 @@ impl<D: Drive> TcpStream<D> {
         }
     }
 
+    pub(crate) fn driver(&self) -> &D {
+        self.ring.driver()
+    }
+
+    /// The local address of this stream.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        super::local_addr(self.fd)
+    }
+
+    /// The address of the peer this stream is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        super::peer_addr(self.fd)
+    }
+
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        sockopt::set(self.fd, opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        sockopt::get(self.fd)
+    }
+
+    /// Set `TCP_NODELAY`, disabling Nagle's algorithm.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.set_opt(TcpNoDelay(nodelay))
+    }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.opt().map(|TcpNoDelay(nodelay)| nodelay)
+    }
+
+    /// Set the time-to-live of outgoing IPv4 packets.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.set_opt(Ttl(ttl))
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.opt().map(|Ttl(ttl)| ttl)
+    }
+
+    /// Set the DSCP (differentiated services codepoint) of outgoing packets, which marks their
+    /// class of service for the network. This sets the type-of-service field of an IPv4 socket
+    /// or the traffic class of an IPv6 one, and fails with `InvalidInput` if `dscp` is not less
+    /// than 64.
+    pub fn set_dscp(&self, dscp: u8) -> io::Result<()> {
+        sockopt::set_dscp(self.fd, dscp)
+    }
+
+    pub fn dscp(&self) -> io::Result<u8> {
+        sockopt::dscp(self.fd)
+    }
+
+    /// Set the type-of-service field of outgoing IPv4 packets.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.set_opt(Tos(tos))
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        self.opt().map(|Tos(tos)| tos)
+    }
+
+    /// Set the traffic class of outgoing IPv6 packets.
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.set_opt(TrafficClassV6(tclass))
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.opt().map(|TrafficClassV6(tclass)| tclass)
+    }
+
+    /// Set `SO_KEEPALIVE`, sending keepalive probes while the connection is idle.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.set_opt(KeepAlive(keepalive))
+    }
+
+    pub fn keepalive(&self) -> io::Result<bool> {
+        self.opt().map(|KeepAlive(keepalive)| keepalive)
+    }
+
+    /// Enable `SO_KEEPALIVE` with the idle time, probe interval and probe count of `keepalive`.
+    pub fn set_tcp_keepalive(&self, keepalive: &TcpKeepalive) -> io::Result<()> {
+        keepalive.apply(self.fd)
+    }
+
+    /// The keepalive configuration of the stream, or `None` if keepalive is disabled.
+    pub fn tcp_keepalive(&self) -> io::Result<Option<TcpKeepalive>> {
+        TcpKeepalive::read(self.fd)
+    }
+
+    /// Wait until the socket is readable.
+    ///
+    /// This allows users who manage their own buffers to wait for readiness, and then perform
+    /// their own non-blocking reads on the socket's fd. Data which has already been read into this
+    /// stream's buffer through `AsyncBufRead` does not make the socket readable.
+    pub fn poll_readable(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_ready(ctx, Op::Readable, PollFlags::POLLIN)
+    }
+
+    /// Wait until the socket is writable.
+    pub fn poll_writable(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_ready(ctx, Op::Writable, PollFlags::POLLOUT)
+    }
+
+    /// Read from the socket without waiting for data to arrive.
+    ///
+    /// Data already in this stream's buffer is returned first. Otherwise, the read is submitted
+    /// with `RWF_NOWAIT`, and fails with `WouldBlock` if the socket has no data to read yet. This
+    /// is useful as a fast path before falling back to an awaited read.
+    pub fn poll_try_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        let mut inner = ready!(self.as_mut().poll_fill_buf_with(ctx, sys::RWF_NOWAIT))?;
+        let len = io::Read::read(&mut inner, buf)?;
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+
+    /// Write to the socket without waiting for space in its send buffer.
+    ///
+    /// The write is submitted with `RWF_NOWAIT`, and fails with `WouldBlock` if it cannot be
+    /// performed immediately.
+    pub fn poll_try_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.poll_write_with(ctx, slice, sys::RWF_NOWAIT)
+    }
+
+    pub fn try_read<'a>(&'a mut self, buf: &'a mut [u8]) -> TryRead<'a, D> where D: Unpin {
+        TryRead { stream: Pin::new(self), buf }
+    }
+
+    pub fn try_write<'a>(&'a mut self, buf: &'a [u8]) -> TryWrite<'a, D> where D: Unpin {
+        TryWrite { stream: Pin::new(self), buf }
+    }
+
+    /// Receive data with `IORING_OP_RECV`, passing `flags` to the kernel.
+    ///
+    /// Data already in this stream's buffer is returned first. With `MSG_PEEK`, the data is
+    /// returned without being consumed, so it is returned again by the next read.
+    pub fn recv_with_flags<'a>(&'a mut self, buf: &'a mut [u8], flags: MsgFlags)
+        -> TcpRecv<'a, D> where D: Unpin
+    {
+        TcpRecv { stream: Pin::new(self), buf, flags }
+    }
+
+    /// Receive data without consuming it, so that the next read returns it again.
+    ///
+    /// This is a receive with `MSG_PEEK`, for example to tell a TLS handshake from plaintext
+    /// before handing the stream to the protocol which handles it.
+    pub fn peek<'a>(&'a mut self, buf: &'a mut [u8]) -> TcpRecv<'a, D> where D: Unpin {
+        self.recv_with_flags(buf, MsgFlags::MSG_PEEK)
+    }
+
+    pub fn poll_peek(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.poll_recv_with_flags(ctx, buf, MsgFlags::MSG_PEEK)
+    }
+
+    /// Send data with `IORING_OP_SEND`, passing `flags` to the kernel, like `MSG_MORE` to batch
+    /// several sends into fewer packets.
+    pub fn send_with_flags<'a>(&'a mut self, buf: &'a [u8], flags: MsgFlags)
+        -> TcpSend<'a, D> where D: Unpin
+    {
+        TcpSend { stream: Pin::new(self), buf, flags }
+    }
+
+    pub fn poll_recv_with_flags(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        // Data peeked by a receive submitted here is left in the socket, so it must not stay in
+        // the buffer as well; data which was already buffered stays there.
+        let fresh = self.as_mut().buf().buffered_from_read().is_empty();
+        let mut inner = ready!(self.as_mut().poll_fill_buf_recv(ctx, flags))?;
+        let len = io::Read::read(&mut inner, buf)?;
+        match flags.contains(MsgFlags::MSG_PEEK) {
+            true if fresh   => self.buf().clear(),
+            true            => { }
+            false           => self.consume(len),
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    pub fn poll_send_with_flags(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        slice: &[u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        self.as_mut().guard_op(Op::Write);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        let data = ready!(buf.fill_buf(|mut buf| {
+            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
+        }))?;
+        let result = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_send(fd, data, flags);
+            }
+            sqe
+        }));
+        buf.clear();
+        Poll::Ready(Ok(result? as usize))
+    }
+
+    /// Send `buf` without copying it into the kernel, with `IORING_OP_SEND_ZC`.
+    ///
+    /// The kernel sends directly from `buf`, so the future only completes, returning the buffer,
+    /// once the kernel has reported that it no longer uses it; this is usually after the data
+    /// has been acknowledged by the peer. If the future is dropped, the buffer is kept alive until
+    /// then. This is worthwhile for large sends; small ones are cheaper to copy.
+    ///
+    /// On kernels which do not support zero-copy sends (before 6.0), this sends `buf` with
+    /// `IORING_OP_SEND` instead. The send does not use this stream's buffer, so it can run
+    /// alongside a read.
+    pub fn send_zc(&self, buf: Vec<u8>) -> SendZc<'_, D> where D: Clone {
+        SendZc {
+            ring: self.ring.with_driver(self.driver().clone()),
+            buf: Some(buf.into_boxed_slice()),
+            sent: None,
+            stream: self,
+        }
+    }
+
+    /// Receive data as a stream of buffers, with a multishot receive where the kernel supports
+    /// it (Linux 6.0 and later).
+    ///
+    /// The stream provides `count` buffers of `size` bytes to the kernel as the buffer group
+    /// `group`, which must not be used by anything else on the driver's io-uring instance, and
+    /// must fit in the kernel's 16-bit buffer group ids. Each item is the data the kernel
+    /// received into one of them, and the stream ends when the peer shuts down its write half.
+    /// See [`RecvStream`] for how the buffers are reused. Like [`send_zc`](TcpStream::send_zc),
+    /// this does not use the stream's buffer; it should not be used alongside reads, which would
+    /// take some of the data.
+    pub fn recv_stream(&self, group: BufferGroupId, count: u16, size: u32) -> RecvStream<'_, D>
+        where D: Clone
+    {
+        RecvStream::new(self, group, count, size)
+    }
+
+    /// Send `len` bytes of `file`, starting at `offset`, without copying them through userspace.
+    ///
+    /// The data is moved with `IORING_OP_SPLICE`, from the file into a pipe and from the pipe
+    /// into the socket, like `sendfile(2)`. The future completes with the number of bytes sent,
+    /// which is less than `len` if the file ends first; the file's own offset is not changed.
+    /// Like [`send_zc`](TcpStream::send_zc), this does not use the stream's buffer, so it can run
+    /// alongside a read.
+    pub fn send_file<'a>(&'a self, file: &'a impl AsRawFd, offset: u64, len: u64)
+        -> SendFile<'a, D> where D: Clone
+    {
+        SendFile {
+            ring: self.ring.with_driver(self.driver().clone()),
+            pipe: None,
+            file: file.as_raw_fd(),
+            offset,
+            remaining: len,
+            buffered: 0,
+            sent: 0,
+            stream: self,
+        }
+    }
+
+    /// Shut down the read half, the write half or both halves of the connection.
+    ///
+    /// This uses `IORING_OP_SHUTDOWN` on kernels which support it (5.11 and later), and the
+    /// `shutdown(2)` syscall otherwise.
+    pub fn shutdown(&mut self, how: Shutdown) -> TcpShutdown<'_, D> where D: Unpin {
+        Pin::new(self).shutdown_pinned(how)
+    }
+
+    pub fn shutdown_pinned(self: Pin<&mut Self>, how: Shutdown) -> TcpShutdown<'_, D> {
+        TcpShutdown { stream: self, how }
+    }
+
+    pub fn poll_shutdown(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, how: Shutdown)
+        -> Poll<io::Result<()>>
+    {
+        let how = match how {
+            Shutdown::Read  => libc::SHUT_RD,
+            Shutdown::Write => libc::SHUT_WR,
+            Shutdown::Both  => libc::SHUT_RDWR,
+        };
+        let fd = self.fd;
+        if !ring::is_supported(sys::IORING_OP_SHUTDOWN) {
+            return Poll::Ready(match unsafe { libc::shutdown(fd, how) } {
+                0   => Ok(()),
+                _   => Err(io::Error::last_os_error()),
+            });
+        }
+        self.as_mut().guard_op(Op::Shutdown);
+        ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_raw(&mut sqe, sys::IORING_OP_SHUTDOWN, fd, 0, how as u32, 0);
+            }
+            sqe
+        }))?;
+        *self.split().2 = Op::Nothing;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_fill_buf_with(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, rw_flags: i32)
+        -> Poll<io::Result<&[u8]>>
+    {
+        self.as_mut().guard_op(Op::Read);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        buf.fill_buf(|buf| {
+            let n = ready!(ring.poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_read(fd, buf, 0);
+                    sys::set_rw_flags(&mut sqe, rw_flags);
+                }
+                sqe
+            }))?;
+            Poll::Ready(Ok(n))
+        })
+    }
+
+    fn poll_fill_buf_recv(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, flags: MsgFlags)
+        -> Poll<io::Result<&[u8]>>
+    {
+        self.as_mut().guard_op(Op::Read);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        buf.fill_buf(|buf| {
+            let n = ready!(ring.poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sys::prep_recv(&mut sqe, fd, buf, flags);
+                }
+                sqe
+            }))?;
+            Poll::Ready(Ok(n))
+        })
+    }
+
+    fn poll_write_with(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8], rw_flags: i32)
+        -> Poll<io::Result<usize>>
+    {
+        self.as_mut().guard_op(Op::Write);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        let data = ready!(buf.fill_buf(|mut buf| {
+            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
+        }))?;
+        let result = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_write(fd, data, 0);
+                sys::set_rw_flags(&mut sqe, rw_flags);
+            }
+            sqe
+        }));
+        buf.clear();
+        Poll::Ready(Ok(result? as usize))
+    }
+
+    fn poll_ready(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, op: Op, flags: PollFlags)
+        -> Poll<io::Result<()>>
+    {
+        self.as_mut().guard_op(op);
+        let fd = self.fd;
+        ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_poll_add(fd, flags);
+            }
+            sqe
+        }))?;
+        *self.split().2 = Op::Nothing;
+        Poll::Ready(Ok(()))
+    }
+
     fn guard_op(self: Pin<&mut Self>, op: Op) {
         let (ring, buf, active) = self.split();
         if *active == Op::Closed {
@@ -98,42 +588,217 @@ impl<D: Drive> TcpStream<D> {
     }
 }
 
-pub struct Connect<D: Drive = DemoDriver>(
-    Result<Submission<event::Connect, D>, Option<io::Error>>
-);
+pub struct TcpShutdown<'a, D: Drive> {
+    stream: Pin<&'a mut TcpStream<D>>,
+    how: Shutdown,
+}
+
+impl<'a, D: Drive> Future for TcpShutdown<'a, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let how = self.how;
+        self.stream.as_mut().poll_shutdown(ctx, how)
+    }
+}
+
+pub struct TcpRecv<'a, D: Drive> {
+    stream: Pin<&'a mut TcpStream<D>>,
+    buf: &'a mut [u8],
+    flags: MsgFlags,
+}
+
+impl<'a, D: Drive> Future for TcpRecv<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.stream.as_mut().poll_recv_with_flags(ctx, this.buf, this.flags)
+    }
+}
+
+pub struct TcpSend<'a, D: Drive> {
+    stream: Pin<&'a mut TcpStream<D>>,
+    buf: &'a [u8],
+    flags: MsgFlags,
+}
+
+impl<'a, D: Drive> Future for TcpSend<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (buf, flags) = (self.buf, self.flags);
+        self.stream.as_mut().poll_send_with_flags(ctx, buf, flags)
+    }
+}
+
+/// A future which sends a buffer from a [`TcpStream`] without copying it.
+pub struct SendZc<'a, D: Drive> {
+    ring: Ring<D>,
+    buf: Option<Box<[u8]>>,
+    sent: Option<io::Result<u32>>,
+    stream: &'a TcpStream<D>,
+}
+
+impl<'a, D: Drive> Future for SendZc<'a, D> {
+    type Output = (Vec<u8>, io::Result<usize>);
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let fd = this.stream.fd;
+        loop {
+            let data = this.buf.as_deref().expect("polled SendZc future after completion");
+            let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+            let (result, more) = ready!(ring.poll_multishot(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    if ring::is_supported(sys::IORING_OP_SEND_ZC) {
+                        let (addr, len) = (data.as_ptr() as u64, data.len() as u32);
+                        sys::prep_raw(&mut sqe, sys::IORING_OP_SEND_ZC, fd, addr, len, 0);
+                    } else {
+                        sqe.prep_send(fd, data, MsgFlags::empty());
+                    }
+                }
+                sqe
+            }));
+            // The send completes first, followed by a notification once the kernel is done with
+            // the buffer if the send reported that one will follow.
+            let sent = match this.sent.take() {
+                Some(sent)  => {
+                    debug_assert!(this.ring.completion_flags() & sys::IORING_CQE_F_NOTIF != 0);
+                    sent
+                }
+                None        => result,
+            };
+            if !more {
+                let buf = this.buf.take().unwrap().into_vec();
+                return Poll::Ready((buf, sent.map(|n| n as usize)));
+            }
+            this.sent = Some(sent);
+        }
+    }
+}
+
+impl<'a, D: Drive> Drop for SendZc<'a, D> {
+    fn drop(&mut self) {
+        self.ring.cancel(Cancellation::from(self.buf.take()));
+    }
+}
 
-impl<D: Drive + Clone> Future for Connect<D> {
-    type Output = io::Result<TcpStream<D>>;
+/// The most data moved into the pipe of a [`SendFile`] at once: the default capacity of a pipe,
+/// so that splicing into the empty pipe does not have to wait for it to be drained.
+const SEND_FILE_CHUNK: u64 = 1 << 16;
+
+pub struct SendFile<'a, D: Drive> {
+    ring: Ring<D>,
+    // The reading and writing ends of the pipe the data passes through
+    pipe: Option<(RawFd, RawFd)>,
+    file: RawFd,
+    offset: u64,
+    remaining: u64,
+    // Bytes in the pipe which have not yet been sent
+    buffered: u32,
+    sent: u64,
+    stream: &'a TcpStream<D>,
+}
+
+impl<'a, D: Drive> Future for SendFile<'a, D> {
+    type Output = io::Result<u64>;
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
-        match self.project() {
-            Ok(mut submission)  => {
-                let (connect, result) = ready!(submission.as_mut().poll(ctx));
-                result?;
-                let driver = submission.driver().clone();
-                Poll::Ready(Ok(TcpStream::from_fd(connect.fd, Ring::new(driver))))
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let (pipe_in, pipe_out) = match this.pipe {
+            Some(pipe)  => pipe,
+            None        => {
+                let mut fds = [0; 2];
+                if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+                    return Poll::Ready(Err(io::Error::last_os_error()));
+                }
+                *this.pipe.get_or_insert((fds[0], fds[1]))
             }
-            Err(err)        => {
-                let err = err.take().expect("polled Connect future after completion");
-                Poll::Ready(Err(err))
+        };
+        loop {
+            let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+            if this.buffered > 0 {
+                let (fd, len) = (this.stream.fd, this.buffered);
+                let n = ready!(ring.poll(ctx, 1, |sqs| {
+                    let mut sqe = sqs.next().unwrap();
+                    unsafe {
+                        sys::prep_splice(&mut sqe, pipe_in, -1, fd, -1, len, 0);
+                    }
+                    sqe
+                }))?;
+                if n == 0 {
+                    return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+                }
+                this.buffered -= n;
+                this.sent += n as u64;
+            } else if this.remaining > 0 {
+                let (fd, offset) = (this.file, this.offset as i64);
+                let len = cmp::min(this.remaining, SEND_FILE_CHUNK) as u32;
+                let n = ready!(ring.poll(ctx, 1, |sqs| {
+                    let mut sqe = sqs.next().unwrap();
+                    unsafe {
+                        sys::prep_splice(&mut sqe, fd, offset, pipe_out, -1, len, 0);
+                    }
+                    sqe
+                }))?;
+                // The file ended before `len` bytes were sent.
+                if n == 0 {
+                    return Poll::Ready(Ok(this.sent));
+                }
+                this.offset += n as u64;
+                this.remaining -= n as u64;
+                this.buffered = n;
+            } else {
+                return Poll::Ready(Ok(this.sent));
             }
         }
     }
 }
 
-impl<D: Drive> Connect<D> {
-    fn project(self: Pin<&mut Self>)
-        -> Result<Pin<&mut Submission<event::Connect, D>>, &mut Option<io::Error>>
-    {
-        unsafe {
-            match &mut Pin::get_unchecked_mut(self).0 {
-                Ok(submission)  => Ok(Pin::new_unchecked(submission)),
-                Err(err)        => Err(err)
+impl<'a, D: Drive> Drop for SendFile<'a, D> {
+    fn drop(&mut self) {
+        // io-uring holds its own references to the pipe, so it can be closed while a splice
+        // is still running.
+        self.ring.cancel(Cancellation::from(()));
+        if let Some((pipe_in, pipe_out)) = self.pipe {
+            unsafe {
+                libc::close(pipe_in);
+                libc::close(pipe_out);
             }
         }
     }
 }
 
+pub struct TryRead<'a, D: Drive> {
+    stream: Pin<&'a mut TcpStream<D>>,
+    buf: &'a mut [u8],
+}
+
+impl<'a, D: Drive> Future for TryRead<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.stream.as_mut().poll_try_read(ctx, this.buf)
+    }
+}
+
+pub struct TryWrite<'a, D: Drive> {
+    stream: Pin<&'a mut TcpStream<D>>,
+    buf: &'a [u8],
+}
+
+impl<'a, D: Drive> Future for TryWrite<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let buf = self.buf;
+        self.stream.as_mut().poll_try_write(ctx, buf)
+    }
+}
+
 impl<D: Drive> AsyncRead for TcpStream<D> {
     fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
         -> Poll<io::Result<usize>>
@@ -146,20 +811,8 @@ impl<D: Drive> AsyncRead for TcpStream<D> {
 }
 
 impl<D: Drive> AsyncBufRead for TcpStream<D> {
-    fn poll_fill_buf(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
-        self.as_mut().guard_op(Op::Read);
-        let fd = self.fd;
-        let (ring, buf, ..) = self.split();
-        buf.fill_buf(|buf| {
-            let n = ready!(ring.poll(ctx, 1, |sqs| { 
-                let mut sqe = sqs.single().unwrap();
-                unsafe {
-                    sqe.prep_read(fd, buf, 0);
-                }
-                sqe
-            }))?;
-            Poll::Ready(Ok(n as u32))
-        })
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.poll_fill_buf_with(ctx, 0)
     }
 
     fn consume(self: Pin<&mut Self>, amt: usize) {
@@ -168,22 +821,8 @@ impl<D: Drive> AsyncBufRead for TcpStream<D> {
 }
 
 impl<D: Drive> AsyncWrite for TcpStream<D> {
-    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8]) -> Poll<io::Result<usize>> {
-        self.as_mut().guard_op(Op::Write);
-        let fd = self.fd;
-        let (ring, buf, ..) = self.split();
-        let data = ready!(buf.fill_buf(|mut buf| {
-            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
-        }))?;
-        let n = ready!(ring.poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
-            unsafe {
-                sqe.prep_write(fd, data, 0);
-            }
-            sqe
-        }))?;
-        buf.clear();
-        Poll::Ready(Ok(n as usize))
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8]) -> Poll<io::Result<usize>> {
+        self.poll_write_with(ctx, slice, 0)
     }
 
     fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -195,7 +834,7 @@ impl<D: Drive> AsyncWrite for TcpStream<D> {
         self.as_mut().guard_op(Op::Close);
         let fd = self.fd;
         ready!(self.as_mut().ring().poll(ctx, 1, |sqs| {
-            let mut sqe = sqs.single().unwrap();
+            let mut sqe = sqs.next().unwrap();
             unsafe {
                 sqe.prep_close(fd);
             }
@@ -206,6 +845,30 @@ impl<D: Drive> AsyncWrite for TcpStream<D> {
     }
 }
 
+impl<D: Drive> AsRawFd for TcpStream<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl<D: Drive + Default> FromRawFd for TcpStream<D> {
+    unsafe fn from_raw_fd(fd: RawFd) -> TcpStream<D> {
+        TcpStream::from_raw_fd_on_driver(fd, D::default())
+    }
+}
+
+impl<D: Drive> IntoRawFd for TcpStream<D> {
+    fn into_raw_fd(self) -> RawFd {
+        self.into_std().into_raw_fd()
+    }
+}
+
+impl<D: Drive> From<TcpStream<D>> for net::TcpStream {
+    fn from(stream: TcpStream<D>) -> net::TcpStream {
+        stream.into_std()
+    }
+}
+
 impl<D: Drive> Drop for TcpStream<D> {
     fn drop(&mut self) {
         match self.active {
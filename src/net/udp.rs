@@ -0,0 +1,378 @@
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::slice;
+use std::task::{Context, Poll};
+
+use iou::sqe::MsgFlags;
+
+use crate::drive::{Drive, DefaultDriver};
+
+use super::{Protocol, Socket, SocketConnect, SocketSend, SocketRecv, SendTo, RecvFrom};
+use super::{PacketInfo, SendToFrom, RecvFromPktInfo};
+use super::sockopt::{self, SetSocketOpt, SocketOpt};
+use super::sockopt::{MulticastLoopV4, MulticastLoopV6, MulticastTtlV4, Tos, TrafficClassV6, Ttl};
+use super::sockopt::{PktInfo, RecvPktInfoV6};
+
+pub type UdpConnect<'a, D> = SocketConnect<'a, D>;
+pub type UdpSend<'a, D> = SocketSend<'a, D>;
+pub type UdpRecv<'a, D> = SocketRecv<'a, D>;
+
+/// A UDP socket
+///
+/// This is a [`Socket`] of type `SOCK_DGRAM` with the UDP protocol, bound when it is created.
+pub struct UdpSocket<D: Drive = DefaultDriver> {
+    inner: Socket<D>,
+}
+
+impl UdpSocket {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<UdpSocket> {
+        UdpSocket::bind_on_driver(addr, DefaultDriver::default())
+    }
+
+    pub fn bind_lite<A: ToSocketAddrs>(addr: A) -> io::Result<UdpSocket> {
+        UdpSocket::bind_lite_on_driver(addr, DefaultDriver::default())
+    }
+}
+
+impl<D: Drive> UdpSocket<D> {
+    pub fn bind_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> io::Result<UdpSocket<D>> {
+        UdpSocket::bind_protocol(addr, Protocol::UDP, driver)
+    }
+
+    /// Bind a UDP-Lite socket (RFC 3828), whose checksum can cover only the start of each
+    /// datagram. It is sent to and received from like a UDP socket.
+    pub fn bind_lite_on_driver<A: ToSocketAddrs>(addr: A, driver: D) -> io::Result<UdpSocket<D>> {
+        UdpSocket::bind_protocol(addr, Protocol::UDP_LITE, driver)
+    }
+
+    fn bind_protocol<A: ToSocketAddrs>(addr: A, protocol: Protocol, driver: D)
+        -> io::Result<UdpSocket<D>>
+    {
+        let (fd, addr) = super::socket(addr, protocol)?;
+        let inner = unsafe { Socket::from_raw_fd_on_driver(fd, driver) };
+        inner.bind(addr)?;
+        Ok(UdpSocket { inner })
+    }
+
+    /// The address this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// The address of the peer this socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        self.inner.set_opt(opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        self.inner.opt()
+    }
+
+    /// Set the time-to-live of outgoing IPv4 datagrams.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.set_opt(Ttl(ttl))
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.opt().map(|Ttl(ttl)| ttl)
+    }
+
+    /// Have the kernel report the destination address and receiving interface of each datagram,
+    /// so that they can be received with [`recv_from_pktinfo`](UdpSocket::recv_from_pktinfo).
+    /// This sets `IP_PKTINFO` on an IPv4 socket and `IPV6_RECVPKTINFO` on an IPv6 one.
+    pub fn set_pktinfo(&self, pktinfo: bool) -> io::Result<()> {
+        match self.local_addr()? {
+            SocketAddr::V4(_)   => self.set_opt(PktInfo(pktinfo)),
+            SocketAddr::V6(_)   => self.set_opt(RecvPktInfoV6(pktinfo)),
+        }
+    }
+
+    /// Set the DSCP (differentiated services codepoint) of outgoing packets, which marks their
+    /// class of service for the network. This sets the type-of-service field of an IPv4 socket
+    /// or the traffic class of an IPv6 one, and fails with `InvalidInput` if `dscp` is not less
+    /// than 64.
+    pub fn set_dscp(&self, dscp: u8) -> io::Result<()> {
+        sockopt::set_dscp(self.as_raw_fd(), dscp)
+    }
+
+    pub fn dscp(&self) -> io::Result<u8> {
+        sockopt::dscp(self.as_raw_fd())
+    }
+
+    /// Set the type-of-service field of outgoing IPv4 packets.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.set_opt(Tos(tos))
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        self.opt().map(|Tos(tos)| tos)
+    }
+
+    /// Set the traffic class of outgoing IPv6 packets.
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.set_opt(TrafficClassV6(tclass))
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.opt().map(|TrafficClassV6(tclass)| tclass)
+    }
+
+    /// Join the IPv4 multicast group `multiaddr` on the interface with the address `interface`,
+    /// or on the default interface if it is `Ipv4Addr::UNSPECIFIED`.
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        let mreq = ip_mreq(multiaddr, interface);
+        self.set_membership(libc::IPPROTO_IP, libc::IP_ADD_MEMBERSHIP, &mreq)
+    }
+
+    /// Leave an IPv4 multicast group joined with `join_multicast_v4`.
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        let mreq = ip_mreq(multiaddr, interface);
+        self.set_membership(libc::IPPROTO_IP, libc::IP_DROP_MEMBERSHIP, &mreq)
+    }
+
+    /// Join the IPv6 multicast group `multiaddr` on the interface with the index `interface`, or
+    /// on the default interface if it is 0.
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        let mreq = ipv6_mreq(multiaddr, interface);
+        self.set_membership(libc::IPPROTO_IPV6, libc::IPV6_ADD_MEMBERSHIP, &mreq)
+    }
+
+    /// Leave an IPv6 multicast group joined with `join_multicast_v6`.
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        let mreq = ipv6_mreq(multiaddr, interface);
+        self.set_membership(libc::IPPROTO_IPV6, libc::IPV6_DROP_MEMBERSHIP, &mreq)
+    }
+
+    /// Set whether IPv4 multicast datagrams this socket sends are delivered back to the host.
+    pub fn set_multicast_loop_v4(&self, multicast_loop: bool) -> io::Result<()> {
+        self.set_opt(MulticastLoopV4(multicast_loop))
+    }
+
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.opt().map(|MulticastLoopV4(multicast_loop)| multicast_loop)
+    }
+
+    /// Set whether IPv6 multicast datagrams this socket sends are delivered back to the host.
+    pub fn set_multicast_loop_v6(&self, multicast_loop: bool) -> io::Result<()> {
+        self.set_opt(MulticastLoopV6(multicast_loop))
+    }
+
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.opt().map(|MulticastLoopV6(multicast_loop)| multicast_loop)
+    }
+
+    /// Set the time-to-live of IPv4 multicast datagrams this socket sends. This is 1 by default,
+    /// which keeps them on the local network.
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        self.set_opt(MulticastTtlV4(ttl))
+    }
+
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        self.opt().map(|MulticastTtlV4(ttl)| ttl)
+    }
+
+    fn set_membership<T>(&self, level: libc::c_int, name: libc::c_int, mreq: &T) -> io::Result<()> {
+        let ptr = mreq as *const T as *const u8;
+        let mreq = unsafe { slice::from_raw_parts(ptr, mem::size_of::<T>()) };
+        sockopt::set_bytes(self.as_raw_fd(), level, name, mreq)
+    }
+
+    /// Receive and send datagrams as a [`Stream`](futures_core::Stream) and a
+    /// [`Sink`](futures_sink::Sink) of their payloads and peers' addresses.
+    #[cfg(feature = "framed")]
+    pub fn framed(self) -> super::UdpFramed<D> {
+        super::UdpFramed::new(self)
+    }
+
+    /// Connect the socket to a peer, so that it can `send` and `recv`, and only receives
+    /// datagrams from that peer.
+    pub fn connect<A: ToSocketAddrs>(&mut self, addr: A) -> UdpConnect<'_, D> where D: Unpin {
+        self.inner.connect(addr)
+    }
+
+    pub fn connect_pinned<A: ToSocketAddrs>(self: Pin<&mut Self>, addr: A) -> UdpConnect<'_, D> {
+        self.inner().connect_pinned(addr)
+    }
+
+    /// Send a datagram to the connected peer.
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> UdpSend<'a, D> where D: Unpin {
+        self.inner.send(buf)
+    }
+
+    pub fn send_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a [u8]) -> UdpSend<'a, D> {
+        self.inner().send_pinned(buf)
+    }
+
+    /// Send a datagram to the connected peer, passing `flags` to the kernel.
+    pub fn send_with_flags<'a>(&'a mut self, buf: &'a [u8], flags: MsgFlags) -> UdpSend<'a, D>
+        where D: Unpin
+    {
+        self.inner.send_with_flags(buf, flags)
+    }
+
+    /// Receive a datagram from the connected peer.
+    ///
+    /// If the datagram is larger than `buf`, the rest of it is discarded.
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> UdpRecv<'a, D> where D: Unpin {
+        self.inner.recv(buf)
+    }
+
+    pub fn recv_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> UdpRecv<'a, D> {
+        self.inner().recv_pinned(buf)
+    }
+
+    /// Receive a datagram from the connected peer, passing `flags` to the kernel. With
+    /// `MSG_PEEK`, the datagram is left to be received again.
+    pub fn recv_with_flags<'a>(&'a mut self, buf: &'a mut [u8], flags: MsgFlags)
+        -> UdpRecv<'a, D> where D: Unpin
+    {
+        self.inner.recv_with_flags(buf, flags)
+    }
+
+    /// Send a datagram to `addr`.
+    pub fn send_to<'a>(&'a mut self, buf: &'a [u8], addr: SocketAddr) -> SendTo<'a, D> where
+        D: Unpin
+    {
+        self.inner.send_to(buf, addr)
+    }
+
+    pub fn send_to_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a [u8], addr: SocketAddr)
+        -> SendTo<'a, D>
+    {
+        self.inner().send_to_pinned(buf, addr)
+    }
+
+    /// Receive a datagram, along with the address of the peer which sent it.
+    ///
+    /// If the datagram is larger than `buf`, the rest of it is discarded.
+    pub fn recv_from<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvFrom<'a, D> where D: Unpin {
+        self.inner.recv_from(buf)
+    }
+
+    pub fn recv_from_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> RecvFrom<'a, D> {
+        self.inner().recv_from_pinned(buf)
+    }
+
+    /// Send a datagram to `addr`, from the address and through the interface in `from`.
+    ///
+    /// A server bound to the unspecified address replies to a datagram by passing the info it
+    /// was received with, so that the reply comes from the address its peer sent to.
+    pub fn send_to_from<'a>(&'a mut self, buf: &'a [u8], addr: SocketAddr, from: PacketInfo)
+        -> SendToFrom<'a, D> where D: Unpin
+    {
+        self.inner.send_to_from(buf, addr, from)
+    }
+
+    pub fn send_to_from_pinned<'a>(
+        self: Pin<&'a mut Self>,
+        buf: &'a [u8],
+        addr: SocketAddr,
+        from: PacketInfo,
+    ) -> SendToFrom<'a, D> {
+        self.inner().send_to_from_pinned(buf, addr, from)
+    }
+
+    /// Receive a datagram, along with the address of the peer which sent it, and the address it
+    /// was sent to and the interface it was received on.
+    ///
+    /// The info is `None` unless it has been enabled with
+    /// [`set_pktinfo`](UdpSocket::set_pktinfo). If the datagram is larger than `buf`, the rest
+    /// of it is discarded.
+    pub fn recv_from_pktinfo<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvFromPktInfo<'a, D>
+        where D: Unpin
+    {
+        self.inner.recv_from_pktinfo(buf)
+    }
+
+    pub fn recv_from_pktinfo_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8])
+        -> RecvFromPktInfo<'a, D>
+    {
+        self.inner().recv_from_pktinfo_pinned(buf)
+    }
+
+    pub fn poll_connect(self: Pin<&mut Self>, ctx: &mut Context<'_>, addr: SocketAddr)
+        -> Poll<io::Result<()>>
+    {
+        self.inner().poll_connect(ctx, addr)
+    }
+
+    pub fn poll_send(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        self.inner().poll_send(ctx, buf, flags)
+    }
+
+    pub fn poll_recv(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        self.inner().poll_recv(ctx, buf, flags)
+    }
+
+    pub fn poll_send_to(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &[u8], addr: SocketAddr)
+        -> Poll<io::Result<usize>>
+    {
+        self.inner().poll_send_to(ctx, buf, addr)
+    }
+
+    pub fn poll_recv_from(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<(usize, SocketAddr)>>
+    {
+        self.inner().poll_recv_from(ctx, buf)
+    }
+
+    pub fn poll_send_to_from(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+        addr: SocketAddr,
+        from: &PacketInfo,
+    ) -> Poll<io::Result<usize>> {
+        self.inner().poll_send_to_from(ctx, buf, addr, from)
+    }
+
+    pub fn poll_recv_from_pktinfo(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<(usize, SocketAddr, Option<PacketInfo>)>>
+    {
+        self.inner().poll_recv_from_pktinfo(ctx, buf)
+    }
+
+    #[inline(always)]
+    fn inner(self: Pin<&mut Self>) -> Pin<&mut Socket<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.inner) }
+    }
+}
+
+impl<D: Drive> AsRawFd for UdpSocket<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+fn ip_mreq(multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> libc::ip_mreq {
+    libc::ip_mreq {
+        imr_multiaddr: libc::in_addr { s_addr: u32::from_ne_bytes(multiaddr.octets()) },
+        imr_interface: libc::in_addr { s_addr: u32::from_ne_bytes(interface.octets()) },
+    }
+}
+
+fn ipv6_mreq(multiaddr: &Ipv6Addr, interface: u32) -> libc::ipv6_mreq {
+    libc::ipv6_mreq {
+        ipv6mr_multiaddr: libc::in6_addr { s6_addr: multiaddr.octets() },
+        ipv6mr_interface: interface,
+    }
+}
@@ -0,0 +1,334 @@
+//! Pipes whose IO runs on io-uring
+//!
+//! In addition to reading and writing, both ends of a pipe can splice data to or from another
+//! file descriptor, like a socket or a file, without copying it through userspace.
+
+use std::future::Future;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
+
+use crate::buf::Buffer;
+use crate::drive::{Drive, DefaultDriver};
+use crate::ring::Ring;
+use crate::sys;
+
+/// The writing end of a pipe
+pub struct Sender<D: Drive = DefaultDriver> {
+    end: End<D>,
+}
+
+/// The reading end of a pipe
+pub struct Receiver<D: Drive = DefaultDriver> {
+    end: End<D>,
+}
+
+/// Create a pipe run on the default driver.
+pub fn pipe() -> io::Result<(Sender, Receiver)> {
+    pipe_on_driver(DefaultDriver::default())
+}
+
+/// Create a pipe run on the provided driver.
+pub fn pipe_on_driver<D: Drive + Clone>(driver: D) -> io::Result<(Sender<D>, Receiver<D>)> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let receiver = Receiver { end: End::new(fds[0], driver.clone()) };
+    let sender = Sender { end: End::new(fds[1], driver) };
+    Ok((sender, receiver))
+}
+
+struct End<D: Drive> {
+    ring: Ring<D>,
+    buf: Buffer,
+    active: Op,
+    fd: RawFd,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Op {
+    Read,
+    Write,
+    Splice,
+    Close,
+    Nothing,
+    Closed,
+}
+
+impl<D: Drive> End<D> {
+    fn new(fd: RawFd, driver: D) -> End<D> {
+        End {
+            ring: Ring::new(driver),
+            buf: Buffer::default(),
+            active: Op::Nothing,
+            fd,
+        }
+    }
+
+    fn set_pipe_size(&self, size: usize) -> io::Result<usize> {
+        match unsafe { libc::fcntl(self.fd, libc::F_SETPIPE_SZ, size as libc::c_int) } {
+            -1      => Err(io::Error::last_os_error()),
+            size    => Ok(size as usize),
+        }
+    }
+
+    fn pipe_size(&self) -> io::Result<usize> {
+        match unsafe { libc::fcntl(self.fd, libc::F_GETPIPE_SZ) } {
+            -1      => Err(io::Error::last_os_error()),
+            size    => Ok(size as usize),
+        }
+    }
+
+    fn guard_op(self: Pin<&mut Self>, op: Op) {
+        let (ring, buf, active) = self.split();
+        if *active == Op::Closed {
+            panic!("Attempted to perform IO on a closed pipe");
+        } else if *active != Op::Nothing && *active != op {
+            ring.cancel_pinned(buf.cancellation());
+        }
+        *active = op;
+    }
+
+    fn cancel(&mut self) {
+        self.active = Op::Nothing;
+        self.ring.cancel(self.buf.cancellation());
+    }
+
+    #[inline(always)]
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Buffer, &mut Op) {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            (Pin::new_unchecked(&mut this.ring), &mut this.buf, &mut this.active)
+        }
+    }
+
+    fn confirm_close(self: Pin<&mut Self>) {
+        *self.split().2 = Op::Closed;
+    }
+
+    fn poll_fill_buf(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.as_mut().guard_op(Op::Read);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        buf.fill_buf(|buf| {
+            let n = ready!(ring.poll(ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_read(fd, buf, 0);
+                }
+                sqe
+            }))?;
+            Poll::Ready(Ok(n))
+        })
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.split().1.consume(amt);
+    }
+
+    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.as_mut().guard_op(Op::Write);
+        let fd = self.fd;
+        let (ring, buf, ..) = self.split();
+        let data = ready!(buf.fill_buf(|mut buf| {
+            Poll::Ready(Ok(io::Write::write(&mut buf, slice)? as u32))
+        }))?;
+        let n = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_write(fd, data, 0);
+            }
+            sqe
+        }))?;
+        buf.clear();
+        Poll::Ready(Ok(n as usize))
+    }
+
+    fn poll_splice(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        fd_in: RawFd,
+        fd_out: RawFd,
+        len: u32,
+    ) -> Poll<io::Result<u32>> {
+        self.as_mut().guard_op(Op::Splice);
+        let n = ready!(self.as_mut().split().0.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sys::prep_splice(&mut sqe, fd_in, -1, fd_out, -1, len, 0);
+            }
+            sqe
+        }))?;
+        *self.split().2 = Op::Nothing;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.as_mut().guard_op(Op::Close);
+        let fd = self.fd;
+        ready!(self.as_mut().split().0.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_close(fd);
+            }
+            sqe
+        }))?;
+        self.confirm_close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<D: Drive> Drop for End<D> {
+    fn drop(&mut self) {
+        match self.active {
+            Op::Closed  => { }
+            Op::Nothing => unsafe { libc::close(self.fd); },
+            _           => self.cancel(),
+        }
+    }
+}
+
+impl<D: Drive> Sender<D> {
+    pub(crate) fn from_fd(fd: RawFd, driver: D) -> Sender<D> {
+        Sender { end: End::new(fd, driver) }
+    }
+
+    /// Move up to `len` bytes from `fd` into the pipe, without copying them through userspace.
+    ///
+    /// If `fd` is a file, the data is read from its current offset.
+    pub fn splice_from(&mut self, fd: &impl AsRawFd, len: u32) -> Splice<'_, D> where D: Unpin {
+        Pin::new(self).splice_from_pinned(fd, len)
+    }
+
+    pub fn splice_from_pinned(self: Pin<&mut Self>, fd: &impl AsRawFd, len: u32)
+        -> Splice<'_, D>
+    {
+        let fd_out = self.end.fd;
+        Splice { end: self.end(), fd_in: fd.as_raw_fd(), fd_out, len }
+    }
+
+    /// Set the capacity of the pipe, returning the capacity the kernel actually allocated.
+    pub fn set_pipe_size(&self, size: usize) -> io::Result<usize> {
+        self.end.set_pipe_size(size)
+    }
+
+    /// The capacity of the pipe.
+    pub fn pipe_size(&self) -> io::Result<usize> {
+        self.end.pipe_size()
+    }
+
+    #[inline(always)]
+    fn end(self: Pin<&mut Self>) -> Pin<&mut End<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.end) }
+    }
+}
+
+impl<D: Drive> Receiver<D> {
+    pub(crate) fn from_fd(fd: RawFd, driver: D) -> Receiver<D> {
+        Receiver { end: End::new(fd, driver) }
+    }
+
+    /// Move up to `len` bytes from the pipe into `fd`, without copying them through userspace.
+    ///
+    /// If `fd` is a file, the data is written at its current offset. Data which has already been
+    /// read into this receiver's buffer is not included.
+    pub fn splice_to(&mut self, fd: &impl AsRawFd, len: u32) -> Splice<'_, D> where D: Unpin {
+        Pin::new(self).splice_to_pinned(fd, len)
+    }
+
+    pub fn splice_to_pinned(self: Pin<&mut Self>, fd: &impl AsRawFd, len: u32)
+        -> Splice<'_, D>
+    {
+        let fd_in = self.end.fd;
+        Splice { end: self.end(), fd_in, fd_out: fd.as_raw_fd(), len }
+    }
+
+    /// Set the capacity of the pipe, returning the capacity the kernel actually allocated.
+    pub fn set_pipe_size(&self, size: usize) -> io::Result<usize> {
+        self.end.set_pipe_size(size)
+    }
+
+    /// The capacity of the pipe.
+    pub fn pipe_size(&self) -> io::Result<usize> {
+        self.end.pipe_size()
+    }
+
+    #[inline(always)]
+    fn end(self: Pin<&mut Self>) -> Pin<&mut End<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.end) }
+    }
+}
+
+impl<D: Drive> AsyncWrite for Sender<D> {
+    fn poll_write(self: Pin<&mut Self>, ctx: &mut Context<'_>, slice: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.end().poll_write(ctx, slice)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_write(ctx, &[]))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.end().poll_close(ctx)
+    }
+}
+
+impl<D: Drive> AsyncRead for Receiver<D> {
+    fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        let mut inner = ready!(self.as_mut().poll_fill_buf(ctx))?;
+        let len = io::Read::read(&mut inner, buf)?;
+        self.consume(len);
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<D: Drive> AsyncBufRead for Receiver<D> {
+    fn poll_fill_buf(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.end().poll_fill_buf(ctx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.end().consume(amt)
+    }
+}
+
+impl<D: Drive> AsRawFd for Sender<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.end.fd
+    }
+}
+
+impl<D: Drive> AsRawFd for Receiver<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.end.fd
+    }
+}
+
+/// A future which splices data into or out of a pipe.
+pub struct Splice<'a, D: Drive> {
+    end: Pin<&'a mut End<D>>,
+    fd_in: RawFd,
+    fd_out: RawFd,
+    len: u32,
+}
+
+impl<'a, D: Drive> Future for Splice<'a, D> {
+    type Output = io::Result<u32>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u32>> {
+        let (fd_in, fd_out, len) = (self.fd_in, self.fd_out, self.len);
+        self.end.as_mut().poll_splice(ctx, fd_in, fd_out, len)
+    }
+}
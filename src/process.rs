@@ -0,0 +1,353 @@
+//! Spawn child processes whose IO runs on io-uring
+//!
+//! A [`Command`] is configured like a `std::process::Command`. The piped standard streams of the
+//! children it spawns are ringbahn [pipes](crate::pipe), and waiting for a child to exit polls a
+//! pidfd for the child on io-uring. Pidfds require Linux 5.3 or later.
+
+use std::ffi::OsStr;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::os::unix::io::{IntoRawFd, RawFd};
+use std::path::Path;
+use std::pin::Pin;
+use std::process;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use futures_io::AsyncBufRead;
+use iou::sqe::PollFlags;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::pipe::{Sender, Receiver};
+use crate::ring::{Ring, Cancellation};
+
+pub use std::process::{ExitStatus, Output, Stdio};
+
+/// A builder for spawning child processes.
+pub struct Command {
+    inner: process::Command,
+    stdin: bool,
+    stdout: bool,
+    stderr: bool,
+}
+
+impl Command {
+    pub fn new(program: impl AsRef<OsStr>) -> Command {
+        Command {
+            inner: process::Command::new(program),
+            stdin: false,
+            stdout: false,
+            stderr: false,
+        }
+    }
+
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Command {
+        self.inner.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.inner.args(args);
+        self
+    }
+
+    pub fn env(&mut self, key: impl AsRef<OsStr>, val: impl AsRef<OsStr>) -> &mut Command {
+        self.inner.env(key, val);
+        self
+    }
+
+    pub fn env_remove(&mut self, key: impl AsRef<OsStr>) -> &mut Command {
+        self.inner.env_remove(key);
+        self
+    }
+
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.inner.env_clear();
+        self
+    }
+
+    pub fn current_dir(&mut self, dir: impl AsRef<Path>) -> &mut Command {
+        self.inner.current_dir(dir);
+        self
+    }
+
+    /// Configure the child's standard input. If it is piped, the child's `stdin` is a `Sender`.
+    pub fn stdin(&mut self, cfg: impl Into<Stdio>) -> &mut Command {
+        self.inner.stdin(cfg);
+        self.stdin = true;
+        self
+    }
+
+    /// Configure the child's standard output. If it is piped, the child's `stdout` is a
+    /// `Receiver`.
+    pub fn stdout(&mut self, cfg: impl Into<Stdio>) -> &mut Command {
+        self.inner.stdout(cfg);
+        self.stdout = true;
+        self
+    }
+
+    /// Configure the child's standard error. If it is piped, the child's `stderr` is a
+    /// `Receiver`.
+    pub fn stderr(&mut self, cfg: impl Into<Stdio>) -> &mut Command {
+        self.inner.stderr(cfg);
+        self.stderr = true;
+        self
+    }
+
+    /// Spawn the child process on the default driver.
+    ///
+    /// Standard streams which have not been configured are inherited from this process.
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        self.spawn_on_driver(DefaultDriver::default())
+    }
+
+    /// Spawn the child process, waiting for it to exit with `status`.
+    pub fn status(&mut self) -> Status {
+        self.status_on_driver(DefaultDriver::default())
+    }
+
+    /// Spawn the child process, collecting all of its output with `output`.
+    ///
+    /// Unless they have been configured, standard output and standard error are piped, and
+    /// standard input is null.
+    pub fn output(&mut self) -> WaitWithOutput {
+        self.output_on_driver(DefaultDriver::default())
+    }
+
+    pub fn spawn_on_driver<D: Drive + Clone>(&mut self, driver: D) -> io::Result<Child<D>> {
+        self.spawn_with_defaults(driver, Stdio::inherit, Stdio::inherit)
+    }
+
+    pub fn status_on_driver<D: Drive + Clone>(&mut self, driver: D) -> Status<D> {
+        Status(self.spawn_on_driver(driver).map_err(Some))
+    }
+
+    pub fn output_on_driver<D: Drive + Clone>(&mut self, driver: D) -> WaitWithOutput<D> {
+        match self.spawn_with_defaults(driver, Stdio::null, Stdio::piped) {
+            Ok(child)   => child.wait_with_output(),
+            Err(err)    => WaitWithOutput {
+                child: Err(Some(err)),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            },
+        }
+    }
+
+    fn spawn_with_defaults<D: Drive + Clone>(
+        &mut self,
+        driver: D,
+        stdin: fn() -> Stdio,
+        output: fn() -> Stdio,
+    ) -> io::Result<Child<D>> {
+        if !self.stdin { self.inner.stdin(stdin()); }
+        if !self.stdout { self.inner.stdout(output()); }
+        if !self.stderr { self.inner.stderr(output()); }
+
+        let result = self.inner.spawn();
+
+        // Restore the streams which were not configured to the standard library's default, so
+        // that the defaults of one way of spawning do not leak into the next.
+        if !self.stdin { self.inner.stdin(Stdio::inherit()); }
+        if !self.stdout { self.inner.stdout(Stdio::inherit()); }
+        if !self.stderr { self.inner.stderr(Stdio::inherit()); }
+
+        Child::new(result?, driver)
+    }
+}
+
+/// A child process spawned by a [`Command`].
+///
+/// Dropping a `Child` does not kill or wait for the process.
+pub struct Child<D: Drive = DefaultDriver> {
+    pub stdin: Option<Sender<D>>,
+    pub stdout: Option<Receiver<D>>,
+    pub stderr: Option<Receiver<D>>,
+    child: process::Child,
+    ring: Ring<D>,
+    pidfd: RawFd,
+    waiting: bool,
+}
+
+impl<D: Drive + Clone> Child<D> {
+    fn new(mut child: process::Child, driver: D) -> io::Result<Child<D>> {
+        let pid = child.id() as libc::pid_t;
+        let pidfd = match unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) } {
+            -1  => {
+                let err = io::Error::last_os_error();
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(err);
+            }
+            fd  => fd as RawFd,
+        };
+        let stdin = child.stdin.take().map(|stdin| {
+            Sender::from_fd(stdin.into_raw_fd(), driver.clone())
+        });
+        let stdout = child.stdout.take().map(|stdout| {
+            Receiver::from_fd(stdout.into_raw_fd(), driver.clone())
+        });
+        let stderr = child.stderr.take().map(|stderr| {
+            Receiver::from_fd(stderr.into_raw_fd(), driver.clone())
+        });
+        Ok(Child {
+            ring: Ring::new(driver),
+            waiting: false,
+            stdin, stdout, stderr, child, pidfd,
+        })
+    }
+}
+
+impl<D: Drive> Child<D> {
+    /// The OS-assigned process identifier of the child.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Send `SIGKILL` to the child.
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()
+    }
+
+    /// Wait for the child to exit.
+    ///
+    /// The child's stdin is not closed first; if the child is reading from it, close it before
+    /// waiting.
+    pub fn wait(&mut self) -> Wait<'_, D> where D: Unpin {
+        Pin::new(self).wait_pinned()
+    }
+
+    pub fn wait_pinned(self: Pin<&mut Self>) -> Wait<'_, D> {
+        Wait { child: self }
+    }
+
+    /// Close the child's stdin, collect its output and wait for it to exit.
+    pub fn wait_with_output(mut self) -> WaitWithOutput<D> {
+        drop(self.stdin.take());
+        WaitWithOutput {
+            child: Ok(self),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+
+    pub fn poll_wait(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<ExitStatus>> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        if !this.waiting {
+            if let Some(status) = this.child.try_wait()? {
+                return Poll::Ready(Ok(status));
+            }
+            this.waiting = true;
+        }
+        let pidfd = this.pidfd;
+        let ring = unsafe { Pin::new_unchecked(&mut this.ring) };
+        let result = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            unsafe {
+                sqe.prep_poll_add(pidfd, PollFlags::POLLIN);
+            }
+            sqe
+        }));
+        this.waiting = false;
+        result?;
+        // The pidfd is readable once the child has exited, so this reaps it without blocking.
+        Poll::Ready(this.child.wait())
+    }
+}
+
+impl<D: Drive> Drop for Child<D> {
+    fn drop(&mut self) {
+        if self.waiting {
+            self.ring.cancel(Cancellation::from(()));
+        }
+        unsafe { libc::close(self.pidfd); }
+    }
+}
+
+/// A future which waits for a child process to exit.
+pub struct Wait<'a, D: Drive> {
+    child: Pin<&'a mut Child<D>>,
+}
+
+impl<'a, D: Drive> Future for Wait<'a, D> {
+    type Output = io::Result<ExitStatus>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.child.as_mut().poll_wait(ctx)
+    }
+}
+
+/// A future which spawns a child process and waits for it to exit.
+pub struct Status<D: Drive = DefaultDriver>(Result<Child<D>, Option<io::Error>>);
+
+impl<D: Drive> Future for Status<D> {
+    type Output = io::Result<ExitStatus>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        match unsafe { &mut Pin::get_unchecked_mut(self).0 } {
+            Ok(child)   => unsafe { Pin::new_unchecked(child) }.poll_wait(ctx),
+            Err(err)    => {
+                let err = err.take().expect("polled Status future after completion");
+                Poll::Ready(Err(err))
+            }
+        }
+    }
+}
+
+/// A future which collects the output of a child process and waits for it to exit.
+pub struct WaitWithOutput<D: Drive = DefaultDriver> {
+    child: Result<Child<D>, Option<io::Error>>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl<D: Drive> Future for WaitWithOutput<D> {
+    type Output = io::Result<Output>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let child = match &mut this.child {
+            Ok(child)   => child,
+            Err(err)    => {
+                let err = err.take().expect("polled WaitWithOutput future after completion");
+                return Poll::Ready(Err(err));
+            }
+        };
+        // Both pipes are drained together, so that a child which fills one of them while this
+        // is waiting on the other cannot deadlock.
+        let stdout = poll_read_to_end(&mut child.stdout, ctx, &mut this.stdout)?;
+        let stderr = poll_read_to_end(&mut child.stderr, ctx, &mut this.stderr)?;
+        if stdout.is_pending() || stderr.is_pending() {
+            return Poll::Pending;
+        }
+        let status = ready!(unsafe { Pin::new_unchecked(child) }.poll_wait(ctx))?;
+        Poll::Ready(Ok(Output {
+            stdout: mem::take(&mut this.stdout),
+            stderr: mem::take(&mut this.stderr),
+            status,
+        }))
+    }
+}
+
+/// Read from a pipe until it reaches EOF, then drop it.
+fn poll_read_to_end<D: Drive>(
+    pipe: &mut Option<Receiver<D>>,
+    ctx: &mut Context<'_>,
+    buf: &mut Vec<u8>,
+) -> Poll<io::Result<()>> {
+    while let Some(receiver) = pipe {
+        let mut receiver = unsafe { Pin::new_unchecked(receiver) };
+        let data = ready!(receiver.as_mut().poll_fill_buf(ctx))?;
+        if data.is_empty() {
+            *pipe = None;
+        } else {
+            let len = data.len();
+            buf.extend_from_slice(data);
+            receiver.consume(len);
+        }
+    }
+    Poll::Ready(Ok(()))
+}
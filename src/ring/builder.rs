@@ -0,0 +1,145 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use iou::{SQE, SQEs};
+use iou::sqe::SubmissionFlags;
+
+use crate::drive::Drive;
+use crate::event;
+use crate::ring::{Ring, Cancellation};
+
+/// A builder for a [`Ring`] with non-default behavior.
+///
+/// Every event submitted through a ring constructed by this builder will be prepared according to
+/// this configuration. Because the builder is not tied to any driver, the same builder can be used
+/// to construct many rings; for example, all of the streams accepted by a listener.
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    timeout: Option<Duration>,
+    retry: Retry,
+    serialize: bool,
+}
+
+/// A policy for resubmitting events which failed with a transient error.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Retry {
+    /// Never resubmit an event; all errors are returned to the caller.
+    #[default]
+    Never,
+    /// Resubmit an event up to this many times if it fails with `EINTR`.
+    Interrupted(u32),
+    /// Resubmit an event up to this many times if it fails with `EINTR` or `EAGAIN`.
+    Transient(u32),
+}
+
+impl Builder {
+    /// Construct a builder with the default configuration.
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// Link a timeout to every event submitted through the ring.
+    ///
+    /// If the event does not complete within this duration, the kernel will cancel it and the
+    /// ring will return an error of kind `TimedOut`.
+    ///
+    /// The timeout is prepared on an additional SQE following the event. Events which consume
+    /// every SQE they are given (for example, by calling `SQEs::single`) leave no room for it, and
+    /// will be submitted without a timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Builder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the policy for resubmitting events which fail with a transient error.
+    pub fn retry(mut self, retry: Retry) -> Builder {
+        self.retry = retry;
+        self
+    }
+
+    /// Whether every event should wait for all previously submitted events to complete before it
+    /// is started (`IOSQE_IO_DRAIN`).
+    pub fn serialize(mut self, serialize: bool) -> Builder {
+        self.serialize = serialize;
+        self
+    }
+
+    /// Construct a ring on top of this driver.
+    pub fn build<D: Drive>(&self, driver: D) -> Ring<D> {
+        Ring::with_config(driver, self.config())
+    }
+
+    fn config(&self) -> Config {
+        Config {
+            timeout: self.timeout.map(|timeout| (timeout, Arc::new(event::timespec(timeout)))),
+            retry: self.retry,
+            serialize: self.serialize,
+        }
+    }
+}
+
+impl Retry {
+    fn allows(&self, err: &io::Error, attempts: u32) -> bool {
+        match *self {
+            Retry::Never                => false,
+            Retry::Interrupted(max)     => {
+                attempts < max && err.raw_os_error() == Some(libc::EINTR)
+            }
+            Retry::Transient(max)       => {
+                attempts < max && matches!(err.raw_os_error(), Some(libc::EINTR | libc::EAGAIN))
+            }
+        }
+    }
+}
+
+/// The configuration of a ring, as constructed by a `Builder`.
+#[derive(Clone, Default)]
+pub(crate) struct Config {
+    timeout: Option<(Duration, Arc<uring_sys::__kernel_timespec>)>,
+    retry: Retry,
+    serialize: bool,
+}
+
+impl Config {
+    /// The number of SQEs needed in addition to those needed by the event itself.
+    pub(crate) fn extra_sqes(&self) -> u32 {
+        self.timeout.is_some() as u32
+    }
+
+    /// Prepare an event, linking a timeout to it if one is configured. The timeout's SQE is
+    /// returned as well, so that its CQE can be reported to the event's completion: the kernel
+    /// completes it with `ETIME` if it cancelled the event.
+    pub(crate) fn prepare<'sq>(
+        &self,
+        sqs: &mut SQEs<'sq>,
+        prepare: impl FnOnce(&mut SQEs<'sq>) -> SQE<'sq>,
+    ) -> (SQE<'sq>, Option<SQE<'sq>>) {
+        let mut sqe = prepare(sqs);
+        if self.serialize {
+            sqe.set_flags(SubmissionFlags::IO_DRAIN);
+        }
+        let mut linked = None;
+        if let Some((_, ts)) = &self.timeout {
+            if let Some(mut timeout) = sqs.next() {
+                sqe.set_flags(SubmissionFlags::IO_LINK);
+                unsafe { timeout.prep_link_timeout(ts); }
+                linked = Some(timeout);
+            }
+        }
+        (sqe, linked)
+    }
+
+    pub(crate) fn should_retry(&self, err: &io::Error, attempts: u32) -> bool {
+        self.retry.allows(err, attempts)
+    }
+
+    /// The timespec of a linked timeout may still be read by the kernel after the ring has been
+    /// dropped, so it must be kept alive by the cancellation as well.
+    pub(crate) fn cancellation(&self, cancellation: Cancellation) -> Cancellation {
+        match &self.timeout {
+            Some((_, ts))   => cancellation.keep(ts.clone()),
+            None            => cancellation,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::any::Any;
 use std::ffi::CString;
+use std::io;
 use std::mem;
 use std::ptr;
 
@@ -17,10 +18,24 @@ pub struct Cancellation {
     data: *mut (),
     metadata: usize,
     drop: unsafe fn(*mut (), usize),
+    close_results: bool,
 }
 
+/// A type which can be stored in a [`Cancellation`] as a raw pointer and metadata word.
+///
+/// # Safety
+///
+/// Implementers must ensure that `drop_raw`, when passed the values returned by `into_raw`,
+/// correctly reconstructs and drops the original object.
 pub unsafe trait Cancel {
     fn into_raw(self) -> (*mut (), usize);
+
+    /// Drop an object which was converted into its raw parts with `into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// The arguments must be the values returned by a call to `into_raw` on the same type, and
+    /// they must not be used again after this is called.
     unsafe fn drop_raw(data: *mut (), metadata: usize);
 }
 
@@ -91,6 +106,11 @@ unsafe impl Cancel for () {
     unsafe fn drop_raw(_: *mut (), _: usize) { }
 }
 
+/// A [`Cancel`] type which does not use the metadata word of its raw representation.
+///
+/// # Safety
+///
+/// Implementers must accept any value for the metadata argument of `drop_raw`.
 pub unsafe trait CancelNarrow: Cancel { }
 
 unsafe impl<T> CancelNarrow for Box<T> { }
@@ -113,7 +133,30 @@ unsafe impl<T: CancelNarrow, U: CancelNarrow> Cancel for (T, U) {
 impl Cancellation {
     fn new<T: Cancel>(object: T) -> Cancellation {
         let (data, metadata) = object.into_raw();
-        Cancellation { data, metadata, drop: T::drop_raw }
+        Cancellation { data, metadata, drop: T::drop_raw, close_results: false }
+    }
+
+    /// Close the file descriptors which the event returns after it has been cancelled, like those
+    /// of accepted connections, rather than leaking them.
+    pub fn close_results(mut self) -> Cancellation {
+        self.close_results = true;
+        self
+    }
+
+    /// Keep `object` alive along with the resources of this cancellation.
+    pub(crate) fn keep<T: Any + Send + Sync>(self, object: T) -> Cancellation {
+        let close_results = self.close_results;
+        let shared: Box<dyn Any + Send + Sync> = Box::new((self, object));
+        let mut cancellation = Cancellation::from(shared);
+        cancellation.close_results = close_results;
+        cancellation
+    }
+
+    /// Clean up a result of the event which completed after it was cancelled.
+    pub(crate) fn complete(&self, result: io::Result<u32>) {
+        if let (true, Ok(fd)) = (self.close_results, result) {
+            unsafe { libc::close(fd as libc::c_int); }
+        }
     }
 }
 
@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::ring::Cancellation;
+
+/// A queue of events whose submissions were cancelled
+///
+/// When a [`Submission`](crate::Submission) is dropped before its event completes, the event
+/// (and any buffers it owns) normally has to stay alive until the kernel completes it, and is
+/// then destroyed. A submission which has been given a queue with
+/// [`reclaim_into`](crate::Submission::reclaim_into) instead pushes its event onto the queue once
+/// the kernel has completed it, so that its resources can be reused, for example by returning
+/// its buffer to a pool.
+///
+/// Clones of a queue share the same events.
+pub struct CancelledEvents<E> {
+    queue: Arc<Mutex<VecDeque<E>>>,
+}
+
+impl<E> CancelledEvents<E> {
+    /// Construct an empty queue.
+    pub fn new() -> CancelledEvents<E> {
+        CancelledEvents { queue: Arc::new(Mutex::new(VecDeque::new())) }
+    }
+
+    /// Take the event which was reclaimed first, if any have been.
+    pub fn pop(&self) -> Option<E> {
+        self.queue.lock().pop_front()
+    }
+
+    /// Take every event which has been reclaimed.
+    pub fn drain(&self) -> Vec<E> {
+        self.queue.lock().drain(..).collect()
+    }
+
+    /// The number of events which have been reclaimed and not yet taken.
+    pub fn len(&self) -> usize {
+        self.queue.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().is_empty()
+    }
+}
+
+impl<E: Send + 'static> CancelledEvents<E> {
+    /// A cancellation which pushes the event onto this queue when it is dropped.
+    pub(crate) fn cancellation(&self, event: E) -> Cancellation {
+        Cancellation::from(Box::new(Reclaim { event: Some(event), queue: self.clone() }))
+    }
+}
+
+impl<E> Clone for CancelledEvents<E> {
+    fn clone(&self) -> CancelledEvents<E> {
+        CancelledEvents { queue: self.queue.clone() }
+    }
+}
+
+impl<E> Default for CancelledEvents<E> {
+    fn default() -> CancelledEvents<E> {
+        CancelledEvents::new()
+    }
+}
+
+struct Reclaim<E> {
+    event: Option<E>,
+    queue: CancelledEvents<E>,
+}
+
+impl<E> Drop for Reclaim<E> {
+    fn drop(&mut self) {
+        if let Some(event) = self.event.take() {
+            self.queue.queue.lock().push_back(event);
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use std::collections::VecDeque;
 use std::io;
 use std::mem::{self, ManuallyDrop};
+use std::sync::Arc;
 use std::task::Waker;
 
 use parking_lot::Mutex;
 
 use crate::ring::Cancellation;
+use crate::scope::Tracker;
+use crate::sys;
 use iou::CQE;
 
 use State::*;
@@ -21,7 +25,36 @@ use State::*;
 /// This API is not publicly visible outside of this crate. (The Completion type in the public API
 /// is an opaque wrapper aroud this type). End users do not need to understand the completion API.
 pub struct Completion {
-    state: ManuallyDrop<Box<Mutex<State>>>,
+    state: ManuallyDrop<Box<Mutex<Inner>>>,
+}
+
+struct Inner {
+    state: State,
+    flags: u32,
+    // The scope's tracker, and the token it knows the event by
+    tracker: Option<(Arc<Tracker>, u64)>,
+    /// Results of a multishot event which have not been checked yet.
+    more: VecDeque<(io::Result<u32>, u32)>,
+    // Whether a scope is cancelling the event, so the completion must stay allocated until its
+    // cancellation has completed, and whether it was released in the meantime
+    held: bool,
+    released: bool,
+    // Whether the CQE of a timeout linked to the event has yet to arrive, the result of the event
+    // if it was cancelled before then, which waits for it, and whether the timeout fired
+    linked: bool,
+    deferred: Option<(io::Result<u32>, u32)>,
+    timed_out: bool,
+}
+
+/// The bit set in the user data of a linked timeout, whose CQE is reported to the completion of
+/// its event; completions are aligned, so their addresses never have it set.
+const LINKED_TIMEOUT: u64 = 1;
+
+impl Inner {
+    /// Whether the completion must stay allocated after it has been released.
+    fn in_use(&self) -> bool {
+        self.held || self.linked
+    }
 }
 
 enum State {
@@ -36,32 +69,77 @@ impl Completion {
     /// io-uring, the waker this completion holds will be awoken.
     pub fn new(waker: Waker) -> Completion {
         Completion {
-            state: ManuallyDrop::new(Box::new(Mutex::new(Submitted(waker)))),
+            state: ManuallyDrop::new(Box::new(Mutex::new(Inner {
+                state: Submitted(waker),
+                flags: 0,
+                tracker: None,
+                more: VecDeque::new(),
+                held: false,
+                released: false,
+                linked: false,
+                deferred: None,
+                timed_out: false,
+            }))),
         }
     }
 
     /// Get the address of this completion, so that it can set as the user_data field of the SQE
     /// being prepared.
     pub fn addr(&self) -> u64 {
-        &**self.state as *const Mutex<State> as usize as u64
+        &**self.state as *const Mutex<Inner> as usize as u64
+    }
+
+    /// Get the user data of a timeout linked to this event, whose CQE tells whether an event which
+    /// was cancelled timed out.
+    pub fn link_timeout(&self) -> u64 {
+        self.state.lock().linked = true;
+        self.addr() | LINKED_TIMEOUT
+    }
+
+    /// Report the completion of this event to a scope's tracker, which must wait for it.
+    pub fn track(&self, tracker: Arc<Tracker>) {
+        let token = tracker.start(self.addr());
+        self.state.lock().tracker = Some((tracker, token));
     }
 
     /// Check if the completion has completed. If it has, the result of the completion will be
     /// returned and the completion will be deallocated. If it has not been completed, the waker
     /// field will be updated to the new waker if the old waker would not wake the same task.
     pub fn check(self, waker: &Waker) -> Result<io::Result<u32>, Completion> {
-        let mut state = self.state.lock();
-        match mem::replace(&mut *state, State::Empty) {
+        self.check_with_flags(waker).map(|(result, _)| result)
+    }
+
+    /// Check if the completion has completed, like `check`, also returning the flags of its CQE.
+    pub fn check_with_flags(self, waker: &Waker) -> Result<(io::Result<u32>, u32), Completion> {
+        self.check_multishot(waker).map(|(result, flags, more)| {
+            debug_assert!(more.is_none(), "checked a multishot event with check_with_flags");
+            (result, flags)
+        })
+    }
+
+    /// Check if a multishot event has completed, like `check_with_flags`. A result which the
+    /// event will follow with more is returned along with the completion, which must be checked
+    /// again for the rest of them.
+    pub fn check_multishot(self, waker: &Waker)
+        -> Result<(io::Result<u32>, u32, Option<Completion>), Completion>
+    {
+        let mut inner = self.state.lock();
+        if let Some((result, flags)) = inner.more.pop_front() {
+            drop(inner);
+            return Ok((result, flags, Some(self)));
+        }
+        match mem::replace(&mut inner.state, State::Empty) {
             Submitted(old_waker)    => {
                 let waker = if old_waker.will_wake(waker) { old_waker } else { waker.clone() };
-                *state = Submitted(waker);
-                drop(state);
+                inner.state = Submitted(waker);
+                drop(inner);
                 Err(self)
             }
             Completed(result)       => {
-                drop(state);
-                drop(ManuallyDrop::into_inner(self.state));
-                Ok(result)
+                let flags = inner.flags;
+                drop(inner);
+                self.release();
+                Ok((result, flags, None))
             }
             _                       => unreachable!()
         }
@@ -70,51 +148,188 @@ impl Completion {
     /// Cancel interest in this completion. The Cancellation callback will be stored to clean up
     /// resources shared with the kernel when the event completes.
     pub fn cancel(self, callback: Cancellation) {
-        let mut state = self.state.lock();
-        match &*state {
-            Submitted(_)    => {
-                *state = Cancelled(callback);
-                drop(state);
+        let mut inner = self.state.lock();
+        for (result, _) in inner.more.drain(..) {
+            callback.complete(result);
+        }
+        match mem::replace(&mut inner.state, State::Empty) {
+            Submitted(_)        => {
+                inner.state = Cancelled(callback);
+                drop(inner);
             }
-            Completed(_)    => {
+            Completed(result)   => {
+                callback.complete(result);
                 drop(callback);
-                drop(state);
-                drop(ManuallyDrop::into_inner(self.state));
+                drop(inner);
+                self.release();
             }
-            _               => unreachable!()
+            _                   => unreachable!()
         }
     }
 
-    fn complete(self, result: io::Result<u32>) {
-        let mut state = self.state.lock();
-        match mem::replace(&mut *state, State::Empty) {
+    fn complete(self, result: io::Result<u32>, flags: u32) {
+        if flags & sys::IORING_CQE_F_MORE != 0 {
+            return self.complete_more(result, flags);
+        }
+        let mut result = result;
+        if result.as_ref().is_err_and(|err| err.raw_os_error() == Some(libc::ECANCELED)) {
+            let mut inner = self.state.lock();
+            if inner.linked {
+                inner.deferred = Some((result, flags));
+                return;
+            } else if inner.timed_out {
+                result = Err(io::Error::from_raw_os_error(libc::ETIMEDOUT));
+            }
+        }
+        // The scope's tracker stops tracking the event while the completion is still allocated,
+        // since a scope may cancel any event it tracks by its address.
+        let tracker = self.state.lock().tracker.take();
+        if let Some((tracker, token)) = tracker {
+            tracker.finish(token);
+        }
+        let mut inner = self.state.lock();
+        inner.flags = flags;
+        match mem::replace(&mut inner.state, State::Empty) {
             Submitted(waker)    => {
-                *state = Completed(result);
+                inner.state = Completed(result);
+                drop(inner);
                 waker.wake();
             }
             Cancelled(callback) => {
+                callback.complete(result);
                 drop(callback);
-                drop(state);
-                drop(ManuallyDrop::into_inner(self.state));
+                drop(inner);
+                self.release();
+            }
+            _                   => unreachable!()
+        }
+    }
+
+    /// Report a result of a multishot event which will complete again, so the completion stays
+    /// allocated.
+    fn complete_more(self, result: io::Result<u32>, flags: u32) {
+        let mut inner = self.state.lock();
+        match &inner.state {
+            Submitted(waker)    => {
+                let waker = waker.clone();
+                inner.more.push_back((result, flags));
+                drop(inner);
+                waker.wake();
             }
+            Cancelled(callback) => callback.complete(result),
             _                   => unreachable!()
         }
     }
+
+    /// Report the result of the timeout linked to this event. The kernel cancels an event whose
+    /// linked timeout fires, and completes the timeout with `ETIME`.
+    fn complete_timeout(self, result: io::Result<u32>) {
+        let mut inner = self.state.lock();
+        inner.linked = false;
+        inner.timed_out = result.is_err_and(|err| err.raw_os_error() == Some(libc::ETIME));
+        if let Some((result, flags)) = inner.deferred.take() {
+            drop(inner);
+            return self.complete(result, flags);
+        }
+        if inner.released && !inner.in_use() {
+            drop(inner);
+            drop(ManuallyDrop::into_inner(self.state));
+        }
+    }
+
+    /// Deallocate the completion, unless a scope is holding it or its linked timeout has not
+    /// completed; then it is deallocated once neither is the case.
+    fn release(self) {
+        let mut inner = self.state.lock();
+        if inner.in_use() {
+            inner.released = true;
+            return;
+        }
+        drop(inner);
+        drop(ManuallyDrop::into_inner(self.state));
+    }
+}
+
+/// Keeps the completion of an event allocated while a scope cancels it, so that its address is
+/// not reused by another event which the cancellation could find instead.
+pub(crate) struct Hold {
+    addr: u64,
 }
 
+impl Hold {
+    /// Hold the completion at `addr`.
+    ///
+    /// # Safety
+    ///
+    /// The completion must still be allocated, and must not be held already.
+    pub(crate) unsafe fn new(addr: u64) -> Hold {
+        (*(addr as *const Mutex<Inner>)).lock().held = true;
+        Hold { addr }
+    }
+
+    pub(crate) fn addr(&self) -> u64 {
+        self.addr
+    }
+}
+
+impl Drop for Hold {
+    fn drop(&mut self) {
+        let state = self.addr as *mut Mutex<Inner>;
+        let mut inner = unsafe { (*state).lock() };
+        inner.held = false;
+        if inner.released && !inner.in_use() {
+            drop(inner);
+            drop(unsafe { Box::from_raw(state) });
+        }
+    }
+}
+
+/// Complete the event of a CQE.
+///
+/// iou's `CQE` drops the flags it does not know, so events which report a selected buffer or
+/// complete more than once need their CQEs to be completed with `complete_raw`.
 pub fn complete(cqe: CQE) {
-    unsafe {
-        let result = cqe.result();
-        let user_data = cqe.user_data();
-        // iou should never raise LIBURING_UDATA_TIMEOUTs, this is just to catch bugs in iou
-        debug_assert!(user_data != uring_sys::LIBURING_UDATA_TIMEOUT);
-        let state = user_data as *mut Mutex<State>;
-
-        if !state.is_null() {
-            let completion = Completion {
-                state: ManuallyDrop::new(Box::from_raw(state))
-            };
-            completion.complete(result);
+    complete_addr(cqe.user_data(), cqe.result(), cqe.raw_flags());
+}
+
+/// Complete the event of a CQE read directly from the completion queue, with all of its flags.
+pub(crate) fn complete_raw(cqe: uring_sys::io_uring_cqe) {
+    let result = match cqe.res {
+        res if res < 0  => Err(io::Error::from_raw_os_error(-res)),
+        res             => Ok(res as u32),
+    };
+    complete_addr(cqe.user_data, result, cqe.flags);
+}
+
+fn complete_addr(user_data: u64, result: io::Result<u32>, flags: u32) {
+    // iou should never raise LIBURING_UDATA_TIMEOUTs, this is just to catch bugs in iou
+    debug_assert!(user_data != uring_sys::LIBURING_UDATA_TIMEOUT);
+    let state = (user_data & !LINKED_TIMEOUT) as *mut Mutex<Inner>;
+
+    if !state.is_null() {
+        let completion = Completion {
+            state: ManuallyDrop::new(unsafe { Box::from_raw(state) }),
+        };
+        match user_data & LINKED_TIMEOUT {
+            0   => completion.complete(result, flags),
+            _   => completion.complete_timeout(result),
         }
+    }
+}
+
+/// Whether a CQE is the last one of an event, rather than one of the results of a multishot event
+/// which will complete again, or that of an SQE prepared alongside an event, like a linked
+/// timeout.
+pub(crate) fn is_final(cqe: &uring_sys::io_uring_cqe) -> bool {
+    cqe.user_data != 0
+        && cqe.user_data & LINKED_TIMEOUT == 0
+        && cqe.flags & sys::IORING_CQE_F_MORE == 0
+}
+
+/// Complete an event which was emulated on a thread, rather than submitted to io-uring.
+pub(crate) fn complete_emulated(addr: u64, result: io::Result<u32>) {
+    let completion = Completion {
+        state: ManuallyDrop::new(unsafe { Box::from_raw(addr as *mut Mutex<Inner>) }),
     };
+    completion.complete(result, 0);
 }
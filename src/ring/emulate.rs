@@ -0,0 +1,181 @@
+//! Emulation of the operations the kernel does not support
+//!
+//! Which opcodes the kernel supports is probed once, on a throwaway io-uring instance. An event
+//! whose opcode is missing is run with blocking syscalls on a pool of threads instead, and
+//! completes through the same completion state as an event run by the kernel, so it is waited on
+//! and cancelled in the same way.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use once_cell::sync::Lazy;
+use parking_lot::{Condvar, Mutex};
+
+use crate::drive::Drive;
+use crate::sys;
+
+use super::{Ring, State, Completion};
+use super::completion;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Whether the kernel supports an io-uring opcode.
+pub(crate) fn is_supported(opcode: u8) -> bool {
+    static PROBE: Lazy<Option<Box<sys::io_uring_probe>>> = Lazy::new(|| probe().ok());
+
+    match &*PROBE {
+        Some(probe) => {
+            opcode <= probe.last_op
+                && probe.ops[opcode as usize].flags & sys::IO_URING_OP_SUPPORTED != 0
+        }
+        // Probing was added in the same release as IORING_OP_FALLOCATE, so a kernel which cannot
+        // be probed supports none of the opcodes from that one on.
+        None        => opcode < uring_sys::IoRingOp::IORING_OP_FALLOCATE as u8,
+    }
+}
+
+fn probe() -> io::Result<Box<sys::io_uring_probe>> {
+    unsafe {
+        let mut params: uring_sys::io_uring_params = mem::zeroed();
+        let fd = match libc::syscall(libc::SYS_io_uring_setup, 1, &mut params) {
+            -1  => return Err(io::Error::last_os_error()),
+            fd  => fd as RawFd,
+        };
+        let mut probe: Box<sys::io_uring_probe> = Box::new(mem::zeroed());
+        let arg = &mut *probe as *mut sys::io_uring_probe as *mut libc::c_void;
+        let result = sys::register(fd, sys::IORING_REGISTER_PROBE, arg, 256);
+        libc::close(fd);
+        result.map(|()| probe)
+    }
+}
+
+struct Pool {
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+struct PoolState {
+    jobs: VecDeque<Job>,
+    idle: usize,
+}
+
+static POOL: Lazy<Pool> = Lazy::new(|| Pool {
+    state: Mutex::new(PoolState { jobs: VecDeque::new(), idle: 0 }),
+    available: Condvar::new(),
+});
+
+/// Run a job on the pool, starting another thread if every thread is busy.
+///
+/// Emulated operations can block for as long as the operation would have been pending in the
+/// kernel, like an accept waiting for a connection, so the pool is not bounded.
+fn spawn(job: Job) {
+    let mut state = POOL.state.lock();
+    state.jobs.push_back(job);
+    if state.jobs.len() > state.idle {
+        drop(state);
+        thread::spawn(work);
+    } else {
+        drop(state);
+        POOL.available.notify_one();
+    }
+}
+
+/// Run blocking work other than an emulated event on the pool, like resolving a host name,
+/// returning a future which completes with its result.
+pub(crate) fn spawn_blocking<T: Send + 'static>(job: impl FnOnce() -> T + Send + 'static)
+    -> Blocking<T>
+{
+    let slot = Arc::new(Mutex::new(Slot { result: None, waker: None }));
+    let shared = slot.clone();
+    spawn(Box::new(move || {
+        let result = job();
+        let mut slot = shared.lock();
+        slot.result = Some(result);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }));
+    Blocking { slot }
+}
+
+/// The result of work run on the pool with `spawn_blocking`
+///
+/// Dropping this does not stop the work, whose result is discarded when it completes.
+pub(crate) struct Blocking<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+struct Slot<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.slot.lock();
+        match slot.result.take() {
+            Some(result)    => Poll::Ready(result),
+            None            => {
+                slot.waker = Some(ctx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn work() {
+    let mut state = POOL.state.lock();
+    loop {
+        match state.jobs.pop_front() {
+            Some(job)   => {
+                drop(state);
+                job();
+                state = POOL.state.lock();
+            }
+            None        => {
+                state.idle += 1;
+                POOL.available.wait(&mut state);
+                state.idle -= 1;
+            }
+        }
+    }
+}
+
+impl<D: Drive> Ring<D> {
+    /// Whether an event has been started on the ring and not yet completed, so that polling the
+    /// ring waits for it rather than preparing another.
+    pub(crate) fn is_running(&self) -> bool {
+        matches!(self.state, State::Prepared(_) | State::Submitted(_))
+    }
+
+    /// Start an event which is emulated with `run` on the emulation pool, rather than prepared on
+    /// io-uring. Polling the ring waits for it to complete, as it would for any other event.
+    ///
+    /// Emulated events are not submitted through the driver, so they are not tracked by scopes.
+    pub(crate) fn start_emulated(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        run: Box<dyn FnOnce() -> io::Result<u32> + Send>,
+    ) {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        match this.state {
+            // A cancelled event keeps running until it completes, releasing its resources then;
+            // nothing needs to be cancelled on io-uring before starting a job on the pool.
+            State::Inert | State::Cancelled(_)  => { }
+            _                                   => panic!("started an emulated event on a busy Ring"),
+        }
+        let completion = Completion::new(ctx.waker().clone());
+        let addr = completion.addr();
+        this.state = State::Submitted(completion);
+        spawn(Box::new(move || completion::complete_emulated(addr, run())));
+    }
+}
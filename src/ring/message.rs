@@ -0,0 +1,224 @@
+//! Messages between io-uring instances
+//!
+//! A [`Mailbox`] is a future which completes when a message is posted to it. It does not belong
+//! to any io-uring instance: its [`Token`] is delivered with `IORING_OP_MSG_RING` to whichever
+//! instance a [`RingHandle`] refers to, and the message wakes the task waiting on the mailbox
+//! when that instance's completions are processed. This allows a thread to wake a task parked on
+//! another thread's ring, without any other synchronization.
+
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use futures_core::ready;
+use iou::CQE;
+use iou::cqe::CompletionFlags;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::ring::{Ring, Cancellation, Completion};
+use crate::sys;
+
+/// A handle to an io-uring instance, which can be used to post messages to it from any thread.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RingHandle {
+    fd: RawFd,
+}
+
+impl RingHandle {
+    /// Get a handle to the io-uring instance of this driver.
+    ///
+    /// This returns an error of kind `Unsupported` if the driver does not expose its instance.
+    ///
+    /// # Safety
+    ///
+    /// Any driver can implement [`Drive::ring_fd`], so the fd it returns is trusted as by
+    /// [`RingHandle::from_raw_fd`]: it must be an io-uring instance, and every completion posted
+    /// to it must be passed to ringbahn's [`complete`](crate::drive::complete) function while the
+    /// handle is in use. The drivers provided by ringbahn meet these requirements.
+    pub unsafe fn of<D: Drive>(driver: &D) -> io::Result<RingHandle> {
+        match driver.ring_fd() {
+            Some(fd)    => Ok(RingHandle { fd }),
+            None        => Err(io::Error::new(io::ErrorKind::Unsupported,
+                                              "driver does not expose its io-uring instance")),
+        }
+    }
+
+    /// Construct a handle from the file descriptor of an io-uring instance.
+    ///
+    /// # Safety
+    ///
+    /// The fd must be an io-uring instance, and every completion posted to it must be passed to
+    /// ringbahn's [`complete`](crate::drive::complete) function while the handle is in use.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> RingHandle {
+        RingHandle { fd }
+    }
+
+    /// The file descriptor of the io-uring instance.
+    pub fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Post a message to the mailbox this token belongs to, through the io-uring instance of this
+    /// handle, using the default driver to submit the message.
+    ///
+    /// Messages are limited to 31 bits; larger messages fail with `InvalidInput`.
+    pub fn send(&self, token: Token, message: u32) -> SendMessage<DefaultDriver> {
+        self.send_on_driver(token, message, DefaultDriver::default())
+    }
+
+    /// Post a message to the mailbox this token belongs to, through the io-uring instance of this
+    /// handle, using the provided driver to submit the message.
+    pub fn send_on_driver<D: Drive>(&self, token: Token, message: u32, driver: D)
+        -> SendMessage<D>
+    {
+        SendMessage {
+            ring: Ring::new(driver),
+            fd: self.fd,
+            token: Some(token),
+            message,
+            polled: false,
+        }
+    }
+}
+
+/// A future which completes with the first message posted to its [`Token`].
+///
+/// If the token is dropped without a message being posted to it, or posting the message fails,
+/// the mailbox completes with an error instead.
+pub struct Mailbox {
+    completion: Option<Completion>,
+}
+
+/// The sending half of a [`Mailbox`].
+///
+/// A token can be sent to any thread, and can be used to post exactly one message.
+#[derive(Debug)]
+pub struct Token {
+    addr: u64,
+}
+
+impl Mailbox {
+    /// Construct a mailbox and the token used to post a message to it.
+    pub fn new() -> (Mailbox, Token) {
+        let completion = Completion::new(Waker::from(Arc::new(Unregistered)));
+        let token = Token { addr: completion.addr() };
+        (Mailbox { completion: Some(completion) }, token)
+    }
+}
+
+impl Future for Mailbox {
+    type Output = io::Result<u32>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u32>> {
+        let completion = self.completion.take().expect("polled Mailbox after completion");
+        match completion.check(ctx.waker()) {
+            Ok(result)      => Poll::Ready(result),
+            Err(completion) => {
+                self.completion = Some(completion);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Mailbox {
+    fn drop(&mut self) {
+        if let Some(completion) = self.completion.take() {
+            completion.cancel(Cancellation::from(()));
+        }
+    }
+}
+
+impl Token {
+    fn fail(self, errno: i32) {
+        let addr = self.addr;
+        mem::forget(self);
+        super::completion::complete(CQE::from_raw_parts(addr, -errno, CompletionFlags::empty()));
+    }
+}
+
+impl Drop for Token {
+    fn drop(&mut self) {
+        super::completion::complete(CQE::from_raw_parts(self.addr, -libc::EPIPE,
+                                                        CompletionFlags::empty()));
+    }
+}
+
+/// A future which posts a message to a [`Mailbox`].
+pub struct SendMessage<D: Drive> {
+    ring: Ring<D>,
+    fd: RawFd,
+    token: Option<Token>,
+    message: u32,
+    polled: bool,
+}
+
+impl<D: Drive> SendMessage<D> {
+    #[allow(clippy::type_complexity)]
+    fn split(self: Pin<&mut Self>)
+        -> (Pin<&mut Ring<D>>, RawFd, &mut Option<Token>, u32, &mut bool)
+    {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let ring = Pin::new_unchecked(&mut this.ring);
+            (ring, this.fd, &mut this.token, this.message, &mut this.polled)
+        }
+    }
+}
+
+impl<D: Drive> Future for SendMessage<D> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (ring, fd, token, message, polled) = self.split();
+        let addr = token.as_ref().expect("polled SendMessage after completion").addr;
+        if message > i32::MAX as u32 {
+            token.take().unwrap().fail(libc::EINVAL);
+            return Poll::Ready(Err(io::Error::from_raw_os_error(libc::EINVAL)));
+        }
+        *polled = true;
+        let result = ready!(ring.poll(ctx, 1, |sqs| unsafe {
+            let mut sqe = sqs.next().unwrap();
+            sys::prep_raw(&mut sqe, sys::IORING_OP_MSG_RING, fd, 0, message, addr);
+            sqe
+        }));
+        let token = token.take().unwrap();
+        match result {
+            Ok(_)       => {
+                // The message has been posted to the mailbox; it is now responsible for the
+                // completion.
+                mem::forget(token);
+                Poll::Ready(Ok(()))
+            }
+            Err(err)    => {
+                token.fail(err.raw_os_error().unwrap_or(libc::EIO));
+                Poll::Ready(Err(err))
+            }
+        }
+    }
+}
+
+impl<D: Drive> Drop for SendMessage<D> {
+    fn drop(&mut self) {
+        if !self.polled {
+            return;
+        }
+        if let Some(token) = self.token.take() {
+            // Whether or not the message will be posted is unknown, so the token cannot fail the
+            // mailbox. If the message is never posted, the mailbox's completion is leaked.
+            mem::forget(token);
+            self.ring.cancel(Cancellation::from(()));
+        }
+    }
+}
+
+/// The waker of a mailbox which has not yet been polled.
+struct Unregistered;
+
+impl Wake for Unregistered {
+    fn wake(self: Arc<Self>) { }
+}
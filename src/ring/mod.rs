@@ -1,4 +1,10 @@
+mod builder;
 mod cancellation;
+mod cancelled;
+mod emulate;
+mod message;
+mod napi;
+mod shared;
 pub(crate) mod completion;
 
 use std::io;
@@ -10,9 +16,17 @@ use futures_core::ready;
 use iou::{SQE, SQEs};
 
 use crate::drive::{self, Drive};
+use crate::drive::DefaultDriver;
 
+pub use builder::{Builder, Retry};
 pub use cancellation::{Cancellation, Cancel, CancelNarrow};
+pub use cancelled::CancelledEvents;
+pub use message::{RingHandle, Mailbox, Token, SendMessage};
+pub use napi::Napi;
+pub use shared::SharedRing;
+pub(crate) use builder::Config;
 pub(crate) use completion::Completion;
+pub(crate) use emulate::{is_supported, spawn_blocking, Blocking};
 
 use State::*;
 
@@ -28,9 +42,12 @@ use State::*;
 /// whatever type of IO they would be attempting to submit. Additionally, users should note that
 /// `Ring` does not implement `Drop`. In order to cancel any ongoing IO, users are responsible for
 /// implementing drop to call cancel properly.
-pub struct Ring<D: Drive> {
+pub struct Ring<D: Drive = DefaultDriver> {
     state: State,
     driver: D,
+    config: Config,
+    attempts: u32,
+    flags: u32,
 }
 
 enum State {
@@ -50,7 +67,14 @@ impl<D: Default + Drive> Default for Ring<D> {
 
 impl<D: Drive + Clone> Clone for Ring<D> {
     fn clone(&self) -> Ring<D> {
-        Ring::new(self.driver.clone())
+        Ring::with_config(self.driver.clone(), self.config.clone())
+    }
+}
+
+impl Ring {
+    /// Construct a builder to configure a Ring before it is constructed.
+    pub fn builder() -> Builder {
+        Builder::new()
     }
 }
 
@@ -58,9 +82,16 @@ impl<D: Drive> Ring<D> {
     /// Construct a new Ring on top of a driver.
     #[inline(always)]
     pub fn new(driver: D) -> Ring<D> {
+        Ring::with_config(driver, Config::default())
+    }
+
+    #[inline(always)]
+    pub(crate) fn with_config(driver: D, config: Config) -> Ring<D> {
         Ring {
             state: Inert,
-            driver
+            attempts: 0,
+            flags: 0,
+            driver, config,
         }
     }
 
@@ -68,12 +99,29 @@ impl<D: Drive> Ring<D> {
         &self.driver
     }
 
+    /// The flags of the CQE of the last event this ring completed.
+    ///
+    /// Some events report information in these flags, like the id of the buffer chosen for a
+    /// buffer-select read, which is stored in their upper 16 bits.
+    pub fn completion_flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Construct a ring on another driver with the same configuration as this ring.
+    pub(crate) fn with_driver<E: Drive>(&self, driver: E) -> Ring<E> {
+        Ring::with_config(driver, self.config.clone())
+    }
+
     /// Poll the ring state machine.
     ///
     /// This accepts a callback, `prepare`, which prepares an event to be submitted to io-uring.
     /// This callback will only be called once during an iteration of ring's state machine: once an
     /// event has been prepared, until it is completed or cancelled, a single ring instance will
     /// not prepare any additional events.
+    ///
+    /// If the ring was constructed with a retry policy, an event which fails with a transient
+    /// error will be prepared again with the `prepare` callback passed to the call which observed
+    /// the failure.
     #[inline]
     pub fn poll(
         mut self: Pin<&mut Self>,
@@ -81,26 +129,73 @@ impl<D: Drive> Ring<D> {
         count: u32,
         prepare: impl for<'sq> FnOnce(&mut SQEs<'sq>) -> SQE<'sq>,
     ) -> Poll<io::Result<u32>> {
-        match self.state {
+        let result = match self.state {
             Inert | Cancelled(_) => {
                 ready!(self.as_mut().poll_prepare(ctx, count, prepare));
                 ready!(self.as_mut().poll_submit(ctx));
-                Poll::Pending
+                return Poll::Pending;
             }
             Prepared(_)             => {
                 match self.as_mut().poll_complete(ctx) {
-                    ready @ Poll::Ready(..) => ready,
+                    Poll::Ready(result)     => result,
                     Poll::Pending           => {
                         ready!(self.poll_submit(ctx));
-                        Poll::Pending
+                        return Poll::Pending;
                     }
                 }
             }
-            Submitted(_)            => self.poll_complete(ctx),
+            Submitted(_)            => ready!(self.as_mut().poll_complete(ctx)),
             Lost                    => panic!("Ring in a bad state; driver is faulty"),
+        };
+
+        let this = unsafe { Pin::get_unchecked_mut(self.as_mut()) };
+        match result {
+            Err(err) if this.config.should_retry(&err, this.attempts) => {
+                this.attempts += 1;
+                ready!(self.as_mut().poll_prepare(ctx, count, prepare));
+                ready!(self.as_mut().poll_submit(ctx));
+                Poll::Pending
+            }
+            result                                                  => {
+                this.attempts = 0;
+                Poll::Ready(result)
+            }
         }
     }
 
+    /// Poll the ring state machine for a multishot event, which the kernel completes once for
+    /// each of its results.
+    ///
+    /// This returns each result along with whether the event will complete again. Until it
+    /// returns `false`, the event stays submitted and polling the ring returns its next result
+    /// rather than preparing another event with `prepare`. Multishot events are not retried.
+    pub fn poll_multishot(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        count: u32,
+        prepare: impl for<'sq> FnOnce(&mut SQEs<'sq>) -> SQE<'sq>,
+    ) -> Poll<(io::Result<u32>, bool)> {
+        let (result, more) = match self.state {
+            Inert | Cancelled(_) => {
+                ready!(self.as_mut().poll_prepare(ctx, count, prepare));
+                ready!(self.as_mut().poll_submit(ctx));
+                return Poll::Pending;
+            }
+            Prepared(_)             => {
+                match self.as_mut().poll_complete_multishot(ctx) {
+                    Poll::Ready(result)     => result,
+                    Poll::Pending           => {
+                        ready!(self.poll_submit(ctx));
+                        return Poll::Pending;
+                    }
+                }
+            }
+            Submitted(_)            => ready!(self.as_mut().poll_complete_multishot(ctx)),
+            Lost                    => panic!("Ring in a bad state; driver is faulty"),
+        };
+        Poll::Ready((result, more))
+    }
+
     #[inline(always)]
     fn poll_prepare(
         self: Pin<&mut Self>,
@@ -108,21 +203,22 @@ impl<D: Drive> Ring<D> {
         count: u32,
         prepare: impl for<'sq> FnOnce(&mut SQEs<'sq>) -> SQE<'sq>,
     ) -> Poll<()> {
-        let (driver, state) = self.split();
+        let (driver, state, config) = self.split();
+        let count = count + config.extra_sqes();
         let completion = match *state {
             Cancelled(prev) => {
                 ready!(driver.poll_prepare(ctx, count + 1, |mut sqs, ctx| {
                     *state = Lost;
                     unsafe { sqs.hard_linked().next().unwrap().prep_cancel(prev, 0); }
-                    let sqe = prepare(&mut sqs);
-                    drive::Completion::new(sqe, sqs, ctx)
+                    let (sqe, timeout) = config.prepare(&mut sqs, prepare);
+                    drive::Completion::new(sqe, sqs, ctx).linked(timeout)
                 }))
             }
             Inert           => {
                 ready!(driver.poll_prepare(ctx, count, |mut sqs, ctx| {
                     *state = Lost;
-                    let sqe = prepare(&mut sqs);
-                    drive::Completion::new(sqe, sqs, ctx)
+                    let (sqe, timeout) = config.prepare(&mut sqs, prepare);
+                    drive::Completion::new(sqe, sqs, ctx).linked(timeout)
                 }))
             }
             _               => unreachable!(),
@@ -133,7 +229,7 @@ impl<D: Drive> Ring<D> {
 
     #[inline(always)]
     fn poll_submit(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<()> {
-        let (driver, state) = self.split();
+        let (driver, state, ..) = self.split();
         // TODO figure out how to handle this result
         let _ = ready!(driver.poll_submit(ctx));
         if let Prepared(completion) | Submitted(completion) = mem::replace(state, Lost) {
@@ -146,12 +242,14 @@ impl<D: Drive> Ring<D> {
 
     #[inline(always)]
     fn poll_complete(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u32>> {
-        let (_, state) = self.split();
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let state = &mut this.state;
         match mem::replace(state, Lost) {
             Prepared(completion)    => {
-                match completion.check(ctx.waker()) {
-                    Ok(result)      => {
+                match completion.check_with_flags(ctx.waker()) {
+                    Ok((result, flags)) => {
                         *state = Inert;
+                        this.flags = flags;
                         Poll::Ready(result)
                     }
                     Err(completion) => {
@@ -161,9 +259,10 @@ impl<D: Drive> Ring<D> {
                 }
             }
             Submitted(completion)   => {
-                match completion.check(ctx.waker()) {
-                    Ok(result)      => {
+                match completion.check_with_flags(ctx.waker()) {
+                    Ok((result, flags)) => {
                         *state = Inert;
+                        this.flags = flags;
                         Poll::Ready(result)
                     }
                     Err(completion) => {
@@ -176,26 +275,61 @@ impl<D: Drive> Ring<D> {
         }
     }
 
+    #[inline(always)]
+    fn poll_complete_multishot(self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<(io::Result<u32>, bool)>
+    {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let state = &mut this.state;
+        let (completion, restore): (_, fn(Completion) -> State) = match mem::replace(state, Lost) {
+            Prepared(completion)    => (completion, Prepared),
+            Submitted(completion)   => (completion, Submitted),
+            _                       => unreachable!(),
+        };
+        match completion.check_multishot(ctx.waker()) {
+            Ok((result, flags, more))   => {
+                this.flags = flags;
+                let more = match more {
+                    Some(completion)    => {
+                        *state = restore(completion);
+                        true
+                    }
+                    None                => {
+                        *state = Inert;
+                        false
+                    }
+                };
+                Poll::Ready((result, more))
+            }
+            Err(completion)             => {
+                *state = restore(completion);
+                Poll::Pending
+            }
+        }
+    }
+
     /// Cancel any ongoing IO with this cancellation.
     ///
     /// Users are responsible for ensuring that the cancellation passed would be appropriate to
     /// clean up the resources of the running event.
     #[inline]
     pub fn cancel(&mut self, cancellation: Cancellation) {
-        self.state.cancel(cancellation);
+        self.attempts = 0;
+        self.state.cancel(self.config.cancellation(cancellation));
     }
 
     /// Cancel any ongoing IO, but from a pinned reference.
     ///
     /// This has the same behavior of as Ring::cancel.
     pub fn cancel_pinned(self: Pin<&mut Self>, cancellation: Cancellation) {
-        self.split().1.cancel(cancellation);
+        unsafe { Pin::get_unchecked_mut(self).cancel(cancellation) }
     }
 
-    fn split(self: Pin<&mut Self>) -> (Pin<&mut D>, &mut State) {
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut D>, &mut State, &Config) {
         unsafe {
             let this = Pin::get_unchecked_mut(self);
-            (Pin::new_unchecked(&mut this.driver), &mut this.state)
+            let driver = Pin::new_unchecked(&mut this.driver);
+            (driver, &mut this.state, &this.config)
         }
     }
 }
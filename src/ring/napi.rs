@@ -0,0 +1,44 @@
+use std::io;
+use std::time::Duration;
+
+use crate::sys;
+
+use super::RingHandle;
+
+/// The NAPI busy-poll configuration of an io-uring instance
+///
+/// When NAPI is registered, waiting for completions on the instance busy-polls the network
+/// devices of the sockets it is receiving on, instead of sleeping until an interrupt arrives.
+/// This trades CPU time for lower network latency.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Napi {
+    /// How long to busy-poll before sleeping; this has microsecond precision
+    pub busy_poll_timeout: Duration,
+    /// Whether to prefer busy-polling over the network device's interrupts
+    pub prefer_busy_poll: bool,
+}
+
+impl RingHandle {
+    /// Enable NAPI busy-polling on the io-uring instance.
+    pub fn register_napi(&self, napi: Napi) -> io::Result<()> {
+        let mut arg = sys::io_uring_napi {
+            busy_poll_to: napi.busy_poll_timeout.as_micros().min(u32::MAX as u128) as u32,
+            prefer_busy_poll: napi.prefer_busy_poll as u8,
+            ..Default::default()
+        };
+        let arg = &mut arg as *mut sys::io_uring_napi as *mut libc::c_void;
+        unsafe { sys::register(self.as_raw_fd(), sys::IORING_REGISTER_NAPI, arg, 1) }
+    }
+
+    /// Disable NAPI busy-polling on the io-uring instance, returning the configuration which was
+    /// registered.
+    pub fn unregister_napi(&self) -> io::Result<Napi> {
+        let mut arg = sys::io_uring_napi::default();
+        let ptr = &mut arg as *mut sys::io_uring_napi as *mut libc::c_void;
+        unsafe { sys::register(self.as_raw_fd(), sys::IORING_UNREGISTER_NAPI, ptr, 1)?; }
+        Ok(Napi {
+            busy_poll_timeout: Duration::from_micros(arg.busy_poll_to as u64),
+            prefer_busy_poll: arg.prefer_busy_poll != 0,
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use std::io;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use iou::SQEs;
+use parking_lot::Mutex;
+
+use crate::drive::{Drive, Completion, DefaultDriver};
+
+/// A driver which can be shared between many tasks and threads
+///
+/// Cloning a `SharedRing` does not clone the driver it wraps: every clone submits events through
+/// the same driver, taking a lock to prepare and submit them. Each event still has its own
+/// completion, identified by the user_data of its SQE, so any number of IO objects and
+/// submissions on different tasks can use clones of one `SharedRing` concurrently.
+pub struct SharedRing<D: Drive = DefaultDriver> {
+    driver: Arc<Mutex<D>>,
+}
+
+impl<D: Drive> SharedRing<D> {
+    /// Share a driver.
+    pub fn new(driver: D) -> SharedRing<D> {
+        SharedRing { driver: Arc::new(Mutex::new(driver)) }
+    }
+
+    fn prepare_shared<'cx>(
+        &self,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        let mut driver = self.lock();
+        // The driver is never moved out of its allocation, so it is effectively pinned.
+        let driver = unsafe { Pin::new_unchecked(&mut *driver) };
+        driver.poll_prepare(ctx, count, prepare)
+    }
+
+    fn submit_shared(&self, ctx: &mut Context<'_>) -> Poll<io::Result<u32>> {
+        let mut driver = self.lock();
+        let driver = unsafe { Pin::new_unchecked(&mut *driver) };
+        driver.poll_submit(ctx)
+    }
+
+    #[inline(always)]
+    fn lock(&self) -> parking_lot::MutexGuard<'_, D> {
+        self.driver.lock()
+    }
+}
+
+impl<D: Drive + Default> Default for SharedRing<D> {
+    fn default() -> SharedRing<D> {
+        SharedRing::new(D::default())
+    }
+}
+
+impl<D: Drive> Clone for SharedRing<D> {
+    fn clone(&self) -> SharedRing<D> {
+        SharedRing { driver: self.driver.clone() }
+    }
+}
+
+impl<D: Drive> Drive for SharedRing<D> {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        self.get_mut().prepare_shared(ctx, count, prepare)
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        self.get_mut().submit_shared(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        self.lock().ring_fd()
+    }
+}
+
+/// A shared reference to a `SharedRing` is also a driver, so that one can be used by many IO
+/// objects without cloning it.
+impl<D: Drive> Drive for &SharedRing<D> {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        self.prepare_shared(ctx, count, prepare)
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        self.submit_shared(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        self.lock().ring_fd()
+    }
+}
@@ -0,0 +1,246 @@
+//! Scopes which outlive every event submitted within them
+//!
+//! Normally, when a future performing IO is dropped before its event completes, the event keeps
+//! running in the kernel, and the resources shared with it (like buffers) are only released when
+//! it eventually completes. A scope instead tracks every event submitted through it, and does not
+//! complete until the kernel has completed all of them, cancelling any which are still running
+//! once the scope's own future has finished.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use futures_core::ready;
+use iou::SQEs;
+use parking_lot::Mutex;
+
+use crate::drive::{Drive, Completion, DefaultDriver};
+use crate::ring::{Ring, Cancellation, SharedRing};
+use crate::ring::completion::Hold;
+
+/// Run a future within a scope, using the default driver.
+///
+/// The closure is passed a [`Scope`], which is a driver: events submitted through it, or by IO
+/// objects constructed on it, are tracked by the scope. The returned future completes with the
+/// output of the closure's future once every one of those events has completed.
+///
+/// ```no_run
+/// use futures::io::AsyncReadExt;
+/// use ringbahn::fs::File;
+///
+/// # fn main() -> std::io::Result<()> { futures::executor::block_on(async {
+/// let contents = ringbahn::scope(|s| async move {
+///     let mut file = File::open_on_driver("README.md", s).await?;
+///     let mut contents = String::new();
+///     file.read_to_string(&mut contents).await?;
+///     Ok::<_, std::io::Error>(contents)
+/// }).await?;
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub fn scope<F, Fut>(f: F) -> Scoped<Fut> where
+    F: FnOnce(Scope) -> Fut,
+    Fut: Future,
+{
+    scope_on_driver(DefaultDriver::default(), f)
+}
+
+/// Run a future within a scope, using the provided driver.
+pub fn scope_on_driver<D, F, Fut>(driver: D, f: F) -> Scoped<Fut, D> where
+    D: Drive,
+    F: FnOnce(Scope<D>) -> Fut,
+    Fut: Future,
+{
+    let scope = Scope { driver: SharedRing::new(driver), tracker: Arc::default() };
+    Scoped {
+        ring: Ring::new(scope.driver.clone()),
+        tracker: scope.tracker.clone(),
+        future: Some(f(scope)),
+        output: None,
+        cancelled: HashSet::new(),
+        cancelling: None,
+    }
+}
+
+/// A driver which tracks the events submitted through it
+///
+/// Clones of a scope share its driver and its tracking, so one can be given to each IO object
+/// used within the scope.
+pub struct Scope<D: Drive = DefaultDriver> {
+    driver: SharedRing<D>,
+    tracker: Arc<Tracker>,
+}
+
+impl<D: Drive> Clone for Scope<D> {
+    fn clone(&self) -> Scope<D> {
+        Scope { driver: self.driver.clone(), tracker: self.tracker.clone() }
+    }
+}
+
+impl<D: Drive> Drive for Scope<D> {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        let this = self.get_mut();
+        let tracker = &this.tracker;
+        Pin::new(&mut this.driver).poll_prepare(ctx, count, |sqs, ctx| {
+            let completion = prepare(sqs, ctx);
+            completion.real.track(tracker.clone());
+            completion
+        })
+    }
+
+    fn poll_submit(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<io::Result<u32>> {
+        Pin::new(&mut self.get_mut().driver).poll_submit(ctx)
+    }
+
+    fn ring_fd(&self) -> Option<RawFd> {
+        self.driver.ring_fd()
+    }
+}
+
+/// A future which completes when its scope's future and all of the events submitted through the
+/// scope have completed.
+///
+/// Events are only waited for if this future is polled to completion. If it is dropped early,
+/// the events which are still running are cancelled, but not waited for: as outside of a scope,
+/// the resources they share with the kernel are released once they complete.
+pub struct Scoped<F: Future, D: Drive = DefaultDriver> {
+    future: Option<F>,
+    output: Option<F::Output>,
+    ring: Ring<SharedRing<D>>,
+    tracker: Arc<Tracker>,
+    // The tokens of the events which have been cancelled
+    cancelled: HashSet<u64>,
+    // The event being cancelled, which is held until its cancellation completes
+    cancelling: Option<Hold>,
+}
+
+impl<F: Future, D: Drive> Future for Scoped<F, D> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+
+        if let Some(future) = &mut this.future {
+            let output = ready!(unsafe { Pin::new_unchecked(future) }.poll(ctx));
+            this.output = Some(output);
+            this.future = None;
+        }
+
+        loop {
+            if let Some(hold) = &this.cancelling {
+                let addr = hold.addr();
+                let ring = Pin::new(&mut this.ring);
+                // Events which have already completed fail to be cancelled; that's fine.
+                let _ = ready!(ring.poll(ctx, 1, |sqs| {
+                    let mut sqe = sqs.next().unwrap();
+                    unsafe {
+                        sqe.prep_cancel(addr, 0);
+                    }
+                    sqe
+                }));
+                this.cancelling = None;
+            }
+
+            let mut state = this.tracker.state.lock();
+            if state.running.is_empty() {
+                return Poll::Ready(this.output.take().expect("polled Scoped after completion"));
+            }
+            match state.running.iter().find(|(token, _)| !this.cancelled.contains(token)) {
+                Some((&token, &addr))   => {
+                    this.cancelled.insert(token);
+                    // The event is still running while its token is tracked.
+                    this.cancelling = Some(unsafe { Hold::new(addr) });
+                }
+                None                    => {
+                    state.waker = Some(ctx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl<F: Future, D: Drive> Drop for Scoped<F, D> {
+    fn drop(&mut self) {
+        if let Some(hold) = self.cancelling.take() {
+            self.ring.cancel(Cancellation::from(Box::new(hold)));
+        }
+        let running: Vec<Hold> = {
+            let state = self.tracker.state.lock();
+            state.running.iter()
+                .filter(|(token, _)| !self.cancelled.contains(token))
+                .map(|(_, &addr)| unsafe { Hold::new(addr) })
+                .collect()
+        };
+        let mut ctx = Context::from_waker(Waker::noop());
+        for hold in running {
+            // The cancellation is submitted without waiting for it, so its ring is abandoned; the
+            // event is let go of once it completes.
+            let addr = hold.addr();
+            let mut ring = Ring::new(self.ring.driver().clone());
+            let _ = Pin::new(&mut ring).poll(&mut ctx, 1, |sqs| {
+                let mut sqe = sqs.next().unwrap();
+                unsafe {
+                    sqe.prep_cancel(addr, 0);
+                }
+                sqe
+            });
+            ring.cancel(Cancellation::from(Box::new(hold)));
+        }
+    }
+}
+
+/// The events which have been submitted through a scope and have not completed.
+#[derive(Default)]
+pub(crate) struct Tracker {
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    // The address of each running event, by a token which is unique within the scope: once an
+    // event completes, its address can be reused by the next one.
+    running: HashMap<u64, u64>,
+    next_token: u64,
+    waker: Option<Waker>,
+}
+
+impl Tracker {
+    /// Track the event at `addr`, returning the token which it is known by.
+    pub(crate) fn start(&self, addr: u64) -> u64 {
+        let mut state = self.state.lock();
+        let token = state.next_token;
+        state.next_token += 1;
+        state.running.insert(token, addr);
+        // A scope which is waiting for its events needs to cancel this one as well.
+        if let Some(waker) = state.waker.take() {
+            drop(state);
+            waker.wake();
+        }
+        token
+    }
+
+    pub(crate) fn finish(&self, token: u64) {
+        let mut state = self.state.lock();
+        state.running.remove(&token);
+        if state.running.is_empty() {
+            if let Some(waker) = state.waker.take() {
+                drop(state);
+                waker.wake();
+            }
+        }
+    }
+}
@@ -6,40 +6,76 @@ use std::task::{Context, Poll};
 
 use futures_core::ready;
 
-use crate::{Event, Drive, ring::Ring};
+use crate::{Event, Drive};
+use crate::event::Reclaimable;
+use crate::ring::{self, Ring, Cancellation, CancelledEvents};
 
 /// A [`Future`] representing an event submitted to io-uring
 pub struct Submission<E: Event, D: Drive> {
     ring: Ring<D>,
     event: Option<E>,
+    reclaim: Option<Box<dyn Fn(E) -> Cancellation + Send + Sync>>,
+    started: bool,
 }
 
 impl<E: Event, D: Drive> Submission<E, D> {
     /// Construct a new submission from an event and a driver.
     pub fn new(event: E, driver: D) -> Submission<E, D> {
+        Submission::on_ring(event, Ring::new(driver))
+    }
+
+    /// Construct a new submission from an event and a ring, such as one constructed by a
+    /// [`ring::Builder`](crate::ring::Builder).
+    pub fn on_ring(event: E, ring: Ring<D>) -> Submission<E, D> {
         Submission {
-            ring: Ring::new(driver),
+            ring,
             event: Some(event),
+            reclaim: None,
+            started: false,
         }
     }
 
+    /// Push the event onto this queue once it completes if the submission is cancelled, instead
+    /// of destroying it.
+    ///
+    /// This allows the resources owned by the event, like its buffer, to be recovered when the
+    /// submission is dropped while the event is still running, such as when it loses a
+    /// `select!`. Only events which keep no state the kernel uses inline can be reclaimed, since
+    /// the event is moved before the kernel is done with it.
+    pub fn reclaim_into(self: Pin<&mut Self>, queue: &CancelledEvents<E>)
+        where E: Reclaimable + Send + 'static
+    {
+        let queue = queue.clone();
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        this.reclaim = Some(Box::new(move |event| queue.cancellation(event)));
+    }
+
     /// Access the driver this submission is using
     pub fn driver(&self) -> &D {
         self.ring.driver()
     }
 
     pub fn replace_event(self: Pin<&mut Self>, event: E) {
-        let (ring, event_slot) = self.split();
-        if let Some(event) = event_slot.take() {
-            ring.cancel_pinned(E::cancel(ManuallyDrop::new(event)))
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        if let Some(event) = this.event.take() {
+            let cancellation = this.cancellation(event);
+            unsafe { Pin::new_unchecked(&mut this.ring) }.cancel_pinned(cancellation)
+        }
+        this.event = Some(event);
+        this.started = false;
+    }
+
+    fn cancellation(&self, event: E) -> Cancellation {
+        match &self.reclaim {
+            Some(reclaim)   => reclaim(event),
+            None            => E::cancel(ManuallyDrop::new(event)),
         }
-        *event_slot = Some(event);
     }
 
-    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Option<E>) {
+    fn split(self: Pin<&mut Self>) -> (Pin<&mut Ring<D>>, &mut Option<E>, &mut bool) {
         unsafe {
             let this = Pin::get_unchecked_mut(self);
-            (Pin::new_unchecked(&mut this.ring), &mut this.event)
+            (Pin::new_unchecked(&mut this.ring), &mut this.event, &mut this.started)
         }
     }
 }
@@ -51,11 +87,23 @@ impl<E, D> Future for Submission<E, D> where
     type Output = (E, io::Result<u32>);
 
     fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
-        let (ring, event) = self.split();
+        let (mut ring, event, started) = self.split();
 
         let result = if let Some(event) = event {
+            if !*started {
+                *started = true;
+                if let Some(emulation) = unsafe { event.emulate() } {
+                    if !ring::is_supported(emulation.opcode) {
+                        ring.as_mut().start_emulated(ctx, emulation.run);
+                        return Poll::Pending;
+                    }
+                }
+            }
             let count = event.sqes_needed();
-            ready!(ring.poll(ctx, count, |sqs| unsafe { event.prepare(sqs) }))
+            let poll = ring.as_mut().poll(ctx, count, |sqs| unsafe { event.prepare(sqs) });
+            let result = ready!(poll);
+            event.set_completion_flags(ring.completion_flags());
+            result
         } else {
             panic!("polled Submission after completion")
         };
@@ -68,7 +116,8 @@ impl<E, D> Future for Submission<E, D> where
 impl<E: Event, D: Drive> Drop for Submission<E, D> {
     fn drop(&mut self) {
         if let Some(event) = self.event.take() {
-            self.ring.cancel(E::cancel(ManuallyDrop::new(event)))
+            let cancellation = self.cancellation(event);
+            self.ring.cancel(cancellation)
         }
     }
 }
@@ -0,0 +1,338 @@
+//! Definitions from the io-uring interface which are newer than those provided by uring-sys.
+//!
+//! SQEs for these operations are prepared by writing the fields of the raw SQE directly.
+
+use iou::SQE;
+use iou::registrar::UringFd;
+use iou::sqe::{BufferGroupId, MsgFlags, SubmissionFlags};
+
+pub const IORING_OP_OPENAT2: u8 = 28;
+pub const IORING_OP_SHUTDOWN: u8 = 34;
+pub const IORING_OP_RENAMEAT: u8 = 35;
+pub const IORING_OP_UNLINKAT: u8 = 36;
+pub const IORING_OP_MKDIRAT: u8 = 37;
+pub const IORING_OP_SYMLINKAT: u8 = 38;
+pub const IORING_OP_LINKAT: u8 = 39;
+pub const IORING_OP_MSG_RING: u8 = 40;
+pub const IORING_OP_FSETXATTR: u8 = 41;
+pub const IORING_OP_FGETXATTR: u8 = 43;
+pub const IORING_OP_SOCKET: u8 = 45;
+pub const IORING_OP_URING_CMD: u8 = 46;
+pub const IORING_OP_SEND_ZC: u8 = 47;
+pub const IORING_OP_FTRUNCATE: u8 = 55;
+pub const IORING_OP_BIND: u8 = 56;
+pub const IORING_OP_LISTEN: u8 = 57;
+
+/// The number of the `openat2(2)` system call, which libc does not define for every target.
+pub const SYS_OPENAT2: libc::c_long = 437;
+
+/// A read or write flag which fails the operation with `EAGAIN` rather than waiting for data or
+/// space to become available.
+pub const RWF_NOWAIT: i32 = 0x8;
+
+/// Set in the `ioprio` field of an accept to keep accepting connections, completing once for
+/// each of them.
+pub const IORING_ACCEPT_MULTISHOT: u16 = 1 << 0;
+
+/// Set in the `ioprio` field of a receive to keep receiving, completing once each time data
+/// arrives. The receive must select its buffers from a group of provided buffers.
+pub const IORING_RECV_MULTISHOT: u16 = 1 << 1;
+
+/// Set in the flags of a CQE when the event that completed will complete again.
+pub const IORING_CQE_F_MORE: u32 = 1 << 1;
+
+/// Set in the flags of the CQE with which a zero-copy send reports that the kernel no longer
+/// uses its buffer.
+pub const IORING_CQE_F_NOTIF: u32 = 1 << 3;
+
+/// Passed as the file index of an operation which installs a file in the fixed-file table to
+/// have the kernel allocate a free slot.
+pub const IORING_FILE_INDEX_ALLOC: u32 = !0;
+
+pub const IORING_REGISTER_PROBE: libc::c_uint = 8;
+pub const IORING_REGISTER_NAPI: libc::c_uint = 27;
+pub const IORING_UNREGISTER_NAPI: libc::c_uint = 28;
+
+/// The argument of `IORING_REGISTER_NAPI` and `IORING_UNREGISTER_NAPI`.
+#[repr(C)]
+#[derive(Default)]
+#[allow(non_camel_case_types)]
+pub struct io_uring_napi {
+    pub busy_poll_to: u32,
+    pub prefer_busy_poll: u8,
+    pub opcode: u8,
+    pub pad: [u8; 2],
+    pub op_param: u32,
+    pub resv: u32,
+}
+
+/// Set in the flags of a probed operation which the kernel supports.
+pub const IO_URING_OP_SUPPORTED: u16 = 1;
+
+/// An entry of the argument of `IORING_REGISTER_PROBE`.
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+#[allow(non_camel_case_types)]
+pub struct io_uring_probe_op {
+    pub op: u8,
+    pub resv: u8,
+    pub flags: u16,
+    pub resv2: u32,
+}
+
+/// The argument of `IORING_REGISTER_PROBE`, with room for every possible opcode.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct io_uring_probe {
+    pub last_op: u8,
+    pub ops_len: u8,
+    pub resv: u16,
+    pub resv2: [u32; 3],
+    pub ops: [io_uring_probe_op; 256],
+}
+
+/// Call `io_uring_register(2)` with an opcode iou does not know about.
+pub unsafe fn register(fd: i32, opcode: libc::c_uint, arg: *mut libc::c_void, nr_args: u32)
+    -> std::io::Result<()>
+{
+    match libc::syscall(libc::SYS_io_uring_register, fd, opcode, arg, nr_args) {
+        -1  => Err(std::io::Error::last_os_error()),
+        _   => Ok(()),
+    }
+}
+
+/// The `uring_cmd` operation discarding a range of a block device.
+pub const BLOCK_URING_CMD_DISCARD: u32 = 0x1200;
+
+/// Block device ioctls which libc does not define.
+pub const BLKGETSIZE64: libc::c_ulong = 0x80081272;
+pub const BLKDISCARD: libc::c_ulong = 0x1277;
+
+/// Prepare an SQE with a raw opcode and the common fields used by most operations.
+///
+/// The SQE is expected to have been cleared by iou (which prepares every SQE it hands out as a
+/// nop), so any field not written here is zero.
+pub unsafe fn prep_raw(sqe: &mut SQE<'_>, opcode: u8, fd: i32, addr: u64, len: u32, off: u64) {
+    let raw = sqe.raw_mut();
+    raw.opcode = opcode;
+    raw.fd = fd;
+    raw.addr = addr;
+    raw.len = len;
+    raw.off_addr2.off = off;
+}
+
+/// Set the slot of the fixed-file table an operation installs its file into, rather than the
+/// process's file descriptor table. `None` has the kernel allocate a free slot.
+pub unsafe fn set_file_index(sqe: &mut SQE<'_>, file_index: Option<u32>) {
+    let file_index = match file_index {
+        Some(index) => index + 1,
+        None        => IORING_FILE_INDEX_ALLOC,
+    };
+    sqe.raw_mut().buf_index.buf_index.splice_fd_in = file_index as i32;
+}
+
+/// Set the per-IO flags of a read or write operation.
+pub unsafe fn set_rw_flags(sqe: &mut SQE<'_>, flags: i32) {
+    sqe.raw_mut().cmd_flags.rw_flags = flags;
+}
+
+/// Have the kernel choose the buffer of an operation from a group of provided buffers.
+pub unsafe fn set_buffer_group(sqe: &mut SQE<'_>, group: BufferGroupId) {
+    sqe.raw_mut().buf_index.buf_index.index_or_group = group.id as u16;
+    sqe.set_flags(SubmissionFlags::BUFFER_SELECT);
+}
+
+/// Prepare a receive into `buf`.
+///
+/// iou's `prep_recv` prepares a send from the buffer rather than a receive into it.
+pub unsafe fn prep_recv(sqe: &mut SQE<'_>, fd: impl UringFd, buf: &mut [u8], flags: MsgFlags) {
+    let opcode = uring_sys::IoRingOp::IORING_OP_RECV as u8;
+    prep_raw(sqe, opcode, fd.as_raw_fd(), buf.as_mut_ptr() as u64, buf.len() as u32, 0);
+    sqe.raw_mut().cmd_flags.msg_flags = flags.bits() as u32;
+    fd.update_sqe(sqe);
+}
+
+/// Prepare an event creating a socket.
+pub unsafe fn prep_socket(sqe: &mut SQE<'_>, domain: i32, ty: i32, protocol: i32) {
+    prep_raw(sqe, IORING_OP_SOCKET, domain, 0, protocol as u32, ty as u64);
+}
+
+/// Prepare a splice event.
+///
+/// The wrapper uring-sys provides for `io_uring_prep_splice` passes its arguments to liburing in
+/// the wrong order, so splice events have to be prepared here instead.
+pub unsafe fn prep_splice(
+    sqe: &mut SQE<'_>,
+    fd_in: i32,
+    off_in: i64,
+    fd_out: i32,
+    off_out: i64,
+    len: u32,
+    flags: u32,
+) {
+    let opcode = uring_sys::IoRingOp::IORING_OP_SPLICE as u8;
+    prep_raw(sqe, opcode, fd_out, off_in as u64, len, off_out as u64);
+    let raw = sqe.raw_mut();
+    raw.cmd_flags.splice_flags = flags;
+    raw.buf_index.buf_index.splice_fd_in = fd_in;
+}
+
+/// Prepare an event giving the kernel advice about the IO which will be done on the `len` bytes
+/// at `offset` in a file, like `posix_fadvise(2)`.
+///
+/// uring-sys declares `io_uring_prep_fadvise` but does not build it into its shim, so programs
+/// which call iou's wrapper fail to link.
+pub unsafe fn prep_fadvise(sqe: &mut SQE<'_>, fd: i32, offset: u64, len: u32, advice: i32) {
+    let opcode = uring_sys::IoRingOp::IORING_OP_FADVISE as u8;
+    prep_raw(sqe, opcode, fd, 0, len, offset);
+    set_op_flags(sqe, advice as u32);
+}
+
+/// Set the flags of an operation, which share a field of the SQE with the flags of reads and
+/// writes, like the flags of a rename or an unlink.
+pub unsafe fn set_op_flags(sqe: &mut SQE<'_>, flags: u32) {
+    sqe.raw_mut().cmd_flags.rw_flags = flags as i32;
+}
+
+/// Prepare an event renaming `old_path`, relative to `old_dir_fd`, to `new_path`, relative to
+/// `new_dir_fd`, like `renameat2(2)`.
+pub unsafe fn prep_renameat(
+    sqe: &mut SQE<'_>,
+    old_dir_fd: i32,
+    old_path: *const libc::c_char,
+    new_dir_fd: i32,
+    new_path: *const libc::c_char,
+    flags: u32,
+) {
+    let (old_path, new_path) = (old_path as u64, new_path as u64);
+    prep_raw(sqe, IORING_OP_RENAMEAT, old_dir_fd, old_path, new_dir_fd as u32, new_path);
+    set_op_flags(sqe, flags);
+}
+
+/// Prepare an event removing `path`, relative to `dir_fd`, like `unlinkat(2)`.
+pub unsafe fn prep_unlinkat(sqe: &mut SQE<'_>, dir_fd: i32, path: *const libc::c_char, flags: u32) {
+    prep_raw(sqe, IORING_OP_UNLINKAT, dir_fd, path as u64, 0, 0);
+    set_op_flags(sqe, flags);
+}
+
+/// Prepare an event creating the directory `path`, relative to `dir_fd`, like `mkdirat(2)`.
+pub unsafe fn prep_mkdirat(sqe: &mut SQE<'_>, dir_fd: i32, path: *const libc::c_char, mode: u32) {
+    prep_raw(sqe, IORING_OP_MKDIRAT, dir_fd, path as u64, mode, 0);
+}
+
+/// Prepare an event opening `path`, relative to `dir_fd`, like `openat2(2)`. `how` points to a
+/// `struct open_how` of `size` bytes.
+pub unsafe fn prep_openat2(
+    sqe: &mut SQE<'_>,
+    dir_fd: i32,
+    path: *const libc::c_char,
+    how: *const libc::c_void,
+    size: u32,
+) {
+    prep_raw(sqe, IORING_OP_OPENAT2, dir_fd, path as u64, size, how as u64);
+}
+
+/// Prepare an event creating a symbolic link at `link_path`, relative to `new_dir_fd`, which
+/// points to `target`, like `symlinkat(2)`.
+pub unsafe fn prep_symlinkat(
+    sqe: &mut SQE<'_>,
+    target: *const libc::c_char,
+    new_dir_fd: i32,
+    link_path: *const libc::c_char,
+) {
+    prep_raw(sqe, IORING_OP_SYMLINKAT, new_dir_fd, target as u64, 0, link_path as u64);
+}
+
+/// Prepare an event creating a hard link at `new_path`, relative to `new_dir_fd`, to the file
+/// at `old_path`, relative to `old_dir_fd`, like `linkat(2)`.
+pub unsafe fn prep_linkat(
+    sqe: &mut SQE<'_>,
+    old_dir_fd: i32,
+    old_path: *const libc::c_char,
+    new_dir_fd: i32,
+    new_path: *const libc::c_char,
+    flags: u32,
+) {
+    let (old_path, new_path) = (old_path as u64, new_path as u64);
+    prep_raw(sqe, IORING_OP_LINKAT, old_dir_fd, old_path, new_dir_fd as u32, new_path);
+    set_op_flags(sqe, flags);
+}
+
+/// Prepare an event reading the extended attribute `name` of `fd` into the `len` bytes at
+/// `value`, like `fgetxattr(2)`.
+pub unsafe fn prep_fgetxattr(
+    sqe: &mut SQE<'_>,
+    fd: i32,
+    name: *const libc::c_char,
+    value: *mut u8,
+    len: u32,
+) {
+    prep_raw(sqe, IORING_OP_FGETXATTR, fd, name as u64, len, value as u64);
+}
+
+/// Prepare an event setting the extended attribute `name` of `fd` to the `len` bytes at `value`,
+/// like `fsetxattr(2)`.
+pub unsafe fn prep_fsetxattr(
+    sqe: &mut SQE<'_>,
+    fd: i32,
+    name: *const libc::c_char,
+    value: *const u8,
+    len: u32,
+    flags: u32,
+) {
+    prep_raw(sqe, IORING_OP_FSETXATTR, fd, name as u64, len, value as u64);
+    set_op_flags(sqe, flags);
+}
+
+/// Prepare an event truncating or extending `fd` to `len` bytes, like `ftruncate(2)`.
+pub unsafe fn prep_ftruncate(sqe: &mut SQE<'_>, fd: i32, len: u64) {
+    prep_raw(sqe, IORING_OP_FTRUNCATE, fd, 0, 0, len);
+}
+
+/// Prepare a `sync_file_range(2)` of `len` bytes at `offset`. A length of 0 syncs to the end of
+/// the file.
+pub unsafe fn prep_sync_file_range(sqe: &mut SQE<'_>, fd: i32, offset: u64, len: u32, flags: u32) {
+    let opcode = uring_sys::IoRingOp::IORING_OP_SYNC_FILE_RANGE as u8;
+    prep_raw(sqe, opcode, fd, 0, len, offset);
+    sqe.raw_mut().cmd_flags.sync_range_flags = flags;
+}
+
+/// Prepare a `uring_cmd` event, passing the command two 64-bit arguments in `addr` and `addr3`.
+pub unsafe fn prep_uring_cmd(sqe: &mut SQE<'_>, fd: i32, cmd_op: u32, arg1: u64, arg2: u64) {
+    // cmd_op occupies the low half of the off field.
+    prep_raw(sqe, IORING_OP_URING_CMD, fd, arg1, 0, cmd_op as u64);
+    sqe.raw_mut().buf_index.__pad2[1] = arg2;
+}
+
+/// Take the next CQE from the completion queue of `ring`, if there is one.
+///
+/// iou's `CQE` drops the flags it does not know, like the id of the buffer an event selected and
+/// whether a multishot event will complete again, so drivers read the CQEs they complete here.
+pub unsafe fn peek_cqe(ring: *mut uring_sys::io_uring) -> Option<uring_sys::io_uring_cqe> {
+    let mut cqe = std::ptr::null_mut();
+    match uring_sys::io_uring_peek_cqe(ring, &mut cqe) {
+        0   => Some(take_cqe(ring, cqe)),
+        _   => None,
+    }
+}
+
+/// Wait for the next CQE from the completion queue of `ring`, and take it, like `peek_cqe`.
+pub unsafe fn wait_cqe(ring: *mut uring_sys::io_uring) -> std::io::Result<uring_sys::io_uring_cqe> {
+    let mut cqe = std::ptr::null_mut();
+    match uring_sys::io_uring_wait_cqe(ring, &mut cqe) {
+        0   => Ok(take_cqe(ring, cqe)),
+        err => Err(std::io::Error::from_raw_os_error(-err)),
+    }
+}
+
+unsafe fn take_cqe(ring: *mut uring_sys::io_uring, cqe: *mut uring_sys::io_uring_cqe)
+    -> uring_sys::io_uring_cqe
+{
+    let taken = uring_sys::io_uring_cqe {
+        user_data: (*cqe).user_data,
+        res: (*cqe).res,
+        flags: (*cqe).flags,
+    };
+    uring_sys::io_uring_cqe_seen(ring, cqe);
+    taken
+}
@@ -0,0 +1,215 @@
+//! TLS over ringbahn's TCP streams, with rustls
+//!
+//! This module is enabled by the `tls` feature. A [`TlsStream`] runs a rustls session over a
+//! [`TcpStream`]: ciphertext is read into the stream's own buffer by io-uring and passed to
+//! rustls from there, and the records rustls produces are written with the stream's writes.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use futures::io::{AsyncReadExt, AsyncWriteExt};
+//! use ringbahn::net::TcpStream;
+//! use ringbahn::tls::TlsStream;
+//!
+//! # fn main() -> std::io::Result<()> { futures::executor::block_on(async {
+//! let config = Arc::new(rustls::ClientConfig::new());
+//! let stream = TcpStream::connect(("example.com", 443)).await?;
+//! let mut tls = TlsStream::client(stream, config, "example.com")?;
+//! tls.write_all(b"GET / HTTP/1.0\r\nHost: example.com\r\n\r\n").await?;
+//! let mut response = Vec::new();
+//! tls.read_to_end(&mut response).await?;
+//! # Ok(())
+//! # })
+//! # }
+//! ```
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::ready;
+use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
+use rustls::{ClientConfig, ClientSession, ServerConfig, ServerSession, Session};
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::net::TcpStream;
+
+/// A TLS session over a [`TcpStream`]
+///
+/// The handshake is driven by the first reads and writes, or explicitly with
+/// [`TlsStream::handshake`]. Like the `TcpStream` it wraps, a `TlsStream` performs one operation
+/// at a time: starting a write cancels a read which is in progress, and the other way around.
+pub struct TlsStream<S: Session, D: Drive = DefaultDriver> {
+    stream: TcpStream<D>,
+    session: S,
+    // Records produced by rustls which have not yet been written to the stream
+    out: Vec<u8>,
+    written: usize,
+    eof: bool,
+    closing: bool,
+}
+
+impl<D: Drive> TlsStream<ClientSession, D> {
+    /// Start a client session with the server `hostname` over a connected stream.
+    pub fn client(stream: TcpStream<D>, config: Arc<ClientConfig>, hostname: &str)
+        -> io::Result<TlsStream<ClientSession, D>>
+    {
+        let name = webpki::DNSNameRef::try_from_ascii_str(hostname)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid DNS name"))?;
+        Ok(TlsStream::new(stream, ClientSession::new(&config, name)))
+    }
+}
+
+impl<D: Drive> TlsStream<ServerSession, D> {
+    /// Start a server session over an accepted stream.
+    pub fn server(stream: TcpStream<D>, config: Arc<ServerConfig>) -> TlsStream<ServerSession, D> {
+        TlsStream::new(stream, ServerSession::new(&config))
+    }
+}
+
+impl<S: Session, D: Drive> TlsStream<S, D> {
+    /// Run `session` over `stream`.
+    pub fn new(stream: TcpStream<D>, session: S) -> TlsStream<S, D> {
+        TlsStream { stream, session, out: Vec::new(), written: 0, eof: false, closing: false }
+    }
+
+    pub fn get_ref(&self) -> (&TcpStream<D>, &S) {
+        (&self.stream, &self.session)
+    }
+
+    pub fn into_inner(self) -> (TcpStream<D>, S) {
+        (self.stream, self.session)
+    }
+
+    /// Complete the handshake, if it has not completed already.
+    pub fn handshake(&mut self) -> Handshake<'_, S, D> where S: Unpin, D: Unpin {
+        Handshake { stream: Pin::new(self) }
+    }
+
+    pub fn poll_handshake(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.session.is_handshaking() {
+            ready!(self.as_mut().poll_write_tls(ctx))?;
+            if self.session.wants_read() && ready!(self.as_mut().poll_read_tls(ctx))? == 0 {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+        }
+        self.poll_write_tls(ctx)
+    }
+
+    /// Write the records rustls has produced to the stream.
+    fn poll_write_tls(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let (mut stream, session, out, written, _) = self.split();
+        while session.wants_write() {
+            session.write_tls(out)?;
+        }
+        while *written < out.len() {
+            let n = ready!(stream.as_mut().poll_write(ctx, &out[*written..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            *written += n;
+        }
+        out.clear();
+        *written = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Pass ciphertext read into the stream's buffer to rustls, returning how much there was.
+    fn poll_read_tls(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let (mut stream, session, out, _, eof) = self.split();
+        let mut data = ready!(stream.as_mut().poll_fill_buf(ctx))?;
+        if data.is_empty() {
+            *eof = true;
+            return Poll::Ready(Ok(0));
+        }
+        let n = session.read_tls(&mut data)?;
+        stream.consume(n);
+        if let Err(err) = session.process_new_packets() {
+            // Queue the alert rustls produced, so that it is sent with the next write.
+            let _ = session.write_tls(out);
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, err)));
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    #[inline(always)]
+    fn split(self: Pin<&mut Self>)
+        -> (Pin<&mut TcpStream<D>>, &mut S, &mut Vec<u8>, &mut usize, &mut bool)
+    {
+        unsafe {
+            let this = Pin::get_unchecked_mut(self);
+            let stream = Pin::new_unchecked(&mut this.stream);
+            (stream, &mut this.session, &mut this.out, &mut this.written, &mut this.eof)
+        }
+    }
+}
+
+impl<S: Session, D: Drive> AsyncRead for TlsStream<S, D> {
+    fn poll_read(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        loop {
+            match self.as_mut().split().1.read(buf) {
+                Ok(0) if !buf.is_empty()    => { }
+                Ok(n)                       => return Poll::Ready(Ok(n)),
+                // rustls reports a close_notify alert from the peer this way
+                Err(err) if err.kind() == io::ErrorKind::ConnectionAborted
+                                            => return Poll::Ready(Ok(0)),
+                Err(err)                    => return Poll::Ready(Err(err)),
+            }
+            if self.eof {
+                return Poll::Ready(Ok(0));
+            }
+            ready!(self.as_mut().poll_write_tls(ctx))?;
+            ready!(self.as_mut().poll_read_tls(ctx))?;
+        }
+    }
+}
+
+impl<S: Session, D: Drive> AsyncWrite for TlsStream<S, D> {
+    fn poll_write(mut self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        // Records from an earlier write are written first, so that at most one write's worth of
+        // records is buffered.
+        ready!(self.as_mut().poll_write_tls(ctx))?;
+        let n = self.as_mut().split().1.write(buf)?;
+        // The data is now buffered, so it has been written even if writing its records has to
+        // wait; they are written by the next write or flush.
+        if let Poll::Ready(Err(err)) = self.poll_write_tls(ctx) {
+            return Poll::Ready(Err(err));
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.as_mut().split().1.flush()?;
+        ready!(self.as_mut().poll_write_tls(ctx))?;
+        self.split().0.poll_flush(ctx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.closing {
+            let this = unsafe { Pin::get_unchecked_mut(self.as_mut()) };
+            this.session.send_close_notify();
+            this.closing = true;
+        }
+        ready!(self.as_mut().poll_write_tls(ctx))?;
+        self.split().0.poll_close(ctx)
+    }
+}
+
+/// A future which completes the handshake of a [`TlsStream`].
+pub struct Handshake<'a, S: Session, D: Drive> {
+    stream: Pin<&'a mut TlsStream<S, D>>,
+}
+
+impl<'a, S: Session, D: Drive> Future for Handshake<'a, S, D> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.as_mut().poll_handshake(ctx)
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use iou::sqe::MsgFlags;
+
+use crate::drive::{Drive, DefaultDriver};
+use crate::net::{Socket, SocketSend, SocketRecv};
+use crate::net::sockopt::{SetSocketOpt, SocketOpt};
+
+use super::socketpair;
+
+/// A unix datagram socket
+///
+/// Unlike a [`UnixStream`](super::UnixStream), each send is received as a separate datagram.
+pub struct UnixDatagram<D: Drive = DefaultDriver> {
+    inner: Socket<D>,
+}
+
+impl UnixDatagram {
+    /// Create a pair of connected datagram sockets, using the default driver.
+    pub fn pair() -> io::Result<(UnixDatagram, UnixDatagram)> {
+        UnixDatagram::pair_on_driver(DefaultDriver::default())
+    }
+}
+
+impl<D: Drive + Clone> UnixDatagram<D> {
+    /// Create a pair of connected datagram sockets, using the provided driver.
+    pub fn pair_on_driver(driver: D) -> io::Result<(UnixDatagram<D>, UnixDatagram<D>)> {
+        let (fd1, fd2) = socketpair(libc::SOCK_DGRAM)?;
+        unsafe {
+            let socket1 = UnixDatagram { inner: Socket::from_raw_fd_on_driver(fd1, driver.clone()) };
+            let socket2 = UnixDatagram { inner: Socket::from_raw_fd_on_driver(fd2, driver) };
+            Ok((socket1, socket2))
+        }
+    }
+}
+
+impl<D: Drive> UnixDatagram<D> {
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        self.inner.set_opt(opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        self.inner.opt()
+    }
+
+    /// Send a datagram to the connected peer.
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> SocketSend<'a, D> where D: Unpin {
+        self.inner.send(buf)
+    }
+
+    pub fn send_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a [u8]) -> SocketSend<'a, D> {
+        self.inner().send_pinned(buf)
+    }
+
+    /// Receive a datagram from the connected peer.
+    ///
+    /// If the datagram is larger than `buf`, the rest of it is discarded.
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> SocketRecv<'a, D> where D: Unpin {
+        self.inner.recv(buf)
+    }
+
+    pub fn recv_pinned<'a>(self: Pin<&'a mut Self>, buf: &'a mut [u8]) -> SocketRecv<'a, D> {
+        self.inner().recv_pinned(buf)
+    }
+
+    pub fn poll_send(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        self.inner().poll_send(ctx, buf, flags)
+    }
+
+    pub fn poll_recv(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+        flags: MsgFlags,
+    ) -> Poll<io::Result<usize>> {
+        self.inner().poll_recv(ctx, buf, flags)
+    }
+
+    #[inline(always)]
+    fn inner(self: Pin<&mut Self>) -> Pin<&mut Socket<D>> {
+        unsafe { Pin::map_unchecked_mut(self, |this| &mut this.inner) }
+    }
+}
+
+impl<D: Drive> AsRawFd for UnixDatagram<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -6,17 +6,20 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures_core::{ready, Stream};
-use nix::sys::socket::{self as nix_socket, SockFlag};
+use nix::sys::socket::SockFlag;
 
-use crate::drive::{Drive, demo::DemoDriver};
+use crate::drive::{Drive, DefaultDriver};
+use crate::net::SockAddr;
 use crate::ring::{Ring, Cancellation};
 
 use super::UnixStream;
+use crate::net::sockopt::{self, SetSocketOpt, SocketOpt};
 
-pub struct UnixListener<D: Drive = DemoDriver> {
+pub struct UnixListener<D: Drive = DefaultDriver> {
     ring: Ring<D>,
     fd: RawFd,
     active: Op,
+    accept_flags: SockFlag,
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
@@ -29,31 +32,110 @@ enum Op {
 
 impl UnixListener {
     pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixListener> {
-        UnixListener::bind_on_driver(path, DemoDriver::default())
+        UnixListener::bind_on_driver(path, DefaultDriver::default())
+    }
+
+    /// Bind a listener to `name` in the abstract namespace, using the default driver.
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixListener> {
+        UnixListener::bind_abstract_on_driver(name, DefaultDriver::default())
     }
 }
 
 impl<D: Drive> UnixListener<D> {
     pub fn bind_on_driver(path: impl AsRef<Path>, driver: D) -> io::Result<UnixListener<D>> {
+        UnixListener::bind_addr(SockAddr::unix(path)?, driver)
+    }
+
+    /// Bind a listener to `name` in Linux's abstract namespace, using the provided driver.
+    ///
+    /// An abstract address is not a file: nothing has to be removed once the listener is closed,
+    /// and it can be connected to with [`UnixStream::connect_abstract`]. `name` does not include
+    /// the leading NUL byte which marks an abstract address.
+    pub fn bind_abstract_on_driver(name: &[u8], driver: D) -> io::Result<UnixListener<D>> {
+        UnixListener::bind_addr(SockAddr::unix_abstract(name)?, driver)
+    }
+
+    fn bind_addr(addr: SockAddr, driver: D) -> io::Result<UnixListener<D>> {
         let fd = super::socket()?;
-        let addr = iou::sqe::SockAddr::Unix(nix_socket::UnixAddr::new(path.as_ref()).unwrap());
-        nix_socket::bind(fd, &addr).map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
-        nix_socket::listen(fd, 128).map_err(|e| e.as_errno().unwrap_or(nix::errno::Errno::EIO))?;
+        let (raw, len) = addr.as_raw();
+        if unsafe { libc::bind(fd, raw, len) } < 0 || unsafe { libc::listen(fd, 128) } < 0 {
+            let err = io::Error::last_os_error();
+            unsafe { libc::close(fd); }
+            return Err(err);
+        }
         let ring = Ring::new(driver);
         Ok(UnixListener {
             active: Op::Nothing,
+            accept_flags: SockFlag::SOCK_CLOEXEC,
             fd, ring,
         })
     }
 
-    pub fn close(&mut self) -> Close<D> where D: Unpin {
+    pub fn close(&mut self) -> Close<'_, D> where D: Unpin {
         Pin::new(self).close_pinned()
     }
 
-    pub fn close_pinned(self: Pin<&mut Self>) -> Close<D> {
+    pub fn close_pinned(self: Pin<&mut Self>) -> Close<'_, D> {
         Close { socket: self }
     }
 
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        sockopt::set(self.fd, opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        sockopt::get(self.fd)
+    }
+
+    /// Set the flags accepted sockets are created with, as by `accept4(2)`.
+    ///
+    /// By default this is `SOCK_CLOEXEC`, so that accepted sockets are not inherited by child
+    /// processes.
+    pub fn set_accept_flags(&mut self, flags: SockFlag) {
+        self.accept_flags = flags;
+    }
+
+    /// The flags accepted sockets are created with.
+    pub fn accept_flags(&self) -> SockFlag {
+        self.accept_flags
+    }
+
+    /// Accept a connection, running the accepted stream on the provided driver.
+    pub fn accept_on<E: Drive>(&mut self, driver: E) -> AcceptOn<'_, D, E> where D: Unpin {
+        Pin::new(self).accept_on_pinned(driver)
+    }
+
+    pub fn accept_on_pinned<E: Drive>(self: Pin<&mut Self>, driver: E) -> AcceptOn<'_, D, E> {
+        AcceptOn { socket: self, driver: Some(driver) }
+    }
+
+    /// Accept a connection, returning the file descriptor of the accepted socket rather than a
+    /// `UnixStream`.
+    ///
+    /// The caller takes ownership of the file descriptor and is responsible for closing it.
+    pub fn accept_raw(&mut self) -> AcceptRaw<'_, D> where D: Unpin {
+        Pin::new(self).accept_raw_pinned()
+    }
+
+    pub fn accept_raw_pinned(self: Pin<&mut Self>) -> AcceptRaw<'_, D> {
+        AcceptRaw { socket: self }
+    }
+
+    pub fn poll_accept_raw(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
+        -> Poll<io::Result<RawFd>>
+    {
+        self.as_mut().guard_op(Op::Accept);
+        let (fd, flags) = (self.fd, self.accept_flags);
+        let fd = ready!(self.as_mut().ring().poll(ctx, 1, |sqs| unsafe {
+            let mut sqe = sqs.next().unwrap();
+            sqe.prep_accept(fd, None, flags);
+            sqe
+        }))? as RawFd;
+        Poll::Ready(Ok(fd))
+    }
+
     fn guard_op(self: Pin<&mut Self>, op: Op) {
         let this = unsafe { Pin::get_unchecked_mut(self) };
         if this.active == Op::Closed {
@@ -101,16 +183,9 @@ impl<D: Drive + Clone> UnixListener<D> {
     pub fn poll_accept(mut self: Pin<&mut Self>, ctx: &mut Context<'_>)
         -> Poll<io::Result<UnixStream<D>>>
     {
-        self.as_mut().guard_op(Op::Accept);
-        let fd = self.fd;
-        let fd = ready!(self.as_mut().ring().poll(ctx, 1, |sqs| unsafe {
-            let mut sqe = sqs.single().unwrap();
-            sqe.prep_accept(fd, None, SockFlag::empty());
-            sqe
-        }))? as RawFd;
+        let fd = ready!(self.as_mut().poll_accept_raw(ctx))?;
         Poll::Ready(Ok(UnixStream::from_fd(fd, self.ring().clone())))
     }
-
 }
 
 impl<D: Drive> Drop for UnixListener<D> {
@@ -135,6 +210,35 @@ impl<'a, D: Drive + Clone> Future for Accept<'a, D> {
     }
 }
 
+pub struct AcceptOn<'a, D: Drive, E: Drive> {
+    socket: Pin<&'a mut UnixListener<D>>,
+    driver: Option<E>,
+}
+
+impl<'a, D: Drive, E: Drive> Future for AcceptOn<'a, D, E> {
+    type Output = io::Result<UnixStream<E>>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        let fd = ready!(this.socket.as_mut().poll_accept_raw(ctx))?;
+        let driver = this.driver.take().expect("polled AcceptOn after completion");
+        let ring = this.socket.ring.with_driver(driver);
+        Poll::Ready(Ok(UnixStream::from_fd(fd, ring)))
+    }
+}
+
+pub struct AcceptRaw<'a, D: Drive> {
+    socket: Pin<&'a mut UnixListener<D>>,
+}
+
+impl<'a, D: Drive> Future for AcceptRaw<'a, D> {
+    type Output = io::Result<RawFd>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.socket.as_mut().poll_accept_raw(ctx)
+    }
+}
+
 pub struct Incoming<'a, D: Drive> {
     accept: Accept<'a, D>,
 }
@@ -166,7 +270,7 @@ impl<'a, D: Drive> Future for Close<'a, D> {
         self.socket.as_mut().guard_op(Op::Close);
         let fd = self.socket.fd;
         ready!(self.socket.as_mut().ring().poll(ctx, 1, |sqs| unsafe {
-            let mut sqe = sqs.single().unwrap();
+            let mut sqe = sqs.next().unwrap();
             sqe.prep_close(fd);
             sqe
         }))?;
@@ -1,11 +1,13 @@
 use std::io;
 use std::os::unix::io::RawFd;
 
+mod datagram;
 mod listener;
 mod stream;
 
-pub use listener::{UnixListener, Close, Accept, Incoming};
-pub use stream::{UnixStream, Connect};
+pub use datagram::UnixDatagram;
+pub use listener::{UnixListener, Close, Accept, AcceptOn, AcceptRaw, Incoming};
+pub use stream::{UnixStream, Connect, SendMsg, RecvMsg};
 
 use nix::sys::socket as nix;
 
@@ -16,9 +18,10 @@ fn socket() -> io::Result<RawFd> {
     }
 }
 
-fn socketpair() -> io::Result<(RawFd, RawFd)> {
-    match nix::socketpair(nix::AddressFamily::Unix, nix::SockType::Stream, None, nix::SockFlag::SOCK_CLOEXEC) {
-        Ok((fd1, fd2))  => Ok((fd1, fd2)),
-        Err(_)          => Err(io::Error::last_os_error()),
+fn socketpair(ty: libc::c_int) -> io::Result<(RawFd, RawFd)> {
+    let mut fds = [0; 2];
+    match unsafe { libc::socketpair(libc::AF_UNIX, ty | libc::SOCK_CLOEXEC, 0, fds.as_mut_ptr()) } {
+        -1  => Err(io::Error::last_os_error()),
+        _   => Ok((fds[0], fds[1])),
     }
 }
@@ -1,50 +1,159 @@
+use std::cmp;
 use std::io;
 use std::future::Future;
-use std::os::unix::io::RawFd;
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::path::Path;
 use std::pin::Pin;
+use std::ptr;
 use std::task::{Context, Poll};
 
 use futures_core::ready;
 use futures_io::{AsyncRead, AsyncBufRead, AsyncWrite};
-use iou::sqe::SockAddr;
-use nix::sys::socket::UnixAddr;
 
-use crate::drive::{Drive, demo::DemoDriver};
+use crate::drive::{Drive, DefaultDriver};
 use crate::event;
-use crate::ring::Ring;
+use crate::ring::{Cancellation, Ring};
+use crate::sys;
 use crate::Submission;
 
 use super::{socket, socketpair};
 
-use crate::net::TcpStream;
+use crate::net::{SockAddr, TcpStream};
+use crate::net::sockopt::{SetSocketOpt, SocketOpt};
 
-pub struct UnixStream<D: Drive = DemoDriver> {
+pub struct UnixStream<D: Drive = DefaultDriver> {
     inner: TcpStream<D>,
+    msg_ring: Option<Ring<D>>,
+    msg: Option<Box<Message>>,
+    msg_op: MsgOp,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum MsgOp {
+    Send,
+    Recv,
+    Nothing,
+}
+
+/// The buffers of a message sent or received with `IORING_OP_SENDMSG` or `IORING_OP_RECVMSG`,
+/// which the kernel may read or write until the operation completes.
+struct Message {
+    hdr: libc::msghdr,
+    iov: libc::iovec,
+    data: Vec<u8>,
+    // u64s, so that the control messages in it are aligned
+    control: Vec<u64>,
+}
+
+unsafe impl Send for Message { }
+unsafe impl Sync for Message { }
+
+impl Message {
+    fn new() -> Box<Message> {
+        Box::new(Message {
+            hdr: unsafe { mem::zeroed() },
+            iov: unsafe { mem::zeroed() },
+            data: Vec::new(),
+            control: Vec::new(),
+        })
+    }
+
+    /// Set up the header for a message with room for `fds` file descriptors.
+    fn header(&mut self, fds: usize) -> *mut libc::msghdr {
+        self.iov = libc::iovec {
+            iov_base: self.data.as_mut_ptr() as *mut libc::c_void,
+            iov_len: self.data.len(),
+        };
+        self.hdr = unsafe { mem::zeroed() };
+        self.hdr.msg_iov = &mut self.iov;
+        self.hdr.msg_iovlen = 1;
+        if fds > 0 {
+            let space = unsafe { libc::CMSG_SPACE((fds * mem::size_of::<RawFd>()) as u32) };
+            self.control.clear();
+            self.control.resize((space as usize).div_ceil(8), 0);
+            self.hdr.msg_control = self.control.as_mut_ptr() as *mut libc::c_void;
+            self.hdr.msg_controllen = space as _;
+        }
+        &mut self.hdr
+    }
+
+    /// Write an `SCM_RIGHTS` control message carrying `fds` into the header's control buffer.
+    unsafe fn write_fds(&mut self, fds: &[RawFd]) {
+        let len = mem::size_of_val(fds);
+        let cmsg = libc::CMSG_FIRSTHDR(&self.hdr);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(len as u32) as _;
+        ptr::copy_nonoverlapping(fds.as_ptr() as *const u8, libc::CMSG_DATA(cmsg), len);
+    }
+
+    /// Move the file descriptors the kernel received into `fds`, returning how many there were.
+    ///
+    /// Any which do not fit in `fds` are closed.
+    unsafe fn read_fds(&self, fds: &mut [RawFd]) -> usize {
+        let mut n = 0;
+        let mut cmsg = libc::CMSG_FIRSTHDR(&self.hdr);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let data = libc::CMSG_DATA(cmsg) as *const RawFd;
+                let len = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                for i in 0..len / mem::size_of::<RawFd>() {
+                    let fd = data.add(i).read_unaligned();
+                    match fds.get_mut(n) {
+                        Some(slot)  => { *slot = fd; n += 1; }
+                        None        => { libc::close(fd); }
+                    }
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&self.hdr, cmsg);
+        }
+        n
+    }
 }
 
 impl UnixStream {
     pub fn connect(path: &impl AsRef<Path>) -> Connect {
-        UnixStream::connect_on_driver(path, DemoDriver::default())
+        UnixStream::connect_on_driver(path, DefaultDriver::default())
+    }
+
+    /// Connect to a listener bound to `name` in the abstract namespace, using the default driver.
+    pub fn connect_abstract(name: &[u8]) -> Connect {
+        UnixStream::connect_abstract_on_driver(name, DefaultDriver::default())
     }
 
     pub fn pair() -> io::Result<(UnixStream, UnixStream)> {
-        UnixStream::pair_on_driver(DemoDriver::default())
+        UnixStream::pair_on_driver(DefaultDriver::default())
     }
 }
 
 impl<D: Drive + Clone> UnixStream<D> {
     pub fn connect_on_driver(path: &impl AsRef<Path>, driver: D) -> Connect<D> {
+        UnixStream::connect_addr(SockAddr::unix(path), driver)
+    }
+
+    /// Connect to a listener bound to `name` in Linux's abstract namespace, using the provided
+    /// driver.
+    ///
+    /// `name` does not include the leading NUL byte which marks an abstract address.
+    pub fn connect_abstract_on_driver(name: &[u8], driver: D) -> Connect<D> {
+        UnixStream::connect_addr(SockAddr::unix_abstract(name), driver)
+    }
+
+    fn connect_addr(addr: io::Result<SockAddr>, driver: D) -> Connect<D> {
+        let addr = match addr {
+            Ok(addr)    => Box::new(addr),
+            Err(e)      => return Connect(Err(Some(e))),
+        };
         let fd = match socket() {
             Ok(fd)  => fd,
             Err(e)  => return Connect(Err(Some(e))),
         };
-        let addr = Box::new(SockAddr::Unix(UnixAddr::new(path.as_ref()).unwrap()));
         Connect(Ok(driver.submit(event::Connect { fd, addr })))
     }
 
     pub fn pair_on_driver(driver: D) -> io::Result<(UnixStream<D>, UnixStream<D>)> {
-        let (fd1, fd2) = socketpair()?;
+        let (fd1, fd2) = socketpair(libc::SOCK_STREAM)?;
         let ring1 = Ring::new(driver.clone());
         let ring2 = Ring::new(driver);
         Ok((UnixStream::from_fd(fd1, ring1), UnixStream::from_fd(fd2, ring2)))
@@ -55,16 +164,120 @@ impl<D: Drive> UnixStream<D> {
     pub(super) fn from_fd(fd: RawFd, ring: Ring<D>) -> UnixStream<D> {
         UnixStream {
             inner: TcpStream::from_fd(fd, ring),
+            msg_ring: None,
+            msg: None,
+            msg_op: MsgOp::Nothing,
         }
     }
 
+    /// Send `buf` along with the file descriptors `fds`, in an `SCM_RIGHTS` control message.
+    ///
+    /// The descriptors stay open in this process; the peer receives duplicates of them.
+    pub fn send_msg<'a>(&'a mut self, buf: &'a [u8], fds: &'a [RawFd]) -> SendMsg<'a, D>
+        where D: Unpin + Clone
+    {
+        SendMsg { stream: Pin::new(self), buf, fds }
+    }
+
+    /// Receive data into `buf` and file descriptors into `fds`, returning the number of bytes
+    /// and the number of descriptors received.
+    ///
+    /// The descriptors are received with `MSG_CMSG_CLOEXEC`, and belong to the caller. Any sent
+    /// with the data which do not fit in `fds` are closed. Data already read into this stream's
+    /// buffer by `AsyncRead` is not returned.
+    pub fn recv_msg<'a>(&'a mut self, buf: &'a mut [u8], fds: &'a mut [RawFd]) -> RecvMsg<'a, D>
+        where D: Unpin + Clone
+    {
+        RecvMsg { stream: Pin::new(self), buf, fds }
+    }
+
+    pub fn poll_send_msg(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &[u8],
+        fds: &[RawFd],
+    ) -> Poll<io::Result<usize>> where D: Clone {
+        let n = ready!(self.poll_msg(ctx, MsgOp::Send, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.extend_from_slice(buf);
+            let hdr = msg.header(fds.len());
+            if !fds.is_empty() {
+                msg.write_fds(fds);
+            }
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_SENDMSG as u8, fd, hdr as u64, 1, 0);
+        }))?;
+        Poll::Ready(Ok(n as usize))
+    }
+
+    pub fn poll_recv_msg(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        buf: &mut [u8],
+        fds: &mut [RawFd],
+    ) -> Poll<io::Result<(usize, usize)>> where D: Clone {
+        let (len, nfds) = (buf.len(), fds.len());
+        let n = ready!(self.as_mut().poll_msg(ctx, MsgOp::Recv, |sqe, fd, msg| unsafe {
+            msg.data.clear();
+            msg.data.resize(len, 0);
+            let hdr = msg.header(nfds);
+            sys::prep_raw(sqe, uring_sys::IoRingOp::IORING_OP_RECVMSG as u8, fd, hdr as u64, 1, 0);
+            sys::set_rw_flags(sqe, libc::MSG_CMSG_CLOEXEC);
+        }))?;
+        let msg = self.msg.as_ref().unwrap();
+        let n = cmp::min(n as usize, len);
+        buf[..n].copy_from_slice(&msg.data[..n]);
+        Poll::Ready(Ok((n, unsafe { msg.read_fds(fds) })))
+    }
+
+    fn poll_msg(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+        op: MsgOp,
+        prepare: impl FnOnce(&mut iou::SQE<'_>, RawFd, &mut Message),
+    ) -> Poll<io::Result<u32>> where D: Clone {
+        let this = unsafe { Pin::get_unchecked_mut(self) };
+        if this.msg_op != MsgOp::Nothing && this.msg_op != op {
+            this.cancel_msg();
+        }
+        this.msg_op = op;
+        let fd = this.inner.as_raw_fd();
+        let driver = this.inner.driver();
+        let ring = this.msg_ring.get_or_insert_with(|| Ring::new(driver.clone()));
+        let msg = this.msg.get_or_insert_with(Message::new);
+        let ring = unsafe { Pin::new_unchecked(ring) };
+        let result = ready!(ring.poll(ctx, 1, |sqs| {
+            let mut sqe = sqs.next().unwrap();
+            prepare(&mut sqe, fd, msg);
+            sqe
+        }));
+        this.msg_op = MsgOp::Nothing;
+        Poll::Ready(result)
+    }
+
+    fn cancel_msg(&mut self) {
+        self.msg_op = MsgOp::Nothing;
+        if let Some(ring) = &mut self.msg_ring {
+            ring.cancel(Cancellation::from(self.msg.take()));
+        }
+    }
+
+    /// Set an option on the socket.
+    pub fn set_opt<O: SetSocketOpt>(&self, opt: O) -> io::Result<()> {
+        self.inner.set_opt(opt)
+    }
+
+    /// Read an option of the socket.
+    pub fn opt<O: SocketOpt>(&self) -> io::Result<O> {
+        self.inner.opt()
+    }
+
     #[inline(always)]
     fn inner(self: Pin<&mut Self>) -> Pin<&mut TcpStream<D>> {
         unsafe { Pin::map_unchecked_mut(self, |this| &mut this.inner) }
     }
 }
 
-pub struct Connect<D: Drive = DemoDriver>(
+pub struct Connect<D: Drive = DefaultDriver>(
     Result<Submission<event::Connect, D>, Option<io::Error>>
 );
 
@@ -90,6 +303,36 @@ impl<D: Drive + Clone> Future for Connect<D> {
     }
 }
 
+pub struct SendMsg<'a, D: Drive> {
+    stream: Pin<&'a mut UnixStream<D>>,
+    buf: &'a [u8],
+    fds: &'a [RawFd],
+}
+
+impl<'a, D: Drive + Clone> Future for SendMsg<'a, D> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (buf, fds) = (self.buf, self.fds);
+        self.stream.as_mut().poll_send_msg(ctx, buf, fds)
+    }
+}
+
+pub struct RecvMsg<'a, D: Drive> {
+    stream: Pin<&'a mut UnixStream<D>>,
+    buf: &'a mut [u8],
+    fds: &'a mut [RawFd],
+}
+
+impl<'a, D: Drive + Clone> Future for RecvMsg<'a, D> {
+    type Output = io::Result<(usize, usize)>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = Pin::into_inner(self);
+        this.stream.as_mut().poll_recv_msg(ctx, this.buf, this.fds)
+    }
+}
+
 impl<D: Drive> AsyncRead for UnixStream<D> {
     fn poll_read(self: Pin<&mut Self>, ctx: &mut Context<'_>, buf: &mut [u8])
         -> Poll<io::Result<usize>>
@@ -121,3 +364,17 @@ impl<D: Drive> AsyncWrite for UnixStream<D> {
         self.inner().poll_close(ctx)
     }
 }
+
+impl<D: Drive> AsRawFd for UnixStream<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl<D: Drive> Drop for UnixStream<D> {
+    fn drop(&mut self) {
+        if self.msg_op != MsgOp::Nothing {
+            self.cancel_msg();
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+use std::thread;
+
+use iou::registrar::{RegisteredFd, PLACEHOLDER_FD};
+use iou::sqe::SockFlag;
+
+use ringbahn::event::*;
+use ringbahn::drive::{demo, Drive};
+
+#[test]
+fn accept_into_fixed_file_table() {
+    let _ = demo::registrar().unwrap().register_files(&[PLACEHOLDER_FD; 4]).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"hello").unwrap();
+    });
+
+    futures::executor::block_on(async move {
+        let (_, result) = demo::driver().submit(AcceptDirect {
+            addr: None,
+            fd: listener.as_raw_fd(),
+            flags: SockFlag::empty(),
+            file_index: None,
+        }).await;
+        let fd = RegisteredFd::new(result.unwrap(), PLACEHOLDER_FD);
+
+        let buf = vec![0; 16].into_boxed_slice();
+        let (event, result) = demo::driver().submit(Read { fd, buf, offset: 0 }).await;
+        let n = result.unwrap() as usize;
+        assert_eq!(&event.buf[..n], b"hello");
+    });
+
+    client.join().unwrap();
+}
@@ -0,0 +1,31 @@
+use std::net::TcpStream as StdTcpStream;
+use std::os::unix::io::AsRawFd;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{SockFlag, TcpListener};
+
+fn fd_flags(fd: i32) -> (bool, bool) {
+    let fd_flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    let fl_flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    (fd_flags & libc::FD_CLOEXEC != 0, fl_flags & libc::O_NONBLOCK != 0)
+}
+
+#[test]
+fn accepted_sockets_are_cloexec() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_eq!(listener.accept_flags(), SockFlag::SOCK_CLOEXEC);
+    let _client = StdTcpStream::connect(addr).unwrap();
+    let (stream, _) = futures::executor::block_on(listener.accept()).unwrap();
+    assert_eq!(fd_flags(stream.as_raw_fd()), (true, false));
+}
+
+#[test]
+fn configured_accept_flags() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    listener.set_accept_flags(SockFlag::SOCK_NONBLOCK);
+    let _client = StdTcpStream::connect(addr).unwrap();
+    let (stream, _) = futures::executor::block_on(listener.accept()).unwrap();
+    assert_eq!(fd_flags(stream.as_raw_fd()), (false, true));
+}
@@ -0,0 +1,82 @@
+use std::io::{Read, Write};
+use std::net::TcpStream as StdTcpStream;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use futures::AsyncWriteExt;
+
+use ringbahn::drive::demo::{self, DemoDriver};
+use ringbahn::net::TcpListener;
+
+fn echo_client(port: u16) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut stream = StdTcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"ping").unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"pong");
+    })
+}
+
+#[test]
+fn accept_on_another_driver() {
+    let mut listener = TcpListener::bind(("127.0.0.1", 47224)).unwrap();
+    let client = echo_client(47224);
+    futures::executor::block_on(async {
+        let (mut stream, _) = listener.accept_on(demo::driver()).await.unwrap();
+        let _: &ringbahn::net::TcpStream<DemoDriver> = &stream;
+        let mut buf = [0; 4];
+        futures::AsyncReadExt::read_exact(&mut stream, &mut buf).await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+    });
+    client.join().unwrap();
+}
+
+#[test]
+fn stream_driver_factory() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 47225), demo::driver()).unwrap();
+    let constructed = Arc::new(AtomicUsize::new(0));
+    let counter = constructed.clone();
+    listener.set_stream_driver(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        demo::driver()
+    });
+    let client = echo_client(47225);
+    futures::executor::block_on(async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        futures::AsyncReadExt::read_exact(&mut stream, &mut buf).await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+    });
+    client.join().unwrap();
+    assert_eq!(constructed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn incoming_on_non_clone_driver() {
+    let mut driver = demo::driver();
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 47226), &mut driver).unwrap();
+    let client = echo_client(47226);
+    futures::executor::block_on(async {
+        let mut incoming = listener.incoming_on(demo::driver);
+        let (mut stream, _) = futures::StreamExt::next(&mut incoming).await.unwrap().unwrap();
+        let mut buf = [0; 4];
+        futures::AsyncReadExt::read_exact(&mut stream, &mut buf).await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+    });
+    client.join().unwrap();
+}
+
+#[test]
+fn accept_raw_fd() {
+    let mut listener = TcpListener::bind(("127.0.0.1", 47227)).unwrap();
+    let client = echo_client(47227);
+    let (fd, _) = futures::executor::block_on(listener.accept_raw()).unwrap();
+    let mut stream = unsafe { <StdTcpStream as std::os::unix::io::FromRawFd>::from_raw_fd(fd) };
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).unwrap();
+    stream.write_all(b"pong").unwrap();
+    drop(stream);
+    client.join().unwrap();
+}
@@ -24,3 +24,17 @@ fn readv_file() {
     assert_eq!(readv.bufs[1][..], ASSERT[4..9]); 
     assert_eq!(readv.bufs[2][..], ASSERT[9..19]); 
 }
+
+#[test]
+fn readv_filled_segments() {
+    let file = File::open("props.txt").unwrap();
+    let bufs: Vec<Box<[u8]>> = vec![Box::new([0; 4]), Box::new([0; 5]), Box::new([0; 10])];
+    let readv = ReadVectored { fd: file.as_raw_fd(), bufs: bufs.into_boxed_slice(), offset: 0 };
+    let (readv, result) = futures::executor::block_on(Submission::new(readv, demo::driver()));
+    let n = result.unwrap() as usize;
+    assert_eq!(readv.filled(n).collect::<Vec<_>>(), [&ASSERT[0..4], &ASSERT[4..9], &ASSERT[9..19]]);
+
+    // As if the read had been short, partly filling the second segment
+    assert_eq!(readv.filled(6).collect::<Vec<_>>(), [&ASSERT[0..4], &ASSERT[4..6]]);
+    assert_eq!(readv.filled(0).count(), 0);
+}
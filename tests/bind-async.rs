@@ -0,0 +1,50 @@
+use std::io::Write;
+use std::net::TcpStream as StdTcpStream;
+use std::thread;
+
+use futures::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::net::TcpListener;
+
+#[test]
+fn bind_and_accept() {
+    futures::executor::block_on(async {
+        let bind = TcpListener::bind_async_on_driver(("127.0.0.1", 47229), demo::driver());
+        let mut listener = bind.await.unwrap();
+        let client = thread::spawn(|| {
+            let mut stream = StdTcpStream::connect(("127.0.0.1", 47229)).unwrap();
+            stream.write_all(b"hello").unwrap();
+        });
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+        client.join().unwrap();
+    });
+}
+
+#[test]
+fn bind_address_in_use() {
+    let _std = std::net::TcpListener::bind(("127.0.0.1", 47230)).unwrap();
+    futures::executor::block_on(async {
+        let bind = TcpListener::bind_async_on_driver(("127.0.0.1", 47230), demo::driver());
+        let err = bind.await.err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    });
+}
+
+#[test]
+fn bind_from_builder() {
+    futures::executor::block_on(async {
+        let builder = TcpListener::builder().reuse_port(true).backlog(16);
+        let bind = builder.bind_async_on_driver(("127.0.0.1", 0), demo::driver());
+        let mut listener = bind.await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            StdTcpStream::connect(addr).unwrap();
+        });
+        listener.accept().await.unwrap();
+        client.join().unwrap();
+    });
+}
@@ -0,0 +1,14 @@
+use ringbahn::drive::demo;
+use ringbahn::fs::{BlockDevice, File};
+
+#[test]
+fn regular_file_is_not_a_block_device() {
+    futures::executor::block_on(async {
+        let file = File::open_on_driver("props.txt", demo::driver()).await.unwrap();
+        let err = BlockDevice::from_file(file).err().unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOTBLK));
+
+        let err = BlockDevice::open_on_driver("props.txt", demo::driver()).await.err().unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOTBLK));
+    });
+}
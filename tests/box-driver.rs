@@ -0,0 +1,21 @@
+use futures::AsyncReadExt;
+
+use ringbahn::drive::{demo, BoxDriver, DefaultDriver};
+use ringbahn::fs::File;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+async fn read_props(driver: BoxDriver) -> Vec<u8> {
+    let mut file: File<BoxDriver> = File::open_on_driver("props.txt", driver).await.unwrap();
+    let mut buf = vec![0; ASSERT.len()];
+    file.read_exact(&mut buf).await.unwrap();
+    buf
+}
+
+#[test]
+fn read_on_box_driver() {
+    futures::executor::block_on(async {
+        assert_eq!(read_props(BoxDriver::new(demo::driver())).await, ASSERT);
+        assert_eq!(read_props(DefaultDriver::default().into()).await, ASSERT);
+    });
+}
@@ -0,0 +1,60 @@
+use std::fs as std_fs;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{AsyncBufReadExt, AsyncReadExt, StreamExt};
+use futures::io::AsyncRead;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{BufReader, File};
+
+/// A reader which returns at most a few bytes from each read
+struct Trickle<'a> {
+    data: &'a [u8],
+    reads: usize,
+}
+
+impl AsyncRead for Trickle<'_> {
+    fn poll_read(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &mut [u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.reads += 1;
+        let len = buf.len().min(self.data.len()).min(4);
+        buf[..len].copy_from_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        Poll::Ready(Ok(len))
+    }
+}
+
+#[test]
+fn read_lines_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lines");
+    let contents: String = (0..1000).map(|n| format!("line {}\n", n)).collect();
+    std_fs::write(&path, &contents).unwrap();
+    futures::executor::block_on(async {
+        let file = File::open_on_driver(&path, demo::driver()).await.unwrap();
+        let lines: Vec<String> = BufReader::new(file).lines().map(Result::unwrap).collect().await;
+        assert_eq!(lines.len(), 1000);
+        assert_eq!(lines[999], "line 999");
+    });
+}
+
+#[test]
+fn buffer_and_bypass() {
+    futures::executor::block_on(async {
+        let mut reader = BufReader::with_capacity(8, Trickle { data: b"0123456789", reads: 0 });
+        let mut byte = [0; 1];
+        reader.read_exact(&mut byte).await.unwrap();
+        assert_eq!(reader.buffer(), b"123");
+
+        // Larger than the capacity, but data is still buffered
+        let mut buf = [0; 8];
+        assert_eq!(reader.read(&mut buf).await.unwrap(), 3);
+        // The buffer is empty, so this reads straight from the inner reader
+        assert_eq!(reader.read(&mut buf).await.unwrap(), 4);
+        assert_eq!(&buf[..4], b"4567");
+        assert_eq!(reader.get_ref().reads, 2);
+    });
+}
@@ -0,0 +1,96 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+use futures::io::AsyncWrite;
+
+use ringbahn::drive::demo;
+use ringbahn::io::{BufWriter, FlushPolicy};
+use ringbahn::pipe;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[derive(Default)]
+struct Counting {
+    data: Vec<u8>,
+    writes: usize,
+}
+
+impl AsyncWrite for Counting {
+    fn poll_write(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8])
+        -> Poll<io::Result<usize>>
+    {
+        self.writes += 1;
+        self.data.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn coalesce_small_writes() {
+    let mut writer = BufWriter::with_policy_on_driver(Counting::default(), FlushPolicy::new(), demo::driver());
+    futures::executor::block_on(async {
+        for chunk in ASSERT.chunks(4) {
+            writer.write_all(chunk).await.unwrap();
+        }
+        assert_eq!(writer.get_ref().writes, 0);
+        assert_eq!(writer.buffer(), ASSERT);
+        writer.flush().await.unwrap();
+        assert_eq!(writer.get_ref().writes, 1);
+        assert_eq!(&writer.get_ref().data[..], ASSERT);
+    });
+}
+
+#[test]
+fn flush_at_threshold() {
+    let policy = FlushPolicy::new().threshold(16);
+    let mut writer = BufWriter::with_policy_on_driver(Counting::default(), policy, demo::driver());
+    futures::executor::block_on(async {
+        for chunk in ASSERT.chunks(4) {
+            writer.write_all(chunk).await.unwrap();
+        }
+        assert_eq!(writer.get_ref().writes, 2);
+        assert_eq!(writer.buffer(), &ASSERT[32..]);
+        writer.write_all(ASSERT).await.unwrap();
+        assert_eq!(writer.get_ref().writes, 4);
+        assert!(writer.buffer().is_empty());
+    });
+}
+
+#[test]
+fn flush_when_due() {
+    let policy = FlushPolicy::new().interval(Duration::from_millis(10));
+    let mut writer = BufWriter::with_policy_on_driver(Counting::default(), policy, demo::driver());
+    futures::executor::block_on(async {
+        let start = Instant::now();
+        writer.write_all(ASSERT).await.unwrap();
+        writer.flush_when_due().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(writer.get_ref().writes, 1);
+        assert_eq!(&writer.get_ref().data[..], ASSERT);
+    });
+}
+
+#[test]
+fn flush_on_drop() {
+    let (sender, mut receiver) = pipe::pipe_on_driver(demo::driver()).unwrap();
+    let policy = FlushPolicy::new().flush_on_drop(true);
+    let mut writer = BufWriter::with_policy_on_driver(sender, policy, demo::driver());
+    futures::executor::block_on(async {
+        writer.write_all(ASSERT).await.unwrap();
+        drop(writer);
+        let mut buf = [0; ASSERT.len()];
+        receiver.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
@@ -0,0 +1,57 @@
+use iou::sqe::{BufferGroupId, MsgFlags};
+
+use ringbahn::Drive;
+use ringbahn::drive::demo;
+use ringbahn::event::{ProvideBuffers, ReadSelect, RecvSelect};
+
+// The tests share the demo driver's ring, so each provides its own group of buffers.
+const READ_GROUP: BufferGroupId = BufferGroupId { id: 7 };
+const RECV_GROUP: BufferGroupId = BufferGroupId { id: 8 };
+
+fn provide(bufs: Box<[u8]>, count: u32, group: BufferGroupId) -> Box<[u8]> {
+    let provide = ProvideBuffers { bufs, count, group, index: 0 };
+    let (provide, result) = futures::executor::block_on(demo::driver().submit(provide));
+    result.unwrap();
+    provide.bufs
+}
+
+#[test]
+fn read_select() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let bufs = provide(vec![0; 32].into(), 2, READ_GROUP);
+
+    assert_eq!(unsafe { libc::write(fds[1], b"hello".as_ptr() as _, 5) }, 5);
+    let read = ReadSelect { fd: fds[0], group: READ_GROUP, len: 16, offset: 0, buffer: None };
+    let (read, result) = futures::executor::block_on(demo::driver().submit(read));
+    assert_eq!(result.unwrap(), 5);
+    let start = read.buffer.unwrap() as usize * 16;
+    assert_eq!(&bufs[start..start + 5], b"hello");
+
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
+
+#[test]
+fn recv_select() {
+    let mut fds = [0; 2];
+    let ret = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(ret, 0);
+    let bufs = provide(vec![0; 32].into(), 2, RECV_GROUP);
+
+    assert_eq!(unsafe { libc::write(fds[1], b"world".as_ptr() as _, 5) }, 5);
+    let recv = RecvSelect {
+        fd: fds[0], group: RECV_GROUP, len: 16, flags: MsgFlags::empty(), buffer: None,
+    };
+    let (recv, result) = futures::executor::block_on(demo::driver().submit(recv));
+    assert_eq!(result.unwrap(), 5);
+    let start = recv.buffer.unwrap() as usize * 16;
+    assert_eq!(&bufs[start..start + 5], b"world");
+
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
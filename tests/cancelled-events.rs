@@ -0,0 +1,49 @@
+use std::pin::Pin;
+use std::thread;
+use std::time::Duration;
+
+use ringbahn::Drive;
+use ringbahn::drive::demo;
+use ringbahn::event::Read;
+use ringbahn::ring::CancelledEvents;
+
+#[test]
+fn reclaim_cancelled_read() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+    let queue = CancelledEvents::new();
+    let read = Read { fd: fds[0], buf: vec![0; 32].into(), offset: 0 };
+    let mut submission = Box::pin(demo::driver().submit(read));
+    submission.as_mut().reclaim_into(&queue);
+    futures::executor::block_on(async {
+        assert!(futures::poll!(submission.as_mut()).is_pending());
+    });
+    drop(submission);
+    assert!(queue.is_empty());
+
+    // Completing the read hands the event back.
+    assert_eq!(unsafe { libc::write(fds[1], b"hello".as_ptr() as _, 5) }, 5);
+    for _ in 0..100 {
+        if !queue.is_empty() { break }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let read = queue.pop().unwrap();
+    assert_eq!(&read.buf[..5], b"hello");
+    assert_eq!(read.buf.len(), 32);
+
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
+
+#[test]
+fn reclaim_unsubmitted_event() {
+    let queue = CancelledEvents::new();
+    let read = Read { fd: 0, buf: vec![0; 32].into(), offset: 0 };
+    let mut submission = Box::pin(demo::driver().submit(read));
+    submission.as_mut().reclaim_into(&queue);
+    drop(Pin::into_inner(submission));
+    assert_eq!(queue.len(), 1);
+}
@@ -0,0 +1,29 @@
+use std::fs;
+use std::net::TcpListener as StdTcpListener;
+use std::thread;
+use std::time::Duration;
+
+use ringbahn::drive::demo;
+use ringbahn::net::TcpStream;
+
+fn open_fds() -> usize {
+    fs::read_dir("/proc/self/fd").unwrap().count()
+}
+
+#[test]
+fn dropped_connect_closes_socket() {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async {
+        // Connect once first, so that the driver has set up its ring.
+        TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let before = open_fds();
+        for _ in 0..8 {
+            let mut connect = Box::pin(TcpStream::connect_on_driver(addr, demo::driver()));
+            // The first poll submits the creation of the socket.
+            assert!(futures::poll!(connect.as_mut()).is_pending());
+        }
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(open_fds(), before);
+    });
+}
@@ -0,0 +1,29 @@
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+
+use ringbahn::drive::demo;
+use ringbahn::net::TcpStream;
+
+#[test]
+fn connect_from_local_addr() {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Bind and drop a listener to find a free local port.
+    let local = StdTcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    futures::executor::block_on(async {
+        let stream = TcpStream::connect_from_on_driver(local, addr, demo::driver()).await.unwrap();
+        assert_eq!(stream.local_addr().unwrap(), local);
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+    });
+    let (_, peer) = listener.accept().unwrap();
+    assert_eq!(peer, local);
+}
+
+#[test]
+fn connect_from_other_family() {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let local: SocketAddr = "[::1]:0".parse().unwrap();
+    futures::executor::block_on(async {
+        let addr = listener.local_addr().unwrap();
+        assert!(TcpStream::connect_from_on_driver(local, addr, demo::driver()).await.is_err());
+    });
+}
@@ -0,0 +1,32 @@
+use std::io;
+use std::time::Duration;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+
+#[test]
+fn connect_within_timeout() {
+    let _listener = TcpListener::bind(("127.0.0.1", 47240)).unwrap();
+    futures::executor::block_on(async {
+        let timeout = Duration::from_secs(5);
+        TcpStream::connect_timeout_on_driver(("127.0.0.1", 47240), timeout, demo::driver()).await.unwrap();
+    });
+}
+
+#[test]
+fn connect_times_out() {
+    // This address is not routed, so the SYN is never answered.
+    futures::executor::block_on(async {
+        let timeout = Duration::from_millis(50);
+        let result = TcpStream::connect_timeout_on_driver(("10.255.255.1", 80), timeout, demo::driver()).await;
+        // Behind a transparent proxy, the connect is answered anyway.
+        let err = match result {
+            Ok(_)       => return,
+            Err(err)    => err,
+        };
+        // Without a route at all, the connect fails immediately instead.
+        if err.raw_os_error() != Some(libc::ENETUNREACH) {
+            assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        }
+    });
+}
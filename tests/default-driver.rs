@@ -0,0 +1,48 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use futures::AsyncReadExt;
+use iou::SQEs;
+
+use ringbahn::drive::{demo, Completion, Drive};
+use ringbahn::fs::File;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+static PREPARED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone)]
+struct Counting(demo::DemoDriver);
+
+impl Drive for Counting {
+    fn poll_prepare<'cx>(
+        self: Pin<&mut Self>,
+        ctx: &mut Context<'cx>,
+        count: u32,
+        prepare: impl FnOnce(SQEs<'_>, &mut Context<'cx>) -> Completion<'cx>,
+    ) -> Poll<Completion<'cx>> {
+        PREPARED.fetch_add(1, Ordering::SeqCst);
+        let inner = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+        inner.poll_prepare(ctx, count, prepare)
+    }
+
+    fn poll_submit(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<io::Result<u32>> {
+        let inner = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+        inner.poll_submit(ctx)
+    }
+}
+
+#[test]
+fn open_on_default_driver() {
+    assert!(ringbahn::set_default_driver(|| Counting(demo::driver())).is_ok());
+    assert!(ringbahn::set_default_driver(demo::driver).is_err());
+    futures::executor::block_on(async move {
+        let mut file = File::open("props.txt").await.unwrap();
+        let mut buf = vec![0; 4096];
+        assert!(file.read(&mut buf).await.is_ok());
+        assert_eq!(&buf[0..ASSERT.len()], ASSERT);
+    });
+    assert!(PREPARED.load(Ordering::SeqCst) >= 2);
+}
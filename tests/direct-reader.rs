@@ -0,0 +1,45 @@
+use std::io;
+
+use futures::StreamExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{DirectReader, File, OpenOptions};
+
+#[test]
+fn scan_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    // Five and a half chunks of 8 KiB, so the last chunk is short
+    let data: Vec<u8> = (0..45056).map(|i| (i / 4096) as u8).collect();
+    std::fs::write(&path, &data).unwrap();
+
+    futures::executor::block_on(async {
+        let mut options = OpenOptions::new();
+        options.read(true).direct(true);
+        let file = match options.open_on_driver(&path, demo::driver()).await {
+            Ok(file)    => file,
+            // Not every filesystem supports direct IO.
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return,
+            Err(err)    => panic!("{}", err),
+        };
+
+        let mut reader = DirectReader::new(file, 8192, 3).unwrap();
+        let mut scanned = Vec::new();
+        while let Some(chunk) = reader.next().await {
+            let chunk = chunk.unwrap();
+            assert_eq!(chunk.offset(), scanned.len() as u64);
+            scanned.extend_from_slice(&chunk);
+            reader.recycle(chunk.into_buf());
+        }
+        assert_eq!(scanned, data);
+        assert!(reader.next().await.is_none());
+    });
+}
+
+#[test]
+fn misaligned_chunks() {
+    let file = std::fs::File::open("props.txt").unwrap();
+    let file = File::from(file);
+    let err = DirectReader::new(file, 1000, 2).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,46 @@
+use std::fs::File as StdFile;
+use std::os::unix::io::AsRawFd;
+
+use futures::AsyncReadExt;
+
+use ringbahn::Drive;
+use ringbahn::drive::demo;
+use ringbahn::event::Read;
+use ringbahn::fs::File;
+use ringbahn::ring::SharedRing;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn submit_on_mut_ref() {
+    let file = StdFile::open("props.txt").unwrap();
+    let mut driver = demo::driver();
+    let read = Read { fd: file.as_raw_fd(), buf: vec![0; ASSERT.len()].into(), offset: 0 };
+    let (read, result) = futures::executor::block_on((&mut driver).submit(read));
+    assert_eq!(result.unwrap() as usize, ASSERT.len());
+    assert_eq!(&read.buf[..], ASSERT);
+}
+
+#[test]
+fn read_on_boxed_driver() {
+    futures::executor::block_on(async {
+        let mut file = File::open_on_driver("props.txt", Box::new(demo::driver())).await.unwrap();
+        let mut buf = vec![0; ASSERT.len()];
+        file.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
+
+#[test]
+fn read_on_shared_ref() {
+    let shared = SharedRing::new(demo::driver());
+    futures::executor::block_on(async {
+        let mut first = File::open_on_driver("props.txt", &shared).await.unwrap();
+        let mut second = File::open_on_driver("props.txt", &shared).await.unwrap();
+        let mut buf = vec![0; ASSERT.len()];
+        first.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+        second.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
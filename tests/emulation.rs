@@ -0,0 +1,52 @@
+use ringbahn::Drive;
+use ringbahn::drive::demo;
+use ringbahn::event::{Event, Emulation, SQE, SQEs};
+
+/// An event with an opcode no kernel supports, so it is always emulated.
+struct Unsupported {
+    value: u32,
+}
+
+impl Event for Unsupported {
+    fn sqes_needed(&self) -> u32 { 1 }
+
+    unsafe fn prepare<'sq>(&mut self, _: &mut SQEs<'sq>) -> SQE<'sq> {
+        panic!("an unsupported event was prepared")
+    }
+
+    unsafe fn emulate(&mut self) -> Option<Emulation> {
+        let value = self.value;
+        Some(Emulation::new(u8::MAX, move || Ok(value)))
+    }
+}
+
+#[test]
+fn emulate_unsupported_opcode() {
+    futures::executor::block_on(async {
+        let (event, result) = demo::driver().submit(Unsupported { value: 42 }).await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(event.value, 42);
+    });
+}
+
+#[test]
+fn emulate_unsupported_opcode_error() {
+    struct Failing;
+
+    impl Event for Failing {
+        fn sqes_needed(&self) -> u32 { 1 }
+
+        unsafe fn prepare<'sq>(&mut self, _: &mut SQEs<'sq>) -> SQE<'sq> {
+            panic!("an unsupported event was prepared")
+        }
+
+        unsafe fn emulate(&mut self) -> Option<Emulation> {
+            Some(Emulation::new(u8::MAX, || Err(std::io::Error::from_raw_os_error(libc::EBADF))))
+        }
+    }
+
+    futures::executor::block_on(async {
+        let (_, result) = demo::driver().submit(Failing).await;
+        assert_eq!(result.unwrap_err().raw_os_error(), Some(libc::EBADF));
+    });
+}
@@ -0,0 +1,49 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+
+use ringbahn::drive::demo;
+use ringbahn::unix::UnixStream;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn pass_pipe_over_unix_stream() {
+    let (mut a, mut b) = UnixStream::pair_on_driver(demo::driver()).unwrap();
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) }, 0);
+    let read_end = unsafe { File::from_raw_fd(fds[0]) };
+    let mut write_end = unsafe { File::from_raw_fd(fds[1]) };
+
+    futures::executor::block_on(async {
+        let n = a.send_msg(b"fd", &[read_end.as_raw_fd()]).await.unwrap();
+        assert_eq!(n, 2);
+
+        let mut buf = [0; 8];
+        let mut received = [-1; 2];
+        let (n, nfds) = b.recv_msg(&mut buf, &mut received).await.unwrap();
+        assert_eq!(&buf[..n], b"fd");
+        assert_eq!(nfds, 1);
+        assert_ne!(received[0], read_end.as_raw_fd());
+
+        write_end.write_all(ASSERT).unwrap();
+        drop(write_end);
+        let mut passed = unsafe { File::from_raw_fd(received[0]) };
+        let mut data = Vec::new();
+        passed.read_to_end(&mut data).unwrap();
+        assert_eq!(&data[..], ASSERT);
+    });
+}
+
+#[test]
+fn recv_msg_without_fds() {
+    let (mut a, mut b) = UnixStream::pair_on_driver(demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        a.send_msg(ASSERT, &[]).await.unwrap();
+        let mut buf = [0; 64];
+        let mut received = [-1; 1];
+        let (n, nfds) = b.recv_msg(&mut buf, &mut received).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(nfds, 0);
+    });
+}
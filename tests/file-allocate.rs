@@ -0,0 +1,37 @@
+use std::fs as std_fs;
+use std::os::unix::fs::MetadataExt;
+
+use futures::AsyncWriteExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{FallocateFlags, File};
+
+#[test]
+fn allocate_and_punch_hole() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    let punched = futures::executor::block_on(async {
+        let mut file = File::create_on_driver(&path, demo::driver()).await.unwrap();
+        file.allocate(0, 1 << 20, FallocateFlags::empty()).await.unwrap();
+        assert_eq!(std_fs::metadata(&path).unwrap().len(), 1 << 20);
+
+        file.allocate(1 << 20, 1 << 20, FallocateFlags::FALLOC_FL_KEEP_SIZE).await.unwrap();
+        assert_eq!(std_fs::metadata(&path).unwrap().len(), 1 << 20);
+
+        file.write_all(&[1; 8192]).await.unwrap();
+        let flags = FallocateFlags::FALLOC_FL_PUNCH_HOLE | FallocateFlags::FALLOC_FL_KEEP_SIZE;
+        match file.allocate(0, 4096, flags).await {
+            Ok(())  => true,
+            // Not every filesystem supports punching holes.
+            Err(err) if err.raw_os_error() == Some(libc::EOPNOTSUPP) => false,
+            Err(err) => panic!("{}", err),
+        }
+    });
+    if !punched {
+        return;
+    }
+    let data = std_fs::read(&path).unwrap();
+    assert!(data[..4096].iter().all(|&b| b == 0));
+    assert!(data[4096..8192].iter().all(|&b| b == 1));
+    assert!(std_fs::metadata(&path).unwrap().blocks() > 0);
+}
@@ -0,0 +1,35 @@
+use std::fs as std_fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs;
+
+#[test]
+fn copy_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let (from, to) = (dir.path().join("from"), dir.path().join("to"));
+    // Larger than one chunk, and not a multiple of it
+    let contents: Vec<u8> = (0..200_000u32).map(|n| n as u8).collect();
+    std_fs::write(&from, &contents).unwrap();
+    std_fs::set_permissions(&from, std_fs::Permissions::from_mode(0o640)).unwrap();
+    std_fs::write(&to, vec![1; 300_000]).unwrap();
+
+    let n = futures::executor::block_on(fs::copy_on_driver(&from, &to, demo::driver())).unwrap();
+    assert_eq!(n, contents.len() as u64);
+    assert_eq!(std_fs::read(&to).unwrap(), contents);
+    assert_eq!(std_fs::metadata(&to).unwrap().permissions().mode() & 0o777, 0o640);
+}
+
+#[test]
+fn copy_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let to = dir.path().join("to");
+    futures::executor::block_on(async {
+        let result = fs::copy_on_driver(dir.path().join("missing"), &to, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+        let result = fs::copy_on_driver(dir.path(), &to, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+    });
+    assert!(!to.exists());
+}
@@ -0,0 +1,49 @@
+use std::io;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{AlignedBuf, OpenOptions};
+
+#[test]
+fn aligned_buf() {
+    let buf = AlignedBuf::new(8192);
+    assert_eq!(buf.len(), 8192);
+    assert_eq!(buf.as_ptr() as usize % AlignedBuf::DEFAULT_ALIGNMENT, 0);
+    assert!(buf.iter().all(|&byte| byte == 0));
+
+    let buf = AlignedBuf::with_alignment(1024, 512).unwrap();
+    assert_eq!(buf.as_ptr() as usize % 512, 0);
+    assert_eq!(AlignedBuf::with_alignment(1024, 3).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn direct_write_and_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    futures::executor::block_on(async {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).direct(true);
+        let mut file = match options.open_on_driver(&path, demo::driver()).await {
+            Ok(file)    => file,
+            // Not every filesystem supports direct IO.
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return,
+            Err(err)    => panic!("{}", err),
+        };
+
+        let mut buf = AlignedBuf::new(8192);
+        buf[..4096].fill(1);
+        buf[4096..].fill(2);
+        let (_, result) = file.write_direct(buf, 4096).await;
+        assert_eq!(result.unwrap(), 8192);
+
+        let (buf, result) = file.read_direct(AlignedBuf::new(4096), 8192).await;
+        assert_eq!(result.unwrap(), 4096);
+        assert!(buf.iter().all(|&byte| byte == 2));
+
+        // Misaligned IO is rejected before it reaches the kernel
+        let (_, result) = file.read_direct(buf, 100).await;
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let (_, result) = file.write_direct(AlignedBuf::new(100), 0).await;
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    });
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 12288);
+}
@@ -0,0 +1,46 @@
+use std::fs as std_fs;
+use std::os::unix::io::AsRawFd;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+fn try_flock(file: &std_fs::File, operation: i32) -> bool {
+    unsafe { libc::flock(file.as_raw_fd(), operation | libc::LOCK_NB) == 0 }
+}
+
+#[test]
+fn exclusive_and_shared_locks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lock");
+    std_fs::write(&path, b"").unwrap();
+    let other = std_fs::File::open(&path).unwrap();
+    futures::executor::block_on(async {
+        let mut file = File::open_on_driver(&path, demo::driver()).await.unwrap();
+        file.lock_exclusive().await.unwrap();
+        assert!(!try_flock(&other, libc::LOCK_SH));
+        file.unlock().await.unwrap();
+        assert!(try_flock(&other, libc::LOCK_SH));
+
+        file.lock_shared().await.unwrap();
+        assert!(!try_flock(&other, libc::LOCK_EX));
+        assert!(try_flock(&other, libc::LOCK_UN));
+    });
+}
+
+#[test]
+fn lock_waits_for_release() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lock");
+    std_fs::write(&path, b"").unwrap();
+    let other = std_fs::File::open(&path).unwrap();
+    assert!(try_flock(&other, libc::LOCK_EX));
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(other);
+    });
+    futures::executor::block_on(async {
+        let mut file = File::open_on_driver(&path, demo::driver()).await.unwrap();
+        file.lock_exclusive().await.unwrap();
+    });
+    release.join().unwrap();
+}
@@ -0,0 +1,34 @@
+use std::fs as std_fs;
+
+use futures::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+#[test]
+fn concurrent_positional_io() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    std_fs::write(&path, b"0123456789").unwrap();
+    let std_file = std_fs::OpenOptions::new().read(true).write(true).open(&path).unwrap();
+    futures::executor::block_on(async {
+        let mut file = File::run_on_driver(std_file, demo::driver());
+        let (first, second) = futures::join!(
+            file.read_at(vec![0; 4].into_boxed_slice(), 2),
+            file.read_at(vec![0; 4].into_boxed_slice(), 6),
+        );
+        assert_eq!((&first.0[..], first.1.unwrap()), (&b"2345"[..], 4));
+        assert_eq!((&second.0[..], second.1.unwrap()), (&b"6789"[..], 4));
+
+        let (_, result) = file.read_at(vec![0; 4].into_boxed_slice(), 10).await;
+        assert_eq!(result.unwrap(), 0);
+
+        let (_, result) = file.write_at(b"abc".to_vec().into_boxed_slice(), 7).await;
+        assert_eq!(result.unwrap(), 3);
+
+        // The file's position is untouched
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).await.unwrap();
+        assert_eq!(contents, "0123456abc");
+    });
+}
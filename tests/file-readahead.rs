@@ -0,0 +1,19 @@
+use futures::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+#[test]
+fn readahead_then_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    std::fs::write(&path, vec![7; 1 << 16]).unwrap();
+    futures::executor::block_on(async {
+        let mut file = File::open_on_driver(&path, demo::driver()).await.unwrap();
+        file.readahead(0, 1 << 15).await.unwrap();
+        file.readahead(1 << 15, 0).await.unwrap();
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).await.unwrap();
+        assert_eq!(data, vec![7; 1 << 16]);
+    });
+}
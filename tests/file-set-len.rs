@@ -0,0 +1,27 @@
+use std::fs as std_fs;
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+#[test]
+fn truncate_and_extend() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    futures::executor::block_on(async {
+        let mut file = File::create_on_driver(&path, demo::driver()).await.unwrap();
+        file.write_all(b"0123456789").await.unwrap();
+        file.set_len(4).await.unwrap();
+        assert_eq!(std_fs::read(&path).unwrap(), b"0123");
+        file.set_len(8).await.unwrap();
+        assert_eq!(std_fs::read(&path).unwrap(), b"0123\0\0\0\0");
+
+        let mut file = File::open_on_driver(&path, demo::driver()).await.unwrap();
+        let mut contents = vec![];
+        file.read_to_end(&mut contents).await.unwrap();
+        assert_eq!(contents.len(), 8);
+        // Opened read-only
+        assert_eq!(file.set_len(0).await.unwrap_err().raw_os_error(), Some(libc::EINVAL));
+    });
+}
@@ -0,0 +1,50 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn from_std_continues_at_offset() {
+    let mut std_file = std::fs::File::open("props.txt").unwrap();
+    let mut buf = [0; 4];
+    std_file.read_exact(&mut buf).unwrap();
+
+    let mut file = File::from_std(std_file, demo::driver());
+    let mut buf = vec![0; ASSERT.len() - 4];
+    futures::executor::block_on(file.read_exact(&mut buf)).unwrap();
+    assert_eq!(&buf[..], &ASSERT[4..]);
+}
+
+#[test]
+fn into_std_keeps_offset() {
+    let mut tmp = tempfile::tempfile().unwrap();
+    tmp.write_all(b"hello, world").unwrap();
+    tmp.seek(SeekFrom::Start(0)).unwrap();
+
+    let mut file = File::from_std(tmp, demo::driver());
+    let mut buf = [0; 7];
+    futures::executor::block_on(file.read_exact(&mut buf)).unwrap();
+
+    let mut std_file = file.into_std();
+    let mut rest = String::new();
+    std_file.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "world");
+}
+
+#[test]
+fn raw_fd_round_trip() {
+    let tmp = tempfile::tempfile().unwrap();
+    let mut file: File = unsafe { File::from_raw_fd(tmp.into_raw_fd()) };
+    futures::executor::block_on(file.write_all(b"memfd")).unwrap();
+
+    let mut std_file = unsafe { std::fs::File::from_raw_fd(file.into_raw_fd()) };
+    std_file.seek(SeekFrom::Start(0)).unwrap();
+    let mut contents = String::new();
+    std_file.read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "memfd");
+}
@@ -0,0 +1,39 @@
+use futures::AsyncWriteExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn sync_all_and_sync_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal");
+    futures::executor::block_on(async {
+        let mut file = File::create_on_driver(&path, demo::driver()).await.unwrap();
+        file.write_all(ASSERT).await.unwrap();
+        file.sync_data().await.unwrap();
+        file.write_all(ASSERT).await.unwrap();
+        file.sync_all().await.unwrap();
+        // Writing continues where it left off after a sync.
+        file.write_all(ASSERT).await.unwrap();
+    });
+    assert_eq!(std::fs::read(&path).unwrap(), ASSERT.repeat(3));
+}
+
+#[test]
+fn sync_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    futures::executor::block_on(async {
+        let mut file = File::create_on_driver(&path, demo::driver()).await.unwrap();
+        file.write_all(&ASSERT.repeat(100)).await.unwrap();
+        let len = ASSERT.len() as u32 * 50;
+        file.sync_range(0, len, libc::SYNC_FILE_RANGE_WRITE).await.unwrap();
+        let flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE | libc::SYNC_FILE_RANGE_WRITE
+            | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+        file.sync_range(0, 0, flags).await.unwrap();
+        let result = file.sync_range(0, 0, !0).await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::EINVAL));
+    });
+}
@@ -0,0 +1,43 @@
+use std::fs as std_fs;
+use std::io;
+
+use ringbahn::drive::demo;
+use ringbahn::fs;
+
+#[test]
+fn write_then_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    let contents: Vec<u8> = (0..100_000u32).map(|n| n as u8).collect();
+    std_fs::write(&path, vec![1; 200_000]).unwrap();
+    futures::executor::block_on(async {
+        fs::write_on_driver(&path, &contents, demo::driver()).await.unwrap();
+        assert_eq!(std_fs::read(&path).unwrap(), contents);
+        assert_eq!(fs::read_on_driver(&path, demo::driver()).await.unwrap(), contents);
+
+        fs::write_on_driver(&path, b"", demo::driver()).await.unwrap();
+        assert!(fs::read_on_driver(&path, demo::driver()).await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn read_file_without_size() {
+    // Files in procfs report a size of 0
+    futures::executor::block_on(async {
+        let status = fs::read_on_driver("/proc/self/status", demo::driver()).await.unwrap();
+        assert!(String::from_utf8(status).unwrap().contains("Pid:"));
+    });
+}
+
+#[test]
+fn read_write_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    futures::executor::block_on(async {
+        let result = fs::read_on_driver(dir.path().join("missing"), demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+        let result = fs::read_on_driver(dir.path(), demo::driver()).await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::EISDIR));
+        let result = fs::write_on_driver(dir.path().join("a/b"), b"x", demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+    });
+}
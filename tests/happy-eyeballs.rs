@@ -0,0 +1,38 @@
+use std::io;
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+
+use ringbahn::drive::demo;
+use ringbahn::net::TcpStream;
+
+fn refused_addr() -> SocketAddr {
+    // Bind and drop a listener to find a port nothing is listening on.
+    StdTcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+#[test]
+fn falls_back_to_next_address() {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let addrs = [refused_addr(), listener.local_addr().unwrap()];
+    futures::executor::block_on(async {
+        let stream = TcpStream::connect_on_driver(&addrs[..], demo::driver()).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addrs[1]);
+    });
+}
+
+#[test]
+fn reports_last_error() {
+    let addrs = [refused_addr(), refused_addr()];
+    futures::executor::block_on(async {
+        let result = TcpStream::connect_on_driver(&addrs[..], demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::ConnectionRefused);
+    });
+}
+
+#[test]
+fn no_addresses() {
+    let addrs: [SocketAddr; 0] = [];
+    futures::executor::block_on(async {
+        let result = TcpStream::connect_on_driver(&addrs[..], demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+    });
+}
@@ -0,0 +1,66 @@
+use std::io::Read;
+use std::net::TcpStream as StdTcpStream;
+use std::thread;
+use std::time::Duration;
+
+use futures::{AsyncWriteExt, StreamExt};
+
+use ringbahn::drive::demo;
+use ringbahn::net::TcpListener;
+
+#[test]
+fn incoming_accepts_every_connection() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 47241), demo::driver()).unwrap();
+    let clients: Vec<_> = (0..4).map(|_| thread::spawn(|| {
+        let mut stream = StdTcpStream::connect(("127.0.0.1", 47241)).unwrap();
+        let local = stream.local_addr().unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"pong");
+        local
+    })).collect();
+    futures::executor::block_on(async {
+        let mut incoming = listener.incoming();
+        for _ in 0..4 {
+            let (mut stream, addr) = incoming.next().await.unwrap().unwrap();
+            assert!(addr.ip().is_loopback());
+            stream.write_all(b"pong").await.unwrap();
+        }
+    });
+    for client in clients {
+        client.join().unwrap();
+    }
+}
+
+#[test]
+fn incoming_ends_after_shutdown() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = listener.shutdown_handle();
+    let client = thread::spawn(move || StdTcpStream::connect(addr).unwrap());
+    futures::executor::block_on(async {
+        let mut incoming = listener.incoming();
+        assert!(incoming.next().await.unwrap().is_ok());
+        handle.shutdown_accepting();
+        assert!(incoming.next().await.is_none());
+    });
+    client.join().unwrap();
+}
+
+#[test]
+fn dropped_listener_closes_queued_connections() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let clients = [StdTcpStream::connect(addr).unwrap(), StdTcpStream::connect(addr).unwrap()];
+    futures::executor::block_on(async {
+        let mut incoming = listener.incoming();
+        incoming.next().await.unwrap().unwrap();
+        // Give the multishot accept time to accept the second connection too.
+        thread::sleep(Duration::from_millis(50));
+    });
+    drop(listener);
+    for mut client in clients {
+        client.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(client.read(&mut [0; 1]).unwrap(), 0);
+    }
+}
@@ -0,0 +1,37 @@
+use std::net::SocketAddr;
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream, UdpSocket};
+use ringbahn::net::sockopt::Ipv6Only;
+
+#[test]
+fn tcp_over_ipv6() {
+    let listener = TcpListener::builder().only_v6(true);
+    let mut listener = listener.bind_on_driver(("::1", 47244), demo::driver()).unwrap();
+    assert_eq!(listener.opt::<Ipv6Only>().unwrap(), Ipv6Only(true));
+    futures::executor::block_on(async {
+        let mut client = TcpStream::connect_on_driver(("::1", 47244), demo::driver()).await.unwrap();
+        let (mut stream, addr) = listener.accept().await.unwrap();
+        assert!(matches!(addr, SocketAddr::V6(_)));
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+}
+
+#[test]
+fn udp_over_ipv6() {
+    let mut a = UdpSocket::bind_on_driver(("::1", 0), demo::driver()).unwrap();
+    let mut b = UdpSocket::bind_on_driver(("::1", 0), demo::driver()).unwrap();
+    let b_addr = b.local_addr().unwrap();
+    futures::executor::block_on(async {
+        a.send_to(b"ping", b_addr).await.unwrap();
+        let mut buf = [0; 4];
+        let (n, from) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(from, a.local_addr().unwrap());
+    });
+}
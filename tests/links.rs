@@ -0,0 +1,38 @@
+use std::fs as std_fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use ringbahn::drive::demo;
+use ringbahn::fs;
+
+#[test]
+fn symlink_and_read_link() {
+    let dir = tempfile::tempdir().unwrap();
+    let (file, link) = (dir.path().join("file"), dir.path().join("link"));
+    std_fs::write(&file, b"contents").unwrap();
+    futures::executor::block_on(async {
+        fs::symlink_on_driver("file", &link, demo::driver()).await.unwrap();
+        assert_eq!(std_fs::read(&link).unwrap(), b"contents");
+        assert_eq!(fs::read_link(&link).await.unwrap(), Path::new("file"));
+
+        let result = fs::symlink_on_driver("file", &link, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::AlreadyExists);
+        let result = fs::read_link(&file).await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::EINVAL));
+    });
+}
+
+#[test]
+fn hard_link() {
+    let dir = tempfile::tempdir().unwrap();
+    let (file, link) = (dir.path().join("file"), dir.path().join("link"));
+    std_fs::write(&file, b"contents").unwrap();
+    futures::executor::block_on(async {
+        fs::hard_link_on_driver(&file, &link, demo::driver()).await.unwrap();
+        let result = fs::hard_link_on_driver(dir.path().join("missing"), &link, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+    });
+    assert_eq!(std_fs::metadata(&file).unwrap().ino(), std_fs::metadata(&link).unwrap().ino());
+    assert_eq!(std_fs::metadata(&file).unwrap().nlink(), 2);
+}
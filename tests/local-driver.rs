@@ -0,0 +1,68 @@
+use std::thread;
+use std::time::Duration;
+
+use futures::AsyncReadExt;
+use futures::channel::oneshot;
+use futures::future::{self, Either};
+
+use ringbahn::drive::local;
+use ringbahn::fs::File;
+use ringbahn::pipe;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn read_file() {
+    local::block_on(async move {
+        let mut file = File::open_on_driver("props.txt", local::driver()).await.unwrap();
+        let mut buf = Vec::new();
+        assert!(file.read_to_end(&mut buf).await.is_ok());
+        assert_eq!(&buf[0..ASSERT.len()], ASSERT);
+    });
+}
+
+#[test]
+fn select_many_reads() {
+    async fn act() {
+        let mut file = File::open_on_driver("props.txt", local::driver()).await.unwrap();
+        let mut buf = vec![0; 64];
+        assert!(file.read(&mut buf).await.is_ok());
+        assert_eq!(&buf[0..ASSERT.len()], ASSERT);
+    }
+
+    local::block_on(async move {
+        futures::join!(act(), act(), act(), act(), act(), act(), act(), act());
+    });
+}
+
+#[test]
+fn is_blocking() {
+    assert!(!local::is_blocking());
+    local::block_on(async {
+        assert!(local::is_blocking());
+        local::block_on(async {});
+        assert!(local::is_blocking());
+    });
+    assert!(!local::is_blocking());
+}
+
+#[test]
+fn wake_from_another_thread() {
+    local::block_on(async {
+        let (_sender, mut receiver) = pipe::pipe_on_driver(local::driver()).unwrap();
+        let mut buf = [0; 8];
+        // Nothing is written to the pipe, so the thread waits for the read to complete until it
+        // is woken.
+        let read = receiver.read(&mut buf);
+        let (tx, rx) = oneshot::channel();
+        let waker = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send(()).unwrap();
+        });
+        match future::select(read, rx).await {
+            Either::Right((result, _))  => result.unwrap(),
+            Either::Left(_)             => panic!("read from an empty pipe"),
+        }
+        waker.join().unwrap();
+    });
+}
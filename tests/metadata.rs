@@ -0,0 +1,49 @@
+use std::fs as std_fs;
+use std::os::unix::fs::{MetadataExt, symlink};
+
+use futures::AsyncWriteExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{self, File};
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn file_metadata() {
+    let mut file = File::run_on_driver(tempfile::tempfile().unwrap(), demo::driver());
+    futures::executor::block_on(async {
+        file.write_all(ASSERT).await.unwrap();
+        let metadata = file.metadata().await.unwrap();
+        assert!(metadata.is_file());
+        assert_eq!(metadata.len(), ASSERT.len() as u64);
+        assert!(metadata.modified().is_ok());
+    });
+}
+
+#[test]
+fn path_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    let link = dir.path().join("link");
+    std_fs::write(&path, ASSERT).unwrap();
+    symlink(&path, &link).unwrap();
+    let expected = std_fs::metadata(&path).unwrap();
+    futures::executor::block_on(async {
+        let metadata = fs::metadata_on_driver(&link, demo::driver()).await.unwrap();
+        assert!(metadata.is_file());
+        assert_eq!(metadata.len(), ASSERT.len() as u64);
+        assert_eq!(metadata.mode(), expected.mode());
+        assert_eq!(metadata.ino(), expected.ino());
+        assert_eq!(metadata.dev(), expected.dev());
+        assert_eq!(metadata.modified().unwrap(), expected.modified().unwrap());
+
+        let metadata = fs::symlink_metadata_on_driver(&link, demo::driver()).await.unwrap();
+        assert!(metadata.is_symlink());
+
+        let metadata = fs::metadata_on_driver(dir.path(), demo::driver()).await.unwrap();
+        assert!(metadata.is_dir());
+
+        let missing = fs::metadata_on_driver(dir.path().join("missing"), demo::driver()).await;
+        assert_eq!(missing.err().unwrap().kind(), std::io::ErrorKind::NotFound);
+    });
+}
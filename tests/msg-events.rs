@@ -0,0 +1,63 @@
+use std::fs::File;
+use std::io::Read;
+use std::net::UdpSocket;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::net::UnixStream;
+
+use iou::sqe::MsgFlags;
+
+use ringbahn::Submission;
+use ringbahn::event::{SendMsg, RecvMsg};
+use ringbahn::drive::demo;
+use ringbahn::net::SockAddr;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn datagram_to_address() {
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let bufs: Vec<Box<[u8]>> = vec![Box::from(&ASSERT[..9]), Box::from(&ASSERT[9..])];
+    let mut send = SendMsg::new(sender.as_raw_fd(), bufs.into_boxed_slice());
+    send.addr = Some(SockAddr::from(receiver.local_addr().unwrap()));
+    let (_, result) = futures::executor::block_on(Submission::new(send, demo::driver()));
+    assert_eq!(result.unwrap() as usize, ASSERT.len());
+
+    let bufs: Vec<Box<[u8]>> = vec![Box::new([0; 4]), Box::new([0; 64])];
+    let recv = RecvMsg::new(receiver.as_raw_fd(), bufs.into_boxed_slice());
+    let (recv, result) = futures::executor::block_on(Submission::new(recv, demo::driver()));
+    assert_eq!(result.unwrap() as usize, ASSERT.len());
+    assert_eq!(&recv.bufs[0][..], &ASSERT[..4]);
+    assert_eq!(&recv.bufs[1][..ASSERT.len() - 4], &ASSERT[4..]);
+    let addr = recv.addr().unwrap().unwrap();
+    assert_eq!(addr.as_inet(), Some(sender.local_addr().unwrap()));
+    assert!(!recv.msg_flags().contains(MsgFlags::MSG_TRUNC));
+}
+
+#[test]
+fn pass_fd() {
+    let (left, right) = UnixStream::pair().unwrap();
+    let file = File::open("props.txt").unwrap();
+
+    let mut send = SendMsg::new(left.as_raw_fd(), vec![Box::from(&b"fd"[..])].into_boxed_slice());
+    send.set_fds(&[file.as_raw_fd()]);
+    let (_, result) = futures::executor::block_on(Submission::new(send, demo::driver()));
+    assert_eq!(result.unwrap(), 2);
+
+    let bufs: Vec<Box<[u8]>> = vec![Box::new([0; 8])];
+    let mut recv = RecvMsg::new(right.as_raw_fd(), bufs.into_boxed_slice());
+    recv.reserve_fds(2);
+    recv.flags = MsgFlags::MSG_CMSG_CLOEXEC;
+    let (recv, result) = futures::executor::block_on(Submission::new(recv, demo::driver()));
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(&recv.bufs[0][..2], b"fd");
+
+    let fds = recv.received_fds();
+    assert_eq!(fds.len(), 1);
+    assert_ne!(fds[0], file.as_raw_fd());
+    let mut received = unsafe { File::from_raw_fd(fds[0]) };
+    let mut buf = vec![0; ASSERT.len()];
+    received.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..], ASSERT);
+}
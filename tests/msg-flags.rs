@@ -0,0 +1,67 @@
+use futures::io::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{MsgFlags, TcpListener, TcpStream, UdpSocket};
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn tcp_peek_then_read() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async move {
+        let mut client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+        client.send_with_flags(ASSERT, MsgFlags::empty()).await.unwrap();
+
+        let mut buf = [0; 64];
+        let n = server.recv_with_flags(&mut buf, MsgFlags::MSG_PEEK).await.unwrap();
+        assert_eq!(&buf[..n], &ASSERT[..n]);
+
+        let mut buf = vec![0; ASSERT.len()];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
+
+#[test]
+fn udp_peek_then_recv() {
+    let mut a = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let mut b = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+    futures::executor::block_on(async {
+        a.connect(b_addr).await.unwrap();
+        b.connect(a_addr).await.unwrap();
+        a.send_with_flags(ASSERT, MsgFlags::empty()).await.unwrap();
+
+        let mut buf = [0; 64];
+        let n = b.recv_with_flags(&mut buf, MsgFlags::MSG_PEEK).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+
+        let mut buf = [0; 64];
+        let n = b.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+    });
+}
+
+#[test]
+fn tcp_peek_does_not_consume() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async move {
+        let mut client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+        // The first byte of a TLS handshake record
+        client.send_with_flags(&[0x16, 0x03, 0x01], MsgFlags::empty()).await.unwrap();
+
+        let mut first = [0; 1];
+        assert_eq!(server.peek(&mut first).await.unwrap(), 1);
+        assert_eq!(first[0], 0x16);
+        assert_eq!(server.peek(&mut first).await.unwrap(), 1);
+
+        let mut buf = [0; 3];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x16, 0x03, 0x01]);
+    });
+}
@@ -0,0 +1,13 @@
+use std::time::Duration;
+
+use ringbahn::drive::demo;
+use ringbahn::ring::{Napi, RingHandle};
+
+#[test]
+fn register_and_unregister() {
+    // The demo driver passes every completion of its instance to ringbahn.
+    let handle = unsafe { RingHandle::of(&demo::driver()) }.unwrap();
+    let napi = Napi { busy_poll_timeout: Duration::from_micros(50), prefer_busy_poll: true };
+    handle.register_napi(napi).unwrap();
+    assert_eq!(handle.unregister_napi().unwrap(), napi);
+}
@@ -0,0 +1,52 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener as StdTcpListener, TcpStream as StdTcpStream};
+use std::thread;
+
+use futures::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+
+#[test]
+fn listener_from_std() {
+    let std_listener = StdTcpListener::bind(("127.0.0.1", 0)).unwrap();
+    std_listener.set_nonblocking(true).unwrap();
+    let addr = std_listener.local_addr().unwrap();
+    let mut listener = TcpListener::from_std(std_listener, demo::driver()).unwrap();
+    let client = thread::spawn(move || {
+        let mut stream = StdTcpStream::connect(addr).unwrap();
+        stream.write_all(b"ping").unwrap();
+    });
+    futures::executor::block_on(async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+    client.join().unwrap();
+    let listener = listener.into_std();
+    assert_eq!(listener.local_addr().unwrap(), addr);
+}
+
+#[test]
+fn stream_round_trip_through_std() {
+    let listener = StdTcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.write_all(b"ping").unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"pong");
+    });
+    let std_stream = StdTcpStream::connect(addr).unwrap();
+    let mut stream = TcpStream::from_std(std_stream, demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+    let mut std_stream = stream.into_std();
+    std_stream.write_all(b"pong").unwrap();
+    server.join().unwrap();
+}
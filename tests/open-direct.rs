@@ -0,0 +1,31 @@
+use iou::registrar::{RegisteredFd, PLACEHOLDER_FD};
+use iou::sqe::{Mode, OFlag};
+
+use ringbahn::event::*;
+use ringbahn::drive::{demo, Drive};
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn open_into_fixed_file_table() {
+    let _ = demo::registrar().unwrap().register_files(&[PLACEHOLDER_FD; 4]).unwrap();
+
+    futures::executor::block_on(async move {
+        let open = OpenAtDirect::without_dir("props.txt", OFlag::O_RDONLY, Mode::empty(), None);
+        let (_, result) = demo::driver().submit(open).await;
+        let allocated = result.unwrap();
+
+        let open = OpenAtDirect::without_dir("props.txt", OFlag::O_RDONLY, Mode::empty(), Some(3));
+        let (_, result) = demo::driver().submit(open).await;
+        assert_eq!(result.unwrap(), 0);
+
+        for index in [allocated, 3] {
+            let fd = RegisteredFd::new(index, PLACEHOLDER_FD);
+            let buf = vec![0; 1024].into_boxed_slice();
+            let (event, result) = demo::driver().submit(Read { fd, buf, offset: 0 }).await;
+            let n = result.unwrap() as usize;
+            assert!(n >= ASSERT.len());
+            assert_eq!(&event.buf[..ASSERT.len()], ASSERT);
+        }
+    });
+}
@@ -0,0 +1,55 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+
+use futures::AsyncWriteExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::OpenOptions;
+
+#[test]
+fn create_and_append() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    futures::executor::block_on(async {
+        let mut options = OpenOptions::new();
+        options.append(true).create(true).mode(0o600);
+        let mut file = options.open_on_driver(&path, demo::driver()).await.unwrap();
+        file.write_all(b"first\n").await.unwrap();
+        drop(file);
+        let mut file = options.open_on_driver(&path, demo::driver()).await.unwrap();
+        file.write_all(b"second\n").await.unwrap();
+    });
+    assert_eq!(fs::read(&path).unwrap(), b"first\nsecond\n");
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+}
+
+#[test]
+fn create_new_and_truncate() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    fs::write(&path, b"existing").unwrap();
+    futures::executor::block_on(async {
+        let result = OpenOptions::new().write(true).create_new(true)
+            .open_on_driver(&path, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::AlreadyExists);
+        let file = OpenOptions::new().write(true).truncate(true)
+            .open_on_driver(&path, demo::driver()).await.unwrap();
+        drop(file);
+    });
+    assert_eq!(fs::read(&path).unwrap(), b"");
+}
+
+#[test]
+fn invalid_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    futures::executor::block_on(async {
+        let result = OpenOptions::new().open_on_driver(&path, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        let result = OpenOptions::new().read(true).create(true)
+            .open_on_driver(&path, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+    });
+    assert!(!path.exists());
+}
@@ -0,0 +1,42 @@
+use std::fs as std_fs;
+use std::os::unix::fs::symlink;
+
+use futures::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{Dir, OpenOptions, ResolveFlags};
+
+#[test]
+fn open_beneath_dir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("root");
+    std_fs::create_dir(&root).unwrap();
+    std_fs::write(root.join("inside"), b"inside").unwrap();
+    std_fs::write(tmp.path().join("outside"), b"outside").unwrap();
+    symlink("../outside", root.join("escape")).unwrap();
+
+    futures::executor::block_on(async {
+        let dir = Dir::open_on_driver(&root, demo::driver()).await.unwrap();
+        let mut options = OpenOptions::new();
+        options.read(true).resolve(ResolveFlags::BENEATH);
+
+        let mut file = match options.open_at_on_driver(&dir, "inside", demo::driver()).await {
+            Ok(file)    => file,
+            // Kernels before 5.6 do not support openat2.
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return,
+            Err(err)    => panic!("{}", err),
+        };
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).await.unwrap();
+        assert_eq!(contents, "inside");
+
+        for path in &["../outside", "escape"] {
+            let result = options.open_at_on_driver(&dir, path, demo::driver()).await;
+            assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::EXDEV));
+        }
+
+        options.resolve(ResolveFlags::NO_SYMLINKS);
+        let result = options.open_at_on_driver(&dir, "escape", demo::driver()).await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::ELOOP));
+    });
+}
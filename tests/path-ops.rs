@@ -0,0 +1,32 @@
+use std::fs as std_fs;
+use std::io;
+
+use ringbahn::drive::demo;
+use ringbahn::fs;
+
+#[test]
+fn create_rename_and_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let sub = dir.path().join("sub");
+    let file = sub.join("file");
+    let renamed = sub.join("renamed");
+    futures::executor::block_on(async {
+        fs::create_dir_on_driver(&sub, demo::driver()).await.unwrap();
+        assert!(std_fs::metadata(&sub).unwrap().is_dir());
+        let result = fs::create_dir_on_driver(&sub, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::AlreadyExists);
+
+        std_fs::write(&file, b"contents").unwrap();
+        fs::rename_on_driver(&file, &renamed, demo::driver()).await.unwrap();
+        assert!(!file.exists());
+        assert_eq!(std_fs::read(&renamed).unwrap(), b"contents");
+
+        let result = fs::remove_dir_on_driver(&sub, demo::driver()).await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::ENOTEMPTY));
+        fs::remove_file_on_driver(&renamed, demo::driver()).await.unwrap();
+        let result = fs::remove_file_on_driver(&renamed, demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+        fs::remove_dir_on_driver(&sub, demo::driver()).await.unwrap();
+    });
+    assert!(!sub.exists());
+}
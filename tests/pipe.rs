@@ -0,0 +1,45 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::pipe;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn write_and_read() {
+    let (mut sender, mut receiver) = pipe::pipe_on_driver(demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        sender.write_all(ASSERT).await.unwrap();
+        sender.close().await.unwrap();
+        let mut buf = Vec::new();
+        receiver.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
+
+#[test]
+fn splice_through_pipe() {
+    let (mut sender, mut receiver) = pipe::pipe_on_driver(demo::driver()).unwrap();
+    let file = std::fs::File::open("props.txt").unwrap();
+    let mut out = tempfile::tempfile().unwrap();
+    futures::executor::block_on(async {
+        let n = sender.splice_from(&file, ASSERT.len() as u32).await.unwrap();
+        assert_eq!(n as usize, ASSERT.len());
+        let n = receiver.splice_to(&out, n).await.unwrap();
+        assert_eq!(n as usize, ASSERT.len());
+    });
+    out.seek(SeekFrom::Start(0)).unwrap();
+    let mut buf = Vec::new();
+    out.read_to_end(&mut buf).unwrap();
+    assert_eq!(&buf[..], ASSERT);
+}
+
+#[test]
+fn pipe_size() {
+    let (sender, receiver) = pipe::pipe_on_driver(demo::driver()).unwrap();
+    let size = sender.set_pipe_size(1 << 17).unwrap();
+    assert!(size >= 1 << 17);
+    assert_eq!(receiver.pipe_size().unwrap(), size);
+}
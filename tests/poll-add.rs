@@ -0,0 +1,36 @@
+use std::future::Future;
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+
+use iou::sqe::PollFlags;
+
+use ringbahn::Submission;
+use ringbahn::event::PollAdd;
+use ringbahn::drive::demo;
+
+#[test]
+fn poll_until_readable() {
+    let (mut left, right) = UnixStream::pair().unwrap();
+    let poll = PollAdd { fd: right.as_raw_fd(), flags: PollFlags::POLLIN };
+    let mut submission = Box::pin(Submission::new(poll, demo::driver()));
+
+    // Nothing has been written, so the poll does not complete
+    let waker = futures::task::noop_waker();
+    let mut ctx = std::task::Context::from_waker(&waker);
+    assert!(submission.as_mut().poll(&mut ctx).is_pending());
+
+    left.write_all(b"ready").unwrap();
+    let (_, result) = futures::executor::block_on(submission);
+    let ready = PollFlags::from_bits_truncate(result.unwrap() as _);
+    assert!(ready.contains(PollFlags::POLLIN));
+}
+
+#[test]
+fn poll_writable() {
+    let (left, _right) = UnixStream::pair().unwrap();
+    let poll = PollAdd { fd: left.as_raw_fd(), flags: PollFlags::POLLOUT };
+    let (_, result) = futures::executor::block_on(Submission::new(poll, demo::driver()));
+    let ready = PollFlags::from_bits_truncate(result.unwrap() as _);
+    assert!(ready.contains(PollFlags::POLLOUT));
+}
@@ -0,0 +1,50 @@
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::process::{Command, Stdio};
+
+#[test]
+fn output() {
+    futures::executor::block_on(async {
+        let output = Command::new("sh")
+            .args(["-c", "echo out; echo err >&2"])
+            .output_on_driver(demo::driver())
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(&output.stdout[..], b"out\n");
+        assert_eq!(&output.stderr[..], b"err\n");
+    });
+}
+
+#[test]
+fn status() {
+    futures::executor::block_on(async {
+        let status = Command::new("sh")
+            .args(["-c", "exit 3"])
+            .status_on_driver(demo::driver())
+            .await
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+    });
+}
+
+#[test]
+fn piped_stdin() {
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn_on_driver(demo::driver())
+        .unwrap();
+    futures::executor::block_on(async {
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"ping").await.unwrap();
+        stdin.close().await.unwrap();
+        drop(stdin);
+
+        let mut buf = Vec::new();
+        child.stdout.as_mut().unwrap().read_to_end(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], b"ping");
+        assert!(child.wait().await.unwrap().success());
+    });
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeSet;
+use std::fs as std_fs;
+
+use futures::StreamExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{self, Dir};
+
+#[test]
+fn read_dir_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    std_fs::write(dir.path().join("a"), b"a").unwrap();
+    std_fs::create_dir(dir.path().join("b")).unwrap();
+    // Enough entries that they are read in several batches
+    for i in 0..1000 {
+        std_fs::write(dir.path().join(format!("file-{:04}", i)), b"").unwrap();
+    }
+    futures::executor::block_on(async {
+        let mut entries = fs::read_dir_on_driver(dir.path(), demo::driver()).await.unwrap();
+        let mut names = BTreeSet::new();
+        while let Some(entry) = entries.next().await {
+            let entry = entry.unwrap();
+            assert_eq!(entry.path(), dir.path().join(entry.file_name()));
+            if let Some(file_type) = entry.file_type() {
+                assert_eq!(file_type.is_dir(), entry.file_name() == "b");
+            }
+            names.insert(entry.file_name().to_owned());
+        }
+        assert_eq!(names.len(), 1002);
+        assert!(names.contains(std::ffi::OsStr::new("a")));
+        assert!(!names.contains(std::ffi::OsStr::new(".")));
+    });
+}
+
+#[test]
+fn open_missing_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    futures::executor::block_on(async {
+        let result = Dir::open_on_driver(dir.path().join("missing"), demo::driver()).await;
+        assert_eq!(result.err().unwrap().kind(), std::io::ErrorKind::NotFound);
+        std_fs::write(dir.path().join("file"), b"").unwrap();
+        let result = Dir::open_on_driver(dir.path().join("file"), demo::driver()).await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::ENOTDIR));
+    });
+}
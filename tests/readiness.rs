@@ -0,0 +1,31 @@
+use std::io::Write;
+use std::net::TcpStream as StdTcpStream;
+use std::os::unix::io::AsRawFd;
+use std::pin::Pin;
+use std::thread;
+
+use futures::future::poll_fn;
+
+use ringbahn::net::TcpListener;
+
+#[test]
+fn wait_for_readiness() {
+    let mut listener = TcpListener::bind(("127.0.0.1", 47231)).unwrap();
+    let client = thread::spawn(|| {
+        let mut stream = StdTcpStream::connect(("127.0.0.1", 47231)).unwrap();
+        stream.write_all(b"ping").unwrap();
+        stream
+    });
+    futures::executor::block_on(async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        poll_fn(|ctx| Pin::new(&mut stream).poll_writable(ctx)).await.unwrap();
+        poll_fn(|ctx| Pin::new(&mut stream).poll_readable(ctx)).await.unwrap();
+
+        let mut buf = [0; 4];
+        let fd = stream.as_raw_fd();
+        let n = unsafe { libc::recv(fd, buf.as_mut_ptr() as _, 4, libc::MSG_DONTWAIT) };
+        assert_eq!(n, 4);
+        assert_eq!(&buf, b"ping");
+    });
+    drop(client.join().unwrap());
+}
@@ -0,0 +1,38 @@
+use futures::{AsyncWriteExt, StreamExt};
+use iou::sqe::BufferGroupId;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+
+// The tests share the demo driver's ring, so this group must not be used by other tests.
+const GROUP: BufferGroupId = BufferGroupId { id: 17 };
+
+#[test]
+fn recv_stream_reuses_buffers() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async move {
+        let mut client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        let send = async {
+            // More messages than buffers, so that they have to be provided again.
+            for i in 0..8u8 {
+                client.write_all(&[i; 16]).await.unwrap();
+                client.flush().await.unwrap();
+            }
+            client.close().await.unwrap();
+        };
+        let recv = async {
+            let mut received = Vec::new();
+            let mut stream = server.recv_stream(GROUP, 2, 64);
+            while let Some(data) = stream.next().await {
+                received.extend(data.unwrap());
+            }
+            received
+        };
+        let ((), received) = futures::join!(send, recv);
+        let expected: Vec<u8> = (0..8u8).flat_map(|i| vec![i; 16]).collect();
+        assert_eq!(received, expected);
+    });
+}
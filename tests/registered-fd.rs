@@ -17,7 +17,7 @@ fn test_registered_fd_ops() {
         let buf = vec![0; 1024].into_boxed_slice();
         let (event, result) = demo::driver().submit(Read { fd, buf, offset: 0 }).await;
         let n = result.unwrap() as _;
-        let data = String::from_utf8_lossy(&event.buf[..n]).to_owned();
+        let data = String::from_utf8_lossy(&event.buf[..n]).into_owned();
         ringbahn::println!(demo::driver(), "{}", data).await;
 
         // statx file and print statx to stdout
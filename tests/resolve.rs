@@ -0,0 +1,47 @@
+use std::io;
+use std::net::{SocketAddr, TcpListener as StdTcpListener};
+
+use futures::future::{self, Ready};
+
+use ringbahn::drive::demo;
+use ringbahn::net::{GaiResolver, Resolve, TcpStream};
+
+/// A resolver which resolves every host to one address
+struct Fixed(SocketAddr);
+
+impl Resolve for Fixed {
+    type Future = Ready<io::Result<Vec<SocketAddr>>>;
+
+    fn resolve(&self, _: &str, port: u16) -> Self::Future {
+        future::ready(Ok(vec![SocketAddr::new(self.0.ip(), port)]))
+    }
+}
+
+#[test]
+fn connect_host_with_resolver() {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async {
+        let resolver = Fixed(addr);
+        let host = "example.invalid";
+        let connect = TcpStream::connect_host_on_driver(host, addr.port(), &resolver, demo::driver());
+        let stream = connect.await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+    });
+}
+
+#[test]
+fn gai_resolver() {
+    let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async {
+        let addrs = GaiResolver.resolve("127.0.0.1", addr.port()).await.unwrap();
+        assert_eq!(addrs, [addr]);
+        let addrs = GaiResolver.resolve("localhost", addr.port()).await.unwrap();
+        assert!(addrs.iter().all(|resolved| resolved.ip().is_loopback()));
+        let connect = TcpStream::connect_host_on_driver("localhost", addr.port(), &GaiResolver,
+                                                       demo::driver());
+        let stream = connect.await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+    });
+}
@@ -0,0 +1,129 @@
+use std::io;
+use std::pin::Pin;
+use std::thread;
+use std::time::Duration;
+
+use futures::future::poll_fn;
+
+use ringbahn::ring::Ring;
+use ringbahn::drive::{demo, local, Drive};
+use ringbahn::event::Read;
+
+const IORING_ASYNC_CANCEL_FD: u32 = 1 << 1;
+
+#[test]
+fn read_with_timeout() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let mut ring = Ring::builder().timeout(Duration::from_millis(10)).build(demo::driver());
+    let mut buf = [0; 16];
+    let result = futures::executor::block_on(poll_fn(|ctx| {
+        Pin::new(&mut ring).poll(ctx, 1, |sqs| unsafe {
+            let mut sqe = sqs.next().unwrap();
+            sqe.prep_read(fds[0], &mut buf[..], 0);
+            sqe
+        })
+    }));
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
+
+#[test]
+fn read_with_timeout_on_local_driver() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let mut ring = Ring::builder().timeout(Duration::from_millis(10)).build(local::driver());
+    let mut buf = [0; 16];
+    let result = local::block_on(poll_fn(|ctx| {
+        Pin::new(&mut ring).poll(ctx, 1, |sqs| unsafe {
+            let mut sqe = sqs.next().unwrap();
+            sqe.prep_read(fds[0], &mut buf[..], 0);
+            sqe
+        })
+    }));
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
+
+#[test]
+fn serialized_write_waits_for_read() {
+    let mut read_fds = [0; 2];
+    let mut write_fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(read_fds.as_mut_ptr()) }, 0);
+    assert_eq!(unsafe { libc::pipe(write_fds.as_mut_ptr()) }, 0);
+    let readable = |fd| {
+        let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+        unsafe { libc::poll(&mut pollfd, 1, 0) == 1 }
+    };
+
+    futures::executor::block_on(async {
+        let buf = vec![0; 1].into_boxed_slice();
+        let mut read = demo::driver().submit(Read { fd: read_fds[0], buf, offset: 0 });
+        assert!(futures::poll!(&mut read).is_pending());
+
+        let mut ring = Ring::builder().serialize(true).build(demo::driver());
+        let mut write = poll_fn(|ctx| {
+            Pin::new(&mut ring).poll(ctx, 1, |sqs| unsafe {
+                let mut sqe = sqs.next().unwrap();
+                sqe.prep_write(write_fds[1], &b"x"[..], 0);
+                sqe
+            })
+        });
+        assert!(futures::poll!(&mut write).is_pending());
+
+        // The write is drained behind the read, so it does not start until the read completes.
+        thread::sleep(Duration::from_millis(20));
+        assert!(!readable(write_fds[0]));
+
+        assert_eq!(unsafe { libc::write(read_fds[1], b"x".as_ptr().cast(), 1) }, 1);
+        assert_eq!(read.await.1.unwrap(), 1);
+        assert_eq!(write.await.unwrap(), 1);
+        assert!(readable(write_fds[0]));
+    });
+    for fd in read_fds.iter().chain(&write_fds) {
+        unsafe { libc::close(*fd); }
+    }
+}
+
+#[test]
+fn cancelled_read_is_not_timed_out() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let reader = fds[0];
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        // Cancel every event reading the pipe, as another part of a program might.
+        let mut ring = Ring::new(demo::driver());
+        futures::executor::block_on(poll_fn(|ctx| {
+            Pin::new(&mut ring).poll(ctx, 1, |sqs| unsafe {
+                let mut sqe = sqs.next().unwrap();
+                let raw = sqe.raw_mut();
+                raw.opcode = uring_sys::IoRingOp::IORING_OP_ASYNC_CANCEL as u8;
+                raw.fd = reader;
+                raw.cmd_flags.cancel_flags = IORING_ASYNC_CANCEL_FD;
+                sqe
+            })
+        })).unwrap();
+    });
+    let mut ring = Ring::builder().timeout(Duration::from_secs(10)).build(demo::driver());
+    let mut buf = [0; 16];
+    let result = futures::executor::block_on(poll_fn(|ctx| {
+        Pin::new(&mut ring).poll(ctx, 1, |sqs| unsafe {
+            let mut sqe = sqs.next().unwrap();
+            sqe.prep_read(fds[0], &mut buf[..], 0);
+            sqe
+        })
+    }));
+    assert_eq!(result.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+    canceller.join().unwrap();
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
@@ -0,0 +1,36 @@
+use std::thread;
+
+use futures::executor::block_on;
+
+use ringbahn::drive::demo;
+use ringbahn::ring::{Mailbox, RingHandle};
+
+#[test]
+fn wake_from_another_thread() {
+    let (mailbox, token) = Mailbox::new();
+    // The demo driver passes every completion of its instance to ringbahn.
+    let handle = unsafe { RingHandle::of(&demo::driver()) }.unwrap();
+    let sender = thread::spawn(move || {
+        block_on(handle.send_on_driver(token, 7, demo::driver())).unwrap();
+    });
+    assert_eq!(block_on(mailbox).unwrap(), 7);
+    sender.join().unwrap();
+}
+
+#[test]
+fn dropped_token() {
+    let (mailbox, token) = Mailbox::new();
+    thread::spawn(move || drop(token)).join().unwrap();
+    let err = block_on(mailbox).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn message_too_large() {
+    let (mailbox, token) = Mailbox::new();
+    // The demo driver passes every completion of its instance to ringbahn.
+    let handle = unsafe { RingHandle::of(&demo::driver()) }.unwrap();
+    let err = block_on(handle.send_on_driver(token, u32::MAX, demo::driver())).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(block_on(mailbox).is_err());
+}
@@ -0,0 +1,50 @@
+use std::fs as std_fs;
+use std::os::unix::io::AsRawFd;
+
+use futures::AsyncSeekExt;
+
+use ringbahn::Submission;
+use ringbahn::event::{Read, RwFlags, WithRwFlags, Write};
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn events_with_flags() {
+    let file = tempfile::tempfile().unwrap();
+    let fd = file.as_raw_fd();
+    futures::executor::block_on(async {
+        let write = Write { fd, buf: Box::from(ASSERT), offset: 0 };
+        let write = WithRwFlags { event: write, flags: RwFlags::DSYNC };
+        let (_, result) = Submission::new(write, demo::driver()).await;
+        assert_eq!(result.unwrap() as usize, ASSERT.len());
+
+        // The data was just written, so it is in the page cache
+        let read = Read { fd, buf: vec![0; ASSERT.len()].into(), offset: 0 };
+        let read = WithRwFlags { event: read, flags: RwFlags::NOWAIT };
+        let (read, result) = Submission::new(read, demo::driver()).await;
+        assert_eq!(result.unwrap() as usize, ASSERT.len());
+        assert_eq!(&read.event.buf[..], ASSERT);
+    });
+}
+
+#[test]
+fn file_with_flags() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    let std_file = std_fs::OpenOptions::new()
+        .read(true).write(true).create(true).truncate(true)
+        .open(&path);
+    futures::executor::block_on(async {
+        let mut file = File::run_on_driver(std_file.unwrap(), demo::driver());
+        let n = file.write_with_flags(ASSERT, RwFlags::DSYNC).await.unwrap();
+        assert_eq!(n, ASSERT.len());
+        assert_eq!(std_fs::read(&path).unwrap(), ASSERT);
+
+        file.seek(std::io::SeekFrom::Start(0)).await.unwrap();
+        let mut buf = vec![0; ASSERT.len()];
+        let n = file.read_with_flags(&mut buf, RwFlags::NOWAIT).await.unwrap();
+        assert_eq!(&buf[..n], &ASSERT[..n]);
+    });
+}
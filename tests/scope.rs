@@ -0,0 +1,61 @@
+use std::thread;
+use std::time::Duration;
+
+use futures::AsyncReadExt;
+
+use ringbahn::Drive;
+use ringbahn::drive::demo;
+use ringbahn::event::Read;
+use ringbahn::fs::File;
+use ringbahn::pipe;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn scope_returns_output() {
+    let buf = futures::executor::block_on(ringbahn::scope_on_driver(demo::driver(), |s| async {
+        let mut file = File::open_on_driver("props.txt", s).await.unwrap();
+        let mut buf = vec![0; ASSERT.len()];
+        file.read_exact(&mut buf).await.unwrap();
+        buf
+    }));
+    assert_eq!(&buf[..], ASSERT);
+}
+
+#[test]
+fn scope_cancels_abandoned_events() {
+    futures::executor::block_on(ringbahn::scope_on_driver(demo::driver(), |s| async {
+        let (_sender, mut receiver) = pipe::pipe_on_driver(s).unwrap();
+        let mut buf = [0; 8];
+        // Nothing is ever written to the pipe, so this read would never complete on its own.
+        assert!(futures::poll!(receiver.read(&mut buf)).is_pending());
+    }));
+}
+
+#[test]
+fn dropped_scope_cancels_events() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let fd = fds[0];
+
+    let mut scoped = Box::pin(ringbahn::scope_on_driver(demo::driver(), |s| async move {
+        let read = Read { fd, buf: vec![0; 8].into(), offset: 0 };
+        let _ = s.submit(read).await;
+        futures::future::pending::<()>().await
+    }));
+    futures::executor::block_on(async {
+        assert!(futures::poll!(scoped.as_mut()).is_pending());
+    });
+    drop(scoped);
+    thread::sleep(Duration::from_millis(50));
+
+    // The read was cancelled, so what is written stays in the pipe.
+    assert_eq!(unsafe { libc::write(fds[1], b"hello".as_ptr() as _, 5) }, 5);
+    let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+    assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 1000) }, 1);
+
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
@@ -0,0 +1,47 @@
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::net::{SctpListener, SctpStream, UdpSocket};
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn sctp_association() {
+    let mut listener = match SctpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()) {
+        Ok(listener)    => listener,
+        // The kernel was built without SCTP, or the module is not loaded
+        Err(err) if err.raw_os_error() == Some(libc::EPROTONOSUPPORT) => return,
+        Err(err)        => panic!("{}", err),
+    };
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async move {
+        let connect = SctpStream::connect_on_driver(addr, demo::driver());
+        let (client, accepted) = futures::join!(connect, listener.accept());
+        let mut client = client.unwrap();
+        let (mut server, peer) = accepted.unwrap();
+        assert_eq!(peer, client.local_addr().unwrap());
+        client.write_all(ASSERT).await.unwrap();
+        let mut buf = vec![0; ASSERT.len()];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
+
+#[test]
+fn udp_lite_send_to() {
+    let a = UdpSocket::bind_lite_on_driver(("127.0.0.1", 0), demo::driver());
+    let mut a = match a {
+        Ok(socket)      => socket,
+        Err(err) if err.raw_os_error() == Some(libc::EPROTONOSUPPORT) => return,
+        Err(err)        => panic!("{}", err),
+    };
+    let mut b = UdpSocket::bind_lite_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let b_addr = b.local_addr().unwrap();
+    futures::executor::block_on(async {
+        a.send_to(ASSERT, b_addr).await.unwrap();
+        let mut buf = [0; 64];
+        let (n, from) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(from, a.local_addr().unwrap());
+    });
+}
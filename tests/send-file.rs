@@ -0,0 +1,48 @@
+use std::io::Write;
+
+use futures::io::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+
+#[test]
+fn send_file_range() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let data: Vec<u8> = (0..200 * 1024).map(|i| i as u8).collect();
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(&data).unwrap();
+    futures::executor::block_on(async move {
+        let client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        let len = data.len() as u64 - 1000;
+        let send = client.send_file(&file, 1000, len);
+        let recv = async {
+            let mut buf = vec![0; len as usize];
+            server.read_exact(&mut buf).await.unwrap();
+            buf
+        };
+        let (sent, received) = futures::join!(send, recv);
+        assert_eq!(sent.unwrap(), len);
+        assert_eq!(&received[..], &data[1000..]);
+    });
+}
+
+#[test]
+fn send_file_past_end() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(b"short file").unwrap();
+    futures::executor::block_on(async move {
+        let client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        assert_eq!(client.send_file(&file, 6, 100).await.unwrap(), 4);
+        drop(client);
+        let mut buf = Vec::new();
+        server.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], b"file");
+    });
+}
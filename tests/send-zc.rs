@@ -0,0 +1,26 @@
+use futures::io::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+
+#[test]
+fn send_zc_returns_buffer() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let data: Vec<u8> = (0..64 * 1024).map(|i| i as u8).collect();
+    futures::executor::block_on(async move {
+        let client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        let send = client.send_zc(data.clone());
+        let recv = async {
+            let mut buf = vec![0; data.len()];
+            server.read_exact(&mut buf).await.unwrap();
+            buf
+        };
+        let ((buf, result), received) = futures::join!(send, recv);
+        assert_eq!(result.unwrap(), data.len());
+        assert_eq!(buf, data);
+        assert_eq!(received, data);
+    });
+}
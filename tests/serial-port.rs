@@ -0,0 +1,56 @@
+use std::ffi::CStr;
+use std::io;
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::io::SerialPort;
+
+/// Open a pseudoterminal, returning the fd of its master and the path of its slave.
+fn pty() -> (libc::c_int, String) {
+    unsafe {
+        let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(master >= 0);
+        assert_eq!(libc::grantpt(master), 0);
+        assert_eq!(libc::unlockpt(master), 0);
+        let mut name = [0 as libc::c_char; 64];
+        assert_eq!(libc::ptsname_r(master, name.as_mut_ptr(), name.len()), 0);
+        (master, CStr::from_ptr(name.as_ptr()).to_str().unwrap().to_owned())
+    }
+}
+
+#[test]
+fn read_and_write_raw() {
+    let (master, path) = pty();
+    let mut port = SerialPort::open_on_driver(&path, demo::driver()).unwrap();
+    port.set_raw().unwrap();
+    port.set_baud_rate(115200).unwrap();
+    assert_eq!(port.baud_rate().unwrap(), 115200);
+
+    futures::executor::block_on(async {
+        assert_eq!(unsafe { libc::write(master, b"ping".as_ptr() as _, 4) }, 4);
+        let mut buf = [0; 4];
+        port.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        port.write_all(b"pong").await.unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(unsafe { libc::read(master, buf.as_mut_ptr() as _, 4) }, 4);
+        assert_eq!(&buf, b"pong");
+    });
+    unsafe { libc::close(master); }
+}
+
+#[test]
+fn unsupported_baud_rate() {
+    let (master, path) = pty();
+    let port = SerialPort::open_on_driver(&path, demo::driver()).unwrap();
+    let err = port.set_baud_rate(12345).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    unsafe { libc::close(master); }
+}
+
+#[test]
+fn not_a_tty() {
+    assert!(SerialPort::open_on_driver("props.txt", demo::driver()).is_err());
+}
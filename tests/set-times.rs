@@ -0,0 +1,41 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ringbahn::drive::demo;
+use ringbahn::fs::{self, File};
+
+#[test]
+fn set_file_times() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    std::fs::write(&path, b"data").unwrap();
+    let accessed = UNIX_EPOCH + Duration::new(1_000_000_000, 500);
+    let modified = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+    futures::executor::block_on(async {
+        let mut file = File::open_on_driver(&path, demo::driver()).await.unwrap();
+        file.set_times(Some(accessed), Some(modified)).await.unwrap();
+    });
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert_eq!(metadata.accessed().unwrap(), accessed);
+    assert_eq!(metadata.modified().unwrap(), modified);
+}
+
+#[test]
+fn set_path_times() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    std::fs::write(&path, b"data").unwrap();
+    let modified_before = std::fs::metadata(&path).unwrap().modified().unwrap();
+    let accessed = UNIX_EPOCH - Duration::new(86400, 250);
+    futures::executor::block_on(async {
+        fs::set_times(&path, Some(accessed), None).await.unwrap();
+    });
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert_eq!(metadata.accessed().unwrap(), accessed);
+    assert_eq!(metadata.modified().unwrap(), modified_before);
+    assert!(metadata.modified().unwrap() <= SystemTime::now());
+
+    let missing = dir.path().join("missing");
+    let now = Some(SystemTime::now());
+    let err = futures::executor::block_on(fs::set_times(&missing, now, now)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
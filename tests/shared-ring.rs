@@ -0,0 +1,31 @@
+use std::thread;
+
+use futures::AsyncReadExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+use ringbahn::ring::SharedRing;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+fn is_send_sync<T: Send + Sync>(_: &T) { }
+
+#[test]
+fn read_from_many_threads() {
+    let shared = SharedRing::new(demo::driver());
+    is_send_sync(&shared);
+
+    let threads: Vec<_> = (0..4).map(|_| {
+        let shared = shared.clone();
+        thread::spawn(move || futures::executor::block_on(async move {
+            let mut file = File::open_on_driver("props.txt", shared).await.unwrap();
+            let mut buf = vec![0; ASSERT.len()];
+            file.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf[..], ASSERT);
+        }))
+    }).collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
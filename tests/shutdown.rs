@@ -0,0 +1,29 @@
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream as StdTcpStream};
+use std::thread;
+
+use futures::AsyncWriteExt;
+
+use ringbahn::net::TcpListener;
+
+#[test]
+fn shutdown_write_half() {
+    let mut listener = TcpListener::bind(("127.0.0.1", 47245)).unwrap();
+    let client = thread::spawn(|| {
+        let mut stream = StdTcpStream::connect(("127.0.0.1", 47245)).unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).unwrap();
+        assert_eq!(&buf, b"bye");
+        // The server only shut down its write half, so it can still read this.
+        stream.write_all(b"ack").unwrap();
+    });
+    futures::executor::block_on(async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(b"bye").await.unwrap();
+        stream.shutdown(Shutdown::Write).await.unwrap();
+        let mut buf = [0; 3];
+        futures::AsyncReadExt::read_exact(&mut stream, &mut buf).await.unwrap();
+        assert_eq!(&buf, b"ack");
+    });
+    client.join().unwrap();
+}
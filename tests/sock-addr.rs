@@ -0,0 +1,27 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use ringbahn::net::SockAddr;
+
+#[test]
+fn inet_addr() {
+    let std: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+    let addr = SockAddr::from(std);
+    assert_eq!(addr.as_inet(), Some(std));
+    assert_eq!(addr.as_unix_path(), None);
+}
+
+#[test]
+fn unix_addr() {
+    let addr = SockAddr::unix("/tmp/ringbahn.sock").unwrap();
+    assert_eq!(addr.as_unix_path(), Some(Path::new("/tmp/ringbahn.sock")));
+    assert_eq!(addr.as_inet(), None);
+}
+
+#[test]
+fn unix_abstract_addr() {
+    let addr = SockAddr::unix_abstract(b"ringbahn").unwrap();
+    assert_eq!(addr.as_unix_abstract(), Some(&b"ringbahn"[..]));
+    assert_eq!(addr.as_unix_path(), None);
+    assert_eq!(format!("{:?}", addr), "@ringbahn");
+}
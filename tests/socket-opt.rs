@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use ringbahn::net::{TcpKeepalive, TcpListener, TcpStream, UdpSocket};
+use ringbahn::net::sockopt::*;
+use ringbahn::unix::UnixStream;
+
+#[test]
+fn listener_opts() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    assert_eq!(listener.opt::<ReuseAddr>().unwrap(), ReuseAddr(true));
+
+    listener.set_opt(Ttl(42)).unwrap();
+    assert_eq!(listener.opt::<Ttl>().unwrap(), Ttl(42));
+
+    listener.set_opt(Linger(Some(Duration::from_secs(3)))).unwrap();
+    assert_eq!(listener.opt::<Linger>().unwrap(), Linger(Some(Duration::from_secs(3))));
+
+    assert!(listener.opt::<SoError>().unwrap().0.is_none());
+
+    listener.set_opt(PktInfo(true)).unwrap();
+    assert_eq!(listener.opt::<PktInfo>().unwrap(), PktInfo(true));
+
+    // A value which does not fit in a c_int is rejected, rather than truncated.
+    let err = listener.set_opt(RecvBufferSize(1 << 40)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    let result = TcpListener::builder().socket_opt(Ttl(u32::MAX)).bind(("127.0.0.1", 0));
+    assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::EINVAL));
+}
+
+#[test]
+fn tcp_convenience_opts() {
+    let listener = TcpListener::bind(("127.0.0.1", 47242)).unwrap();
+    listener.set_ttl(17).unwrap();
+    assert_eq!(listener.ttl().unwrap(), 17);
+
+    futures::executor::block_on(async {
+        let stream = TcpStream::connect(("127.0.0.1", 47242)).await.unwrap();
+        assert!(!stream.nodelay().unwrap());
+        stream.set_nodelay(true).unwrap();
+        assert!(stream.nodelay().unwrap());
+        stream.set_keepalive(true).unwrap();
+        assert!(stream.keepalive().unwrap());
+    });
+}
+
+#[test]
+fn tcp_keepalive_config() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    futures::executor::block_on(async {
+        let stream = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(stream.tcp_keepalive().unwrap(), None);
+
+        let keepalive = TcpKeepalive::new()
+            .time(Duration::from_secs(30))
+            .interval(Duration::from_secs(5))
+            .retries(4);
+        stream.set_tcp_keepalive(&keepalive).unwrap();
+        assert_eq!(stream.tcp_keepalive().unwrap(), Some(keepalive));
+        assert_eq!(stream.opt::<TcpKeepIdle>().unwrap(), TcpKeepIdle(30));
+    });
+}
+
+#[test]
+fn stream_opts() {
+    let (a, _b) = UnixStream::pair().unwrap();
+    a.set_opt(SendBufferSize(8192)).unwrap();
+    // Linux doubles the requested size to leave room for bookkeeping.
+    assert_eq!(a.opt::<SendBufferSize>().unwrap(), SendBufferSize(16384));
+    assert!(a.set_opt(TcpNoDelay(true)).is_err());
+}
+
+#[test]
+fn listeners_share_port() {
+    let builder = TcpListener::builder().reuse_port(true);
+    let a = builder.bind(("127.0.0.1", 47243)).unwrap();
+    let b = builder.bind(("127.0.0.1", 47243)).unwrap();
+    assert_eq!(a.opt::<ReusePort>().unwrap(), ReusePort(true));
+    assert_eq!(b.opt::<ReusePort>().unwrap(), ReusePort(true));
+    assert!(TcpListener::bind(("127.0.0.1", 47243)).is_err());
+}
+
+#[test]
+fn local_and_peer_addr() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_ne!(addr.port(), 0);
+
+    futures::executor::block_on(async {
+        let stream = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        assert!(stream.local_addr().unwrap().ip().is_loopback());
+    });
+}
+
+#[test]
+fn listener_backlog() {
+    let mut listener = TcpListener::builder().backlog(1024).bind(("127.0.0.1", 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let clients: Vec<_> = (0..16).map(|_| std::net::TcpStream::connect(addr).unwrap()).collect();
+
+    futures::executor::block_on(async {
+        for _ in &clients {
+            listener.accept().await.unwrap();
+        }
+    });
+}
+
+#[test]
+fn listener_builder_opts() {
+    let listener = TcpListener::builder()
+        .fastopen(16)
+        .socket_opt(RecvBufferSize(1 << 16))
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+    assert_eq!(listener.opt::<TcpFastOpen>().unwrap(), TcpFastOpen(16));
+    assert!(listener.opt::<RecvBufferSize>().unwrap().0 >= 1 << 16);
+}
+
+#[test]
+fn dscp_marking() {
+    let socket = UdpSocket::bind(("127.0.0.1", 0)).unwrap();
+    socket.set_ttl(9).unwrap();
+    assert_eq!(socket.ttl().unwrap(), 9);
+    socket.set_dscp(46).unwrap();
+    assert_eq!(socket.dscp().unwrap(), 46);
+    assert_eq!(socket.tos().unwrap(), 46 << 2);
+    assert!(socket.set_dscp(64).is_err());
+
+    let socket = UdpSocket::bind(("::1", 0)).unwrap();
+    socket.set_dscp(10).unwrap();
+    assert_eq!(socket.tclass_v6().unwrap(), 10 << 2);
+}
@@ -0,0 +1,51 @@
+use ringbahn::drive::demo;
+use ringbahn::net::{Socket, UdpSocket};
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn generic_datagram_socket() {
+    let mut socket = Socket::new_on_driver(libc::AF_INET, libc::SOCK_DGRAM, libc::IPPROTO_UDP,
+                                           demo::driver()).unwrap();
+    socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let mut udp = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let socket_addr = socket.local_addr().unwrap();
+    let udp_addr = udp.local_addr().unwrap();
+
+    futures::executor::block_on(async {
+        socket.send_to(ASSERT, udp_addr).await.unwrap();
+        let mut buf = [0; 64];
+        let (n, from) = udp.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(from, socket_addr);
+
+        udp.send_to(&buf[..n], socket_addr).await.unwrap();
+        let mut buf = [0; 64];
+        let (n, from) = socket.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(from, udp_addr);
+    });
+}
+
+#[test]
+fn invalid_socket_type() {
+    assert!(Socket::new(libc::AF_INET, -1, 0).is_err());
+}
+
+#[test]
+fn socket_event() {
+    let socket = ringbahn::event::Socket {
+        domain: libc::AF_INET,
+        ty: libc::SOCK_STREAM | libc::SOCK_CLOEXEC,
+        protocol: 0,
+    };
+    let (_, result) = futures::executor::block_on(ringbahn::Submission::new(socket, demo::driver()));
+    let fd = result.unwrap() as i32;
+    let mut ty = 0;
+    let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let ptr = &mut ty as *mut libc::c_int as *mut libc::c_void;
+    assert_eq!(unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_TYPE, ptr, &mut len) }, 0);
+    assert_eq!(ty, libc::SOCK_STREAM);
+    assert_ne!(unsafe { libc::fcntl(fd, libc::F_GETFD) } & libc::FD_CLOEXEC, 0);
+    unsafe { libc::close(fd); }
+}
@@ -0,0 +1,41 @@
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn read_and_write_halves_concurrently() {
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    futures::executor::block_on(async move {
+        let client = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let (mut client_read, mut client_write) = client.into_split();
+        let (mut server_read, mut server_write) = server.into_split();
+
+        // Both reads are in flight before either side writes.
+        let client_side = async {
+            let mut buf = vec![0; ASSERT.len()];
+            client_read.read_exact(&mut buf).await.unwrap();
+            buf
+        };
+        let server_side = async {
+            let mut buf = vec![0; ASSERT.len()];
+            server_read.read_exact(&mut buf).await.unwrap();
+            server_write.write_all(&buf).await.unwrap();
+            server_write.close().await.unwrap();
+        };
+        let writes = async {
+            client_write.write_all(ASSERT).await.unwrap();
+        };
+        let (echoed, (), ()) = futures::join!(client_side, server_side, writes);
+        assert_eq!(&echoed[..], ASSERT);
+
+        // The server only shut down its write half, so the client sees the end of the stream.
+        let mut rest = Vec::new();
+        client_read.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    });
+}
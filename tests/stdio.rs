@@ -14,3 +14,30 @@ fn write_stdout() {
         assert_eq!(n, ASSERT.len());
     });
 }
+
+#[test]
+fn write_stderr() {
+    futures::executor::block_on(async {
+        let n = ringbahn::io::stderr_on_driver(demo::driver()).write(ASSERT).await.unwrap();
+        assert_eq!(n, ASSERT.len());
+    });
+}
+
+#[test]
+fn read_stdin() {
+    use futures::AsyncReadExt;
+
+    let mut fds = [0; 2];
+    unsafe {
+        assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+        assert_eq!(libc::dup2(fds[0], libc::STDIN_FILENO), libc::STDIN_FILENO);
+        libc::close(fds[0]);
+        assert_eq!(libc::write(fds[1], ASSERT.as_ptr() as _, ASSERT.len()), ASSERT.len() as isize);
+        libc::close(fds[1]);
+    }
+    futures::executor::block_on(async {
+        let mut buf = Vec::new();
+        ringbahn::io::stdin_on_driver(demo::driver()).read_to_end(&mut buf).await.unwrap();
+        assert_eq!(&buf[..], ASSERT);
+    });
+}
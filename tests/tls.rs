@@ -0,0 +1,63 @@
+#![cfg(feature = "tls")]
+
+use std::io::BufReader;
+use std::sync::Arc;
+
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use rustls::internal::pemfile;
+
+use ringbahn::drive::demo;
+use ringbahn::net::{TcpListener, TcpStream};
+use ringbahn::tls::TlsStream;
+
+const CA: &[u8] = include_bytes!("tls/ca.pem");
+const CERT: &[u8] = include_bytes!("tls/cert.pem");
+const KEY: &[u8] = include_bytes!("tls/key.pem");
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+fn configs() -> (Arc<rustls::ClientConfig>, Arc<rustls::ServerConfig>) {
+    let mut client = rustls::ClientConfig::new();
+    client.root_store.add_pem_file(&mut BufReader::new(CA)).unwrap();
+
+    let certs = pemfile::certs(&mut BufReader::new(CERT)).unwrap();
+    let key = pemfile::pkcs8_private_keys(&mut BufReader::new(KEY)).unwrap().remove(0);
+    let mut server = rustls::ServerConfig::new(rustls::NoClientAuth::new());
+    server.set_single_cert(certs, key).unwrap();
+
+    (Arc::new(client), Arc::new(server))
+}
+
+#[test]
+fn echo_over_tls() {
+    let (client_config, server_config) = configs();
+    let mut listener = TcpListener::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    futures::executor::block_on(async move {
+        let client = async {
+            let stream = TcpStream::connect_on_driver(addr, demo::driver()).await.unwrap();
+            let mut tls = TlsStream::client(stream, client_config, "localhost").unwrap();
+            tls.write_all(ASSERT).await.unwrap();
+            tls.flush().await.unwrap();
+            let mut buf = vec![0; ASSERT.len()];
+            tls.read_exact(&mut buf).await.unwrap();
+            tls.close().await.unwrap();
+            buf
+        };
+        let server = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut tls = TlsStream::server(stream, server_config);
+            tls.handshake().await.unwrap();
+            let mut buf = vec![0; ASSERT.len()];
+            tls.read_exact(&mut buf).await.unwrap();
+            tls.write_all(&buf).await.unwrap();
+            tls.flush().await.unwrap();
+            let mut rest = Vec::new();
+            tls.read_to_end(&mut rest).await.unwrap();
+            assert!(rest.is_empty());
+        };
+        let (echoed, ()) = futures::join!(client, server);
+        assert_eq!(&echoed[..], ASSERT);
+    });
+}
@@ -0,0 +1,29 @@
+use std::fs as std_fs;
+use std::io;
+
+use futures::AsyncWriteExt;
+
+use ringbahn::drive::demo;
+use ringbahn::fs;
+
+#[test]
+fn write_and_link_tempfile() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("persisted");
+    futures::executor::block_on(async {
+        let mut file = match fs::tempfile_in_on_driver(dir.path(), demo::driver()).await {
+            Ok(file)    => file,
+            // Not every filesystem supports O_TMPFILE.
+            Err(err) if err.raw_os_error() == Some(libc::EOPNOTSUPP) => return,
+            Err(err)    => panic!("{}", err),
+        };
+        file.write_all(b"contents").await.unwrap();
+        file.flush().await.unwrap();
+        assert_eq!(std_fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        file.link_into(&path).await.unwrap();
+        assert_eq!(std_fs::read(&path).unwrap(), b"contents");
+        let result = file.link_into(&path).await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::AlreadyExists);
+    });
+}
@@ -0,0 +1,45 @@
+use std::io::{self, Write};
+use std::net::TcpStream as StdTcpStream;
+use std::thread;
+
+use ringbahn::fs::File;
+use ringbahn::net::TcpListener;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn try_read_cached_file() {
+    // Read props.txt once so that it is in the page cache.
+    let _ = std::fs::read("props.txt").unwrap();
+    futures::executor::block_on(async move {
+        let mut file = File::open("props.txt").await.unwrap();
+        let mut buf = vec![0; 4096];
+        let n = file.try_read(&mut buf).await.unwrap();
+        assert!(n >= ASSERT.len());
+        assert_eq!(&buf[0..ASSERT.len()], ASSERT);
+    });
+}
+
+#[test]
+fn try_read_would_block() {
+    let mut listener = TcpListener::bind(("127.0.0.1", 47232)).unwrap();
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    let client = thread::spawn(move || {
+        let mut stream = StdTcpStream::connect(("127.0.0.1", 47232)).unwrap();
+        rx.recv().unwrap();
+        stream.write_all(b"ping").unwrap();
+    });
+    futures::executor::block_on(async {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        let err = stream.try_read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        tx.send(()).unwrap();
+        futures::AsyncReadExt::read_exact(&mut stream, &mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        assert_eq!(stream.try_write(b"pong").await.unwrap(), 4);
+    });
+    client.join().unwrap();
+}
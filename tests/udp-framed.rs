@@ -0,0 +1,35 @@
+#![cfg(feature = "framed")]
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+
+use ringbahn::drive::demo;
+use ringbahn::net::UdpSocket;
+
+#[test]
+fn round_trip() {
+    let a = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let b = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+    let mut a = a.framed();
+    let mut b = b.framed();
+    futures::executor::block_on(async {
+        a.send((Bytes::from_static(b"ping"), b_addr)).await.unwrap();
+        let (data, from) = b.next().await.unwrap().unwrap();
+        assert_eq!(&data[..], b"ping");
+        assert_eq!(from, a_addr);
+
+        // b echoes each datagram back, and a reads the echoes as a stream.
+        b.send((data, from)).await.unwrap();
+        a.send((Bytes::from_static(b"pong"), b_addr)).await.unwrap();
+        let (data, from) = b.next().await.unwrap().unwrap();
+        b.send((data, from)).await.unwrap();
+
+        let echoes: Vec<_> = a.by_ref().take(2).map(|item| item.unwrap()).collect().await;
+        assert_eq!(echoes, vec![
+            (Bytes::from_static(b"ping"), b_addr),
+            (Bytes::from_static(b"pong"), b_addr),
+        ]);
+    });
+}
@@ -0,0 +1,131 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use ringbahn::drive::demo;
+use ringbahn::net::UdpSocket;
+
+const ASSERT: &[u8] = b"But this formidable power of death -";
+
+#[test]
+fn send_to_and_recv_from() {
+    let mut a = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let mut b = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+    futures::executor::block_on(async {
+        let n = a.send_to(ASSERT, b_addr).await.unwrap();
+        assert_eq!(n, ASSERT.len());
+        let mut buf = [0; 64];
+        let (n, from) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(from, a_addr);
+    });
+}
+
+#[test]
+fn connected_send_and_recv() {
+    let mut a = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let mut b = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+    futures::executor::block_on(async {
+        a.connect(b_addr).await.unwrap();
+        b.connect(a_addr).await.unwrap();
+        a.send(ASSERT).await.unwrap();
+        let mut buf = [0; 64];
+        let n = b.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+    });
+}
+
+#[test]
+fn recv_truncates_to_buffer() {
+    let mut a = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let mut b = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let b_addr = b.local_addr().unwrap();
+    futures::executor::block_on(async {
+        a.send_to(ASSERT, b_addr).await.unwrap();
+        let mut buf = [0; 8];
+        let (n, _) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(n, 8);
+        assert_eq!(&buf[..], &ASSERT[..8]);
+    });
+}
+
+#[test]
+fn multicast_loopback() {
+    let group = Ipv4Addr::new(239, 255, 42, 98);
+    let mut receiver = UdpSocket::bind_on_driver(("0.0.0.0", 47246), demo::driver()).unwrap();
+    receiver.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED).unwrap();
+
+    let mut sender = UdpSocket::bind_on_driver(("0.0.0.0", 0), demo::driver()).unwrap();
+    sender.set_multicast_loop_v4(true).unwrap();
+    sender.set_multicast_ttl_v4(1).unwrap();
+    assert!(sender.multicast_loop_v4().unwrap());
+    assert_eq!(sender.multicast_ttl_v4().unwrap(), 1);
+
+    futures::executor::block_on(async {
+        sender.send_to(ASSERT, SocketAddr::from((group, 47246))).await.unwrap();
+        let mut buf = [0; 64];
+        let (n, _) = receiver.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+    });
+    receiver.leave_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED).unwrap();
+}
+
+#[test]
+fn reply_from_destination_address() {
+    let mut server = UdpSocket::bind_on_driver(("0.0.0.0", 0), demo::driver()).unwrap();
+    server.set_pktinfo(true).unwrap();
+    let port = server.local_addr().unwrap().port();
+    let mut client = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let client_addr = client.local_addr().unwrap();
+    // All of 127.0.0.0/8 is loopback, but a reply is routed from 127.0.0.1 by default.
+    let server_addr = SocketAddr::from(([127, 0, 0, 2], port));
+    futures::executor::block_on(async {
+        client.send_to(ASSERT, server_addr).await.unwrap();
+        let mut buf = [0; 64];
+        let (n, from, info) = server.recv_from_pktinfo(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(from, client_addr);
+        let info = info.unwrap();
+        assert_eq!(info.addr, server_addr.ip());
+        assert_ne!(info.interface, 0);
+
+        server.send_to_from(&buf[..n], from, info).await.unwrap();
+        let (n, reply_from) = client.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], ASSERT);
+        assert_eq!(reply_from, server_addr);
+    });
+}
+
+#[test]
+fn pktinfo_v6() {
+    let mut server = UdpSocket::bind_on_driver(("::", 0), demo::driver()).unwrap();
+    server.set_pktinfo(true).unwrap();
+    let server_addr = SocketAddr::from((Ipv6Addr::LOCALHOST, server.local_addr().unwrap().port()));
+    let mut client = UdpSocket::bind_on_driver(("::1", 0), demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        client.send_to(ASSERT, server_addr).await.unwrap();
+        let mut buf = [0; 64];
+        let (n, from, info) = server.recv_from_pktinfo(&mut buf).await.unwrap();
+        let info = info.unwrap();
+        assert_eq!(info.addr, Ipv6Addr::LOCALHOST);
+
+        server.send_to_from(&buf[..n], from, info).await.unwrap();
+        let (_, reply_from) = client.recv_from(&mut buf).await.unwrap();
+        assert_eq!(reply_from, server_addr);
+    });
+}
+
+#[test]
+fn no_pktinfo_unless_enabled() {
+    let mut server = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    let server_addr = server.local_addr().unwrap();
+    let mut client = UdpSocket::bind_on_driver(("127.0.0.1", 0), demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        client.send_to(ASSERT, server_addr).await.unwrap();
+        let mut buf = [0; 64];
+        let (_, _, info) = server.recv_from_pktinfo(&mut buf).await.unwrap();
+        assert_eq!(info, None);
+    });
+}
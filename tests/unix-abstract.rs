@@ -0,0 +1,19 @@
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+use ringbahn::drive::demo;
+use ringbahn::unix::{UnixListener, UnixStream};
+
+#[test]
+fn connect_abstract() {
+    let name = format!("ringbahn-test-{}", std::process::id());
+    let mut listener = UnixListener::bind_abstract_on_driver(name.as_bytes(), demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        let connect = UnixStream::connect_abstract_on_driver(name.as_bytes(), demo::driver());
+        let (client, server) = futures::join!(connect, listener.accept());
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = [0; 5];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+    });
+}
@@ -0,0 +1,16 @@
+use ringbahn::drive::demo;
+use ringbahn::unix::UnixDatagram;
+
+#[test]
+fn datagram_pair() {
+    let (mut a, mut b) = UnixDatagram::pair_on_driver(demo::driver()).unwrap();
+    futures::executor::block_on(async {
+        a.send(b"first").await.unwrap();
+        a.send(b"second").await.unwrap();
+        let mut buf = [0; 32];
+        let n = b.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"first");
+        let n = b.recv(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"second");
+    });
+}
@@ -0,0 +1,31 @@
+use std::ffi::OsString;
+use std::io;
+
+use ringbahn::drive::demo;
+use ringbahn::fs::File;
+
+#[test]
+fn get_set_and_list_xattrs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+    futures::executor::block_on(async {
+        let mut file = File::create_on_driver(&path, demo::driver()).await.unwrap();
+        match file.set_xattr("user.ringbahn", b"value").await {
+            Ok(())  => { }
+            // Older kernels, and some filesystems, do not support this.
+            Err(err) if err.kind() == io::ErrorKind::Unsupported => return,
+            Err(err) if err.raw_os_error() == Some(libc::EOPNOTSUPP) => return,
+            Err(err) => panic!("{}", err),
+        }
+        assert_eq!(file.get_xattr("user.ringbahn").await.unwrap(), b"value");
+        let result = file.get_xattr("user.missing").await;
+        assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::ENODATA));
+        let result = file.get_xattr("user.\0").await;
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+
+        file.set_xattr("user.other", b"").await.unwrap();
+        let mut names = file.list_xattr().await.unwrap();
+        names.sort();
+        assert_eq!(names, vec![OsString::from("user.other"), OsString::from("user.ringbahn")]);
+    });
+}